@@ -0,0 +1,55 @@
+//! Benchmarks for reading and passing large values.
+//!
+//! Each benchmark defines a large list or dictionary once, then measures a
+//! pre-parsed program that reads it 100 times. With shared storage a read is
+//! a reference-count increment, so the cost should not grow with the size of
+//! the value.
+//!
+//! Run with `cargo bench --bench value_sharing`.
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+use bccl::{Evaluator, Lexer, Parser, Program};
+
+fn parse(code: &str) -> Program {
+    let tokens = Lexer::new(code).tokenize().unwrap();
+    Parser::new(tokens).parse().unwrap()
+}
+
+/// An evaluator with `xs` bound to a list of `size` integers and `cfg`
+/// bound to a dictionary of `size` entries.
+fn evaluator_with_data(size: usize) -> Evaluator {
+    let items: Vec<String> = (0..size).map(|i| i.to_string()).collect();
+    let entries: Vec<String> = (0..size).map(|i| format!("\"k{}\": {}", i, i)).collect();
+    let setup = format!("xs = [{}]; cfg = {{{}}}", items.join(", "), entries.join(", "));
+    let mut evaluator = Evaluator::new();
+    evaluator.evaluate_program(&parse(&setup)).unwrap();
+    evaluator
+}
+
+/// `code` repeated 100 times as separate statements.
+fn repeated(code: &str) -> Program {
+    parse(&vec![code; 100].join("; "))
+}
+
+fn bench_large_values(c: &mut Criterion) {
+    let cases = [
+        ("read_variable", repeated("ys = xs")),
+        ("pass_argument", repeated("type(xs)")),
+        ("config_lookup", repeated("cfg[\"k7\"]")),
+    ];
+
+    for (name, program) in &cases {
+        let mut group = c.benchmark_group(*name);
+        for size in [10, 1_000, 10_000] {
+            let mut evaluator = evaluator_with_data(size);
+            group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+                b.iter(|| black_box(evaluator.evaluate_program(program).unwrap()))
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_large_values);
+criterion_main!(benches);
@@ -0,0 +1,10 @@
+//! `bccl-lsp`: the BCCL language server, speaking LSP over standard input
+//! and output.
+
+use std::io;
+use bccl::LanguageServer;
+
+fn main() -> io::Result<()> {
+    let code = LanguageServer::new().run(io::stdin().lock(), io::stdout().lock())?;
+    std::process::exit(code);
+}
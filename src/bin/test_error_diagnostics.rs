@@ -1,5 +1,5 @@
 use bccl::{Lexer, Parser, Evaluator};
-use miette::{IntoDiagnostic, Result};
+use miette::Result;
 
 fn test_error_diagnostic(name: &str, input: &str) {
     println!("\n=== {} ===", name);
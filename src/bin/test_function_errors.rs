@@ -3,7 +3,7 @@ use bccl::parser::Parser;
 use bccl::evaluator::Evaluator;
 
 fn main() {
-    let test_cases = vec![
+    let test_cases = [
         "unknown_function(5)",
         "max(5)",              // wrong argument count
         "max(5, 10, 15)",      // wrong argument count  
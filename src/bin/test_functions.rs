@@ -3,7 +3,7 @@ use bccl::parser::Parser;
 use bccl::evaluator::Evaluator;
 
 fn main() {
-    let test_cases = vec![
+    let test_cases = [
         "max(5, 10)",
         "max(3.14, 2)",
         "min(5, 10)", 
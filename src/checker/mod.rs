@@ -0,0 +1,594 @@
+//! # Checker Module
+//!
+//! Static type checking: finds type errors in a whole program before any of
+//! it runs, e.g. `"total: " + 3` or `len = 3; len(xs)`, with the span of the
+//! offending expression.
+//!
+//! ## Types
+//!
+//! The checker knows the types of the values BCCL has: `int`, `float`,
+//! `bool`, `str`, `list`, `dict` and `function`. An `int` can be used where a
+//! `float` is expected. A value whose type cannot be known ahead of time, such
+//! as a list element or the result of `max()`, has type `any`, which fits
+//! everywhere: code without annotations is only checked where the types are
+//! certain, so the checker never rejects a program that runs.
+//!
+//! ## Annotations
+//!
+//! An assignment may declare its variable's type, `count: int = 0`. Every
+//! later assignment to the variable must fit the declared type, and uses of
+//! the variable see the declared type rather than the value's. Declaring
+//! `any` lets the variable hold anything. Annotations are checked here only;
+//! the evaluator ignores them, but remembers the types declared by the
+//! programs it has run and passes them to later checks, so a REPL
+//! declaration holds for the rest of the session.
+//!
+//! BCCL has no function definitions yet, so there are no parameter or return
+//! annotations: the signatures of built-ins come from `get_builtin_functions`
+//! and from a table of their result types.
+//!
+//! ## Inference
+//!
+//! Statements are checked in order, so a variable has the type of the value
+//! most recently assigned to it (or its declared type). Arithmetic always
+//! produces a `float`, as it does at runtime, so `n: int = n + 1` is an error
+//! while `n: int = int(n + 1)` is not.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+use crate::error::{BcclError, BcclResult, Span};
+use crate::evaluator::{get_builtin_functions, Capabilities, FunctionSignature, Value};
+use crate::parser::{BinaryOp, CompoundOp, Expr, Program, Stmt, TypeAnnotation, UnaryOp};
+
+#[cfg(test)]
+mod tests;
+
+/// The static type of a BCCL value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Type {
+    /// Unknown ahead of time; fits everywhere
+    Any,
+    Int,
+    Float,
+    Bool,
+    Str,
+    List,
+    Dict,
+    Function,
+}
+
+impl Type {
+    /// Every type, in declaration order.
+    pub const ALL: [Type; 8] = [
+        Type::Any,
+        Type::Int,
+        Type::Float,
+        Type::Bool,
+        Type::Str,
+        Type::List,
+        Type::Dict,
+        Type::Function,
+    ];
+
+    /// The name used in annotations and error messages.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Type::Any => "any",
+            Type::Int => "int",
+            Type::Float => "float",
+            Type::Bool => "bool",
+            Type::Str => "str",
+            Type::List => "list",
+            Type::Dict => "dict",
+            Type::Function => "function",
+        }
+    }
+
+    /// Parses a type name such as `int`.
+    pub fn from_name(name: &str) -> Option<Type> {
+        Type::ALL.into_iter().find(|ty| ty.name() == name)
+    }
+
+    /// The type of a runtime value.
+    pub fn of(value: &Value) -> Type {
+        match value {
+            Value::Number(_) => Type::Float,
+            Value::Integer(_) => Type::Int,
+            Value::Boolean(_) => Type::Bool,
+            Value::String(_) => Type::Str,
+            Value::List(_) => Type::List,
+            Value::Dictionary(_) => Type::Dict,
+            Value::Function(_) => Type::Function,
+        }
+    }
+
+    /// Whether a value of this type can be used where `target` is expected.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bccl::Type;
+    /// assert!(Type::Int.is_assignable_to(Type::Float));
+    /// assert!(!Type::Float.is_assignable_to(Type::Int));
+    /// assert!(Type::Any.is_assignable_to(Type::Str) && Type::Str.is_assignable_to(Type::Any));
+    /// ```
+    pub fn is_assignable_to(self, target: Type) -> bool {
+        self == target || self == Type::Any || target == Type::Any || (self == Type::Int && target == Type::Float)
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(self, Type::Int | Type::Float)
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Checks the types of a program before it runs.
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::{BcclError, Lexer, Parser, TypeChecker};
+/// let program = Parser::new(Lexer::new("name: str = \"total\"\nname + 1").tokenize().unwrap()).parse().unwrap();
+/// match TypeChecker::new().check(&program) {
+///     Err(BcclError::TypeMismatch { expected, found, .. }) => assert_eq!((expected.as_str(), found.as_str()), ("int or float", "str")),
+///     other => panic!("unexpected {:?}", other),
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TypeChecker<'a> {
+    /// Functions a call can reach when no variable hides them
+    functions: &'a HashMap<String, FunctionSignature>,
+    /// Types of the variables that exist before the program runs
+    variables: HashMap<String, Type>,
+    /// Types declared by annotations before the program runs
+    declared: HashMap<String, Type>,
+}
+
+impl Default for TypeChecker<'static> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypeChecker<'static> {
+    /// Creates a checker that knows every built-in, whatever its capability,
+    /// and no variables.
+    pub fn new() -> Self {
+        static BUILTINS: OnceLock<HashMap<String, FunctionSignature>> = OnceLock::new();
+        Self {
+            functions: BUILTINS.get_or_init(|| get_builtin_functions(Capabilities::all())),
+            variables: HashMap::new(),
+            declared: HashMap::new(),
+        }
+    }
+}
+
+impl<'a> TypeChecker<'a> {
+    /// Sets the functions calls are checked against, e.g. an evaluator's
+    /// built-ins together with the host's own. They are borrowed, not copied.
+    pub fn with_functions<'b>(self, functions: &'b HashMap<String, FunctionSignature>) -> TypeChecker<'b> {
+        TypeChecker { functions, variables: self.variables, declared: self.declared }
+    }
+
+    /// Adds variables that exist before the program runs, such as the REPL's
+    /// earlier results.
+    pub fn with_variables<I: IntoIterator<Item = (String, Type)>>(mut self, variables: I) -> Self {
+        self.variables.extend(variables);
+        self
+    }
+
+    /// Adds types declared by annotations in earlier programs, such as the
+    /// REPL's earlier inputs: assignments must keep to them, and uses of the
+    /// variables see them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bccl::{Lexer, Parser, Type, TypeChecker};
+    /// let program = Parser::new(Lexer::new("n = \"three\"").tokenize().unwrap()).parse().unwrap();
+    /// let checker = TypeChecker::new().with_declarations([("n".to_string(), Type::Int)]);
+    /// assert!(checker.check(&program).is_err());
+    /// ```
+    pub fn with_declarations<I: IntoIterator<Item = (String, Type)>>(mut self, declared: I) -> Self {
+        self.declared.extend(declared);
+        self
+    }
+
+    /// The types `program`'s annotations declare, in order; annotations
+    /// naming an unknown type are skipped.
+    pub fn declarations(program: &Program) -> impl Iterator<Item = (String, Type)> + '_ {
+        program.statements.iter().filter_map(|stmt| match stmt {
+            Stmt::Assignment { name, annotation: Some(annotation), .. } => {
+                Type::from_name(&annotation.name).map(|ty| (name.clone(), ty))
+            }
+            _ => None,
+        })
+    }
+
+    /// Checks `program`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If no type errors were found
+    /// * `Err(error)` - Every type error found (several are returned as
+    ///   `BcclError::Multiple`)
+    pub fn check(&self, program: &Program) -> BcclResult<()> {
+        let inference = self.run(program);
+        if inference.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(BcclError::multiple(inference.errors))
+        }
+    }
+
+    /// The type of each variable after `program` has run, ignoring any type
+    /// errors.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bccl::{Lexer, Parser, Type, TypeChecker};
+    /// let program = Parser::new(Lexer::new("n = 1\nr: float = n\nn = str(n)").tokenize().unwrap()).parse().unwrap();
+    /// let types = TypeChecker::new().infer(&program);
+    /// assert_eq!((types["n"], types["r"]), (Type::Str, Type::Float));
+    /// ```
+    pub fn infer(&self, program: &Program) -> HashMap<String, Type> {
+        self.run(program).variables
+    }
+
+    fn run(&self, program: &Program) -> Inference<'_> {
+        let mut variables = self.variables.clone();
+        // Uses of a declared variable see its declared type, not its value's
+        variables.extend(self.declared.iter().map(|(name, ty)| (name.clone(), *ty)));
+        let mut inference = Inference {
+            functions: self.functions,
+            variables,
+            declared: self.declared.clone(),
+            errors: Vec::new(),
+        };
+        for stmt in &program.statements {
+            inference.statement(stmt);
+        }
+        inference
+    }
+}
+
+/// The state of one `TypeChecker` run.
+struct Inference<'a> {
+    functions: &'a HashMap<String, FunctionSignature>,
+    /// Each variable's type at the current statement
+    variables: HashMap<String, Type>,
+    /// Types given by annotations, which later assignments must keep to
+    declared: HashMap<String, Type>,
+    errors: Vec<BcclError>,
+}
+
+impl Inference<'_> {
+    fn statement(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Expression { expr, .. } => {
+                self.expr(expr);
+            }
+            Stmt::Assignment { name, value, annotation, .. } => {
+                let found = self.expr(value);
+                if let Some(annotation) = annotation {
+                    self.declare(name, annotation);
+                }
+                let ty = match self.declared.get(name) {
+                    Some(&declared) => {
+                        if !found.is_assignable_to(declared) {
+                            self.errors.push(BcclError::type_mismatch(
+                                &format!("cannot assign {} to '{}', which is declared as {}", a(found), name, declared),
+                                value.span(),
+                                declared.name(),
+                                found.name(),
+                                &assignment_suggestion(found, declared),
+                            ));
+                        }
+                        declared
+                    }
+                    None => found,
+                };
+                self.variables.insert(name.clone(), ty);
+            }
+            Stmt::CompoundAssignment { name, operator, value, span, .. } => {
+                let current = self.variables.get(name).copied().unwrap_or(Type::Any);
+                let found = self.expr(value);
+                let message = format!("`{}` needs a number", operator.symbol());
+                let numeric = self.expect_number(current, *span, &format!("{} on the left, but '{}' is {}", message, name, a(current)))
+                    & self.expect_number(found, value.span(), &message);
+                let ty = match self.declared.get(name) {
+                    Some(&declared) => {
+                        if numeric && !Type::Float.is_assignable_to(declared) {
+                            self.errors.push(BcclError::type_mismatch(
+                                &format!("`{}` makes '{}' a float, but it is declared as {}", operator.symbol(), name, declared),
+                                *span,
+                                declared.name(),
+                                Type::Float.name(),
+                                &format!("Arithmetic always produces a float; write `{} = int({} {} ...)` to keep an int", name, name, compound_symbol(*operator)),
+                            ));
+                        }
+                        declared
+                    }
+                    None if numeric => Type::Float,
+                    None => Type::Any,
+                };
+                self.variables.insert(name.clone(), ty);
+            }
+        }
+    }
+
+    /// Records the type an annotation declares, or reports an unknown type.
+    fn declare(&mut self, name: &str, annotation: &TypeAnnotation) {
+        match Type::from_name(&annotation.name) {
+            Some(ty) => {
+                self.declared.insert(name.to_string(), ty);
+            }
+            None => {
+                self.errors.push(BcclError::unknown_type(&annotation.name, annotation.span));
+                self.declared.remove(name);
+            }
+        }
+    }
+
+    /// The type of `expr`. After a type error the result is `any`, so one
+    /// mistake is reported once.
+    fn expr(&mut self, expr: &Expr) -> Type {
+        match expr {
+            Expr::Number { .. } => Type::Float,
+            Expr::Integer { .. } => Type::Int,
+            Expr::Boolean { .. } => Type::Bool,
+            Expr::String { .. } => Type::Str,
+            Expr::Identifier { name, .. } => match self.variables.get(name) {
+                Some(&ty) => ty,
+                None if self.functions.contains_key(name) => Type::Function,
+                None => Type::Any,
+            },
+            Expr::Binary { left, operator, right, span } => {
+                let left_type = self.expr(left);
+                let right_type = self.expr(right);
+                self.binary(*operator, (left_type, left.span()), (right_type, right.span()), *span)
+            }
+            Expr::Unary { operator, operand, .. } => {
+                let operand_type = self.expr(operand);
+                match operator {
+                    UnaryOp::Minus | UnaryOp::Plus => {
+                        let message = format!("unary `{}` needs a number", operator.symbol());
+                        if self.expect_number(operand_type, operand.span(), &message) { Type::Float } else { Type::Any }
+                    }
+                    UnaryOp::Not => Type::Bool,
+                }
+            }
+            Expr::FunctionCall { name, args, kwargs, span, .. } => self.call(name, args, kwargs, *span),
+            Expr::Unpack { value, .. } => {
+                self.expr(value);
+                Type::Any
+            }
+            Expr::List { elements, .. } => {
+                for element in elements {
+                    self.expr(element);
+                }
+                Type::List
+            }
+            Expr::Dictionary { pairs, .. } => {
+                for (_, value) in pairs {
+                    self.expr(value);
+                }
+                Type::Dict
+            }
+            Expr::Index { object, index, .. } => {
+                let object_type = self.expr(object);
+                let index_type = self.expr(index);
+                let key = match object_type {
+                    Type::List => Type::Int,
+                    Type::Dict => Type::Str,
+                    Type::Any => return Type::Any,
+                    other => {
+                        self.mismatch(&format!("{} cannot be indexed", a(other)), object.span(), "list or dict", other, "Only lists and dictionaries can be indexed");
+                        return Type::Any;
+                    }
+                };
+                if !index_type.is_assignable_to(key) {
+                    let message = format!("{} index must be {}", a(object_type), a(key));
+                    self.mismatch(&message, index.span(), key.name(), index_type, "Convert the index with int(...) or str(...)");
+                }
+                Type::Any
+            }
+        }
+    }
+
+    fn binary(&mut self, operator: BinaryOp, (left, left_span): (Type, Span), (right, right_span): (Type, Span), span: Span) -> Type {
+        match operator {
+            BinaryOp::Add | BinaryOp::Subtract | BinaryOp::Multiply | BinaryOp::Divide => {
+                let message = format!("`{}` needs numbers", operator.symbol());
+                let numeric = self.expect_number(left, left_span, &message) & self.expect_number(right, right_span, &message);
+                if numeric { Type::Float } else { Type::Any }
+            }
+            BinaryOp::Equal | BinaryOp::NotEqual => Type::Bool,
+            BinaryOp::Less | BinaryOp::Greater | BinaryOp::LessEqual | BinaryOp::GreaterEqual => {
+                let message = format!("`{}` compares numbers with numbers or strings with strings", operator.symbol());
+                let comparable = |ty: Type, span: Span, errors: &mut Vec<BcclError>| {
+                    let ok = ty == Type::Any || ty == Type::Str || ty.is_numeric();
+                    if !ok {
+                        errors.push(BcclError::type_mismatch(&message, span, "int, float or str", ty.name(), "Only numbers and strings can be ordered"));
+                    }
+                    ok
+                };
+                if comparable(left, left_span, &mut self.errors) & comparable(right, right_span, &mut self.errors)
+                    && left != Type::Any && right != Type::Any && left.is_numeric() != right.is_numeric()
+                {
+                    let expected = if left.is_numeric() { "int or float" } else { "str" };
+                    self.errors.push(BcclError::type_mismatch(
+                        &format!("cannot compare {} with {}", a(left), a(right)),
+                        span,
+                        expected,
+                        right.name(),
+                        "Convert one side with str(...) or float(...)",
+                    ));
+                }
+                Type::Bool
+            }
+            // The result is one of the operands
+            BinaryOp::And | BinaryOp::Or => if left == right { left } else { Type::Any },
+            BinaryOp::In | BinaryOp::NotIn => {
+                match right {
+                    Type::List | Type::Any => {}
+                    Type::Dict => {
+                        if !left.is_assignable_to(Type::Str) {
+                            let message = format!("`{}` looks up a dictionary by string key", operator.symbol());
+                            self.mismatch(&message, left_span, "str", left, "Dictionary keys are always strings; convert with str(...)");
+                        }
+                    }
+                    other => {
+                        let message = format!("`{}` needs a list or dictionary on the right", operator.symbol());
+                        self.mismatch(&message, right_span, "list or dict", other, "Membership is only defined for lists and dictionaries");
+                    }
+                }
+                Type::Bool
+            }
+        }
+    }
+
+    /// Checks a call and returns the type of its result.
+    fn call(&mut self, name: &str, args: &[Expr], kwargs: &[(Option<String>, Expr)], span: Span) -> Type {
+        let mut arg_types = Vec::new();
+        let mut unpacked = false;
+        for arg in args {
+            if let Expr::Unpack { value, .. } = arg {
+                let ty = self.expr(value);
+                self.expect(ty, Type::List, value.span(), "`*` unpacks a list into arguments");
+                unpacked = true;
+            } else {
+                arg_types.push((self.expr(arg), arg.span()));
+            }
+        }
+        for (keyword, value) in kwargs {
+            let ty = self.expr(value);
+            if keyword.is_none() {
+                self.expect(ty, Type::Dict, value.span(), "`**` unpacks a dictionary into keyword arguments");
+                unpacked = true;
+            }
+        }
+
+        // A variable hides a function of the same name
+        match self.variables.get(name) {
+            Some(Type::Function | Type::Any) => return Type::Any,
+            Some(&other) => {
+                let message = format!("'{}' is {}, not a function", name, a(other));
+                self.mismatch(&message, span, "function", other, "Rename the variable, or call a function instead");
+                return Type::Any;
+            }
+            None => {}
+        }
+        let Some(signature) = self.functions.get(name) else {
+            return Type::Any;
+        };
+
+        // With `*` or `**` the number of arguments is only known at runtime
+        if !unpacked {
+            let placeholder = Value::list(Vec::new());
+            let positional: Vec<_> = arg_types.iter().map(|&(_, span)| (placeholder.clone(), span)).collect();
+            let keywords: Vec<_> = kwargs.iter()
+                .filter_map(|(keyword, value)| keyword.clone().map(|keyword| (keyword, placeholder.clone(), value.span())))
+                .collect();
+            if let Err(error) = signature.bind_arguments(&positional, &keywords, span) {
+                self.errors.push(error);
+                return result_type(name);
+            }
+            self.builtin_arguments(name, &arg_types);
+        }
+        result_type(name)
+    }
+
+    /// Checks the positional arguments of built-ins that take a function and
+    /// an iterable.
+    fn builtin_arguments(&mut self, name: &str, args: &[(Type, Span)]) {
+        let (function, values) = match name {
+            "map" | "filter" | "reduce" | "group_by" => (args.first(), args.get(1)),
+            "sorted" => (None, args.first()),
+            _ => return,
+        };
+        if let Some(&(ty, span)) = function {
+            self.expect(ty, Type::Function, span, &format!("the first argument of {}() is called on every value", name));
+        }
+        if let Some(&(ty, span)) = values
+            && !matches!(ty, Type::Any | Type::List | Type::Str | Type::Dict)
+        {
+            let message = format!("{}() needs a list, string or dictionary of values", name);
+            self.mismatch(&message, span, "list, str or dict", ty, "Pass the values as a list, e.g. [a, b, c]");
+        }
+    }
+
+    /// Reports a type error unless `found` can be used as `expected`.
+    fn expect(&mut self, found: Type, expected: Type, span: Span, message: &str) -> bool {
+        let ok = found.is_assignable_to(expected);
+        if !ok {
+            self.mismatch(message, span, expected.name(), found, &format!("Use {} here", a(expected)));
+        }
+        ok
+    }
+
+    /// Reports a type error unless `found` can be a number.
+    fn expect_number(&mut self, found: Type, span: Span, message: &str) -> bool {
+        let ok = found == Type::Any || found.is_numeric();
+        if !ok {
+            let suggestion = if found == Type::Str {
+                "Convert the string with int(...) or float(...)"
+            } else {
+                "Arithmetic is only defined for numbers"
+            };
+            self.mismatch(message, span, "int or float", found, suggestion);
+        }
+        ok
+    }
+
+    fn mismatch(&mut self, message: &str, span: Span, expected: &str, found: Type, suggestion: &str) {
+        self.errors.push(BcclError::type_mismatch(message, span, expected, found.name(), suggestion));
+    }
+}
+
+/// What a built-in returns; `any` for those whose result depends on their
+/// arguments, such as `max()`, and for functions the checker does not know.
+fn result_type(name: &str) -> Type {
+    match name {
+        "int" | "write_file" => Type::Int,
+        "float" | "sum" | "product" | "now" | "random" => Type::Float,
+        "str" | "type" | "read_file" => Type::Str,
+        "bool" | "is_number" | "is_integer" | "is_boolean" | "is_string" | "is_list" | "is_dict" => Type::Bool,
+        "list" | "map" | "filter" | "sorted" => Type::List,
+        "dict" | "group_by" => Type::Dict,
+        _ => Type::Any,
+    }
+}
+
+/// The help for assigning a `found` value to a variable declared `declared`.
+fn assignment_suggestion(found: Type, declared: Type) -> String {
+    match (found, declared) {
+        (Type::Float, Type::Int) => "Arithmetic always produces a float; convert the value with int(...)".to_string(),
+        _ => format!("Convert the value to {}, or declare the variable as `any`", a(declared)),
+    }
+}
+
+/// The type's name with its indefinite article, e.g. `an int`.
+fn a(ty: Type) -> String {
+    match ty {
+        Type::Any | Type::Int => format!("an {}", ty),
+        _ => format!("a {}", ty),
+    }
+}
+
+/// The binary operator a compound assignment applies, e.g. `+` for `+=`.
+fn compound_symbol(operator: CompoundOp) -> &'static str {
+    match operator {
+        CompoundOp::Add => "+",
+        CompoundOp::Subtract => "-",
+        CompoundOp::Multiply => "*",
+        CompoundOp::Divide => "/",
+    }
+}
@@ -0,0 +1,195 @@
+use super::*;
+use crate::{Evaluator, Lexer, Parser};
+use crate::test_support::Rng;
+
+fn parse(source: &str) -> Program {
+    Parser::new(Lexer::new(source).tokenize().unwrap()).parse().unwrap()
+}
+
+/// The `(expected, found, line)` of every type mismatch in `source`, lines
+/// counted from 1.
+fn mismatches(source: &str) -> Vec<(String, String, usize)> {
+    match TypeChecker::new().check(&parse(source)) {
+        Ok(()) => Vec::new(),
+        Err(error) => error.into_errors().into_iter()
+            .map(|error| match error {
+                BcclError::TypeMismatch { expected, found, span, .. } => {
+                    let line = source.chars().take(span.offset()).filter(|c| *c == '\n').count() + 1;
+                    (expected, found, line)
+                }
+                other => panic!("Expected a type mismatch, got {:?}", other),
+            })
+            .collect(),
+    }
+}
+
+fn mismatch(expected: &str, found: &str, line: usize) -> (String, String, usize) {
+    (expected.to_string(), found.to_string(), line)
+}
+
+#[test]
+fn test_type_names() {
+    for ty in Type::ALL {
+        assert_eq!(Type::from_name(ty.name()), Some(ty));
+        assert_eq!(ty.to_string(), ty.name());
+    }
+    assert_eq!(Type::from_name("integer"), None);
+    assert_eq!(Type::of(&Value::Integer(1)), Type::Int);
+    assert_eq!(Type::of(&Value::Number(1.0)), Type::Float);
+    assert_eq!(Type::of(&Value::Function("max".to_string())), Type::Function);
+}
+
+#[test]
+fn test_infers_types_of_expressions() {
+    let types = TypeChecker::new().infer(&parse(
+        "a = 1\nb = a + 1\nc = \"s\" < \"t\"\nd = [a][0]\ne = {\"k\": a}\nf = int(\"3\")\ng = map\nh = a and a\ni = a or \"s\"\nj = -a"
+    ));
+    let expected = [
+        ("a", Type::Int), ("b", Type::Float), ("c", Type::Bool), ("d", Type::Any), ("e", Type::Dict),
+        ("f", Type::Int), ("g", Type::Function), ("h", Type::Int), ("i", Type::Any), ("j", Type::Float),
+    ];
+    for (name, ty) in expected {
+        assert_eq!(types[name], ty, "{}", name);
+    }
+}
+
+#[test]
+fn test_operator_mismatches() {
+    assert_eq!(mismatches("\"a\" + 1"), [mismatch("int or float", "str", 1)]);
+    assert_eq!(mismatches("x = true\n-x"), [mismatch("int or float", "bool", 2)]);
+    assert_eq!(mismatches("1 < \"a\""), [mismatch("int or float", "str", 1)]);
+    assert_eq!(mismatches("[1] < [2]"), [mismatch("int, float or str", "list", 1), mismatch("int, float or str", "list", 1)]);
+    assert_eq!(mismatches("1 in 2"), [mismatch("list or dict", "int", 1)]);
+    assert_eq!(mismatches("1 in {\"a\": 1}"), [mismatch("str", "int", 1)]);
+    assert_eq!(mismatches("xs = [1]\nxs[\"a\"]\n{\"a\": 1}[0]\n\"abc\"[0]"), [
+        mismatch("int", "str", 2), mismatch("str", "int", 3), mismatch("list or dict", "str", 4),
+    ]);
+    // Equality, `not` and truthiness work with any values
+    assert!(mismatches("1 == \"a\"\nnot [1]\n[] or {}").is_empty());
+}
+
+#[test]
+fn test_errors_do_not_cascade() {
+    // The failed addition has type `any`, so only the first mistake is shown
+    assert_eq!(mismatches("x = \"a\" + 1\ny = x * 2\nx[0]"), [mismatch("int or float", "str", 1)]);
+    // Every independent mistake is
+    assert_eq!(mismatches("\"a\" + 1\ntrue - 1").len(), 2);
+    // and together they are not reported as syntax errors
+    let error = TypeChecker::new().check(&parse("\"a\" + 1\ntrue - 1")).unwrap_err();
+    assert_eq!(error.to_string(), "found 2 problems");
+    assert_eq!(miette::Diagnostic::code(&error).unwrap().to_string(), "bccl::multiple");
+}
+
+#[test]
+fn test_calls() {
+    assert_eq!(mismatches("map(1, [1])"), [mismatch("function", "int", 1)]);
+    assert_eq!(mismatches("filter(str, 3)"), [mismatch("list, str or dict", "int", 1)]);
+    assert_eq!(mismatches("n = 3\nn(1)"), [mismatch("function", "int", 2)]);
+    assert!(mismatches("sorted(\"cba\", reverse=true)\ngroup_by(str, {\"a\": 1})\nf = str\nf(1)").is_empty());
+    // Results of built-ins are typed
+    assert_eq!(mismatches("str(1) + 1"), [mismatch("int or float", "str", 1)]);
+
+    let error = TypeChecker::new().check(&parse("int(1, 2)")).unwrap_err();
+    assert!(matches!(error, BcclError::WrongArgumentCount { .. }), "{:?}", error);
+    let error = TypeChecker::new().check(&parse("max(1, colour=2)")).unwrap_err();
+    assert!(matches!(error, BcclError::FunctionArgumentError { .. }), "{:?}", error);
+    // With unpacking the arguments are only known at runtime
+    assert!(TypeChecker::new().check(&parse("xs = [1, 2]\nint(*xs)")).is_ok());
+    assert_eq!(mismatches("max(*1)"), [mismatch("list", "int", 1)]);
+}
+
+#[test]
+fn test_annotations() {
+    assert!(mismatches("n: int = 1\nn = int(n + 1)\nr: float = n\nv: any = \"a\"\nv = 1").is_empty());
+    assert_eq!(mismatches("n: int = 1.5"), [mismatch("int", "float", 1)]);
+    // The declared type outlives the statement that declares it
+    assert_eq!(mismatches("n: int = 1\nn = n + 1"), [mismatch("int", "float", 2)]);
+    assert_eq!(mismatches("n: int = 1\nn += 1"), [mismatch("int", "float", 2)]);
+    assert_eq!(mismatches("s: str = \"a\"\ns += 1"), [mismatch("int or float", "str", 2)]);
+    // Uses see the declared type, not the value's
+    assert_eq!(mismatches("x: any = \"a\"\nx + 1\ny: float = 1\ny[0]"), [mismatch("list or dict", "float", 4)]);
+    // A new annotation replaces the old one
+    assert!(mismatches("x: int = 1\nx: str = \"a\"").is_empty());
+
+    let error = TypeChecker::new().check(&parse("x: integer = 1")).unwrap_err();
+    match error {
+        BcclError::UnknownType { name, span } => assert_eq!((name.as_str(), span.offset(), span.len()), ("integer", 3, 7)),
+        other => panic!("Expected an unknown type, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_known_variables_and_functions() {
+    let program = parse("total + 1\ndouble(total)");
+    assert!(TypeChecker::new().check(&program).is_ok());
+    let checker = TypeChecker::new().with_variables([("total".to_string(), Type::Str)]);
+    assert_eq!(checker.check(&program).unwrap_err().into_errors().len(), 1);
+    // Without `max` among the functions, a call to it is not checked
+    let program = parse("max(1, colour=2)\nmax");
+    assert!(TypeChecker::new().with_functions(&HashMap::new()).check(&program).is_ok());
+}
+
+#[test]
+fn test_evaluator_checks_before_running() {
+    let mut evaluator = Evaluator::new();
+    assert!(!evaluator.type_check_enabled());
+    evaluator.set_type_check(true);
+    evaluator.evaluate_program(&parse("name = \"a\"")).unwrap();
+    // The checker sees the variables left by earlier runs
+    let error = evaluator.evaluate_program(&parse("count = 1\nname * 2")).unwrap_err();
+    assert!(matches!(error, BcclError::TypeMismatch { .. }), "{:?}", error);
+    assert_eq!(evaluator.get_variable_names(), ["name"]);
+    // and the types they declared
+    evaluator.evaluate_program(&parse("n: int = 3\nv: any = \"a\"")).unwrap();
+    let error = evaluator.evaluate_program(&parse("n = \"s\"")).unwrap_err();
+    assert!(matches!(error, BcclError::TypeMismatch { .. }), "{:?}", error);
+    evaluator.check(&parse("v + 1")).unwrap();
+    evaluator.evaluate_program(&parse("n = int(n * 2)\nv = [v]")).unwrap();
+    // Restoring a snapshot forgets them
+    evaluator.restore(&evaluator.snapshot()).unwrap();
+    evaluator.evaluate_program(&parse("n = \"s\"")).unwrap();
+    // Annotations are not enforced at runtime
+    evaluator.set_type_check(false);
+    assert_eq!(evaluator.evaluate_program(&parse("n: int = 1.5\nn")).unwrap().unwrap().display(), "1.5");
+}
+
+/// Source for a random expression, which may or may not run.
+fn expression(rng: &mut Rng, depth: usize) -> String {
+    const ATOMS: [&str; 10] = ["1", "2.5", "true", "\"ab\"", "[1, 2]", "{\"a\": 1}", "x", "y", "str", "max"];
+    if depth == 0 || rng.below(3) == 0 {
+        return rng.pick(&ATOMS).to_string();
+    }
+    let (a, b) = (expression(rng, depth - 1), expression(rng, depth - 1));
+    match rng.below(6) {
+        0 | 1 => format!("({} {} {})", a, rng.pick(&["+", "-", "*", "<", "==", "and", "or", "in"]), b),
+        2 => format!("({}{})", rng.pick(&["-", "not "]), a),
+        3 => format!("({})[{}]", a, b),
+        4 => format!("{}({})", rng.pick(&["int", "str", "float", "list", "x", "max", "sorted"]), a),
+        _ => format!("map({}, {})", a, b),
+    }
+}
+
+#[test]
+fn test_accepts_every_program_that_runs() {
+    let mut rng = Rng(0x5eed_0049);
+    let mut ran = 0;
+    for _ in 0..10000 {
+        let source = ["x = 1".to_string(), "y = \"ab\"".to_string()].into_iter()
+            .chain((0..3).map(|_| format!("{} = {}", rng.pick(&["x", "y"]), expression(&mut rng, 3))))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let program = parse(&source);
+        let mut evaluator = Evaluator::new();
+        if evaluator.evaluate_program(&program).is_err() {
+            continue;
+        }
+        ran += 1;
+        assert!(TypeChecker::new().check(&program).is_ok(), "{}", source);
+        // Inferred types agree with the values
+        for (name, ty) in TypeChecker::new().infer(&program) {
+            let value = evaluator.get_variable(&name).unwrap();
+            assert!(ty == Type::Any || ty == Type::of(value), "{}: {} is {}\n{}", name, ty, value.type_name(), source);
+        }
+    }
+    assert!(ran > 500, "only {} programs ran", ran);
+}
@@ -17,4 +17,12 @@ impl BcclError {
             span: span.into(),
         }
     }
-}
\ No newline at end of file
+
+    /// Create a lexer error from an error token's message
+    pub fn lex_error(message: &str, span: Span) -> Self {
+        Self::LexError {
+            message: message.to_string(),
+            span: span.into(),
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use super::span::Span;
+use super::types::BcclError;
+
+impl BcclError {
+    /// Create a warning from the lint rule `rule`. The help text says how to
+    /// silence the rule on one line.
+    pub fn lint_warning(rule: &str, message: &str, span: Span, suggestion: &str) -> Self {
+        Self::LintWarning {
+            message: message.to_string(),
+            span: span.into(),
+            rule: rule.to_string(),
+            suggestion: format!("{}, or add `# bccl: allow({})` to silence this warning", suggestion, rule),
+        }
+    }
+}
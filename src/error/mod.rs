@@ -3,6 +3,8 @@ mod types;
 mod lexer_errors;
 mod parser_errors;
 mod runtime_errors;
+mod lint_errors;
+mod type_errors;
 mod context;
 
 pub use span::Span;
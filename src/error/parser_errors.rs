@@ -34,4 +34,38 @@ impl BcclError {
             expected: expected.to_string(),
         }
     }
-}
\ No newline at end of file
+
+    /// Create a parser error for a statement that follows another on the same
+    /// line without a `;`
+    pub fn missing_separator(span: Span) -> Self {
+        Self::MissingSeparator {
+            span: span.into(),
+        }
+    }
+
+    /// Combine the errors found in one run into a single diagnostic. One error
+    /// is returned as-is; several are wrapped in `BcclError::Multiple`, which
+    /// names no stage of its own, since the parser and the type checker both
+    /// use it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `errors` is empty.
+    pub fn multiple(mut errors: Vec<BcclError>) -> Self {
+        assert!(!errors.is_empty(), "BcclError::multiple needs at least one error");
+        if errors.len() == 1 {
+            errors.pop().unwrap()
+        } else {
+            Self::Multiple { errors }
+        }
+    }
+
+    /// The individual errors: the contents of `BcclError::Multiple`, or this
+    /// error alone.
+    pub fn into_errors(self) -> Vec<BcclError> {
+        match self {
+            Self::Multiple { errors } => errors,
+            error => vec![error],
+        }
+    }
+}
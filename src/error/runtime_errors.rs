@@ -88,6 +88,16 @@ impl BcclError {
         }
     }
 
+    /// Create a wrong argument count error pointing at the first surplus argument
+    pub fn wrong_argument_count_with_span(function_name: &str, expected: usize, actual: usize, span: Span) -> Self {
+        Self::WrongArgumentCount {
+            function_name: function_name.to_string(),
+            expected,
+            actual,
+            span: span.into(),
+        }
+    }
+
     /// Create a function argument type error with proper span and value information
     pub fn function_argument_type_error_with_span(function_name: &str, arg_number: usize, expected_type: &str, actual_type: &str, actual_value: &str, span: Span) -> Self {
         Self::FunctionArgumentTypeError {
@@ -176,7 +186,7 @@ impl BcclError {
             "and" | "or" => "Both operands can be any type. Results follow Python-like truthiness rules.".to_string(),
             "not" => "The 'not' operator works with any value type and returns a boolean.".to_string(),
             "in" | "not in" => "Use 'value in collection' for lists or 'key in dictionary' for dictionaries.".to_string(),
-            "<" | ">" | "<=" | ">=" => "Comparison operators require both operands to be numbers.".to_string(),
+            "<" | ">" | "<=" | ">=" => "Comparison operators require two numbers or two strings.".to_string(),
             _ => "Check the operator documentation for usage examples.".to_string()
         };
         
@@ -230,6 +240,17 @@ impl BcclError {
         }
     }
 
+    /// Create an error for passing a variadic parameter (`*values`) by keyword
+    /// in a call that also passes extra positional arguments
+    pub fn variadic_keyword_argument(function_name: &str, variadic_name: &str, span: Span) -> Self {
+        Self::FunctionArgumentError {
+            message: format!("Parameter '{}' of function '{}' was given both extra positional arguments and a keyword argument", variadic_name, function_name),
+            function_name: function_name.to_string(),
+            span: span.into(),
+            suggestion: format!("Pass the values either positionally, e.g. {}(1, 2, 3), or as one list, e.g. {}({}=[1, 2, 3])", function_name, function_name, variadic_name),
+        }
+    }
+
     /// Create a missing parameter error
     pub fn missing_parameter(function_name: &str, parameter_name: &str, span: Span) -> Self {
         Self::FunctionArgumentError {
@@ -239,4 +260,113 @@ impl BcclError {
             suggestion: format!("Provide a value for parameter '{}' either positionally or using '{} = value'", parameter_name, parameter_name),
         }
     }
-}
\ No newline at end of file
+
+    /// Create an error for `max()`/`min()` style functions called with nothing to choose from
+    pub fn empty_sequence_error(function_name: &str, span: Span) -> Self {
+        Self::FunctionArgumentError {
+            message: format!("{}() was called with no values to choose from", function_name),
+            function_name: function_name.to_string(),
+            span: span.into(),
+            suggestion: format!("Pass at least one value, or a fallback with 'default', e.g. {}([], default=0)", function_name),
+        }
+    }
+
+    /// Create a conversion error for values that cannot be turned into the target type
+    pub fn conversion_error(function_name: &str, target_type: &str, text: &str, span: Span) -> Self {
+        let suggestion = match target_type {
+            "integer" => format!("{}() accepts whole numbers, booleans and integer text like \"42\" or \"-7\"", function_name),
+            "number" => format!("{}() accepts numbers, booleans and numeric text like \"3.14\" or \"1e3\"", function_name),
+            "list" => format!("{}() accepts lists, strings and dictionaries", function_name),
+            "dictionary" => format!("{}() accepts dictionaries and lists of [key, value] pairs with string keys", function_name),
+            _ => format!("Check the value passed to {}()", function_name),
+        };
+
+        Self::ConversionError {
+            message: format!("cannot convert {} to {}", text, target_type),
+            target_type: target_type.to_string(),
+            span: span.into(),
+            label: format!("{} is not a valid {}", text, target_type),
+            suggestion,
+        }
+    }
+
+    /// Wrap an error raised by a callback so the diagnostic notes which
+    /// higher-order built-in invoked it
+    pub fn callback_error(function_name: &str, span: Span, source: BcclError) -> Self {
+        Self::CallbackError {
+            function_name: function_name.to_string(),
+            span: span.into(),
+            errors: vec![source],
+        }
+    }
+
+    /// Create an error for a built-in left out because the host did not grant
+    /// its capability
+    pub fn missing_capability(function_name: &str, capability: &str, span: Span) -> Self {
+        Self::MissingCapability {
+            function_name: function_name.to_string(),
+            capability: capability.to_string(),
+            span: span.into(),
+            suggestion: format!(
+                "The host has not granted '{}'. Embedders can add it with Evaluator::with_capabilities; the REPL accepts --capabilities {}",
+                capability, capability
+            ),
+        }
+    }
+
+    /// Create an error for a run stopped through its `CancellationToken`
+    pub fn cancelled(span: Span) -> Self {
+        Self::Cancelled {
+            message: "the evaluation was cancelled".to_string(),
+            reason: "cancelled".to_string(),
+            span: span.into(),
+            suggestion: "Statements before this one completed; this one and the rest did not run".to_string(),
+        }
+    }
+
+    /// Create an error for a run that was still going at its deadline
+    pub fn deadline_exceeded(span: Span) -> Self {
+        Self::Cancelled {
+            message: "the evaluation ran past its deadline".to_string(),
+            reason: "deadline".to_string(),
+            span: span.into(),
+            suggestion: "Statements before this one completed; this one and the rest did not run".to_string(),
+        }
+    }
+
+    /// Create an error for a run that went past one of its `EvaluatorLimits`
+    pub fn limit_exceeded(limit: &str, max: u64, span: Span) -> Self {
+        let message = match limit {
+            "steps" => format!("evaluation took more than {} steps", max),
+            "call_depth" => format!("function calls nested more than {} deep", max),
+            "collection_size" => format!("a collection grew past {} elements", max),
+            "total_elements" => format!("collections created by the program hold more than {} elements in total", max),
+            "string_length" => format!("a string grew past {} bytes", max),
+            _ => format!("the {} limit of {} was exceeded", limit, max),
+        };
+
+        Self::LimitExceeded {
+            message,
+            limit: limit.to_string(),
+            max,
+            span: span.into(),
+            suggestion: format!("The host limits {} to {}; simplify the program or ask for a higher limit", limit, max),
+        }
+    }
+
+    /// Create an error for a snapshot image that cannot be read or restored
+    pub fn snapshot_error(message: &str, suggestion: &str) -> Self {
+        Self::SnapshotError {
+            message: message.to_string(),
+            suggestion: suggestion.to_string(),
+        }
+    }
+
+    /// Create an error for a snapshot written in another format version
+    pub fn incompatible_snapshot(found: u32, supported: u32) -> Self {
+        Self::SnapshotError {
+            message: format!("snapshot format version {} is not supported (this build reads version {})", found, supported),
+            suggestion: "The snapshot was saved by a different BCCL version; save it again from a session of this version".to_string(),
+        }
+    }
+}
@@ -2,6 +2,7 @@ use miette::SourceSpan;
 
 /// Source span information for tracking locations in the input
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Span {
     pub start: usize,
     pub end: usize,
@@ -24,6 +25,10 @@ impl Span {
         self.end.saturating_sub(self.start)
     }
     
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
     pub fn combine(&self, other: &Span) -> Span {
         Span {
             start: self.start.min(other.start),
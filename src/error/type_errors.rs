@@ -0,0 +1,24 @@
+use super::span::Span;
+use super::types::BcclError;
+
+impl BcclError {
+    /// Create an error for a value whose static type does not fit where it
+    /// is used.
+    pub fn type_mismatch(message: &str, span: Span, expected: &str, found: &str, suggestion: &str) -> Self {
+        Self::TypeMismatch {
+            message: message.to_string(),
+            span: span.into(),
+            expected: expected.to_string(),
+            found: found.to_string(),
+            suggestion: suggestion.to_string(),
+        }
+    }
+
+    /// Create an error for a type annotation that names no type.
+    pub fn unknown_type(name: &str, span: Span) -> Self {
+        Self::UnknownType {
+            name: name.to_string(),
+            span: span.into(),
+        }
+    }
+}
@@ -60,6 +60,27 @@ pub enum BcclError {
         expected: String,
     },
 
+    #[error("Syntax error: two statements on one line")]
+    #[diagnostic(
+        code(bccl::parser::missing_separator),
+        help("Put each statement on its own line, or separate them with ';'")
+    )]
+    MissingSeparator {
+        #[label("a second statement starts here")]
+        span: SourceSpan,
+    },
+
+    #[error("found {} problems", .errors.len())]
+    #[diagnostic(
+        code(bccl::multiple),
+        help("Each problem is listed below; later ones may be caused by earlier ones")
+    )]
+    Multiple {
+        /// Every error found, in source order
+        #[related]
+        errors: Vec<BcclError>,
+    },
+
     #[error("Runtime error: undefined variable '{name}'")]
     #[diagnostic(code(bccl::runtime::undefined_variable))]
     UndefinedVariable {
@@ -234,6 +255,128 @@ pub enum BcclError {
         #[help]
         suggestion: String,
     },
+
+    #[error("Conversion error: {message}")]
+    #[diagnostic(
+        code(bccl::runtime::conversion_error)
+    )]
+    ConversionError {
+        message: String,
+        target_type: String,
+        #[label("{label}")]
+        span: SourceSpan,
+        label: String,
+        #[help]
+        suggestion: String,
+    },
+
+    #[error("Error in function called from {function_name}()")]
+    #[diagnostic(
+        code(bccl::runtime::callback_error),
+        help("The function passed to {function_name}() failed for one of the values")
+    )]
+    CallbackError {
+        function_name: String,
+        #[label("called from {function_name}()")]
+        span: SourceSpan,
+        /// The error raised inside the callback (always exactly one)
+        #[related]
+        errors: Vec<BcclError>,
+    },
+
+    #[error("Runtime error: {function_name}() requires the '{capability}' capability")]
+    #[diagnostic(
+        code(bccl::runtime::missing_capability)
+    )]
+    MissingCapability {
+        function_name: String,
+        capability: String,
+        #[label("{function_name}() is not available here")]
+        span: SourceSpan,
+        #[help]
+        suggestion: String,
+    },
+
+    #[error("Evaluation stopped: {message}")]
+    #[diagnostic(
+        code(bccl::runtime::cancelled)
+    )]
+    Cancelled {
+        message: String,
+        /// Why the run stopped: `cancelled` or `deadline`
+        reason: String,
+        #[label("stopped while running this statement")]
+        span: SourceSpan,
+        #[help]
+        suggestion: String,
+    },
+
+    #[error("Limit exceeded: {message}")]
+    #[diagnostic(
+        code(bccl::runtime::limit_exceeded)
+    )]
+    LimitExceeded {
+        message: String,
+        /// Which limit was hit: `steps`, `call_depth`, `collection_size`,
+        /// `total_elements` or `string_length`
+        limit: String,
+        /// The configured maximum
+        max: u64,
+        #[label("limit reached here")]
+        span: SourceSpan,
+        #[help]
+        suggestion: String,
+    },
+
+    #[error("Snapshot error: {message}")]
+    #[diagnostic(
+        code(bccl::snapshot::invalid)
+    )]
+    SnapshotError {
+        message: String,
+        #[help]
+        suggestion: String,
+    },
+
+    #[error("Type error: {message}")]
+    #[diagnostic(
+        code(bccl::types::mismatch)
+    )]
+    TypeMismatch {
+        message: String,
+        #[label("expected {expected}, found {found}")]
+        span: SourceSpan,
+        expected: String,
+        found: String,
+        #[help]
+        suggestion: String,
+    },
+
+    #[error("Type error: unknown type '{name}'")]
+    #[diagnostic(
+        code(bccl::types::unknown_type),
+        help("Use one of: int, float, bool, str, list, dict, function, any")
+    )]
+    UnknownType {
+        name: String,
+        #[label("not a type")]
+        span: SourceSpan,
+    },
+
+    #[error("Warning: {message}")]
+    #[diagnostic(
+        code(bccl::lint::warning),
+        severity(Warning)
+    )]
+    LintWarning {
+        message: String,
+        /// Name of the lint rule, e.g. `unused-variable`
+        #[label("{rule}")]
+        span: SourceSpan,
+        rule: String,
+        #[help]
+        suggestion: String,
+    },
 }
 
 pub type BcclResult<T> = Result<T, BcclError>;
\ No newline at end of file
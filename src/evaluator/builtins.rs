@@ -11,17 +11,90 @@
 //! - **FunctionSignature**: Enhanced to handle both parameter types
 //! - **Parameter Validation**: Ensures correct argument count and types for both cases
 //! - **Keyword Arguments**: Support for mixed positional and keyword arguments
+//! - **Keyword Options**: Optional keyword-only arguments such as `sorted(xs, key=f)`
+//! - **Native Functions**: Built-ins that receive a `BuiltinCall` and can call back
+//!   into the evaluator, e.g. `map(f, xs)`
 //! - **Span-aware Errors**: Precise error reporting with source location information
 
 use crate::error::{BcclError, BcclResult, Span};
 use super::value::Value;
+use super::conversions;
+use super::higher_order;
+use super::system;
+use super::capabilities::Capabilities;
+use super::Evaluator;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
+/// Signature of a built-in function implementation over its bound argument values.
+pub type BuiltinFn = fn(&[Value]) -> BcclResult<Value>;
+
+/// Signature of a native built-in that needs the evaluator, argument spans or keyword options.
+pub type NativeFn = fn(&BuiltinCall) -> BcclResult<Value>;
+
+/// The implementation behind a function signature.
+#[derive(Debug, Clone, Copy)]
+pub enum FunctionImpl {
+    /// Plain function over the bound argument values
+    Simple(BuiltinFn),
+    /// Function that receives the full `BuiltinCall` context
+    Native(NativeFn),
+}
+
+/// Everything a native built-in needs to run: bound arguments with their spans,
+/// the keyword bindings, and the evaluator for callbacks.
+pub struct BuiltinCall<'a> {
+    /// The evaluator running the call (used to invoke function values)
+    pub evaluator: &'a Evaluator,
+    /// Name of the built-in being called (used in error messages)
+    pub name: &'a str,
+    /// Bound argument values, in parameter order
+    pub args: &'a [Value],
+    /// Source spans for each bound argument
+    pub spans: &'a [Span],
+    /// Keyword bindings with the span of each value: supplied options, every
+    /// keyword-only parameter (defaults filled in) and the `**kwargs` dictionary
+    pub options: HashMap<String, (Value, Span)>,
+    /// Span of the whole call expression
+    pub span: Span,
+}
+
+impl BuiltinCall<'_> {
+    /// Returns the value and span of a keyword binding.
+    /// 
+    /// Options are only present when the caller supplied them; keyword-only
+    /// parameters and `**kwargs` are always present once bound.
+    pub fn option(&self, name: &str) -> Option<&(Value, Span)> {
+        self.options.get(name)
+    }
+
+    /// Calls a function value on behalf of this built-in.
+    /// 
+    /// Any error raised by the callback is wrapped in a `CallbackError` so the
+    /// diagnostic notes which built-in invoked it ("called from map()"). A
+    /// `Cancelled` error is passed through unchanged, since stopping the run
+    /// is not the callback's fault.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `function` - The function value to call
+    /// * `function_span` - Span of the expression that produced the function
+    /// * `args` - Positional arguments with their source spans
+    pub fn call_function(&self, function: &Value, function_span: Span, args: &[(Value, Span)]) -> BcclResult<Value> {
+        self.evaluator
+            .call_value(function, args, &[], function_span)
+            .map_err(|error| match error {
+                BcclError::Cancelled { .. } => error,
+                error => BcclError::callback_error(self.name, self.span, error),
+            })
+    }
+}
+
 /// Defines the parameter requirements for a function.
 #[derive(Debug, Clone)]
 pub enum ParameterSpec {
     /// Fixed number of parameters with specific names
-    /// Example: max(a, b) -> Fixed(["a", "b"])
+    /// Example: pow(base, exp) -> Fixed(["base", "exp"])
     Fixed(Vec<String>),
     /// Minimum required parameters plus variadic parameters
     /// Example: sum(values...) -> Variadic { required: [], variadic_name: "values" }
@@ -30,6 +103,138 @@ pub enum ParameterSpec {
         required: Vec<String>, 
         variadic_name: String 
     },
+    /// Full signature with defaults, keyword-only parameters and `**kwargs`
+    /// Example: f(a, b=2, *rest, flag=false, **opts) -> Full(Parameters { .. })
+    Full(Parameters),
+}
+
+impl ParameterSpec {
+    /// Expands this specification into the general `Parameters` form used by the binder.
+    /// 
+    /// `Fixed` and `Variadic` are shorthands: their parameters have no defaults,
+    /// no keyword-only parameters and no `**kwargs`.
+    pub fn to_parameters(&self) -> Parameters {
+        match self {
+            ParameterSpec::Fixed(params) => Parameters {
+                positional: params.iter().map(|name| Parameter::required(name)).collect(),
+                ..Parameters::default()
+            },
+            ParameterSpec::Variadic { required, variadic_name } => Parameters {
+                positional: required.iter().map(|name| Parameter::required(name)).collect(),
+                rest: Some(variadic_name.clone()),
+                ..Parameters::default()
+            },
+            ParameterSpec::Full(parameters) => parameters.clone(),
+        }
+    }
+}
+
+/// A single named parameter, optionally with a default value.
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    /// Parameter name (used for keyword arguments and error messages)
+    pub name: String,
+    /// Value used when the caller does not supply the parameter; `None` means required
+    pub default: Option<Value>,
+}
+
+impl Parameter {
+    /// Creates a parameter the caller must supply.
+    pub fn required(name: &str) -> Self {
+        Self { name: name.to_string(), default: None }
+    }
+    
+    /// Creates a parameter that falls back to `default` when omitted.
+    pub fn with_default(name: &str, default: Value) -> Self {
+        Self { name: name.to_string(), default: Some(default) }
+    }
+}
+
+/// The general parameter list of a function: `f(a, b=2, *rest, flag=false, **opts)`.
+/// 
+/// # Layout
+/// 
+/// - **positional**: Filled by position or by keyword, in order (`a, b=2`)
+/// - **rest**: Collects extra positional arguments (`*rest`)
+/// - **keyword_only**: Can only be passed by keyword (`flag=false`)
+/// - **var_keyword**: Collects unknown keyword arguments into a dictionary (`**opts`)
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// # use bccl::{Parameters, Value};
+/// let params = Parameters::new()
+///     .positional("a")
+///     .positional_with_default("b", Value::Integer(2))
+///     .rest("rest")
+///     .keyword_only_with_default("flag", Value::Boolean(false))
+///     .var_keyword("opts");
+/// assert_eq!(params.positional.len(), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Parameters {
+    /// Parameters that may be passed by position or keyword
+    pub positional: Vec<Parameter>,
+    /// Name of the parameter collecting extra positional arguments (`*rest`)
+    pub rest: Option<String>,
+    /// Parameters that may only be passed by keyword
+    pub keyword_only: Vec<Parameter>,
+    /// Name of the dictionary collecting extra keyword arguments (`**opts`)
+    pub var_keyword: Option<String>,
+}
+
+impl Parameters {
+    /// Creates an empty parameter list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Adds a required positional parameter.
+    pub fn positional(mut self, name: &str) -> Self {
+        self.positional.push(Parameter::required(name));
+        self
+    }
+    
+    /// Adds a positional parameter with a default value.
+    pub fn positional_with_default(mut self, name: &str, default: Value) -> Self {
+        self.positional.push(Parameter::with_default(name, default));
+        self
+    }
+    
+    /// Collects extra positional arguments under `name`.
+    pub fn rest(mut self, name: &str) -> Self {
+        self.rest = Some(name.to_string());
+        self
+    }
+    
+    /// Adds a required keyword-only parameter.
+    pub fn keyword_only(mut self, name: &str) -> Self {
+        self.keyword_only.push(Parameter::required(name));
+        self
+    }
+    
+    /// Adds a keyword-only parameter with a default value.
+    pub fn keyword_only_with_default(mut self, name: &str, default: Value) -> Self {
+        self.keyword_only.push(Parameter::with_default(name, default));
+        self
+    }
+    
+    /// Collects unknown keyword arguments into a dictionary named `name`.
+    pub fn var_keyword(mut self, name: &str) -> Self {
+        self.var_keyword = Some(name.to_string());
+        self
+    }
+}
+
+/// Arguments after binding a call against a signature.
+#[derive(Debug, Clone, Default)]
+pub struct BoundArguments {
+    /// Positional parameters in declaration order, followed by the `*rest` values
+    pub args: Vec<Value>,
+    /// Source spans for each value in `args`
+    pub spans: Vec<Span>,
+    /// Keyword-only parameters, supplied options and the `**kwargs` dictionary, by name
+    pub keywords: HashMap<String, (Value, Span)>,
 }
 
 /// Represents a function signature with parameter validation and implementation.
@@ -50,7 +255,7 @@ pub enum ParameterSpec {
 /// 
 /// # Examples
 /// 
-/// ```rust
+/// ```rust,ignore
 /// // Fixed parameters: max(a, b)
 /// let max_sig = FunctionSignature::new_fixed("max", vec!["a", "b"], builtin_max_impl);
 /// 
@@ -66,8 +271,10 @@ pub struct FunctionSignature {
     pub name: String,
     /// Parameter specification (fixed or variadic)
     pub parameters: ParameterSpec,
+    /// Optional keyword-only arguments, passed to native functions as `BuiltinCall::options`
+    pub options: Vec<String>,
     /// Function implementation
-    pub function: fn(&[Value]) -> BcclResult<Value>,
+    pub function: FunctionImpl,
 }
 
 impl FunctionSignature {
@@ -81,16 +288,17 @@ impl FunctionSignature {
     /// 
     /// # Examples
     /// 
-    /// ```rust
+    /// ```rust,ignore
     /// let max_sig = FunctionSignature::new_fixed("max", vec!["a", "b"], builtin_max_impl);
     /// ```
-    pub fn new_fixed(name: &str, parameters: Vec<&str>, function: fn(&[Value]) -> BcclResult<Value>) -> Self {
+    pub fn new_fixed(name: &str, parameters: Vec<&str>, function: BuiltinFn) -> Self {
         Self {
             name: name.to_string(),
             parameters: ParameterSpec::Fixed(
                 parameters.into_iter().map(|s| s.to_string()).collect()
             ),
-            function,
+            options: Vec::new(),
+            function: FunctionImpl::Simple(function),
         }
     }
     
@@ -105,24 +313,103 @@ impl FunctionSignature {
     /// 
     /// # Examples
     /// 
-    /// ```rust
+    /// ```rust,ignore
     /// // sum(values...) - no required parameters, all are variadic
     /// let sum_sig = FunctionSignature::new_variadic("sum", vec![], "values", builtin_sum_impl);
     /// 
     /// // clamp(value, bounds...) - one required, rest variadic
     /// let clamp_sig = FunctionSignature::new_variadic("clamp", vec!["value"], "bounds", builtin_clamp_impl);
     /// ```
-    pub fn new_variadic(name: &str, required: Vec<&str>, variadic_name: &str, function: fn(&[Value]) -> BcclResult<Value>) -> Self {
+    pub fn new_variadic(name: &str, required: Vec<&str>, variadic_name: &str, function: BuiltinFn) -> Self {
         Self {
             name: name.to_string(),
             parameters: ParameterSpec::Variadic {
                 required: required.into_iter().map(|s| s.to_string()).collect(),
                 variadic_name: variadic_name.to_string(),
             },
-            function,
+            options: Vec::new(),
+            function: FunctionImpl::Simple(function),
+        }
+    }
+    
+    /// Creates a new native function signature with fixed parameters.
+    /// 
+    /// Native functions receive a `BuiltinCall`, giving them argument spans,
+    /// keyword options and the evaluator (to call function values).
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust,ignore
+    /// let map_sig = FunctionSignature::new_native("map", vec!["function", "values"], builtin_map);
+    /// ```
+    pub fn new_native(name: &str, parameters: Vec<&str>, function: NativeFn) -> Self {
+        Self {
+            name: name.to_string(),
+            parameters: ParameterSpec::Fixed(
+                parameters.into_iter().map(|s| s.to_string()).collect()
+            ),
+            options: Vec::new(),
+            function: FunctionImpl::Native(function),
         }
     }
     
+    /// Creates a new native function signature with variadic parameters.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust,ignore
+    /// let max_sig = FunctionSignature::new_native_variadic("max", vec![], "values", builtin_max_native)
+    ///     .with_options(vec!["key", "default"]);
+    /// ```
+    pub fn new_native_variadic(name: &str, required: Vec<&str>, variadic_name: &str, function: NativeFn) -> Self {
+        Self {
+            name: name.to_string(),
+            parameters: ParameterSpec::Variadic {
+                required: required.into_iter().map(|s| s.to_string()).collect(),
+                variadic_name: variadic_name.to_string(),
+            },
+            options: Vec::new(),
+            function: FunctionImpl::Native(function),
+        }
+    }
+    
+    /// Creates a new native function signature from a full parameter list.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust,ignore
+    /// // sorted(values, *, reverse=false)
+    /// let params = Parameters::new()
+    ///     .positional("values")
+    ///     .keyword_only_with_default("reverse", Value::Boolean(false));
+    /// let sorted_sig = FunctionSignature::new_native_with_parameters("sorted", params, builtin_sorted);
+    /// ```
+    pub fn new_native_with_parameters(name: &str, parameters: Parameters, function: NativeFn) -> Self {
+        Self {
+            name: name.to_string(),
+            parameters: ParameterSpec::Full(parameters),
+            options: Vec::new(),
+            function: FunctionImpl::Native(function),
+        }
+    }
+    
+    /// Declares optional keyword-only arguments for this signature.
+    /// 
+    /// Options can only be passed by keyword (`sorted(xs, key=f)`) and are
+    /// omitted from the bound positional arguments. Unlike keyword-only
+    /// parameters they have no default: an omitted option is simply absent.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust,ignore
+    /// let sorted_sig = FunctionSignature::new_native("sorted", vec!["values"], builtin_sorted)
+    ///     .with_options(vec!["key"]);
+    /// ```
+    pub fn with_options(mut self, options: Vec<&str>) -> Self {
+        self.options = options.into_iter().map(|s| s.to_string()).collect();
+        self
+    }
+    
     /// Legacy constructor for backward compatibility.
     /// 
     /// Creates a fixed-parameter function signature.
@@ -130,7 +417,7 @@ impl FunctionSignature {
     /// # Deprecated
     /// 
     /// Use `new_fixed()` for better clarity.
-    pub fn new(name: &str, parameters: Vec<&str>, function: fn(&[Value]) -> BcclResult<Value>) -> Self {
+    pub fn new(name: &str, parameters: Vec<&str>, function: BuiltinFn) -> Self {
         Self::new_fixed(name, parameters, function)
     }
 
@@ -145,6 +432,7 @@ impl FunctionSignature {
     /// 
     /// # Arguments
     /// 
+    /// * `evaluator` - The evaluator running the call (native functions may call back into it)
     /// * `args` - Positional arguments with their source spans
     /// * `kwargs` - Keyword arguments with their source spans
     /// * `span` - Overall function call span (for general errors)
@@ -156,175 +444,195 @@ impl FunctionSignature {
     /// 
     /// # Examples
     /// 
-    /// ```rust
+    /// ```rust,ignore
     /// // Fixed: max(5, 10)
     /// let args = vec![(Value::Integer(5), span1), (Value::Integer(10), span2)];
-    /// let result = sig.call_with_spans(&args, &[], call_span)?;
+    /// let result = sig.call_with_spans(&evaluator, &args, &[], call_span)?;
     /// 
     /// // Variadic: sum(1, 2, 3, 4)
     /// let args = vec![(Value::Integer(1), span1), (Value::Integer(2), span2), ...];
-    /// let result = sig.call_with_spans(&args, &[], call_span)?;
+    /// let result = sig.call_with_spans(&evaluator, &args, &[], call_span)?;
     /// 
-    /// // Mixed: clamp(value=5, bounds=1, bounds=2, bounds=10)
+    /// // Mixed: clamp(5, 1, 10) or clamp(value=5, bounds=[1, 10])
     /// ```
     /// 
     /// # Error Cases
     /// 
     /// - Fixed functions: Too many/few arguments, unknown parameters
-    /// - Variadic functions: Too few required arguments, unknown parameters
-    pub fn call_with_spans(&self, args: &[(Value, Span)], kwargs: &[(String, Value, Span)], span: Span) -> BcclResult<Value> {
-        match &self.parameters {
-            ParameterSpec::Fixed(params) => {
-                self.call_fixed_with_spans(params, args, kwargs, span)
-            }
-            ParameterSpec::Variadic { required, variadic_name } => {
-                self.call_variadic_with_spans(required, variadic_name, args, kwargs, span)
-            }
-        }
+    /// - Variadic functions: Too few required arguments, unknown parameters,
+    ///   the variadic name passed by keyword alongside extra positional arguments
+    /// 
+    /// See `bind_arguments()` for the full binding algorithm.
+    pub fn call_with_spans(&self, evaluator: &Evaluator, args: &[(Value, Span)], kwargs: &[(String, Value, Span)], span: Span) -> BcclResult<Value> {
+        let bound = self.bind_arguments(args, kwargs, span)?;
+        self.call_impl(evaluator, &bound.args, &bound.spans, bound.keywords, span)
     }
-
-    /// Handles fixed-parameter function calls.
-    fn call_fixed_with_spans(&self, params: &[String], args: &[(Value, Span)], kwargs: &[(String, Value, Span)], span: Span) -> BcclResult<Value> {
-        // Build final argument list respecting parameter order
-        let mut final_args = vec![None; params.len()];
-        let mut final_spans = vec![span; params.len()];
+    
+    /// Binds positional and keyword arguments to this signature's parameters.
+    /// 
+    /// This single binder serves every `ParameterSpec`. Write the signature as
+    /// positional parameters `p1..pn` (some with defaults), an optional `*rest`,
+    /// keyword-only parameters, an optional `**kwargs` and the declared options,
+    /// and the call as positional values `a1..am` plus keyword pairs `k=v`.
+    /// 
+    /// # Binding Algorithm
+    /// 
+    /// 1. **Positional phase.** `ai` binds `pi` for `i <= n`. If `m > n`, the
+    ///    surplus `a(n+1)..am` bind `*rest` in call order; without `*rest` the
+    ///    call fails with `WrongArgumentCount` at `a(n+1)`.
+    /// 2. **Keyword phase.** Each pair, in call order, binds:
+    ///    1. an option or keyword-only parameter named `k`;
+    ///    2. else the positional parameter `pj` named `k`, unless `pj` is
+    ///       already bound (positionally or by an earlier keyword);
+    ///    3. else, if `k` names `*rest`, `v` must be a list and supplies all
+    ///       of the `*rest` values; the call is rejected if phase 1 already
+    ///       bound surplus values to `*rest` (it would be ambiguous which come first);
+    ///    4. else an entry of `**kwargs`;
+    ///    5. else the call fails with an unknown parameter.
+    ///    
+    ///    Binding any name twice is a duplicate parameter error.
+    /// 3. **Completion.** Unbound parameters take their default, or the call
+    ///    fails with a missing parameter. `**kwargs` becomes a dictionary
+    ///    (empty if nothing was collected).
+    /// 
+    /// # Guarantees
+    /// 
+    /// - `bound.args` is always `p1..pn` in declaration order followed by the
+    ///   `*rest` values in call order, whatever order the keywords came in
+    /// - A keyword never moves another value: every call either binds each
+    ///   value to exactly one parameter or is rejected
+    /// 
+    /// # Returns
+    /// 
+    /// * `Ok(bound)` - Values in parameter order plus keyword bindings
+    /// * `Err(error)` - Too many arguments, duplicates, unknown or missing
+    ///   parameters, or `*rest` given both positionally and by keyword
+    pub fn bind_arguments(&self, args: &[(Value, Span)], kwargs: &[(String, Value, Span)], span: Span) -> BcclResult<BoundArguments> {
+        let params = self.parameters.to_parameters();
         
-        // First, fill in positional arguments
-        if args.len() > params.len() {
-            return Err(BcclError::wrong_argument_count(&self.name, params.len(), args.len() + kwargs.len()));
+        // Too many positional arguments without a `*rest` to absorb them
+        if args.len() > params.positional.len() && params.rest.is_none() {
+            let surplus_span = args[params.positional.len()].1;
+            return Err(BcclError::wrong_argument_count_with_span(&self.name, params.positional.len(), args.len(), surplus_span));
         }
         
-        for (i, (value, arg_span)) in args.iter().enumerate() {
-            final_args[i] = Some(value.clone());
-            final_spans[i] = *arg_span;
+        let mut positional: Vec<Option<(Value, Span)>> = vec![None; params.positional.len()];
+        let mut rest = Vec::new();
+        for (i, arg) in args.iter().enumerate() {
+            match positional.get_mut(i) {
+                Some(slot) => *slot = Some(arg.clone()),
+                None => rest.push(arg.clone()),
+            }
         }
         
-        // Then, fill in keyword arguments
+        let mut keywords = HashMap::new();
+        let mut extra_keywords = HashMap::new();
+        let mut rest_by_keyword = false;
         for (param_name, value, arg_span) in kwargs {
-            // Find the parameter index
-            if let Some(param_index) = params.iter().position(|p| p == param_name) {
+            let is_keyword = self.options.contains(param_name)
+                || params.keyword_only.iter().any(|p| &p.name == param_name);
+            
+            if is_keyword {
+                if keywords.insert(param_name.clone(), (value.clone(), *arg_span)).is_some() {
+                    return Err(BcclError::duplicate_parameter(&self.name, param_name, *arg_span));
+                }
+            } else if let Some(index) = params.positional.iter().position(|p| &p.name == param_name) {
                 // Check if this parameter was already provided positionally
-                if final_args[param_index].is_some() {
+                if positional[index].is_some() {
                     return Err(BcclError::duplicate_parameter(&self.name, param_name, *arg_span));
                 }
-                final_args[param_index] = Some(value.clone());
-                final_spans[param_index] = *arg_span;
-            } else {
-                // Parameter name not found in function signature
-                return Err(BcclError::unknown_parameter(&self.name, param_name, *arg_span, params));
-            }
-        }
-        
-        // Check that all parameters are provided
-        let mut provided_args = Vec::new();
-        let mut provided_spans = Vec::new();
-        for (i, (arg_opt, arg_span)) in final_args.into_iter().zip(final_spans.into_iter()).enumerate() {
-            match arg_opt {
-                Some(value) => {
-                    provided_args.push(value);
-                    provided_spans.push(arg_span);
+                positional[index] = Some((value.clone(), *arg_span));
+            } else if params.rest.as_ref() == Some(param_name) {
+                if rest_by_keyword {
+                    return Err(BcclError::duplicate_parameter(&self.name, param_name, *arg_span));
                 }
-                None => {
-                    return Err(BcclError::missing_parameter(&self.name, &params[i], span));
+                if !rest.is_empty() {
+                    return Err(BcclError::variadic_keyword_argument(&self.name, param_name, *arg_span));
+                }
+                match value {
+                    Value::List(items) => rest.extend(items.iter().map(|item| (item.clone(), *arg_span))),
+                    other => {
+                        return Err(BcclError::type_error(
+                            &format!("Parameter '{}' of function '{}' takes a list when passed by keyword", param_name, self.name),
+                            *arg_span,
+                            "list",
+                            other.type_name()
+                        ));
+                    }
+                }
+                rest_by_keyword = true;
+            } else if params.var_keyword.is_some() {
+                if extra_keywords.insert(param_name.clone(), value.clone()).is_some() {
+                    return Err(BcclError::duplicate_parameter(&self.name, param_name, *arg_span));
                 }
+            } else {
+                return Err(BcclError::unknown_parameter(&self.name, param_name, *arg_span, &self.keyword_names(&params)));
             }
         }
         
-        // Call the actual function with span information
-        self.call_impl(&provided_args, &provided_spans)
-    }
-    
-    /// Handles variadic function calls.
-    /// 
-    /// For variadic functions, arguments are processed as:
-    /// 1. Required parameters (filled from positional args first, then kwargs)
-    /// 2. Variadic parameters (remaining positional args, plus kwargs with variadic_name)
-    fn call_variadic_with_spans(&self, required: &[String], variadic_name: &str, args: &[(Value, Span)], kwargs: &[(String, Value, Span)], span: Span) -> BcclResult<Value> {
-        // Check minimum argument count
-        if args.len() + kwargs.len() < required.len() {
-            return Err(BcclError::wrong_argument_count(&self.name, required.len(), args.len() + kwargs.len()));
-        }
-        
-        let mut final_args = Vec::new();
-        let mut final_spans = Vec::new();
-        let mut required_filled = vec![false; required.len()];
-        
-        // Fill required parameters from positional arguments first
-        let mut pos_index = 0;
-        for (i, req_param) in required.iter().enumerate() {
-            if pos_index < args.len() {
-                final_args.push(args[pos_index].0.clone());
-                final_spans.push(args[pos_index].1);
-                required_filled[i] = true;
-                pos_index += 1;
-            }
+        // Fill omitted parameters from their defaults
+        let mut bound = BoundArguments::default();
+        for (param, slot) in params.positional.iter().zip(positional) {
+            let (value, arg_span) = match (slot, &param.default) {
+                (Some(provided), _) => provided,
+                (None, Some(default)) => (default.clone(), span),
+                (None, None) => return Err(BcclError::missing_parameter(&self.name, &param.name, span)),
+            };
+            bound.args.push(value);
+            bound.spans.push(arg_span);
         }
-        
-        // Remaining positional arguments become variadic parameters
-        for i in pos_index..args.len() {
-            final_args.push(args[i].0.clone());
-            final_spans.push(args[i].1);
+        for (value, arg_span) in rest {
+            bound.args.push(value);
+            bound.spans.push(arg_span);
         }
         
-        // Process keyword arguments
-        for (param_name, value, arg_span) in kwargs {
-            if let Some(req_index) = required.iter().position(|p| p == param_name) {
-                // This is a required parameter
-                if required_filled[req_index] {
-                    return Err(BcclError::duplicate_parameter(&self.name, param_name, *arg_span));
-                }
-                
-                // Find where to insert this required parameter
-                if req_index < required.len() {
-                    // Insert at the correct position for required parameters
-                    final_args.insert(req_index, value.clone());
-                    final_spans.insert(req_index, *arg_span);
-                    required_filled[req_index] = true;
+        for param in &params.keyword_only {
+            if !keywords.contains_key(&param.name) {
+                match &param.default {
+                    Some(default) => {
+                        keywords.insert(param.name.clone(), (default.clone(), span));
+                    }
+                    None => return Err(BcclError::missing_parameter(&self.name, &param.name, span)),
                 }
-            } else if param_name == variadic_name {
-                // This is a variadic parameter - add to the end
-                final_args.push(value.clone());
-                final_spans.push(*arg_span);
-            } else {
-                // Unknown parameter
-                let mut all_params = required.to_vec();
-                all_params.push(variadic_name.to_string());
-                return Err(BcclError::unknown_parameter(&self.name, param_name, *arg_span, &all_params));
             }
         }
-        
-        // Check that all required parameters are provided
-        for (i, &filled) in required_filled.iter().enumerate() {
-            if !filled {
-                return Err(BcclError::missing_parameter(&self.name, &required[i], span));
-            }
+        if let Some(var_keyword) = &params.var_keyword {
+            keywords.insert(var_keyword.clone(), (Value::dict(extra_keywords), span));
         }
+        bound.keywords = keywords;
         
-        // Call the actual function with span information
-        self.call_impl(&final_args, &final_spans)
+        Ok(bound)
+    }
+    
+    /// All names accepted by keyword, for "unknown parameter" suggestions.
+    fn keyword_names(&self, params: &Parameters) -> Vec<String> {
+        params.positional.iter().map(|p| p.name.clone())
+            .chain(params.rest.clone())
+            .chain(params.keyword_only.iter().map(|p| p.name.clone()))
+            .chain(self.options.iter().cloned())
+            .collect()
     }
     
-    /// Calls the function implementation with span information for enhanced error reporting.
+    /// Calls the function implementation with the bound arguments.
     /// 
-    /// This method dispatches to span-aware implementations for built-in functions
-    /// that provide better error messages with precise source locations.
+    /// Native functions receive the argument spans in their `BuiltinCall`;
+    /// simple functions only see the values.
     /// 
     /// # Arguments
     /// 
+    /// * `evaluator` - The evaluator running the call
     /// * `args` - Function arguments (required parameters first, then variadic)
     /// * `spans` - Source spans for each argument
+    /// * `options` - Keyword bindings (options, keyword-only parameters, `**kwargs`)
+    /// * `span` - Overall function call span
     /// 
     /// # Returns
     /// 
     /// Function result or error with precise span information.
-    fn call_impl(&self, args: &[Value], spans: &[Span]) -> BcclResult<Value> {
-        // For built-in functions, we need to handle type checking with proper spans
-        match self.name.as_str() {
-            "max" => builtin_max_with_spans(args, spans),
-            "min" => builtin_min_with_spans(args, spans),
-            "sum" => builtin_sum_with_spans(args, spans),
-            "product" => builtin_product_with_spans(args, spans),
-            _ => (self.function)(args), // Fallback to legacy function call
+    fn call_impl(&self, evaluator: &Evaluator, args: &[Value], spans: &[Span], options: HashMap<String, (Value, Span)>, span: Span) -> BcclResult<Value> {
+        match self.function {
+            FunctionImpl::Native(native) => native(&BuiltinCall { evaluator, name: &self.name, args, spans, options, span }),
+            FunctionImpl::Simple(function) => function(args),
         }
     }
 
@@ -345,13 +653,14 @@ impl FunctionSignature {
     /// 
     /// # Deprecated
     /// 
-    /// Prefer `call_with_spans()` for better error reporting.
+    /// Prefer `call_with_spans()` for better error reporting. Native functions
+    /// run against a fresh evaluator, so callbacks only see the built-ins.
     pub fn call(&self, args: &[Value], kwargs: &[(String, Value)], span: Span) -> BcclResult<Value> {
         // Convert to span-aware call by using the general span for all arguments
         let args_with_spans: Vec<(Value, Span)> = args.iter().map(|v| (v.clone(), span)).collect();
         let kwargs_with_spans: Vec<(String, Value, Span)> = kwargs.iter().map(|(k, v)| (k.clone(), v.clone(), span)).collect();
         
-        self.call_with_spans(&args_with_spans, &kwargs_with_spans, span)
+        self.call_with_spans(&Evaluator::new(), &args_with_spans, &kwargs_with_spans, span)
     }
 }
 
@@ -363,8 +672,7 @@ impl FunctionSignature {
 /// 
 /// # Arguments
 /// 
-/// * `args` - Function arguments (all should be numbers)
-/// * `spans` - Source spans for each argument (for error reporting)
+/// * `call` - Bound arguments (all should be numbers) with their spans
 /// 
 /// # Returns
 /// 
@@ -381,16 +689,16 @@ impl FunctionSignature {
 /// 
 /// Integers are automatically converted to floats for calculation.
 /// The result is always a Number (float) to maintain consistency.
-fn builtin_sum_with_spans(args: &[Value], spans: &[Span]) -> BcclResult<Value> {
-    if args.is_empty() {
+fn builtin_sum(call: &BuiltinCall) -> BcclResult<Value> {
+    if call.args.is_empty() {
         return Err(BcclError::wrong_argument_count("sum", 1, 0));
     }
     
     let mut total = 0.0;
-    for (i, arg) in args.iter().enumerate() {
+    for (i, (arg, span)) in call.args.iter().zip(call.spans).enumerate() {
         let num = arg.as_number()
             .ok_or_else(|| BcclError::function_argument_type_error_with_span(
-                "sum", i + 1, "number", arg.type_name(), &arg.display(), spans[i]
+                "sum", i + 1, "number", arg.type_name(), &arg.display(), *span
             ))?;
         total += num;
     }
@@ -404,8 +712,7 @@ fn builtin_sum_with_spans(args: &[Value], spans: &[Span]) -> BcclResult<Value> {
 /// 
 /// # Arguments
 /// 
-/// * `args` - Function arguments (all should be numbers)
-/// * `spans` - Source spans for each argument (for error reporting)
+/// * `call` - Bound arguments (all should be numbers) with their spans
 /// 
 /// # Returns
 /// 
@@ -422,16 +729,16 @@ fn builtin_sum_with_spans(args: &[Value], spans: &[Span]) -> BcclResult<Value> {
 /// 
 /// Integers are automatically converted to floats for calculation.
 /// The result is always a Number (float) to maintain consistency.
-fn builtin_product_with_spans(args: &[Value], spans: &[Span]) -> BcclResult<Value> {
-    if args.is_empty() {
+fn builtin_product(call: &BuiltinCall) -> BcclResult<Value> {
+    if call.args.is_empty() {
         return Err(BcclError::wrong_argument_count("product", 1, 0));
     }
     
     let mut result = 1.0;
-    for (i, arg) in args.iter().enumerate() {
+    for (i, (arg, span)) in call.args.iter().zip(call.spans).enumerate() {
         let num = arg.as_number()
             .ok_or_else(|| BcclError::function_argument_type_error_with_span(
-                "product", i + 1, "number", arg.type_name(), &arg.display(), spans[i]
+                "product", i + 1, "number", arg.type_name(), &arg.display(), *span
             ))?;
         result *= num;
     }
@@ -441,153 +748,163 @@ fn builtin_product_with_spans(args: &[Value], spans: &[Span]) -> BcclResult<Valu
 
 // ===== EXISTING FUNCTIONS (Updated for span-aware dispatch) =====
 
-/// Implementation of the `max(a, b)` built-in function with span-aware error reporting.
+/// Implementation of the `max(values..., key=, default=)` built-in function.
 /// 
-/// Returns the larger of two numeric values. Both arguments must be numbers or integers.
+/// Returns the largest of its arguments, or of the items of a single list argument.
 /// 
 /// # Arguments
 /// 
-/// * `args` - Function arguments [a, b]
-/// * `spans` - Source spans for each argument (for error reporting)
+/// * `call` - Bound arguments; either N scalars or one list/dictionary
+/// 
+/// # Options
+/// 
+/// * `key` - Function applied to each candidate; candidates are compared by its results
+/// * `default` - Returned when there are no candidates (otherwise an error)
 /// 
 /// # Returns
 /// 
-/// * `Ok(Value::Number(max))` - The larger value
-/// * `Err(type_error)` - If either argument is not numeric
+/// * `Ok(value)` - The largest candidate (the first one on ties)
+/// * `Err(error)` - If there are no candidates and no `default`, or two
+///   candidates cannot be compared
 /// 
 /// # Examples
 /// 
-/// - `max(5, 10)` → `10`
-/// - `max(3.14, 2.7)` → `3.14`
-/// - `max(42, 3.14)` → `42` (integer coerced to float)
+/// - `max(5, 10, 3)` → `10`
+/// - `max([4, 7, 1])` → `7`
+/// - `max("pear", "apple")` → `"pear"`
+/// - `max([], default=0)` → `0`
+/// - `max("9", "10", key=int)` → `"10"`
 /// 
 /// # Type Coercion
 /// 
-/// Integers are automatically converted to floats for comparison.
-/// The result is always a Number (float) to maintain consistency.
-fn builtin_max_with_spans(args: &[Value], spans: &[Span]) -> BcclResult<Value> {
-    let a = args[0].as_number()
-        .ok_or_else(|| BcclError::function_argument_type_error_with_span(
-            "max", 1, "number", args[0].type_name(), &args[0].display(), spans[0]
-        ))?;
-    let b = args[1].as_number()
-        .ok_or_else(|| BcclError::function_argument_type_error_with_span(
-            "max", 2, "number", args[1].type_name(), &args[1].display(), spans[1]
-        ))?;
-    
-    Ok(Value::Number(a.max(b)))
+/// Numbers and integers compare numerically and strings compare
+/// lexicographically, as with `<` and `>`. Without `key`, a numeric result
+/// is always a Number (float) to maintain consistency.
+fn builtin_max_native(call: &BuiltinCall) -> BcclResult<Value> {
+    builtin_extreme(call, Ordering::Greater)
 }
 
-/// Implementation of the `min(a, b)` built-in function with span-aware error reporting.
+/// Implementation of the `min(values..., key=, default=)` built-in function.
 /// 
-/// Returns the smaller of two numeric values. Both arguments must be numbers or integers.
-/// 
-/// # Arguments
-/// 
-/// * `args` - Function arguments [a, b]
-/// * `spans` - Source spans for each argument (for error reporting)
-/// 
-/// # Returns
-/// 
-/// * `Ok(Value::Number(min))` - The smaller value
-/// * `Err(type_error)` - If either argument is not numeric
+/// Returns the smallest of its arguments, or of the items of a single list
+/// argument. Options, ties and type coercion follow `max()`.
 /// 
 /// # Examples
 /// 
-/// - `min(5, 10)` → `5`
-/// - `min(3.14, 2.7)` → `2.7`
-/// - `min(42, 3.14)` → `3.14` (integer coerced to float)
-/// 
-/// # Type Coercion
-/// 
-/// Integers are automatically converted to floats for comparison.
-/// The result is always a Number (float) to maintain consistency.
-fn builtin_min_with_spans(args: &[Value], spans: &[Span]) -> BcclResult<Value> {
-    let a = args[0].as_number()
-        .ok_or_else(|| BcclError::function_argument_type_error_with_span(
-            "min", 1, "number", args[0].type_name(), &args[0].display(), spans[0]
-        ))?;
-    let b = args[1].as_number()
-        .ok_or_else(|| BcclError::function_argument_type_error_with_span(
-            "min", 2, "number", args[1].type_name(), &args[1].display(), spans[1]
-        ))?;
-    
-    Ok(Value::Number(a.min(b)))
+/// - `min(5, 10, 3)` → `3`
+/// - `min({"b": 1, "a": 2})` → `"a"` (dictionary keys)
+/// - `min("9", "10", key=int)` → `"9"`
+fn builtin_min_native(call: &BuiltinCall) -> BcclResult<Value> {
+    builtin_extreme(call, Ordering::Less)
 }
 
-// ===== LEGACY IMPLEMENTATIONS =====
-
-// Legacy implementations for backward compatibility
-fn builtin_max_impl(args: &[Value]) -> BcclResult<Value> {
-    let a = args[0].as_number()
-        .ok_or_else(|| BcclError::function_argument_type_error("max", 1, "number", args[0].type_name()))?;
-    let b = args[1].as_number()
-        .ok_or_else(|| BcclError::function_argument_type_error("max", 2, "number", args[1].type_name()))?;
-    
-    Ok(Value::Number(a.max(b)))
-}
-
-fn builtin_min_impl(args: &[Value]) -> BcclResult<Value> {
-    let a = args[0].as_number()
-        .ok_or_else(|| BcclError::function_argument_type_error("min", 1, "number", args[0].type_name()))?;
-    let b = args[1].as_number()
-        .ok_or_else(|| BcclError::function_argument_type_error("min", 2, "number", args[1].type_name()))?;
-    
-    Ok(Value::Number(a.min(b)))
-}
-
-fn builtin_sum_impl(args: &[Value]) -> BcclResult<Value> {
-    if args.is_empty() {
-        return Err(BcclError::wrong_argument_count("sum", 1, 0));
-    }
+/// Shared implementation of `max()` and `min()`.
+/// 
+/// `wanted` is the ordering a candidate's key must have against the current
+/// best key to replace it: `Greater` for `max()`, `Less` for `min()`.
+fn builtin_extreme(call: &BuiltinCall, wanted: Ordering) -> BcclResult<Value> {
+    // Candidates with the span and argument number used in error messages.
+    // A single list (or dictionary, via its sorted keys) supplies the candidates.
+    let candidates: Vec<(Value, Span, usize)> = match call.args {
+        [Value::List(items)] => items.iter().map(|item| (item.clone(), call.spans[0], 1)).collect(),
+        [Value::Dictionary(dict)] => {
+            let mut keys: Vec<&String> = dict.keys().collect();
+            keys.sort();
+            keys.into_iter().map(|k| (Value::string(k.clone()), call.spans[0], 1)).collect()
+        }
+        args => args.iter()
+            .zip(call.spans)
+            .enumerate()
+            .map(|(i, (value, span))| (value.clone(), *span, i + 1))
+            .collect(),
+    };
     
-    let mut total = 0.0;
-    for (i, arg) in args.iter().enumerate() {
-        let num = arg.as_number()
-            .ok_or_else(|| BcclError::function_argument_type_error("sum", i + 1, "number", arg.type_name()))?;
-        total += num;
-    }
+    let key = call.option("key");
+    let mut best: Option<(Value, Value)> = None;
     
-    Ok(Value::Number(total))
-}
-
-fn builtin_product_impl(args: &[Value]) -> BcclResult<Value> {
-    if args.is_empty() {
-        return Err(BcclError::wrong_argument_count("product", 1, 0));
+    for (item, item_span, arg_number) in candidates {
+        let item_key = match key {
+            Some((function, function_span)) => call.call_function(function, *function_span, &[(item.clone(), item_span)])?,
+            None => item.clone(),
+        };
+        if !matches!(item_key, Value::Number(_) | Value::Integer(_) | Value::String(_)) {
+            return Err(BcclError::function_argument_type_error_with_span(
+                call.name, arg_number, "number or string", item_key.type_name(), &item_key.display(), item_span
+            ));
+        }
+        
+        best = match best {
+            None => Some((item_key, item)),
+            Some((best_key, best_item)) => match item_key.compare(&best_key) {
+                Some(ordering) if ordering == wanted => Some((item_key, item)),
+                Some(_) => Some((best_key, best_item)),
+                None => {
+                    return Err(BcclError::type_error(
+                        &format!("{}() cannot compare {} with {}", call.name, item_key.type_name(), best_key.type_name()),
+                        item_span,
+                        best_key.type_name(),
+                        item_key.type_name()
+                    ));
+                }
+            },
+        };
     }
     
-    let mut result = 1.0;
-    for (i, arg) in args.iter().enumerate() {
-        let num = arg.as_number()
-            .ok_or_else(|| BcclError::function_argument_type_error("product", i + 1, "number", arg.type_name()))?;
-        result *= num;
+    match best {
+        Some((_, item)) if key.is_some() => Ok(item),
+        Some((_, item)) => Ok(item.as_number().map(Value::Number).unwrap_or(item)),
+        None => match call.option("default") {
+            Some((default, _)) => Ok(default.clone()),
+            None => Err(BcclError::empty_sequence_error(call.name, call.span)),
+        },
     }
-    
-    Ok(Value::Number(result))
 }
 
+// ===== LEGACY INTERFACE =====
+
 /// Legacy `max` function for backward compatibility.
 /// 
-/// This function provides the old calling interface without span information.
-/// New code should use the span-aware version through FunctionSignature.
+/// Behaves like `max(...)` called from a script without keyword options:
+/// any number of numbers or strings, or a single list. Errors carry no
+/// source location.
 /// 
 /// # Deprecated
 /// 
 /// Use `FunctionSignature::call_with_spans()` for better error reporting.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// # use bccl::{builtin_max, Value};
+/// let largest = builtin_max(&[Value::Integer(3), Value::Number(7.5), Value::Integer(5)]).unwrap();
+/// assert_eq!(largest.display(), "7.5");
+/// ```
 pub fn builtin_max(args: &[Value]) -> BcclResult<Value> {
-    builtin_max_impl(args)
+    legacy_extreme("max", args, Ordering::Greater)
 }
 
 /// Legacy `min` function for backward compatibility.
 /// 
-/// This function provides the old calling interface without span information.
-/// New code should use the span-aware version through FunctionSignature.
+/// Behaves like `min(...)` called from a script without keyword options.
 /// 
 /// # Deprecated
 /// 
 /// Use `FunctionSignature::call_with_spans()` for better error reporting.
 pub fn builtin_min(args: &[Value]) -> BcclResult<Value> {
-    builtin_min_impl(args)
+    legacy_extreme("min", args, Ordering::Less)
+}
+
+/// Runs `builtin_extreme` for the span-less legacy interface.
+fn legacy_extreme(name: &str, args: &[Value], wanted: Ordering) -> BcclResult<Value> {
+    let spans = vec![Span::zero_width(0); args.len()];
+    builtin_extreme(&BuiltinCall {
+        evaluator: &Evaluator::new(),
+        name,
+        args,
+        spans: &spans,
+        options: HashMap::new(),
+        span: Span::zero_width(0),
+    }, wanted)
 }
 
 /// Creates and returns the registry of all built-in functions.
@@ -602,54 +919,123 @@ pub fn builtin_min(args: &[Value]) -> BcclResult<Value> {
 /// 
 /// # Built-in Functions
 /// 
-/// ## Fixed Parameter Functions
-/// - **max(a, b)**: Returns the larger of two numbers
-/// - **min(a, b)**: Returns the smaller of two numbers
-/// 
 /// ## Variadic Functions
+/// - **max(values..., key=, default=)**: Returns the largest value, or the largest item of one list
+/// - **min(values..., key=, default=)**: Returns the smallest value, or the smallest item of one list
 /// - **sum(values...)**: Returns the sum of all numeric arguments
 /// - **product(values...)**: Returns the product of all numeric arguments
 /// 
+/// ## Type Conversion and Introspection
+/// - **int(value)**, **float(value)**, **str(value)**, **bool(value)**,
+///   **list(value)**, **dict(value)**: Convert a value to another type
+/// - **type(value)**: Returns the type name of a value
+/// - **is_number(value)**, **is_integer(value)**, **is_boolean(value)**,
+///   **is_string(value)**, **is_list(value)**, **is_dict(value)**: Type predicates
+/// 
+/// ## Higher-Order Functions
+/// - **map(function, values)**: Applies a function to every item
+/// - **filter(function, values)**: Keeps the items the function accepts
+/// - **reduce(function, values, initial)**: Folds the items into one value
+/// - **sorted(values, key=, reverse=)**: Returns the items in a stable sorted order
+/// - **group_by(function, values)**: Groups items by the function's result
+/// 
+/// ## System Functions
+/// Registered only when `capabilities` grants what they need (see `system`):
+/// `read_file`, `write_file`, `getenv`, `now`, `random`.
+/// 
 /// # Adding New Functions
 /// 
 /// To add a new built-in function:
 /// 
-/// 1. Implement the function (with span-aware version recommended)
-/// 2. Add it to this registry with appropriate parameter specification
-/// 3. Update the dispatch logic in `FunctionSignature::call_impl`
-/// 4. Add comprehensive tests
+/// 1. Implement the function as a `NativeFn`, so its errors can point at
+///    its arguments
+/// 2. Add it to this registry with appropriate parameter specification; a
+///    function that touches the outside world goes in `system::system_functions`
+///    with its `Capability` instead
+/// 3. Add comprehensive tests
 /// 
 /// # Examples
 /// 
 /// ```rust
-/// let functions = get_builtin_functions();
+/// # use bccl::{get_builtin_functions, Capabilities, Capability};
+/// let functions = get_builtin_functions(Capabilities::none());
 /// let max_sig = functions.get("max").unwrap();
 /// let sum_sig = functions.get("sum").unwrap();
+/// assert!(!functions.contains_key("now"));
+/// 
+/// let functions = get_builtin_functions(Capabilities::none().with(Capability::Clock));
+/// assert!(functions.contains_key("now"));
 /// ```
-pub fn get_builtin_functions() -> HashMap<String, FunctionSignature> {
+pub fn get_builtin_functions(capabilities: Capabilities) -> HashMap<String, FunctionSignature> {
     let mut functions = HashMap::new();
     
-    // Fixed parameter functions
+    // Variadic functions
     functions.insert(
         "max".to_string(), 
-        FunctionSignature::new_fixed("max", vec!["a", "b"], builtin_max_impl)
+        FunctionSignature::new_native_variadic("max", vec![], "values", builtin_max_native)
+            .with_options(vec!["key", "default"])
     );
     
     functions.insert(
         "min".to_string(), 
-        FunctionSignature::new_fixed("min", vec!["a", "b"], builtin_min_impl)
+        FunctionSignature::new_native_variadic("min", vec![], "values", builtin_min_native)
+            .with_options(vec!["key", "default"])
     );
     
-    // Variadic functions
     functions.insert(
         "sum".to_string(),
-        FunctionSignature::new_variadic("sum", vec![], "values", builtin_sum_impl)
+        FunctionSignature::new_native_variadic("sum", vec![], "values", builtin_sum)
     );
     
     functions.insert(
         "product".to_string(),
-        FunctionSignature::new_variadic("product", vec![], "values", builtin_product_impl)
+        FunctionSignature::new_native_variadic("product", vec![], "values", builtin_product)
     );
     
+    // Type conversion and introspection functions
+    for signature in conversions::conversion_functions() {
+        functions.insert(signature.name.clone(), signature);
+    }
+    
+    // Higher-order functions call back into the evaluator
+    functions.insert(
+        "map".to_string(),
+        FunctionSignature::new_native("map", vec!["function", "values"], higher_order::builtin_map)
+    );
+    
+    functions.insert(
+        "filter".to_string(),
+        FunctionSignature::new_native("filter", vec!["function", "values"], higher_order::builtin_filter)
+    );
+    
+    functions.insert(
+        "reduce".to_string(),
+        FunctionSignature::new_native("reduce", vec!["function", "values", "initial"], higher_order::builtin_reduce)
+    );
+    
+    functions.insert(
+        "sorted".to_string(),
+        FunctionSignature::new_native_with_parameters(
+            "sorted",
+            Parameters::new()
+                .positional("values")
+                .keyword_only_with_default("reverse", Value::Boolean(false)),
+            higher_order::builtin_sorted
+        )
+        .with_options(vec!["key"])
+    );
+    
+    functions.insert(
+        "group_by".to_string(),
+        FunctionSignature::new_native("group_by", vec!["function", "values"], higher_order::builtin_group_by)
+    );
+    
+    // System functions, gated by capability
+    for (capability, signature) in system::system_functions() {
+        if capabilities.contains(capability) {
+            functions.insert(signature.name.clone(), signature);
+        }
+    }
+    
     functions
 }
\ No newline at end of file
@@ -0,0 +1,70 @@
+//! # Cancellation
+//!
+//! A `CancellationToken` lets another thread stop a running evaluation, for
+//! example the REPL's Ctrl+C handler. Together with an optional deadline
+//! (`Evaluator::set_deadline`) it is checked:
+//!
+//! - before every statement
+//! - before every function call, including callbacks from built-ins such as
+//!   `map()`
+//!
+//! Loops and user-defined functions will check it at their back-edges and
+//! calls in the same way. A stopped run fails with `BcclError::Cancelled`,
+//! reported at the statement that was executing. The check happens before
+//! that statement assigns anything, so the environment holds exactly the
+//! effects of the statements that completed.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A shareable flag that asks an evaluation to stop.
+///
+/// Clones share the same flag, so a host keeps one clone and hands another
+/// to the evaluator (or gets it from `Evaluator::cancellation_token`).
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::{BcclError, CancellationToken, Evaluator, Lexer, Parser};
+/// let mut evaluator = Evaluator::new();
+/// let token = evaluator.cancellation_token();
+///
+/// // Normally called from another thread while the program runs
+/// token.cancel();
+///
+/// let tokens = Lexer::new("x = 1").tokenize().unwrap();
+/// let program = Parser::new(tokens).parse().unwrap();
+/// let error = evaluator.evaluate_program(&program).unwrap_err();
+/// assert!(matches!(error, BcclError::Cancelled { .. }));
+/// assert!(evaluator.get_variable("x").is_none());
+///
+/// // The token stays cancelled until it is reset
+/// token.reset();
+/// assert!(evaluator.evaluate_program(&program).is_ok());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks every evaluation using this token to stop at its next check.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns whether `cancel` has been called since the last `reset`.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Clears the cancellation so the token can be used for the next run.
+    pub fn reset(&self) {
+        self.cancelled.store(false, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,147 @@
+//! # Capabilities
+//!
+//! Built-ins that reach outside the interpreter are grouped by what they
+//! touch, and an evaluator only registers the groups its host grants:
+//!
+//! | Capability | Name         | Built-ins                 |
+//! |------------|--------------|---------------------------|
+//! | `FsRead`   | `fs_read`    | `read_file(path)`         |
+//! | `FsWrite`  | `fs_write`   | `write_file(path, text)`  |
+//! | `Env`      | `env`        | `getenv(name, default=)`  |
+//! | `Clock`    | `clock`      | `now()`                   |
+//! | `Random`   | `random`     | `random()`                |
+//! | `Process`  | `process`    | (none yet)                |
+//!
+//! `Evaluator::new()` grants nothing, so embedded scripts are sandboxed unless
+//! the host opts in with `Evaluator::with_capabilities`. Calling a built-in
+//! whose capability is missing reports `BcclError::MissingCapability` naming
+//! the capability, instead of an undefined function.
+
+use std::fmt;
+
+/// A group of built-ins with access to the outside world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Reading files
+    FsRead,
+    /// Creating and writing files
+    FsWrite,
+    /// Reading environment variables
+    Env,
+    /// Reading the current time
+    Clock,
+    /// Generating random numbers
+    Random,
+    /// Starting processes or exiting
+    Process,
+}
+
+impl Capability {
+    /// Every capability, in declaration order.
+    pub const ALL: [Capability; 6] = [
+        Capability::FsRead,
+        Capability::FsWrite,
+        Capability::Env,
+        Capability::Clock,
+        Capability::Random,
+        Capability::Process,
+    ];
+
+    /// The name used in diagnostics and by the REPL's `--capabilities` flag.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Capability::FsRead => "fs_read",
+            Capability::FsWrite => "fs_write",
+            Capability::Env => "env",
+            Capability::Clock => "clock",
+            Capability::Random => "random",
+            Capability::Process => "process",
+        }
+    }
+
+    /// Parses a capability name such as `fs_read`.
+    pub fn from_name(name: &str) -> Option<Capability> {
+        Capability::ALL.into_iter().find(|capability| capability.name() == name)
+    }
+
+    fn bit(&self) -> u8 {
+        1 << (*self as u8)
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A set of granted capabilities.
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::{Capabilities, Capability, Evaluator};
+/// let capabilities = Capabilities::none().with(Capability::Clock);
+/// assert!(capabilities.contains(Capability::Clock));
+/// assert!(!capabilities.contains(Capability::FsWrite));
+///
+/// let evaluator = Evaluator::with_capabilities(capabilities);
+/// assert_eq!(evaluator.capabilities(), capabilities);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Capabilities {
+    bits: u8,
+}
+
+impl Capabilities {
+    /// The empty set: only pure built-ins are available.
+    pub fn none() -> Self {
+        Self { bits: 0 }
+    }
+
+    /// Every capability.
+    pub fn all() -> Self {
+        Capability::ALL.into_iter().fold(Self::none(), Self::with)
+    }
+
+    /// Returns this set with `capability` added.
+    pub fn with(self, capability: Capability) -> Self {
+        Self { bits: self.bits | capability.bit() }
+    }
+
+    /// Returns this set with `capability` removed.
+    pub fn without(self, capability: Capability) -> Self {
+        Self { bits: self.bits & !capability.bit() }
+    }
+
+    /// Returns whether `capability` is granted.
+    pub fn contains(&self, capability: Capability) -> bool {
+        self.bits & capability.bit() != 0
+    }
+
+    /// The granted capabilities, in declaration order.
+    pub fn iter(&self) -> impl Iterator<Item = Capability> + '_ {
+        Capability::ALL.into_iter().filter(|capability| self.contains(*capability))
+    }
+
+    /// Parses a comma-separated list of capability names, e.g.
+    /// `"fs_read,clock"`. `"all"` and `"none"` (or an empty string) are
+    /// also accepted.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(capabilities)` - The parsed set
+    /// * `Err(name)` - The first name that is not a capability
+    pub fn parse(list: &str) -> Result<Self, String> {
+        match list.trim() {
+            "all" => return Ok(Self::all()),
+            "none" | "" => return Ok(Self::none()),
+            _ => {}
+        }
+        list.split(',')
+            .map(str::trim)
+            .try_fold(Self::none(), |set, name| {
+                Capability::from_name(name).map(|capability| set.with(capability)).ok_or_else(|| name.to_string())
+            })
+    }
+}
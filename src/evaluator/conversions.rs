@@ -0,0 +1,219 @@
+//! # Type Conversion and Introspection Built-ins
+//!
+//! This module implements the built-in functions that convert values between
+//! BCCL types or inspect the type of a value:
+//!
+//! - **Conversions**: `int()`, `float()`, `str()`, `bool()`, `list()`, `dict()`
+//! - **Introspection**: `type()` returns the `Value::type_name` string
+//! - **Predicates**: `is_number()`, `is_integer()`, `is_boolean()`, `is_string()`,
+//!   `is_list()`, `is_dict()`
+//!
+//! ## Error Reporting
+//!
+//! Every conversion is a native built-in and receives the span of its
+//! argument, so a failed conversion such as `int("4x2")` points at the
+//! offending argument and quotes its text.
+
+use std::collections::HashMap;
+use crate::error::{BcclError, BcclResult};
+use super::builtins::{BuiltinCall, FunctionSignature, NativeFn};
+use super::value::Value;
+
+/// Returns the single `value` argument every conversion takes.
+fn argument<'a>(call: &'a BuiltinCall) -> &'a Value {
+    &call.args[0]
+}
+
+/// Converts a value to an integer (`int(value)`).
+///
+/// # Conversion Rules
+///
+/// - **Integer**: returned unchanged
+/// - **Number**: truncated toward zero (`int(3.9)` → `3`)
+/// - **Boolean**: `true` → `1`, `false` → `0`
+/// - **String**: parsed as a whole number after trimming whitespace (`int(" 42 ")` → `42`)
+///
+/// Lists and dictionaries cannot be converted.
+pub fn to_int(call: &BuiltinCall) -> BcclResult<Value> {
+    let value = argument(call);
+    match value {
+        Value::Integer(i) => Ok(Value::Integer(*i)),
+        Value::Number(n) => {
+            let truncated = n.trunc();
+            if truncated.is_finite() && truncated >= i64::MIN as f64 && truncated <= i64::MAX as f64 {
+                Ok(Value::Integer(truncated as i64))
+            } else {
+                Err(BcclError::conversion_error("int", "integer", &value.display(), call.spans[0]))
+            }
+        }
+        Value::Boolean(b) => Ok(Value::Integer(if *b { 1 } else { 0 })),
+        Value::String(s) => s.trim()
+            .parse::<i64>()
+            .map(Value::Integer)
+            .map_err(|_| BcclError::conversion_error("int", "integer", &value.display(), call.spans[0])),
+        Value::List(_) | Value::Dictionary(_) | Value::Function(_) => {
+            Err(BcclError::function_argument_type_error_with_span(
+                "int", 1, "number, boolean or string", value.type_name(), &value.display(), call.spans[0]
+            ))
+        }
+    }
+}
+
+/// Converts a value to a floating-point number (`float(value)`).
+///
+/// # Conversion Rules
+///
+/// - **Number**: returned unchanged
+/// - **Integer**: widened to a number (`float(2)` → `2.0`)
+/// - **Boolean**: `true` → `1.0`, `false` → `0.0`
+/// - **String**: parsed as a decimal number after trimming whitespace (`float("3.5")` → `3.5`)
+///
+/// Lists and dictionaries cannot be converted.
+pub fn to_float(call: &BuiltinCall) -> BcclResult<Value> {
+    let value = argument(call);
+    match value {
+        Value::Number(n) => Ok(Value::Number(*n)),
+        Value::Integer(i) => Ok(Value::Number(*i as f64)),
+        Value::Boolean(b) => Ok(Value::Number(if *b { 1.0 } else { 0.0 })),
+        Value::String(s) => s.trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(Value::Number)
+            .ok_or_else(|| BcclError::conversion_error("float", "number", &value.display(), call.spans[0])),
+        Value::List(_) | Value::Dictionary(_) | Value::Function(_) => {
+            Err(BcclError::function_argument_type_error_with_span(
+                "float", 1, "number, boolean or string", value.type_name(), &value.display(), call.spans[0]
+            ))
+        }
+    }
+}
+
+/// Converts a value to a string (`str(value)`).
+///
+/// Strings are returned unchanged; every other value uses its display form,
+/// so `str(42)` → `"42"` and `str([1, "a"])` → `"[1, \"a\"]"`.
+pub fn to_str(call: &BuiltinCall) -> BcclResult<Value> {
+    let value = argument(call);
+    match value {
+        Value::String(s) => Ok(Value::String(s.clone())),
+        other => Ok(Value::string(other.display())),
+    }
+}
+
+/// Converts a value to a boolean (`bool(value)`) using the truthiness rules of `Value::is_truthy`.
+pub fn to_bool(call: &BuiltinCall) -> BcclResult<Value> {
+    Ok(Value::Boolean(argument(call).is_truthy()))
+}
+
+/// Converts a value to a list (`list(value)`).
+///
+/// # Conversion Rules
+///
+/// - **List**: returned unchanged
+/// - **String**: one single-character string per character (`list("ab")` → `["a", "b"]`)
+/// - **Dictionary**: the keys, in sorted order so the result is deterministic
+pub fn to_list(call: &BuiltinCall) -> BcclResult<Value> {
+    let value = argument(call);
+    match value {
+        Value::List(items) => Ok(Value::List(items.clone())),
+        Value::String(s) => Ok(Value::list(
+            s.chars().map(|c| Value::string(c.to_string())).collect()
+        )),
+        Value::Dictionary(dict) => {
+            let mut keys: Vec<&String> = dict.keys().collect();
+            keys.sort();
+            Ok(Value::list(keys.into_iter().map(|k| Value::string(k.clone())).collect()))
+        }
+        _ => Err(BcclError::conversion_error("list", "list", &value.display(), call.spans[0])),
+    }
+}
+
+/// Converts a value to a dictionary (`dict(value)`).
+///
+/// # Conversion Rules
+///
+/// - **Dictionary**: returned unchanged
+/// - **List**: each element must be a `[key, value]` pair with a string key,
+///   so `dict([["a", 1]])` → `{"a": 1}`
+pub fn to_dict(call: &BuiltinCall) -> BcclResult<Value> {
+    let value = argument(call);
+    match value {
+        Value::Dictionary(dict) => Ok(Value::Dictionary(dict.clone())),
+        Value::List(items) => {
+            let mut dict = HashMap::new();
+            for item in items.iter() {
+                match item {
+                    Value::List(pair) if pair.len() == 2 => {
+                        if let Value::String(key) = &pair[0] {
+                            dict.insert(key.to_string(), pair[1].clone());
+                        } else {
+                            return Err(BcclError::conversion_error("dict", "dictionary", &item.display(), call.spans[0]));
+                        }
+                    }
+                    _ => return Err(BcclError::conversion_error("dict", "dictionary", &item.display(), call.spans[0])),
+                }
+            }
+            Ok(Value::dict(dict))
+        }
+        _ => Err(BcclError::conversion_error("dict", "dictionary", &value.display(), call.spans[0])),
+    }
+}
+
+/// Returns the type name of a value (`type(value)`), e.g. `type(3.5)` → `"number"`.
+pub fn type_of(call: &BuiltinCall) -> BcclResult<Value> {
+    Ok(Value::string(argument(call).type_name().to_string()))
+}
+
+/// `is_number(value)`: true for numbers and integers (anything usable in arithmetic).
+pub fn is_number(call: &BuiltinCall) -> BcclResult<Value> {
+    Ok(Value::Boolean(argument(call).as_number().is_some()))
+}
+
+/// `is_integer(value)`: true only for integer values.
+pub fn is_integer(call: &BuiltinCall) -> BcclResult<Value> {
+    Ok(Value::Boolean(matches!(argument(call), Value::Integer(_))))
+}
+
+/// `is_boolean(value)`: true only for `true` and `false`.
+pub fn is_boolean(call: &BuiltinCall) -> BcclResult<Value> {
+    Ok(Value::Boolean(matches!(argument(call), Value::Boolean(_))))
+}
+
+/// `is_string(value)`: true only for strings.
+pub fn is_string(call: &BuiltinCall) -> BcclResult<Value> {
+    Ok(Value::Boolean(matches!(argument(call), Value::String(_))))
+}
+
+/// `is_list(value)`: true only for lists.
+pub fn is_list(call: &BuiltinCall) -> BcclResult<Value> {
+    Ok(Value::Boolean(matches!(argument(call), Value::List(_))))
+}
+
+/// `is_dict(value)`: true only for dictionaries.
+pub fn is_dict(call: &BuiltinCall) -> BcclResult<Value> {
+    Ok(Value::Boolean(matches!(argument(call), Value::Dictionary(_))))
+}
+
+/// The signatures of every conversion and predicate, each taking a single
+/// `value` parameter, for `get_builtin_functions()`.
+pub fn conversion_functions() -> Vec<FunctionSignature> {
+    let functions: [(&str, NativeFn); 13] = [
+        ("int", to_int),
+        ("float", to_float),
+        ("str", to_str),
+        ("bool", to_bool),
+        ("list", to_list),
+        ("dict", to_dict),
+        ("type", type_of),
+        ("is_number", is_number),
+        ("is_integer", is_integer),
+        ("is_boolean", is_boolean),
+        ("is_string", is_string),
+        ("is_list", is_list),
+        ("is_dict", is_dict),
+    ];
+    functions.into_iter()
+        .map(|(name, function)| FunctionSignature::new_native(name, vec!["value"], function))
+        .collect()
+}
@@ -6,7 +6,21 @@
 //!
 //! ## Variable Management
 //!
-//! - Variables are stored in a HashMap for O(1) lookup
+//! - Every variable name is given a numbered **slot**; values live in a
+//!   `Vec` indexed by slot, so code resolved by the `Resolver` reads and writes
+//!   variables without hashing their names
+//! - A name table maps names to slots for by-name access, the REPL's `:vars`
+//!   command and "did you mean" suggestions
+//! - Slots are never reused or removed, so a resolved program stays valid for
+//!   the lifetime of its environment
+//!
+//! ## Shared Globals
+//!
+//! An environment can sit on top of a frozen **base** (`with_base`), an
+//! `Arc<Environment>` shared by many evaluators, possibly on different threads.
+//! Reads fall through to the base; writes always go to the overlay, so
+//! assigning a global's name shadows it for this environment only. A slot's
+//! `depth` counts how many bases up its value lives.
 //! - Variable names are case-sensitive
 //! - All variables are mutable (can be reassigned)
 //! - No variable declaration required (dynamic typing)
@@ -20,6 +34,9 @@
 //! - Module-level variable isolation
 
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::parser::Slot;
 use super::value::Value;
 
 /// Manages variable storage and lookup for the BCCL interpreter.
@@ -30,23 +47,37 @@ use super::value::Value;
 /// 
 /// # Design
 /// 
-/// - Uses HashMap for efficient O(1) variable lookup
+/// - Values are stored by slot; names map to slots through a HashMap
+/// - A slot can be declared (by the resolver) before its variable is assigned;
+///   until then the variable is undefined
 /// - Variables can store any Value type
 /// - Supports variable redefinition (assignments overwrite)
-/// - Thread-safe operations (no interior mutability)
+/// - Thread-safe operations (no interior mutability), so a frozen environment
+///   can be shared as `Arc<Environment>`
 /// 
 /// # Examples
 /// 
-/// ```rust
+/// ```rust,ignore
 /// let mut env = Environment::new();
 /// env.define("x".to_string(), Value::Integer(42));
 /// assert_eq!(env.get("x"), Some(&Value::Integer(42)));
 /// ```
 pub struct Environment {
-    /// Storage for variable name -> value mappings
-    variables: HashMap<String, Value>,
+    /// Name table: the slot of every declared name
+    slots: HashMap<String, usize>,
+    /// Name of each slot, for `:vars` and suggestions
+    names: Vec<String>,
+    /// Value of each slot; `None` until the variable is first assigned
+    values: Vec<Option<Value>>,
+    /// Frozen environment that reads fall through to
+    base: Option<Arc<Environment>>,
+    /// Distinguishes this environment from every other, for `layout`
+    id: u64,
 }
 
+/// Source of `Environment::id`
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
 impl Environment {
     /// Creates a new empty environment.
     /// 
@@ -56,15 +87,52 @@ impl Environment {
     /// # Examples
     /// 
     /// ```rust
+    /// # use bccl::Environment;
     /// let env = Environment::new();
     /// assert_eq!(env.variable_names().len(), 0);
     /// ```
     pub fn new() -> Self {
         Self {
-            variables: HashMap::new(),
+            slots: HashMap::new(),
+            names: Vec::new(),
+            values: Vec::new(),
+            base: None,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+    
+    /// Creates an empty overlay on top of a shared, frozen `base`.
+    /// 
+    /// The overlay sees every variable of the base and can shadow them, but
+    /// never changes the base, which other evaluators may be reading.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use bccl::{Environment, Value};
+    /// let mut globals = Environment::new();
+    /// globals.define("rate".to_string(), Value::Number(0.2));
+    /// let globals = Arc::new(globals);
+    /// 
+    /// let mut overlay = Environment::with_base(globals.clone());
+    /// assert_eq!(overlay.get("rate").unwrap().display(), "0.2");
+    /// overlay.define("rate".to_string(), Value::Number(0.5));
+    /// assert_eq!(overlay.get("rate").unwrap().display(), "0.5");
+    /// assert_eq!(globals.get("rate").unwrap().display(), "0.2");
+    /// ```
+    pub fn with_base(base: Arc<Environment>) -> Self {
+        Self {
+            base: Some(base),
+            ..Self::new()
         }
     }
     
+    /// Returns the frozen environment this one reads through to, if any.
+    pub fn base(&self) -> Option<&Arc<Environment>> {
+        self.base.as_ref()
+    }
+    
     /// Defines or updates a variable with the given name and value.
     /// 
     /// If the variable already exists, it will be overwritten with the new value.
@@ -78,9 +146,10 @@ impl Environment {
     /// # Examples
     /// 
     /// ```rust
+    /// # use bccl::{Environment, Value};
     /// let mut env = Environment::new();
     /// env.define("x".to_string(), Value::Integer(42));
-    /// env.define("x".to_string(), Value::String("hello".to_string())); // Overwrites
+    /// env.define("x".to_string(), Value::string("hello")); // Overwrites
     /// ```
     /// 
     /// # Variable Naming Rules
@@ -89,13 +158,17 @@ impl Environment {
     /// - Any valid UTF-8 string can be a variable name
     /// - No reserved words enforced at this level (handled by parser)
     pub fn define(&mut self, name: String, value: Value) {
-        self.variables.insert(name, value);
+        let slot = match self.slots.get(&name) {
+            Some(slot) => *slot,
+            None => self.push_slot(name),
+        };
+        self.values[slot] = Some(value);
     }
     
     /// Retrieves the value of a variable by name.
     /// 
-    /// Returns a reference to the stored value if the variable exists,
-    /// or None if the variable is not defined.
+    /// Returns a reference to the stored value if the variable exists here or
+    /// in the base, or None if the variable is not defined.
     /// 
     /// # Arguments
     /// 
@@ -108,7 +181,7 @@ impl Environment {
     /// 
     /// # Examples
     /// 
-    /// ```rust
+    /// ```rust,ignore
     /// let mut env = Environment::new();
     /// env.define("x".to_string(), Value::Integer(42));
     /// 
@@ -121,7 +194,127 @@ impl Environment {
     /// When a variable lookup fails, the evaluator uses this None result
     /// to generate helpful "undefined variable" errors with suggestions.
     pub fn get(&self, name: &str) -> Option<&Value> {
-        self.variables.get(name)
+        self.slots.get(name)
+            .and_then(|slot| self.values[*slot].as_ref())
+            .or_else(|| self.base.as_ref().and_then(|base| base.get(name)))
+    }
+    
+    /// Returns the slot `name` should be read from by a program that never
+    /// assigns it: the slot of a base variable if the name is only defined
+    /// there, otherwise a slot in this environment (declared if needed).
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use bccl::{Environment, Value};
+    /// let mut globals = Environment::new();
+    /// globals.define("pi".to_string(), Value::Number(3.0));
+    /// 
+    /// let mut overlay = Environment::with_base(Arc::new(globals));
+    /// let slot = overlay.resolve("pi");
+    /// assert_eq!(slot.depth, 1);
+    /// assert_eq!(overlay.get_slot(slot).unwrap().display(), "3");
+    /// assert_eq!(overlay.resolve("x").depth, 0);
+    /// ```
+    pub fn resolve(&mut self, name: &str) -> Slot {
+        if !self.slots.contains_key(name) && let Some((depth, index)) = self.base.as_ref().and_then(|base| base.find(name)) {
+            return Slot { depth: depth + 1, index };
+        }
+        self.declare(name)
+    }
+    
+    /// Finds the assigned variable `name` in this environment or its bases,
+    /// returning how many bases up it is and its slot index there.
+    fn find(&self, name: &str) -> Option<(u32, u32)> {
+        match self.slots.get(name) {
+            Some(slot) if self.values[*slot].is_some() => Some((0, *slot as u32)),
+            _ => self.base.as_ref()
+                .and_then(|base| base.find(name))
+                .map(|(depth, index)| (depth + 1, index)),
+        }
+    }
+    
+    /// Returns the slot for `name` in this environment, declaring it (with no
+    /// value) if needed. Assignment targets always use this, so writes never
+    /// reach the base.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// # use bccl::{Environment, Value};
+    /// let mut env = Environment::new();
+    /// let slot = env.declare("x");
+    /// assert!(env.get_slot(slot).is_none()); // declared, not yet assigned
+    /// env.set_slot(slot, Value::Integer(1));
+    /// assert_eq!(env.get("x").unwrap().display(), "1");
+    /// assert_eq!(env.declare("x"), slot);
+    /// ```
+    pub fn declare(&mut self, name: &str) -> Slot {
+        let index = match self.slots.get(name) {
+            Some(slot) => *slot,
+            None => self.push_slot(name.to_string()),
+        };
+        Slot { depth: 0, index: index as u32 }
+    }
+    
+    /// Reads a variable by slot.
+    /// 
+    /// A slot of this environment that is not assigned yet falls back to the
+    /// base by name, so a name the program is about to shadow still reads the
+    /// global until the assignment runs.
+    /// 
+    /// # Returns
+    /// 
+    /// * `Some(&Value)` - The variable's value
+    /// * `None` - The slot is declared but the variable was never assigned
+    pub fn get_slot(&self, slot: Slot) -> Option<&Value> {
+        if slot.depth > 0 {
+            let base = self.base.as_ref()?;
+            return base.get_slot(Slot { depth: slot.depth - 1, index: slot.index });
+        }
+        let index = slot.index as usize;
+        match &self.values[index] {
+            Some(value) => Some(value),
+            None => self.base.as_ref().and_then(|base| base.get(&self.names[index])),
+        }
+    }
+    
+    /// Assigns a variable by slot. A slot in a base is written as a new
+    /// variable of this environment, shadowing the base.
+    pub fn set_slot(&mut self, slot: Slot, value: Value) {
+        if slot.depth == 0 {
+            self.values[slot.index as usize] = Some(value);
+        } else if let Some(name) = self.slot_name(slot) {
+            self.define(name, value);
+        }
+    }
+    
+    /// The name of the variable in `slot`.
+    fn slot_name(&self, slot: Slot) -> Option<String> {
+        if slot.depth == 0 {
+            return self.names.get(slot.index as usize).cloned();
+        }
+        self.base.as_ref()?.slot_name(Slot { depth: slot.depth - 1, index: slot.index })
+    }
+    
+    /// Identifies this environment and how many names it has declared.
+    /// 
+    /// Resolving a program depends only on the declared names and the frozen
+    /// base, so slots resolved at one layout stay valid while it is unchanged.
+    /// A variable shadowing a global at runtime declares a name, which
+    /// changes the layout.
+    pub(crate) fn layout(&self) -> (u64, usize) {
+        (self.id, self.names.len())
+    }
+    
+    /// Adds a new, unassigned slot for `name`.
+    fn push_slot(&mut self, name: String) -> usize {
+        let slot = self.names.len();
+        self.slots.insert(name.clone(), slot);
+        self.names.push(name);
+        self.values.push(None);
+        slot
     }
     
     /// Returns a list of all currently defined variable names.
@@ -133,14 +326,16 @@ impl Environment {
     /// 
     /// # Returns
     /// 
-    /// A vector containing all variable names in arbitrary order.
+    /// A vector containing all variable names, including those of the base,
+    /// in sorted order.
     /// 
     /// # Examples
     /// 
     /// ```rust
+    /// # use bccl::{Environment, Value};
     /// let mut env = Environment::new();
     /// env.define("x".to_string(), Value::Integer(42));
-    /// env.define("name".to_string(), Value::String("Alice".to_string()));
+    /// env.define("name".to_string(), Value::string("Alice"));
     /// 
     /// let names = env.variable_names();
     /// assert_eq!(names.len(), 2);
@@ -150,9 +345,25 @@ impl Environment {
     /// 
     /// # Performance
     /// 
-    /// This method clones all variable names, so it's O(n) in the number of variables.
-    /// Use sparingly in performance-critical code.
+    /// This method clones and sorts all variable names, so it's O(n log n) in the
+    /// number of variables. Use sparingly in performance-critical code.
     pub fn variable_names(&self) -> Vec<String> {
-        self.variables.keys().cloned().collect()
+        let mut names: Vec<String> = self.names.iter()
+            .zip(&self.values)
+            .filter(|(_, value)| value.is_some())
+            .map(|(name, _)| name.clone())
+            .collect();
+        if let Some(base) = &self.base {
+            names.extend(base.variable_names());
+        }
+        names.sort();
+        names.dedup();
+        names
     }
-}
\ No newline at end of file
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
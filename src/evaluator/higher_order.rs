@@ -0,0 +1,188 @@
+//! # Higher-Order Built-ins
+//!
+//! This module implements the built-in functions that take another function as
+//! an argument and call it through the evaluator:
+//!
+//! - **Transformations**: `map(function, values)`, `filter(function, values)`
+//! - **Folding**: `reduce(function, values, initial)`
+//! - **Ordering**: `sorted(values, key=, reverse=)`
+//! - **Grouping**: `group_by(function, values)`
+//!
+//! ## Iterables
+//!
+//! Wherever a built-in expects `values`, it accepts a list, a string (one
+//! single-character string per character) or a dictionary (its keys, in sorted
+//! order), matching the rules of `list(value)`.
+//!
+//! ## Callback Errors
+//!
+//! Callbacks run through `BuiltinCall::call_function`, so an error raised inside
+//! `map(int, ["1", "x"])` is reported as the conversion error together with a
+//! "called from map()" label on the outer call.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use crate::error::{BcclError, BcclResult};
+use super::builtins::BuiltinCall;
+use super::value::Value;
+
+/// Expands an iterable argument into its items.
+///
+/// # Arguments
+///
+/// * `call` - The built-in call, used for the function name in errors
+/// * `position` - 1-based position of the argument (for error messages)
+///
+/// # Returns
+///
+/// * `Ok(items)` - List elements, string characters or sorted dictionary keys
+/// * `Err(type_error)` - If the argument is not iterable
+fn iterable_items(call: &BuiltinCall, position: usize) -> BcclResult<Vec<Value>> {
+    let value = &call.args[position - 1];
+    match value {
+        Value::List(items) => Ok(items.to_vec()),
+        Value::String(s) => Ok(s.chars().map(|c| Value::string(c.to_string())).collect()),
+        Value::Dictionary(dict) => {
+            let mut keys: Vec<&String> = dict.keys().collect();
+            keys.sort();
+            Ok(keys.into_iter().map(|k| Value::string(k.clone())).collect())
+        }
+        _ => Err(BcclError::function_argument_type_error_with_span(
+            call.name, position, "list, string or dictionary", value.type_name(), &value.display(), call.spans[position - 1]
+        )),
+    }
+}
+
+/// Orders two values for sorting, failing with a readable message when they
+/// cannot be compared (see `Value::compare`).
+fn compare_for_sort(call: &BuiltinCall, a: &Value, b: &Value) -> BcclResult<Ordering> {
+    a.compare(b).ok_or_else(|| BcclError::evaluation_error(
+        &format!("{}() cannot compare {} with {}", call.name, a.type_name(), b.type_name()),
+        call.span,
+        Some("Sort keys must all be numbers or all be strings".to_string())
+    ))
+}
+
+/// Implementation of `map(function, values)`.
+///
+/// Calls `function` on every item and returns the results as a list,
+/// e.g. `map(str, [1, 2])` → `["1", "2"]`.
+pub fn builtin_map(call: &BuiltinCall) -> BcclResult<Value> {
+    let items = iterable_items(call, 2)?;
+    let mut results = Vec::with_capacity(items.len());
+    for item in items {
+        results.push(call.call_function(&call.args[0], call.spans[0], &[(item, call.spans[1])])?);
+    }
+    Ok(Value::list(results))
+}
+
+/// Implementation of `filter(function, values)`.
+///
+/// Keeps the items for which `function` returns a truthy value,
+/// e.g. `filter(is_number, [1, "a", 2])` → `[1, 2]`.
+pub fn builtin_filter(call: &BuiltinCall) -> BcclResult<Value> {
+    let items = iterable_items(call, 2)?;
+    let mut results = Vec::new();
+    for item in items {
+        let keep = call.call_function(&call.args[0], call.spans[0], &[(item.clone(), call.spans[1])])?;
+        if keep.is_truthy() {
+            results.push(item);
+        }
+    }
+    Ok(Value::list(results))
+}
+
+/// Implementation of `reduce(function, values, initial)`.
+///
+/// Folds the items from left to right, calling `function(accumulator, item)`
+/// and starting from `initial`, e.g. `reduce(max, [3, 9, 4], 0)` → `9`.
+/// An empty iterable returns `initial` unchanged.
+pub fn builtin_reduce(call: &BuiltinCall) -> BcclResult<Value> {
+    let items = iterable_items(call, 2)?;
+    let mut accumulator = call.args[2].clone();
+    for item in items {
+        accumulator = call.call_function(
+            &call.args[0],
+            call.spans[0],
+            &[(accumulator, call.spans[2]), (item, call.spans[1])]
+        )?;
+    }
+    Ok(accumulator)
+}
+
+/// Implementation of `sorted(values, key=, reverse=)`.
+///
+/// Returns a new list with the items in ascending order. The sort is stable,
+/// so items with equal keys keep their original order.
+///
+/// # Options
+///
+/// * `key` - Function applied to each item; items are ordered by its results
+/// * `reverse` - Keyword-only boolean, default `false`; `true` sorts in
+///   descending order (still stable)
+///
+/// # Examples
+///
+/// - `sorted([3, 1, 2])` → `[1, 2, 3]`
+/// - `sorted(["bb", "a"], key=str)` → `["a", "bb"]`
+/// - `sorted([1, 3, 2], reverse=true)` → `[3, 2, 1]`
+pub fn builtin_sorted(call: &BuiltinCall) -> BcclResult<Value> {
+    let items = iterable_items(call, 1)?;
+
+    // `reverse` is a keyword-only parameter defaulting to false
+    let reverse = match call.option("reverse") {
+        Some((Value::Boolean(b), _)) => *b,
+        Some((other, span)) => {
+            return Err(BcclError::type_error(
+                "sorted() parameter 'reverse' must be a boolean", *span, "boolean", other.type_name()
+            ));
+        }
+        None => false,
+    };
+
+    // Pair every item with its sort key so the callback runs once per item
+    let mut keyed = Vec::with_capacity(items.len());
+    for item in items {
+        let key = match call.option("key") {
+            Some((function, function_span)) => {
+                call.call_function(function, *function_span, &[(item.clone(), call.spans[0])])?
+            }
+            None => item.clone(),
+        };
+        keyed.push((key, item));
+    }
+
+    // `sort_by` cannot fail, so check up front that every key is comparable
+    // with the first one (comparability only depends on the type)
+    if let Some((first, _)) = keyed.first() {
+        for (key, _) in &keyed {
+            compare_for_sort(call, first, key)?;
+        }
+    }
+
+    keyed.sort_by(|(a, _), (b, _)| {
+        let ordering = a.compare(b).unwrap_or(Ordering::Equal);
+        if reverse { ordering.reverse() } else { ordering }
+    });
+
+    Ok(Value::list(keyed.into_iter().map(|(_, item)| item).collect()))
+}
+
+/// Implementation of `group_by(function, values)`.
+///
+/// Returns a dictionary mapping each result of `function` to the list of items
+/// that produced it, in their original order. String results are used as keys
+/// directly; any other result uses its display form, so
+/// `group_by(type, [1, "a", 2])` → `{"integer": [1, 2], "string": ["a"]}`.
+pub fn builtin_group_by(call: &BuiltinCall) -> BcclResult<Value> {
+    let items = iterable_items(call, 2)?;
+    let mut groups: HashMap<String, Vec<Value>> = HashMap::new();
+    for item in items {
+        let key = match call.call_function(&call.args[0], call.spans[0], &[(item.clone(), call.spans[1])])? {
+            Value::String(s) => s.to_string(),
+            other => other.display(),
+        };
+        groups.entry(key).or_default().push(item);
+    }
+    Ok(Value::dict(groups.into_iter().map(|(key, members)| (key, Value::list(members))).collect()))
+}
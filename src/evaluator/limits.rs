@@ -0,0 +1,179 @@
+//! # Execution Limits
+//!
+//! Hosts that run untrusted BCCL (for example from a web form) can bound the
+//! work a single `evaluate_program` call may do with `EvaluatorLimits`:
+//!
+//! - **Steps**: one step per statement and expression node, on either
+//!   backend; the VM counts them from its chunk's step table
+//! - **Call depth**: nested function calls, including callbacks such as the
+//!   function passed to `map()`
+//! - **Collection size**: elements in any one list or dictionary
+//! - **Total elements**: elements in all collections created during the run
+//! - **String length**: bytes in any one string
+//!
+//! Every limit is off by default. A run that hits one stops with
+//! `BcclError::LimitExceeded` at the span being evaluated; statements that
+//! already ran keep their effects, as with any other runtime error.
+//!
+//! ## Accounting
+//!
+//! Usage is counted per `evaluate_program` (or `run_chunk`) call and reset at
+//! the start of the next one. Steps and call depth are counted where every
+//! expression and every call passes through, so loops and user-defined
+//! functions are covered by the same checks. Sizes are checked whenever an
+//! expression creates a value: literals, operators, calls and compound
+//! assignments. The total is an upper bound on what was allocated, since a
+//! call returning an existing collection counts it again.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use crate::error::{BcclError, BcclResult, Span};
+use super::value::Value;
+
+/// Bounds on the work a single run may do. `None` means unlimited.
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::{BcclError, Evaluator, EvaluatorLimits, Lexer, Parser};
+/// let mut evaluator = Evaluator::new();
+/// evaluator.set_limits(EvaluatorLimits::new().with_max_collection_size(3));
+///
+/// let tokens = Lexer::new("[1, 2, 3, 4]").tokenize().unwrap();
+/// let program = Parser::new(tokens).parse().unwrap();
+/// let error = evaluator.evaluate_program(&program).unwrap_err();
+/// assert!(matches!(error, BcclError::LimitExceeded { .. }));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EvaluatorLimits {
+    /// Maximum number of evaluation steps
+    pub max_steps: Option<u64>,
+    /// Maximum depth of nested function calls
+    pub max_call_depth: Option<usize>,
+    /// Maximum number of elements in one list or dictionary
+    pub max_collection_size: Option<usize>,
+    /// Maximum number of elements across all collections created in a run
+    pub max_total_elements: Option<usize>,
+    /// Maximum length of one string, in bytes
+    pub max_string_length: Option<usize>,
+}
+
+impl EvaluatorLimits {
+    /// Creates a configuration with every limit off.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of evaluation steps.
+    pub fn with_max_steps(mut self, steps: u64) -> Self {
+        self.max_steps = Some(steps);
+        self
+    }
+
+    /// Sets the maximum depth of nested function calls.
+    pub fn with_max_call_depth(mut self, depth: usize) -> Self {
+        self.max_call_depth = Some(depth);
+        self
+    }
+
+    /// Sets the maximum number of elements in one list or dictionary.
+    pub fn with_max_collection_size(mut self, size: usize) -> Self {
+        self.max_collection_size = Some(size);
+        self
+    }
+
+    /// Sets the maximum number of elements across all collections in a run.
+    pub fn with_max_total_elements(mut self, total: usize) -> Self {
+        self.max_total_elements = Some(total);
+        self
+    }
+
+    /// Sets the maximum length of one string, in bytes.
+    pub fn with_max_string_length(mut self, length: usize) -> Self {
+        self.max_string_length = Some(length);
+        self
+    }
+}
+
+/// What one run has used so far, checked against `EvaluatorLimits`.
+///
+/// The counters are atomics because evaluation (and native built-ins calling
+/// back into the evaluator) only holds a shared reference, and the evaluator
+/// must stay `Sync`. A run is single-threaded, so `Relaxed` ordering is enough.
+#[derive(Debug, Default)]
+pub(crate) struct Usage {
+    steps: AtomicU64,
+    call_depth: AtomicUsize,
+    elements: AtomicUsize,
+    /// Span of the statement being executed, for `BcclError::Cancelled`
+    statement_start: AtomicUsize,
+    statement_end: AtomicUsize,
+}
+
+impl Usage {
+    /// Clears the counters at the start of a run.
+    pub(crate) fn reset(&self) {
+        self.steps.store(0, Ordering::Relaxed);
+        self.call_depth.store(0, Ordering::Relaxed);
+        self.elements.store(0, Ordering::Relaxed);
+    }
+
+    /// Records the statement that is about to run.
+    pub(crate) fn enter_statement(&self, span: Span) {
+        self.statement_start.store(span.start, Ordering::Relaxed);
+        self.statement_end.store(span.end, Ordering::Relaxed);
+    }
+
+    /// The span of the statement being executed.
+    pub(crate) fn statement(&self) -> Span {
+        Span::new(self.statement_start.load(Ordering::Relaxed), self.statement_end.load(Ordering::Relaxed))
+    }
+
+    /// Counts one evaluation step at `span`.
+    pub(crate) fn step(&self, limits: &EvaluatorLimits, span: Span) -> BcclResult<()> {
+        let steps = self.steps.fetch_add(1, Ordering::Relaxed) + 1;
+        match limits.max_steps {
+            Some(max) if steps > max => Err(BcclError::limit_exceeded("steps", max, span)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Enters a function call at `span`; every successful call must be paired
+    /// with `leave_call`.
+    pub(crate) fn enter_call(&self, limits: &EvaluatorLimits, span: Span) -> BcclResult<()> {
+        let depth = self.call_depth.load(Ordering::Relaxed) + 1;
+        if let Some(max) = limits.max_call_depth && depth > max {
+            return Err(BcclError::limit_exceeded("call_depth", max as u64, span));
+        }
+        self.call_depth.store(depth, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Leaves a function call entered with `enter_call`.
+    pub(crate) fn leave_call(&self) {
+        self.call_depth.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Checks a newly created value against the size limits and adds its
+    /// elements to the run's total.
+    pub(crate) fn allocate(&self, limits: &EvaluatorLimits, value: &Value, span: Span) -> BcclResult<()> {
+        let size = match value {
+            Value::List(items) => items.len(),
+            Value::Dictionary(dict) => dict.len(),
+            Value::String(s) => {
+                if let Some(max) = limits.max_string_length && s.len() > max {
+                    return Err(BcclError::limit_exceeded("string_length", max as u64, span));
+                }
+                return Ok(());
+            }
+            _ => return Ok(()),
+        };
+        if let Some(max) = limits.max_collection_size && size > max {
+            return Err(BcclError::limit_exceeded("collection_size", max as u64, span));
+        }
+        let total = self.elements.fetch_add(size, Ordering::Relaxed) + size;
+        if let Some(max) = limits.max_total_elements && total > max {
+            return Err(BcclError::limit_exceeded("total_elements", max as u64, span));
+        }
+        Ok(())
+    }
+}
@@ -11,6 +11,42 @@
 //! - **Environment**: Variable storage and scoping
 //! - **Value**: Runtime value representation
 //! - **Builtins**: Built-in function implementations
+//! - **Operations**: Operator semantics shared with the bytecode VM (`crate::vm`)
+//!
+//! ## Backends
+//!
+//! `evaluate_program` runs on one of two backends, chosen with
+//! `Evaluator::with_backend` or `set_backend`:
+//!
+//! - **`Backend::TreeWalk`** (default): walks the AST directly
+//! - **`Backend::Bytecode`**: compiles the program to a `Chunk` and runs it on
+//!   the stack VM
+//!
+//! Unless disabled with `set_optimize(false)`, programs first go through the
+//! `Optimizer` (constant folding, builtin resolution).
+//!
+//! Both backends share the environment, the function registry and the
+//! `operations` module, so they produce identical values and diagnostics.
+//!
+//! ## Limits
+//!
+//! `set_limits` bounds the steps, call depth, collection sizes and string
+//! lengths of each run (see `EvaluatorLimits`); both backends enforce them.
+//! Another thread can stop a run through its `CancellationToken`, and
+//! `set_deadline` stops runs that take too long.
+//!
+//! ## Capabilities
+//!
+//! Built-ins with access to files, the environment, the clock or randomness
+//! are only registered when the evaluator's `Capabilities` grant them; an
+//! `Evaluator::new()` grants none.
+//!
+//! ## Snapshots
+//!
+//! `snapshot` saves every variable (and the names of host-defined functions)
+//! as a versioned `Snapshot`, and `restore` loads one back. With the `serde`
+//! feature a snapshot encodes to JSON; the REPL exposes them as `:save` and
+//! `:load`.
 //!
 //! ## Key Features
 //!
@@ -22,29 +58,92 @@
 //! - **Logical operations**: and, or, not, in, not in with short-circuit evaluation
 
 use std::collections::HashMap;
-use crate::parser::{Expr, Stmt, Program, BinaryOp, UnaryOp, CompoundOp};
+use std::sync::Arc;
+use std::time::Instant;
+use crate::parser::{Expr, Stmt, Program, Slot};
 use crate::error::{BcclError, BcclResult, Span};
+use crate::vm::{Chunk, Compiler, Vm};
+use crate::optimizer::Optimizer;
+use crate::resolver::{ResolvedProgram, Resolver};
+use crate::checker::{Type, TypeChecker};
+use limits::Usage;
 
 mod value;
 mod environment;
 mod builtins;
+mod conversions;
+mod higher_order;
+mod limits;
+mod cancellation;
+mod capabilities;
+mod system;
+mod snapshot;
+pub(crate) mod operations;
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests;
 
 pub use value::Value;
 pub use environment::Environment;
-pub use builtins::{builtin_max, builtin_min, FunctionSignature, get_builtin_functions};
+pub use limits::EvaluatorLimits;
+pub use cancellation::CancellationToken;
+pub use capabilities::{Capabilities, Capability};
+pub use snapshot::Snapshot;
+#[cfg(feature = "lsp")]
+pub(crate) use system::required_capability;
+pub use builtins::{builtin_max, builtin_min, BoundArguments, BuiltinCall, FunctionImpl, FunctionSignature, Parameter, ParameterSpec, Parameters, get_builtin_functions};
+
+/// Execution backend used by `Evaluator::evaluate_program`.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// # use bccl::{Backend, Evaluator};
+/// let evaluator = Evaluator::with_backend(Backend::Bytecode);
+/// assert_eq!(evaluator.backend(), Backend::Bytecode);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// Walks the AST directly
+    #[default]
+    TreeWalk,
+    /// Compiles the program to bytecode and runs it on the stack VM
+    Bytecode,
+}
+
+impl Backend {
+    /// Parses a backend name as used by the REPL: `tree` or `vm`.
+    pub fn from_name(name: &str) -> Option<Backend> {
+        match name {
+            "tree" | "treewalk" => Some(Backend::TreeWalk),
+            "vm" | "bytecode" => Some(Backend::Bytecode),
+            _ => None,
+        }
+    }
+    
+    /// The short name of the backend (`tree` or `vm`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::TreeWalk => "tree",
+            Backend::Bytecode => "vm",
+        }
+    }
+}
 
 /// The main evaluator struct that executes BCCL programs.
 /// 
 /// The evaluator maintains:
 /// - An environment for variable storage
-/// - A registry of built-in functions
+/// - A registry of built-in functions, gated by the granted capabilities
+/// - The backend that runs programs (tree-walking or bytecode)
+/// - Whether programs are optimized before they run
+/// - The execution limits and what the current run has used of them
+/// - The cancellation token and deadline that can stop a run
 /// 
 /// # Example
 /// 
-/// ```rust
+/// ```rust,ignore
 /// let mut evaluator = Evaluator::new();
 /// let result = evaluator.evaluate_program(&program)?;
 /// ```
@@ -53,6 +152,31 @@ pub struct Evaluator {
     environment: Environment,
     /// Registry of built-in functions with their signatures
     functions: HashMap<String, FunctionSignature>,
+    /// Capabilities that decide which system built-ins are registered
+    capabilities: Capabilities,
+    /// Backend used by `evaluate_program`
+    backend: Backend,
+    /// Whether `evaluate_program` runs the `Optimizer` first
+    optimize: bool,
+    /// Whether `evaluate_program` runs the `TypeChecker` first
+    type_check: bool,
+    /// Types declared by annotations in earlier runs, which the
+    /// `TypeChecker` keeps later programs to
+    declared: HashMap<String, Type>,
+    /// Bounds on each run
+    limits: EvaluatorLimits,
+    /// Steps, call depth and elements used by the current run
+    usage: Usage,
+    /// Lets another thread stop the current run
+    cancellation: CancellationToken,
+    /// Point in time after which runs stop
+    deadline: Option<Instant>,
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Evaluator {
@@ -61,16 +185,295 @@ impl Evaluator {
     /// The evaluator starts with:
     /// - Empty variable environment
     /// - Pre-loaded built-in functions (max, min, etc.)
+    /// - No capabilities, so no system built-ins such as `read_file()`
     /// 
     /// # Example
     /// 
     /// ```rust
+    /// # use bccl::Evaluator;
     /// let evaluator = Evaluator::new();
     /// ```
     pub fn new() -> Self {
         Self {
             environment: Environment::new(),
-            functions: get_builtin_functions(),
+            functions: get_builtin_functions(Capabilities::none()),
+            capabilities: Capabilities::none(),
+            backend: Backend::TreeWalk,
+            optimize: true,
+            type_check: false,
+            declared: HashMap::new(),
+            limits: EvaluatorLimits::default(),
+            usage: Usage::default(),
+            cancellation: CancellationToken::new(),
+            deadline: None,
+        }
+    }
+    
+    /// Creates a new evaluator that runs programs on the given backend.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// # use bccl::{Backend, Evaluator};
+    /// let evaluator = Evaluator::with_backend(Backend::Bytecode);
+    /// ```
+    pub fn with_backend(backend: Backend) -> Self {
+        Self {
+            backend,
+            ..Self::new()
+        }
+    }
+    
+    /// Creates a new evaluator whose system built-ins are gated by
+    /// `capabilities`.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// # use bccl::{Capabilities, Capability, Evaluator};
+    /// let evaluator = Evaluator::with_capabilities(Capabilities::none().with(Capability::Clock));
+    /// ```
+    pub fn with_capabilities(capabilities: Capabilities) -> Self {
+        Self {
+            functions: get_builtin_functions(capabilities),
+            capabilities,
+            ..Self::new()
+        }
+    }
+    
+    /// Returns the granted capabilities.
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+    
+    /// Changes the granted capabilities, registering or removing system
+    /// built-ins to match. Functions added with `define_function` are kept.
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        for (capability, signature) in system::system_functions() {
+            if capabilities.contains(capability) {
+                self.functions.insert(signature.name.clone(), signature);
+            } else if self.capabilities.contains(capability) {
+                self.functions.remove(&signature.name);
+            }
+        }
+        self.capabilities = capabilities;
+    }
+    
+    /// Creates a new evaluator whose variables sit on top of shared, read-only
+    /// globals (see `Environment::with_base`).
+    /// 
+    /// Programs read globals directly and assigning a global's name shadows it
+    /// in this evaluator only, so any number of evaluators, on any threads, can
+    /// share one `Arc<Environment>`.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use bccl::{Evaluator, Lexer, Parser};
+    /// let mut setup = Evaluator::new();
+    /// let tokens = Lexer::new("rate = 0.25; limits = [10, 20]").tokenize().unwrap();
+    /// setup.evaluate_program(&Parser::new(tokens).parse().unwrap()).unwrap();
+    /// let globals = Arc::new(setup.into_environment());
+    /// 
+    /// let mut evaluator = Evaluator::with_globals(globals.clone());
+    /// let tokens = Lexer::new("rate = rate * 2; rate").tokenize().unwrap();
+    /// let result = evaluator.evaluate_program(&Parser::new(tokens).parse().unwrap()).unwrap();
+    /// assert_eq!(result.unwrap().display(), "0.5");
+    /// assert_eq!(globals.get("rate").unwrap().display(), "0.25");
+    /// ```
+    pub fn with_globals(globals: Arc<Environment>) -> Self {
+        Self {
+            environment: Environment::with_base(globals),
+            ..Self::new()
+        }
+    }
+    
+    /// Consumes the evaluator and returns its variables, e.g. to freeze them
+    /// as globals for `with_globals`.
+    pub fn into_environment(self) -> Environment {
+        self.environment
+    }
+    
+    /// Returns the backend used by `evaluate_program`.
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+    
+    /// Switches the backend used by `evaluate_program`. Variables and
+    /// registered functions are kept.
+    pub fn set_backend(&mut self, backend: Backend) {
+        self.backend = backend;
+    }
+    
+    /// Returns whether `evaluate_program` optimizes programs first.
+    pub fn optimize_enabled(&self) -> bool {
+        self.optimize
+    }
+    
+    /// Enables or disables the optimizer (on by default). Results and errors
+    /// are the same either way; the REPL's `--no-opt` turns it off.
+    pub fn set_optimize(&mut self, optimize: bool) {
+        self.optimize = optimize;
+    }
+    
+    /// Returns whether `evaluate_program` type-checks programs first.
+    pub fn type_check_enabled(&self) -> bool {
+        self.type_check
+    }
+    
+    /// Enables or disables static type checking (off by default). When on,
+    /// a program with type errors is rejected before any of it runs; the
+    /// REPL turns it on when started with `--check`.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// # use bccl::{BcclError, Evaluator, Lexer, Parser};
+    /// let mut evaluator = Evaluator::new();
+    /// evaluator.set_type_check(true);
+    /// 
+    /// let tokens = Lexer::new("x = 1; x = x + \"a\"").tokenize().unwrap();
+    /// let program = Parser::new(tokens).parse().unwrap();
+    /// assert!(matches!(evaluator.evaluate_program(&program), Err(BcclError::TypeMismatch { .. })));
+    /// assert!(evaluator.get_variable_names().is_empty());
+    /// ```
+    pub fn set_type_check(&mut self, type_check: bool) {
+        self.type_check = type_check;
+    }
+    
+    /// Type-checks a program against this evaluator's registered functions,
+    /// the current values of its variables and the types declared by
+    /// annotations in earlier runs (see `TypeChecker`).
+    /// 
+    /// # Returns
+    /// 
+    /// * `Ok(())` - If no type errors were found
+    /// * `Err(error)` - Every type error found
+    pub fn check(&self, program: &Program) -> BcclResult<()> {
+        let variables = self.environment.variable_names().into_iter()
+            .filter_map(|name| self.environment.get(&name).map(Type::of).map(|ty| (name, ty)))
+            .collect::<Vec<_>>();
+        TypeChecker::new()
+            .with_functions(&self.functions)
+            .with_variables(variables)
+            .with_declarations(self.declared.iter().map(|(name, ty)| (name.clone(), *ty)))
+            .check(program)
+    }
+    
+    /// Remembers the types `program` declares once it has run, so later
+    /// programs are checked against them.
+    fn declare(&mut self, program: &Program, result: &BcclResult<Option<Value>>) {
+        if result.is_ok() {
+            self.declared.extend(TypeChecker::declarations(program));
+        }
+    }
+    
+    /// Returns the execution limits (all off by default).
+    pub fn limits(&self) -> &EvaluatorLimits {
+        &self.limits
+    }
+    
+    /// Sets the execution limits applied to every following run.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// # use bccl::{BcclError, Evaluator, EvaluatorLimits, Lexer, Parser};
+    /// let mut evaluator = Evaluator::new();
+    /// evaluator.set_limits(EvaluatorLimits::new().with_max_steps(10));
+    /// 
+    /// let tokens = Lexer::new("1 + 2 + 3 + 4 + 5 + 6").tokenize().unwrap();
+    /// let program = Parser::new(tokens).parse().unwrap();
+    /// evaluator.set_optimize(false);
+    /// match evaluator.evaluate_program(&program) {
+    ///     Err(BcclError::LimitExceeded { limit, .. }) => assert_eq!(limit, "steps"),
+    ///     other => panic!("unexpected {:?}", other),
+    /// }
+    /// ```
+    pub fn set_limits(&mut self, limits: EvaluatorLimits) {
+        self.limits = limits;
+    }
+    
+    /// Returns a clone of the token that cancels this evaluator's runs.
+    /// 
+    /// Call `cancel()` on it from any thread to stop the current run at its
+    /// next statement or function call.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+    
+    /// Replaces the cancellation token, e.g. to cancel several evaluators
+    /// with one token.
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation = token;
+    }
+    
+    /// Sets the point in time after which runs stop with
+    /// `BcclError::Cancelled` (`None`, the default, means no deadline).
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// # use std::time::Instant;
+    /// # use bccl::{BcclError, Evaluator, Lexer, Parser};
+    /// let mut evaluator = Evaluator::new();
+    /// evaluator.set_deadline(Some(Instant::now()));
+    /// 
+    /// let tokens = Lexer::new("max(1, 2)").tokenize().unwrap();
+    /// let program = Parser::new(tokens).parse().unwrap();
+    /// match evaluator.evaluate_program(&program) {
+    ///     Err(BcclError::Cancelled { reason, .. }) => assert_eq!(reason, "deadline"),
+    ///     other => panic!("unexpected {:?}", other),
+    /// }
+    /// ```
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+    }
+    
+    /// Optimizes a program for this evaluator.
+    /// 
+    /// Calls are resolved to registered functions unless a current variable
+    /// shadows them. The result is only valid while that stays true, so it
+    /// should be evaluated straight away; `evaluate_program` does this.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// # use bccl::{Evaluator, Expr, Lexer, Parser, Stmt};
+    /// let tokens = Lexer::new("max(1 + 1, 3)").tokenize().unwrap();
+    /// let program = Parser::new(tokens).parse().unwrap();
+    /// let optimized = Evaluator::new().optimize(&program);
+    /// match &optimized.statements[0] {
+    ///     Stmt::Expression { expr: Expr::FunctionCall { args, builtin, .. }, .. } => {
+    ///         assert!(*builtin);
+    ///         assert!(matches!(args[0], Expr::Number { value, .. } if value == 2.0));
+    ///     }
+    ///     other => panic!("unexpected {:?}", other),
+    /// }
+    /// ```
+    pub fn optimize(&self, program: &Program) -> Program {
+        let functions = self.functions.keys()
+            .filter(|name| self.environment.get(name).is_none())
+            .cloned();
+        Optimizer::new().with_functions(functions).with_limits(self.limits).optimize(program)
+    }
+    
+    /// Assigns variable slots in `program` for this evaluator's environment
+    /// (see `Resolver`). `resolve_program` does this on a copy.
+    pub fn resolve(&mut self, program: &mut Program) {
+        Resolver::new(&mut self.environment).resolve(program);
+    }
+    
+    /// The slot for `name` in the environment, following the `Resolver`'s
+    /// rules: names the code assigns live in this evaluator's own environment,
+    /// others may be read from shared globals. The VM uses this to map a
+    /// chunk's name table to slots when a run starts.
+    pub(crate) fn slot_for(&mut self, name: &str, assigned: bool) -> Slot {
+        if assigned {
+            self.environment.declare(name)
+        } else {
+            self.environment.resolve(name)
         }
     }
     
@@ -78,7 +481,8 @@ impl Evaluator {
     /// 
     /// A program consists of multiple statements. Each statement is evaluated in sequence,
     /// and the result of the last expression statement is returned. Assignment statements
-    /// also return their assigned value.
+    /// also return their assigned value. With type checking on (see
+    /// `set_type_check`), the whole program is checked before it runs.
     /// 
     /// # Arguments
     /// 
@@ -88,11 +492,12 @@ impl Evaluator {
     /// 
     /// * `Ok(Some(value))` - The value of the last expression
     /// * `Ok(None)` - If the program contains no expressions
-    /// * `Err(error)` - If any statement fails to evaluate
+    /// * `Err(error)` - If any statement fails to evaluate, or the program
+    ///   has type errors
     /// 
     /// # Example
     /// 
-    /// ```rust
+    /// ```rust,ignore
     /// let program = parser.parse()?;
     /// match evaluator.evaluate_program(&program)? {
     ///     Some(value) => println!("{}", value.display()),
@@ -100,6 +505,115 @@ impl Evaluator {
     /// }
     /// ```
     pub fn evaluate_program(&mut self, program: &Program) -> BcclResult<Option<Value>> {
+        if self.type_check {
+            self.check(program)?;
+        }
+        let result = match self.backend {
+            Backend::TreeWalk => {
+                let mut resolved = self.resolve_program(program);
+                self.walk_resolved(&mut resolved)
+            }
+            Backend::Bytecode => {
+                let program = if self.optimize {
+                    self.optimize(program)
+                } else {
+                    program.clone()
+                };
+                let chunk = Compiler::compile(&program)?;
+                self.run_chunk(&chunk)
+            }
+        };
+        self.declare(program, &result);
+        result
+    }
+    
+    /// Optimizes (if enabled) and resolves `program` for this evaluator, so
+    /// it can be run many times with `run_resolved` without repeating either.
+    pub fn resolve_program(&mut self, program: &Program) -> ResolvedProgram {
+        let program = if self.optimize {
+            self.optimize(program)
+        } else {
+            program.clone()
+        };
+        ResolvedProgram::new(program, &mut self.environment)
+    }
+    
+    /// Runs a resolved program on the tree-walker, whatever the selected
+    /// backend, type-checking it first if that is enabled. The program is
+    /// resolved again only if this evaluator's environment changed in a way
+    /// that could move its slots (see `ResolvedProgram`).
+    /// 
+    /// Hosts that run the same script many times can resolve it once with
+    /// `resolve_program` and call this for every run.
+    pub fn run_resolved(&mut self, program: &mut ResolvedProgram) -> BcclResult<Option<Value>> {
+        if self.type_check {
+            self.check(program.program())?;
+        }
+        let result = self.walk_resolved(program);
+        self.declare(program.program(), &result);
+        result
+    }
+    
+    fn walk_resolved(&mut self, program: &mut ResolvedProgram) -> BcclResult<Option<Value>> {
+        let program = program.refresh(&mut self.environment);
+        self.usage.reset();
+        self.walk_program(program)
+    }
+    
+    /// Runs a compiled program on the bytecode VM, whatever the selected
+    /// backend. The chunk is run as compiled, without optimization.
+    /// 
+    /// Hosts that run the same script many times can compile it once with
+    /// `Compiler::compile` and call this for every run.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// # use bccl::{Compiler, Evaluator, Lexer, Parser};
+    /// let tokens = Lexer::new("x = 2; x * 21").tokenize().unwrap();
+    /// let program = Parser::new(tokens).parse().unwrap();
+    /// let chunk = Compiler::compile(&program).unwrap();
+    /// 
+    /// let mut evaluator = Evaluator::new();
+    /// let result = evaluator.run_chunk(&chunk).unwrap();
+    /// assert_eq!(result.unwrap().display(), "42");
+    /// ```
+    pub fn run_chunk(&mut self, chunk: &Chunk) -> BcclResult<Option<Value>> {
+        self.usage.reset();
+        Vm::new(self).run(chunk)
+    }
+    
+    /// Counts one evaluation step against the limits.
+    pub(crate) fn step(&self, span: Span) -> BcclResult<()> {
+        self.usage.step(&self.limits, span)
+    }
+    
+    /// Records the statement that is about to run and stops if the run was
+    /// cancelled or is past its deadline.
+    pub(crate) fn enter_statement(&self, span: Span) -> BcclResult<()> {
+        self.usage.enter_statement(span);
+        self.check_interrupt()
+    }
+    
+    /// Fails with `BcclError::Cancelled` if the run was cancelled or is past
+    /// its deadline.
+    fn check_interrupt(&self) -> BcclResult<()> {
+        if self.cancellation.is_cancelled() {
+            return Err(BcclError::cancelled(self.usage.statement()));
+        }
+        if let Some(deadline) = self.deadline && Instant::now() >= deadline {
+            return Err(BcclError::deadline_exceeded(self.usage.statement()));
+        }
+        Ok(())
+    }
+    
+    /// Checks a newly created value against the size limits.
+    pub(crate) fn allocate(&self, value: &Value, span: Span) -> BcclResult<()> {
+        self.usage.allocate(&self.limits, value, span)
+    }
+    
+    /// Evaluates a program by walking its AST (`Backend::TreeWalk`).
+    fn walk_program(&mut self, program: &Program) -> BcclResult<Option<Value>> {
         let mut last_value = None;
         
         for stmt in &program.statements {
@@ -126,34 +640,31 @@ impl Evaluator {
     /// * `Ok(None)` - For statements that don't produce values
     /// * `Err(error)` - If the statement fails to evaluate
     fn evaluate_statement(&mut self, stmt: &Stmt) -> BcclResult<Option<Value>> {
+        self.enter_statement(stmt.span())?;
+        self.step(stmt.span())?;
         match stmt {
             Stmt::Expression { expr, span: _ } => {
                 let value = self.evaluate_expression(expr)?;
                 Ok(Some(value))
             }
-            Stmt::Assignment { name, value, span: _ } => {
+            Stmt::Assignment { name, value, slot, .. } => {
                 let evaluated_value = self.evaluate_expression(value)?;
-                self.environment.define(name.clone(), evaluated_value.clone());
+                self.store_variable(name, *slot, evaluated_value.clone());
                 Ok(Some(evaluated_value))
             }
-            Stmt::CompoundAssignment { name, operator, value, span } => {
+            Stmt::CompoundAssignment { name, operator, value, span, slot } => {
                 // Get the current value of the variable
-                let current_value = self.environment
-                    .get(name)
-                    .cloned()
-                    .ok_or_else(|| {
-                        let available_vars = self.environment.variable_names();
-                        BcclError::undefined_variable(name, *span, &available_vars)
-                    })?;
+                let current_value = self.load_variable(name, *slot, *span)?;
                 
                 // Evaluate the right-hand side
                 let rhs_value = self.evaluate_expression(value)?;
                 
                 // Perform the compound operation
-                let result = self.perform_compound_operation(&current_value, operator, &rhs_value, name, *span)?;
+                let result = operations::compound(&current_value, operator, &rhs_value, name, *span)?;
+                self.allocate(&result, *span)?;
                 
                 // Store the result
-                self.environment.define(name.clone(), result.clone());
+                self.store_variable(name, *slot, result.clone());
                 Ok(Some(result))
             }
         }
@@ -170,6 +681,9 @@ impl Evaluator {
     /// - Collections: lists and dictionaries
     /// - Indexing: list[index] and dict[key]
     /// 
+    /// The operators themselves live in `operations`, which the bytecode VM
+    /// shares, so both backends report the same errors at the same spans.
+    /// 
     /// # Arguments
     /// 
     /// * `expr` - The expression to evaluate
@@ -179,54 +693,47 @@ impl Evaluator {
     /// * `Ok(value)` - The resulting value
     /// * `Err(error)` - If evaluation fails (type errors, undefined variables, etc.)
     fn evaluate_expression(&self, expr: &Expr) -> BcclResult<Value> {
+        self.step(expr.span())?;
+        let value = self.evaluate_node(expr)?;
+        
+        // Only these expressions create values; the rest return existing ones
+        if matches!(expr, Expr::String { .. } | Expr::Binary { .. } | Expr::FunctionCall { .. } | Expr::List { .. } | Expr::Dictionary { .. }) {
+            self.allocate(&value, expr.span())?;
+        }
+        Ok(value)
+    }
+    
+    /// Evaluates one expression node, without the limit checks of
+    /// `evaluate_expression`.
+    fn evaluate_node(&self, expr: &Expr) -> BcclResult<Value> {
         match expr {
             Expr::Number { value, span: _ } => Ok(Value::Number(*value)),
             Expr::Integer { value, span: _ } => Ok(Value::Integer(*value)),
             Expr::Boolean { value, span: _ } => Ok(Value::Boolean(*value)),
-            Expr::String { value, span: _ } => Ok(Value::String(value.clone())),
-            Expr::Identifier { name, span } => {
-                self.environment
-                    .get(name)
-                    .cloned()
-                    .ok_or_else(|| {
-                        let available_vars = self.environment.variable_names();
-                        BcclError::undefined_variable(name, *span, &available_vars)
-                    })
-            }
+            Expr::String { value, span: _ } => Ok(Value::string(value.clone())),
+            Expr::Identifier { name, span, slot } => self.load_name(name, *slot, *span),
             Expr::Binary { left, operator, right, span } => {
-                self.evaluate_binary_expression(left, operator, right, *span)
+                // Both operands are always evaluated, so an error on the right
+                // is reported even when `and`/`or` would return the left value
+                let left_val = self.evaluate_expression(left)?;
+                let right_val = self.evaluate_expression(right)?;
+                operations::binary(operator, left_val, left.span(), right_val, right.span(), *span)
             }
             Expr::Unary { operator, operand, span: _ } => {
                 let operand_val = self.evaluate_expression(operand)?;
-                
-                match operator {
-                    UnaryOp::Minus | UnaryOp::Plus => {
-                        let operand_num = operand_val.as_number()
-                            .ok_or_else(|| BcclError::type_error(
-                                "Operand must be a number", 
-                                operand.span(), 
-                                "number", 
-                                operand_val.type_name()
-                            ))?;
-                        
-                        let result = match operator {
-                            UnaryOp::Minus => -operand_num,
-                            UnaryOp::Plus => operand_num,
-                            _ => unreachable!(),
-                        };
-                        
-                        Ok(Value::Number(result))
-                    }
-                    UnaryOp::Not => {
-                        let result = !operand_val.is_truthy();
-                        Ok(Value::Boolean(result))
-                    }
-                }
+                operations::unary(operator, operand_val, operand.span())
             }
-            Expr::FunctionCall { name, args, kwargs, span } => {
+            Expr::FunctionCall { name, args, kwargs, span, builtin, slot } => {
                 // Evaluate all positional arguments with their spans
                 let mut arg_values_with_spans = Vec::new();
                 for arg in args {
+                    if let Expr::Unpack { value, span: unpack_span } = arg {
+                        // `*xs`: every item is an argument, reported at the `*xs` span
+                        let list = self.evaluate_expression(value)?;
+                        operations::check_unpack_positional(&list, value.span())?;
+                        operations::unpack_positional(list, *unpack_span, &mut arg_values_with_spans);
+                        continue;
+                    }
                     let value = self.evaluate_expression(arg)?;
                     arg_values_with_spans.push((value, arg.span()));
                 }
@@ -235,23 +742,32 @@ impl Evaluator {
                 let mut kwarg_values_with_spans = Vec::new();
                 for (param_name, value_expr) in kwargs {
                     let value = self.evaluate_expression(value_expr)?;
-                    kwarg_values_with_spans.push((param_name.clone(), value, value_expr.span()));
+                    match param_name {
+                        Some(param_name) => {
+                            kwarg_values_with_spans.push((param_name.clone(), value, value_expr.span()));
+                        }
+                        // `**d`: every entry is a keyword argument, in sorted key order
+                        None => {
+                            operations::check_unpack_keywords(&value, value_expr.span())?;
+                            operations::unpack_keywords(value, value_expr.span(), &mut kwarg_values_with_spans);
+                        }
+                    }
                 }
                 
-                // Look up the function signature and call with proper parameter validation
-                if let Some(func_sig) = self.functions.get(name) {
-                    func_sig.call_with_spans(&arg_values_with_spans, &kwarg_values_with_spans, *span)
+                let callee = if *builtin {
+                    Value::Function(name.clone())
                 } else {
-                    let available_functions: Vec<String> = self.functions.keys().cloned().collect();
-                    Err(BcclError::undefined_function(name, *span, &available_functions))
-                }
+                    self.resolve_callee(name, *slot, *span)?
+                };
+                self.call_value(&callee, &arg_values_with_spans, &kwarg_values_with_spans, *span)
             }
+            Expr::Unpack { span, .. } => Err(operations::unpack_outside_call(*span)),
             Expr::List { elements, span: _ } => {
                 let mut values = Vec::new();
                 for element in elements {
                     values.push(self.evaluate_expression(element)?);
                 }
-                Ok(Value::List(values))
+                Ok(Value::list(values))
             }
             Expr::Dictionary { pairs, span: _ } => {
                 let mut dict = HashMap::new();
@@ -259,248 +775,236 @@ impl Evaluator {
                     let value = self.evaluate_expression(value_expr)?;
                     dict.insert(key.clone(), value);
                 }
-                Ok(Value::Dictionary(dict))
+                Ok(Value::dict(dict))
             }
             Expr::Index { object, index, span } => {
-                self.evaluate_index_expression(object, index, *span)
+                let obj_value = self.evaluate_expression(object)?;
+                let index_value = self.evaluate_expression(index)?;
+                operations::index(&obj_value, object.span(), &index_value, index.span(), *span)
             }
         }
     }
     
-    /// Evaluates binary expressions (e.g., `a + b`, `x == y`).
+    /// Resolves a name used as a value.
     /// 
-    /// Handles all binary operations including:
-    /// - Arithmetic: +, -, *, /
-    /// - Comparison: ==, !=, <, >, <=, >=
-    /// - Logical: and, or
-    /// - Membership: in, not in
+    /// A variable wins; otherwise a built-in name evaluates to a function value,
+    /// e.g. `map(str, xs)`.
     /// 
-    /// # Arguments
+    /// # Returns
     /// 
-    /// * `left` - Left operand expression
-    /// * `operator` - Binary operator
-    /// * `right` - Right operand expression
-    /// * `span` - Source location for error reporting
+    /// * `Ok(value)` - The variable's value or the function value
+    /// * `Err(undefined_variable)` - If the name is neither
+    pub(crate) fn load_name(&self, name: &str, slot: Option<Slot>, span: Span) -> BcclResult<Value> {
+        if let Some(value) = self.lookup(name, slot) {
+            return Ok(value.clone());
+        }
+        if self.functions.contains_key(name) {
+            return Ok(Value::Function(name.to_string()));
+        }
+        if let Some(capability) = system::required_capability(name) {
+            return Err(BcclError::missing_capability(name, capability.name(), span));
+        }
+        let available_vars = self.environment.variable_names();
+        Err(BcclError::undefined_variable(name, span, &available_vars))
+    }
+    
+    /// Resolves a variable for compound assignment, which never falls back to
+    /// a built-in.
+    pub(crate) fn load_variable(&self, name: &str, slot: Option<Slot>, span: Span) -> BcclResult<Value> {
+        self.lookup(name, slot)
+            .cloned()
+            .ok_or_else(|| {
+                let available_vars = self.environment.variable_names();
+                BcclError::undefined_variable(name, span, &available_vars)
+            })
+    }
+    
+    /// Stores a variable, replacing any previous value.
+    pub(crate) fn store_variable(&mut self, name: &str, slot: Option<Slot>, value: Value) {
+        match slot {
+            Some(slot) => self.environment.set_slot(slot, value),
+            None => self.environment.define(name.to_string(), value),
+        }
+    }
+    
+    /// Reads a variable by its resolved slot, or by name if it has none.
+    fn lookup(&self, name: &str, slot: Option<Slot>) -> Option<&Value> {
+        match slot {
+            Some(slot) => self.environment.get_slot(slot),
+            None => self.environment.get(name),
+        }
+    }
+    
+    /// Resolves the callee of a call expression `name(...)`.
+    /// 
+    /// A variable shadows a built-in of the same name, so `f = str; f(3)` works.
     /// 
     /// # Returns
     /// 
-    /// * `Ok(value)` - Result of the binary operation
-    /// * `Err(error)` - Type errors, division by zero, etc.
-    fn evaluate_binary_expression(&self, left: &Expr, operator: &BinaryOp, right: &Expr, span: Span) -> BcclResult<Value> {
-        let left_val = self.evaluate_expression(left)?;
-        let right_val = self.evaluate_expression(right)?;
-        
-        match operator {
-            // Arithmetic operations - require numbers
-            BinaryOp::Add | BinaryOp::Subtract | BinaryOp::Multiply | BinaryOp::Divide => {
-                let left_num = left_val.as_number()
-                    .ok_or_else(|| BcclError::type_error(
-                        "Left operand must be a number", 
-                        left.span(), 
-                        "number", 
-                        left_val.type_name()
-                    ))?;
-                    
-                let right_num = right_val.as_number()
-                    .ok_or_else(|| BcclError::type_error(
-                        "Right operand must be a number", 
-                        right.span(), 
-                        "number", 
-                        right_val.type_name()
-                    ))?;
-                
-                let result = match operator {
-                    BinaryOp::Add => left_num + right_num,
-                    BinaryOp::Subtract => left_num - right_num,
-                    BinaryOp::Multiply => left_num * right_num,
-                    BinaryOp::Divide => {
-                        if right_num == 0.0 {
-                            return Err(BcclError::division_by_zero(span, right.span()));
-                        }
-                        left_num / right_num
-                    }
-                    _ => unreachable!(),
-                };
-                
-                Ok(Value::Number(result))
-            }
-            
-            // Equality operations - work with any types
-            BinaryOp::Equal => {
-                let result = self.values_equal(&left_val, &right_val);
-                Ok(Value::Boolean(result))
-            }
-            BinaryOp::NotEqual => {
-                let result = !self.values_equal(&left_val, &right_val);
-                Ok(Value::Boolean(result))
-            }
-            
-            // Comparison operations - require numbers
-            BinaryOp::Less | BinaryOp::Greater | BinaryOp::LessEqual | BinaryOp::GreaterEqual => {
-                let op_str = match operator {
-                    BinaryOp::Less => "<",
-                    BinaryOp::Greater => ">", 
-                    BinaryOp::LessEqual => "<=",
-                    BinaryOp::GreaterEqual => ">=",
-                    _ => unreachable!(),
-                };
-                
-                let left_num = left_val.as_number()
-                    .ok_or_else(|| BcclError::logical_operation_error(
-                        op_str,
-                        &format!("Cannot compare {} with numbers", left_val.type_name()),
-                        left.span()
-                    ))?;
-                    
-                let right_num = right_val.as_number()
-                    .ok_or_else(|| BcclError::logical_operation_error(
-                        op_str,
-                        &format!("Cannot compare numbers with {}", right_val.type_name()),
-                        right.span()
-                    ))?;
-                
-                let result = match operator {
-                    BinaryOp::Less => left_num < right_num,
-                    BinaryOp::Greater => left_num > right_num,
-                    BinaryOp::LessEqual => left_num <= right_num,
-                    BinaryOp::GreaterEqual => left_num >= right_num,
-                    _ => unreachable!(),
-                };
-                
-                Ok(Value::Boolean(result))
-            }
-            
-            // Logical operations - use truthiness
-            BinaryOp::And => {
-                // Short-circuit evaluation: if left is falsy, return left
-                if !left_val.is_truthy() {
-                    Ok(left_val)
-                } else {
-                    Ok(right_val)
-                }
-            }
-            BinaryOp::Or => {
-                // Short-circuit evaluation: if left is truthy, return left
-                if left_val.is_truthy() {
-                    Ok(left_val)
-                } else {
-                    Ok(right_val)
-                }
-            }
-            
-            // Membership operations
-            BinaryOp::In => {
-                self.evaluate_membership(&left_val, &right_val, left, right, false)
-            }
-            BinaryOp::NotIn => {
-                self.evaluate_membership(&left_val, &right_val, left, right, true)
-            }
+    /// * `Ok(value)` - The callee (not yet checked to be callable)
+    /// * `Err(undefined_function)` - If the name is neither
+    pub(crate) fn resolve_callee(&self, name: &str, slot: Option<Slot>, span: Span) -> BcclResult<Value> {
+        if let Some(value) = self.lookup(name, slot) {
+            Ok(value.clone())
+        } else if self.functions.contains_key(name) {
+            Ok(Value::Function(name.to_string()))
+        } else {
+            Err(self.unknown_function(name, span))
         }
     }
     
-    /// Evaluates indexing expressions (e.g., `list[0]`, `dict["key"]`).
+    /// Calls a function value with already-evaluated arguments.
     /// 
-    /// Supports:
-    /// - List indexing with integers: `[1,2,3][0]` → `1`
-    /// - Dictionary lookup with strings: `{"a": 1}["a"]` → `1`
+    /// This is the single entry point for calls: function call expressions use it,
+    /// and native built-ins such as `map()` use it to invoke their callbacks.
     /// 
     /// # Arguments
     /// 
-    /// * `object` - The collection being indexed
-    /// * `index` - The index or key expression
-    /// * `span` - Source location for error reporting
+    /// * `callee` - The value being called (must be a `Value::Function`)
+    /// * `args` - Positional arguments with their source spans
+    /// * `kwargs` - Keyword arguments with their source spans
+    /// * `span` - Source location of the call (or of the callee expression)
     /// 
     /// # Returns
     /// 
-    /// * `Ok(value)` - The value at the specified index/key
-    /// * `Err(error)` - Index out of bounds, key not found, type errors
-    fn evaluate_index_expression(&self, object: &Expr, index: &Expr, span: Span) -> BcclResult<Value> {
-        let obj_value = self.evaluate_expression(object)?;
-        let index_value = self.evaluate_expression(index)?;
-        
-        match (&obj_value, &index_value) {
-            (Value::List(items), Value::Integer(i)) => {
-                let idx = *i as usize;
-                if idx < items.len() {
-                    Ok(items[idx].clone())
-                } else {
-                    Err(BcclError::index_out_of_bounds("list", idx, items.len(), span))
+    /// * `Ok(value)` - The function's result
+    /// * `Err(type_error)` - If `callee` is not a function
+    /// * `Err(error)` - Any error raised by the function itself
+    pub(crate) fn call_value(&self, callee: &Value, args: &[(Value, Span)], kwargs: &[(String, Value, Span)], span: Span) -> BcclResult<Value> {
+        match callee {
+            Value::Function(name) => match self.functions.get(name) {
+                Some(func_sig) => {
+                    self.check_interrupt()?;
+                    self.usage.enter_call(&self.limits, span)?;
+                    let result = func_sig.call_with_spans(self, args, kwargs, span);
+                    self.usage.leave_call();
+                    result
                 }
-            }
-            (Value::Dictionary(dict), Value::String(key)) => {
-                dict.get(key)
-                    .cloned()
-                    .ok_or_else(|| BcclError::key_not_found(key, span, &dict.keys().cloned().collect::<Vec<_>>()))
-            }
-            (Value::List(_), _) => {
-                Err(BcclError::collection_operation_error(
-                    "index",
-                    &format!("List indices must be integers, not {}", index_value.type_name()),
-                    index.span()
-                ))
-            }
-            (Value::Dictionary(_), _) => {
-                Err(BcclError::collection_operation_error(
-                    "index",
-                    &format!("Dictionary keys must be strings, not {}", index_value.type_name()),
-                    index.span()
-                ))
-            }
-            (_, _) => {
-                Err(BcclError::collection_operation_error(
-                    "index",
-                    &format!("Cannot index {} values - only lists and dictionaries support indexing", obj_value.type_name()),
-                    object.span()
-                ))
-            }
+                None => Err(self.unknown_function(name, span)),
+            },
+            other => Err(BcclError::type_error(
+                &format!("{} is not callable", other.display()),
+                span,
+                "function",
+                other.type_name()
+            )),
         }
     }
     
-    /// Evaluates membership expressions (`in` and `not in`).
+    /// Registers a function, making it callable from BCCL code.
     /// 
-    /// Tests whether a value is contained within a collection:
-    /// - `5 in [1, 2, 5]` → `true`
-    /// - `"key" in {"key": "value"}` → `true`
+    /// Host programs use this to add their own functions next to the built-ins.
+    /// A function with the same name as an existing one replaces it.
     /// 
     /// # Arguments
     /// 
-    /// * `left_val` - The value to search for
-    /// * `right_val` - The collection to search in
-    /// * `left` - Left expression (for error reporting)
-    /// * `right` - Right expression (for error reporting)
-    /// * `negate` - Whether this is `not in` (true) or `in` (false)
+    /// * `signature` - The function's name, parameters and implementation
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// # use bccl::{BuiltinCall, Evaluator, FunctionSignature, Parameters, Value, BcclResult};
+    /// fn greet(call: &BuiltinCall) -> BcclResult<Value> {
+    ///     let name = call.args[0].display();
+    ///     let (greeting, _) = call.option("greeting").unwrap();
+    ///     Ok(Value::string(format!("{}, {}", greeting.as_string().unwrap_or("Hello"), name)))
+    /// }
+    /// 
+    /// let mut evaluator = Evaluator::new();
+    /// let params = Parameters::new()
+    ///     .positional("name")
+    ///     .keyword_only_with_default("greeting", Value::string("Hello"));
+    /// evaluator.define_function(FunctionSignature::new_native_with_parameters("greet", params, greet));
+    /// ```
+    pub fn define_function(&mut self, signature: FunctionSignature) {
+        self.functions.insert(signature.name.clone(), signature);
+    }
+    
+    /// The error for calling `name` when no such function is registered: a
+    /// missing capability for system built-ins, otherwise an undefined function.
+    fn unknown_function(&self, name: &str, span: Span) -> BcclError {
+        match system::required_capability(name) {
+            Some(capability) => BcclError::missing_capability(name, capability.name(), span),
+            None => BcclError::undefined_function(name, span, &self.function_names()),
+        }
+    }
+    
+    /// Names of all callable functions, sorted so suggestions are stable.
+    fn function_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.functions.keys().cloned().collect();
+        names.sort();
+        names
+    }
+    
+    /// Captures the session: every variable, and the names of the functions
+    /// added with `define_function`.
+    /// 
+    /// Variables visible through shared globals (`with_globals`) are included,
+    /// so the snapshot restores into a plain evaluator.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// # use bccl::{Evaluator, Snapshot, Value};
+    /// let mut evaluator = Evaluator::new();
+    /// evaluator.set_variable("rate", Value::Number(0.25));
+    /// 
+    /// let snapshot = evaluator.snapshot();
+    /// assert_eq!(snapshot.version, Snapshot::FORMAT_VERSION);
+    /// assert_eq!(snapshot.variables[0].0, "rate");
+    /// ```
+    pub fn snapshot(&self) -> Snapshot {
+        let mut names = self.get_variable_names();
+        names.sort();
+        let variables = names.into_iter()
+            .filter_map(|name| self.get_variable(&name).cloned().map(|value| (name, value)))
+            .collect();
+        let builtins = get_builtin_functions(Capabilities::all());
+        let mut functions: Vec<String> = self.functions.keys()
+            .filter(|name| !builtins.contains_key(*name))
+            .cloned()
+            .collect();
+        functions.sort();
+        Snapshot { version: Snapshot::FORMAT_VERSION, variables, functions }
+    }
+    
+    /// Replaces every variable with those in `snapshot`.
+    /// 
+    /// Settings (backend, limits, capabilities) and registered functions are
+    /// kept, as are shared globals. Types declared by annotations go with the
+    /// old variables.
     /// 
     /// # Returns
     /// 
-    /// * `Ok(Boolean(found))` - Whether the value was found
-    /// * `Err(error)` - If the right operand is not a collection
-    fn evaluate_membership(&self, left_val: &Value, right_val: &Value, left: &Expr, right: &Expr, negate: bool) -> BcclResult<Value> {
-        let found = match right_val {
-            Value::List(items) => {
-                items.iter().any(|item| self.values_equal(left_val, item))
-            }
-            Value::Dictionary(dict) => {
-                if let Value::String(key) = left_val {
-                    dict.contains_key(key)
-                } else {
-                    return Err(BcclError::collection_operation_error(
-                        "membership",
-                        &format!("Dictionary keys must be strings, not {}", left_val.type_name()),
-                        left.span()
-                    ));
-                }
-            }
-            _ => {
-                let op_name = if negate { "not in" } else { "in" };
-                return Err(BcclError::collection_operation_error(
-                    "membership",
-                    &format!("Cannot use '{}' with {} - only lists and dictionaries support membership testing", op_name, right_val.type_name()),
-                    right.span()
-                ));
-            }
-        };
+    /// * `Ok(())` - The variables were replaced
+    /// * `Err(BcclError::SnapshotError)` - If the snapshot has another format
+    ///   version, or names a host function that is not registered here; the
+    ///   evaluator is left unchanged
+    pub fn restore(&mut self, snapshot: &Snapshot) -> BcclResult<()> {
+        if snapshot.version != Snapshot::FORMAT_VERSION {
+            return Err(BcclError::incompatible_snapshot(snapshot.version, Snapshot::FORMAT_VERSION));
+        }
+        let missing: Vec<&str> = snapshot.functions.iter()
+            .filter(|name| !self.functions.contains_key(*name))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return Err(BcclError::snapshot_error(
+                &format!("the snapshot needs functions that are not defined: {}", missing.join(", ")),
+                "Register them with Evaluator::define_function before restoring",
+            ));
+        }
         
-        Ok(Value::Boolean(if negate { !found } else { found }))
+        let mut environment = match self.environment.base() {
+            Some(base) => Environment::with_base(base.clone()),
+            None => Environment::new(),
+        };
+        for (name, value) in &snapshot.variables {
+            environment.define(name.clone(), value.clone());
+        }
+        self.environment = environment;
+        self.declared.clear();
+        Ok(())
     }
     
     /// Gets the value of a variable by name.
@@ -519,6 +1023,14 @@ impl Evaluator {
         self.environment.get(name)
     }
     
+    /// Sets a variable from the host, e.g. the inputs of one evaluation.
+    /// 
+    /// Like an assignment in BCCL, this never changes shared globals; it
+    /// shadows a global of the same name in this evaluator only.
+    pub fn set_variable(&mut self, name: &str, value: Value) {
+        self.environment.define(name.to_string(), value);
+    }
+    
     /// Gets a list of all defined variable names.
     /// 
     /// Used by the REPL's `:vars` command to show all variables.
@@ -529,96 +1041,4 @@ impl Evaluator {
     pub fn get_variable_names(&self) -> Vec<String> {
         self.environment.variable_names()
     }
-    
-    /// Performs compound assignment operations (+=, -=, *=, /=).
-    /// 
-    /// Compound operations modify a variable by applying an operation between
-    /// its current value and a new value.
-    /// 
-    /// # Arguments
-    /// 
-    /// * `current` - The variable's current value
-    /// * `operator` - The compound operator to apply
-    /// * `rhs` - The right-hand side value
-    /// * `variable_name` - Name of the variable (for error messages)
-    /// * `span` - Source location (for error reporting)
-    /// 
-    /// # Returns
-    /// 
-    /// * `Ok(value)` - The result of the compound operation
-    /// * `Err(error)` - If the operation fails (type mismatch, division by zero)
-    fn perform_compound_operation(&self, current: &Value, operator: &CompoundOp, rhs: &Value, variable_name: &str, span: Span) -> BcclResult<Value> {
-        let operator_str = match operator {
-            CompoundOp::Add => "+=",
-            CompoundOp::Subtract => "-=",
-            CompoundOp::Multiply => "*=",
-            CompoundOp::Divide => "/=",
-        };
-        
-        // For compound assignment, both operands must be numbers
-        let current_num = current.as_number()
-            .ok_or_else(|| BcclError::compound_assignment_error(
-                variable_name,
-                operator_str,
-                &format!("Variable '{}' contains {} but {} requires a number", variable_name, current.type_name(), operator_str),
-                span
-            ))?;
-            
-        let rhs_num = rhs.as_number()
-            .ok_or_else(|| BcclError::compound_assignment_error(
-                variable_name,
-                operator_str,
-                &format!("Cannot use {} with {} value", operator_str, rhs.type_name()),
-                span
-            ))?;
-        
-        let result = match operator {
-            CompoundOp::Add => current_num + rhs_num,
-            CompoundOp::Subtract => current_num - rhs_num,
-            CompoundOp::Multiply => current_num * rhs_num,
-            CompoundOp::Divide => {
-                if rhs_num == 0.0 {
-                    return Err(BcclError::division_by_zero(span, span));
-                }
-                current_num / rhs_num
-            }
-        };
-        
-        Ok(Value::Number(result))
-    }
-    
-    /// Compares two values for equality.
-    /// 
-    /// This method handles type coercion between integers and floats,
-    /// and provides proper equality semantics for all BCCL types.
-    /// 
-    /// # Arguments
-    /// 
-    /// * `left` - The left value to compare
-    /// * `right` - The right value to compare
-    /// 
-    /// # Returns
-    /// 
-    /// `true` if the values are considered equal, `false` otherwise.
-    /// 
-    /// # Type Coercion
-    /// 
-    /// - Numbers and integers can be compared (42 == 42.0)
-    /// - Different types are never equal
-    /// - Floating point comparison uses epsilon for precision
-    fn values_equal(&self, left: &Value, right: &Value) -> bool {
-        match (left, right) {
-            (Value::Number(a), Value::Number(b)) => (a - b).abs() < f64::EPSILON,
-            (Value::Integer(a), Value::Integer(b)) => a == b,
-            (Value::Boolean(a), Value::Boolean(b)) => a == b,
-            (Value::String(a), Value::String(b)) => a == b,
-            
-            // Allow comparison between integers and numbers
-            (Value::Number(a), Value::Integer(b)) => (*a - *b as f64).abs() < f64::EPSILON,
-            (Value::Integer(a), Value::Number(b)) => (*a as f64 - *b).abs() < f64::EPSILON,
-            
-            // Different types are not equal
-            _ => false,
-        }
-    }
-}
\ No newline at end of file
+}
@@ -0,0 +1,403 @@
+//! # Operator Semantics
+//!
+//! The operators of BCCL, written once over already-evaluated values so that
+//! every execution backend (the tree-walking `Evaluator` and the bytecode VM)
+//! produces the same results and the same diagnostics.
+//!
+//! Each function receives the spans of the expressions that produced its
+//! operands, so errors point at the same source locations whichever backend
+//! computed the values.
+
+use crate::parser::{BinaryOp, UnaryOp, CompoundOp};
+use crate::error::{BcclError, BcclResult, Span};
+use super::value::Value;
+
+/// Applies a binary operator to two evaluated operands.
+///
+/// `and` and `or` return one of their operands by truthiness; both operands
+/// have already been evaluated by the caller.
+///
+/// # Arguments
+///
+/// * `operator` - The binary operator
+/// * `left`, `left_span` - Left operand and the span of its expression
+/// * `right`, `right_span` - Right operand and the span of its expression
+/// * `span` - Span of the whole binary expression
+///
+/// # Returns
+///
+/// * `Ok(value)` - Result of the binary operation
+/// * `Err(error)` - Type errors, division by zero, etc.
+pub fn binary(operator: &BinaryOp, left: Value, left_span: Span, right: Value, right_span: Span, span: Span) -> BcclResult<Value> {
+    match operator {
+        // Arithmetic operations - require numbers
+        BinaryOp::Add | BinaryOp::Subtract | BinaryOp::Multiply | BinaryOp::Divide => {
+            let left_num = left.as_number()
+                .ok_or_else(|| BcclError::type_error(
+                    "Left operand must be a number",
+                    left_span,
+                    "number",
+                    left.type_name()
+                ))?;
+
+            let right_num = right.as_number()
+                .ok_or_else(|| BcclError::type_error(
+                    "Right operand must be a number",
+                    right_span,
+                    "number",
+                    right.type_name()
+                ))?;
+
+            let result = match operator {
+                BinaryOp::Add => left_num + right_num,
+                BinaryOp::Subtract => left_num - right_num,
+                BinaryOp::Multiply => left_num * right_num,
+                BinaryOp::Divide => {
+                    if right_num == 0.0 {
+                        return Err(BcclError::division_by_zero(span, right_span));
+                    }
+                    left_num / right_num
+                }
+                _ => unreachable!(),
+            };
+
+            Ok(Value::Number(result))
+        }
+
+        // Equality operations - work with any types
+        BinaryOp::Equal => Ok(Value::Boolean(values_equal(&left, &right))),
+        BinaryOp::NotEqual => Ok(Value::Boolean(!values_equal(&left, &right))),
+
+        // Comparison operations - numbers with numbers, strings with strings
+        BinaryOp::Less | BinaryOp::Greater | BinaryOp::LessEqual | BinaryOp::GreaterEqual => {
+            compare(operator, &left, left_span, &right, right_span)
+        }
+
+        // Logical operations - use truthiness
+        BinaryOp::And => {
+            // If left is falsy, return left
+            if !left.is_truthy() {
+                Ok(left)
+            } else {
+                Ok(right)
+            }
+        }
+        BinaryOp::Or => {
+            // If left is truthy, return left
+            if left.is_truthy() {
+                Ok(left)
+            } else {
+                Ok(right)
+            }
+        }
+
+        // Membership operations
+        BinaryOp::In => membership(&left, left_span, &right, right_span, false),
+        BinaryOp::NotIn => membership(&left, left_span, &right, right_span, true),
+    }
+}
+
+/// Applies one of the ordering operators (`<`, `>`, `<=`, `>=`).
+fn compare(operator: &BinaryOp, left: &Value, left_span: Span, right: &Value, right_span: Span) -> BcclResult<Value> {
+    let op_str = match operator {
+        BinaryOp::Less => "<",
+        BinaryOp::Greater => ">",
+        BinaryOp::LessEqual => "<=",
+        BinaryOp::GreaterEqual => ">=",
+        _ => unreachable!(),
+    };
+
+    // Strings compare lexicographically
+    if let (Value::String(a), Value::String(b)) = (left, right) {
+        let result = match operator {
+            BinaryOp::Less => a < b,
+            BinaryOp::Greater => a > b,
+            BinaryOp::LessEqual => a <= b,
+            BinaryOp::GreaterEqual => a >= b,
+            _ => unreachable!(),
+        };
+        return Ok(Value::Boolean(result));
+    }
+
+    if let Value::String(_) = left {
+        return Err(BcclError::logical_operation_error(
+            op_str,
+            &format!("Cannot compare strings with {}", right.type_name()),
+            right_span
+        ));
+    }
+
+    let left_num = left.as_number()
+        .ok_or_else(|| BcclError::logical_operation_error(
+            op_str,
+            &format!("Cannot compare {} with numbers", left.type_name()),
+            left_span
+        ))?;
+
+    let right_num = right.as_number()
+        .ok_or_else(|| BcclError::logical_operation_error(
+            op_str,
+            &format!("Cannot compare numbers with {}", right.type_name()),
+            right_span
+        ))?;
+
+    let result = match operator {
+        BinaryOp::Less => left_num < right_num,
+        BinaryOp::Greater => left_num > right_num,
+        BinaryOp::LessEqual => left_num <= right_num,
+        BinaryOp::GreaterEqual => left_num >= right_num,
+        _ => unreachable!(),
+    };
+
+    Ok(Value::Boolean(result))
+}
+
+/// Applies a unary operator (`-`, `+`, `not`) to an evaluated operand.
+pub fn unary(operator: &UnaryOp, operand: Value, operand_span: Span) -> BcclResult<Value> {
+    match operator {
+        UnaryOp::Minus | UnaryOp::Plus => {
+            let operand_num = operand.as_number()
+                .ok_or_else(|| BcclError::type_error(
+                    "Operand must be a number",
+                    operand_span,
+                    "number",
+                    operand.type_name()
+                ))?;
+
+            let result = match operator {
+                UnaryOp::Minus => -operand_num,
+                UnaryOp::Plus => operand_num,
+                _ => unreachable!(),
+            };
+
+            Ok(Value::Number(result))
+        }
+        UnaryOp::Not => Ok(Value::Boolean(!operand.is_truthy())),
+    }
+}
+
+/// Indexes a list with an integer or a dictionary with a string key.
+///
+/// # Arguments
+///
+/// * `object`, `object_span` - The collection and the span of its expression
+/// * `index`, `index_span` - The index or key and the span of its expression
+/// * `span` - Span of the whole index expression
+///
+/// # Returns
+///
+/// * `Ok(value)` - The value at the specified index/key
+/// * `Err(error)` - Index out of bounds, key not found, type errors
+pub fn index(object: &Value, object_span: Span, index: &Value, index_span: Span, span: Span) -> BcclResult<Value> {
+    match (object, index) {
+        (Value::List(items), Value::Integer(i)) => {
+            let idx = *i as usize;
+            if idx < items.len() {
+                Ok(items[idx].clone())
+            } else {
+                Err(BcclError::index_out_of_bounds("list", idx, items.len(), span))
+            }
+        }
+        (Value::Dictionary(dict), Value::String(key)) => {
+            dict.get(key.as_ref())
+                .cloned()
+                .ok_or_else(|| {
+                    let mut keys: Vec<String> = dict.keys().cloned().collect();
+                    keys.sort();
+                    BcclError::key_not_found(key, span, &keys)
+                })
+        }
+        (Value::List(_), _) => {
+            Err(BcclError::collection_operation_error(
+                "index",
+                &format!("List indices must be integers, not {}", index.type_name()),
+                index_span
+            ))
+        }
+        (Value::Dictionary(_), _) => {
+            Err(BcclError::collection_operation_error(
+                "index",
+                &format!("Dictionary keys must be strings, not {}", index.type_name()),
+                index_span
+            ))
+        }
+        (_, _) => {
+            Err(BcclError::collection_operation_error(
+                "index",
+                &format!("Cannot index {} values - only lists and dictionaries support indexing", object.type_name()),
+                object_span
+            ))
+        }
+    }
+}
+
+/// Tests membership (`in` / `not in`) of a value in a list or dictionary.
+///
+/// # Arguments
+///
+/// * `left`, `left_span` - The value to search for
+/// * `right`, `right_span` - The collection to search in
+/// * `negate` - Whether this is `not in` (true) or `in` (false)
+pub fn membership(left: &Value, left_span: Span, right: &Value, right_span: Span, negate: bool) -> BcclResult<Value> {
+    let found = match right {
+        Value::List(items) => {
+            items.iter().any(|item| values_equal(left, item))
+        }
+        Value::Dictionary(dict) => {
+            if let Value::String(key) = left {
+                dict.contains_key(key.as_ref())
+            } else {
+                return Err(BcclError::collection_operation_error(
+                    "membership",
+                    &format!("Dictionary keys must be strings, not {}", left.type_name()),
+                    left_span
+                ));
+            }
+        }
+        _ => {
+            let op_name = if negate { "not in" } else { "in" };
+            return Err(BcclError::collection_operation_error(
+                "membership",
+                &format!("Cannot use '{}' with {} - only lists and dictionaries support membership testing", op_name, right.type_name()),
+                right_span
+            ));
+        }
+    };
+
+    Ok(Value::Boolean(if negate { !found } else { found }))
+}
+
+/// Performs compound assignment operations (+=, -=, *=, /=).
+///
+/// # Arguments
+///
+/// * `current` - The variable's current value
+/// * `operator` - The compound operator to apply
+/// * `rhs` - The right-hand side value
+/// * `variable_name` - Name of the variable (for error messages)
+/// * `span` - Source location of the statement (for error reporting)
+///
+/// # Returns
+///
+/// * `Ok(value)` - The result of the compound operation
+/// * `Err(error)` - If the operation fails (type mismatch, division by zero)
+pub fn compound(current: &Value, operator: &CompoundOp, rhs: &Value, variable_name: &str, span: Span) -> BcclResult<Value> {
+    let operator_str = match operator {
+        CompoundOp::Add => "+=",
+        CompoundOp::Subtract => "-=",
+        CompoundOp::Multiply => "*=",
+        CompoundOp::Divide => "/=",
+    };
+
+    // For compound assignment, both operands must be numbers
+    let current_num = current.as_number()
+        .ok_or_else(|| BcclError::compound_assignment_error(
+            variable_name,
+            operator_str,
+            &format!("Variable '{}' contains {} but {} requires a number", variable_name, current.type_name(), operator_str),
+            span
+        ))?;
+
+    let rhs_num = rhs.as_number()
+        .ok_or_else(|| BcclError::compound_assignment_error(
+            variable_name,
+            operator_str,
+            &format!("Cannot use {} with {} value", operator_str, rhs.type_name()),
+            span
+        ))?;
+
+    let result = match operator {
+        CompoundOp::Add => current_num + rhs_num,
+        CompoundOp::Subtract => current_num - rhs_num,
+        CompoundOp::Multiply => current_num * rhs_num,
+        CompoundOp::Divide => {
+            if rhs_num == 0.0 {
+                return Err(BcclError::division_by_zero(span, span));
+            }
+            current_num / rhs_num
+        }
+    };
+
+    Ok(Value::Number(result))
+}
+
+/// Checks that the value of `*xs` in a call is a list.
+///
+/// The check runs as soon as `xs` is evaluated, before any later argument,
+/// so a bad unpack is reported ahead of errors further right in the call.
+pub fn check_unpack_positional(value: &Value, value_span: Span) -> BcclResult<()> {
+    match value {
+        Value::List(_) => Ok(()),
+        other => Err(BcclError::type_error(
+            &format!("Cannot unpack {} with '*' - only lists can be unpacked into arguments", other.type_name()),
+            value_span,
+            "list",
+            other.type_name()
+        )),
+    }
+}
+
+/// Checks that the value of `**d` in a call is a dictionary.
+pub fn check_unpack_keywords(value: &Value, value_span: Span) -> BcclResult<()> {
+    match value {
+        Value::Dictionary(_) => Ok(()),
+        other => Err(BcclError::type_error(
+            &format!("Cannot unpack {} with '**' - only dictionaries can be unpacked into keyword arguments", other.type_name()),
+            value_span,
+            "dictionary",
+            other.type_name()
+        )),
+    }
+}
+
+/// Expands a checked `*xs` list into positional arguments, each reported at
+/// the span of the whole `*xs`.
+pub fn unpack_positional(value: Value, unpack_span: Span, args: &mut Vec<(Value, Span)>) {
+    if let Value::List(items) = value {
+        args.extend(items.iter().map(|item| (item.clone(), unpack_span)));
+    }
+}
+
+/// Expands a checked `**d` dictionary into keyword arguments, in sorted key
+/// order, each reported at the span of `d`.
+pub fn unpack_keywords(value: Value, value_span: Span, kwargs: &mut Vec<(String, Value, Span)>) {
+    if let Value::Dictionary(dict) = value {
+        let mut entries: Vec<(String, Value)> = dict.iter().map(|(key, item)| (key.clone(), item.clone())).collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        kwargs.extend(entries.into_iter().map(|(key, item)| (key, item, value_span)));
+    }
+}
+
+/// The error for `*xs` outside call arguments, which the parser never
+/// produces but an AST built by hand can contain.
+pub fn unpack_outside_call(span: Span) -> BcclError {
+    BcclError::evaluation_error(
+        "'*' unpacking is only allowed in function call arguments",
+        span,
+        None
+    )
+}
+
+/// Compares two values for equality.
+///
+/// # Type Coercion
+///
+/// - Numbers and integers can be compared (42 == 42.0)
+/// - Different types are never equal
+/// - Floating point comparison uses epsilon for precision
+pub fn values_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => (a - b).abs() < f64::EPSILON,
+        (Value::Integer(a), Value::Integer(b)) => a == b,
+        (Value::Boolean(a), Value::Boolean(b)) => a == b,
+        (Value::String(a), Value::String(b)) => a == b,
+        (Value::Function(a), Value::Function(b)) => a == b,
+
+        // Allow comparison between integers and numbers
+        (Value::Number(a), Value::Integer(b)) => (*a - *b as f64).abs() < f64::EPSILON,
+        (Value::Integer(a), Value::Number(b)) => (*a as f64 - *b).abs() < f64::EPSILON,
+
+        // Different types are not equal
+        _ => false,
+    }
+}
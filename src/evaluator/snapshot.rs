@@ -0,0 +1,127 @@
+//! # Snapshots
+//!
+//! `Evaluator::snapshot` captures a session as a `Snapshot`: every variable
+//! with its value, plus the names of the functions the host added with
+//! `define_function`. `Evaluator::restore` replaces the evaluator's variables
+//! with the snapshot's. Native functions are Rust code and cannot be saved, so
+//! restoring checks that the same names are registered instead.
+//!
+//! ## Format
+//!
+//! With the `serde` feature, `Snapshot::encode` writes the snapshot as JSON
+//! through its serde derive, using the same schema as `Value` (see
+//! `value.rs`), and `Snapshot::decode` reads it back:
+//!
+//! ```text
+//! {"version":1,"variables":[["total",{"type":"number","value":3.5}]],"functions":["greet"]}
+//! ```
+//!
+//! `Snapshot::decode` and `Evaluator::restore` reject images whose version is
+//! not `Snapshot::FORMAT_VERSION` with `BcclError::SnapshotError`, before
+//! anything is changed. JSON has no form for non-finite numbers, so `encode`
+//! refuses a snapshot that holds one rather than write an image that cannot be
+//! read back.
+
+use super::value::Value;
+#[cfg(feature = "serde")]
+use crate::error::{BcclError, BcclResult};
+
+/// A saved session: variables and the host functions they may refer to.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "serde")] {
+/// # use bccl::{Evaluator, Lexer, Parser, Snapshot};
+/// let mut session = Evaluator::new();
+/// let tokens = Lexer::new("x = 40; names = [\"a\", \"b\"]").tokenize().unwrap();
+/// session.evaluate_program(&Parser::new(tokens).parse().unwrap()).unwrap();
+///
+/// let image = session.snapshot().encode().unwrap();
+/// let mut restored = Evaluator::new();
+/// restored.restore(&Snapshot::decode(&image).unwrap()).unwrap();
+/// assert_eq!(restored.get_variable("x").unwrap().display(), "40");
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    /// Format version the snapshot was written in
+    pub version: u32,
+    /// Variables and their values, sorted by name
+    pub variables: Vec<(String, Value)>,
+    /// Names of host-defined functions, sorted
+    pub functions: Vec<String>,
+}
+
+/// The part of an image that is read before anything else, so a snapshot
+/// from another format version is reported as such even if its layout changed.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct Header {
+    version: u32,
+}
+
+impl Snapshot {
+    /// The format version this build writes and reads.
+    pub const FORMAT_VERSION: u32 = 1;
+
+    /// Encodes the snapshot as JSON.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(image)` - The encoded snapshot
+    /// * `Err(BcclError::SnapshotError)` - If a variable holds an infinite or
+    ///   NaN number, which JSON cannot represent
+    #[cfg(feature = "serde")]
+    pub fn encode(&self) -> BcclResult<String> {
+        if let Some((name, _)) = self.variables.iter().find(|(_, value)| !is_finite(value)) {
+            return Err(BcclError::snapshot_error(
+                &format!("'{}' holds a number that is not finite, which a snapshot cannot store", name),
+                "Reassign or delete the variable before saving",
+            ));
+        }
+        serde_json::to_string(self).map_err(|error| BcclError::snapshot_error(
+            &error.to_string(),
+            "Snapshots can only hold values that serialize to JSON",
+        ))
+    }
+
+    /// Decodes a snapshot written by `encode`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(snapshot)` - The decoded snapshot
+    /// * `Err(BcclError::SnapshotError)` - If the text is not a snapshot, was
+    ///   written in another format version, or is malformed (the message names
+    ///   the line and column)
+    #[cfg(feature = "serde")]
+    pub fn decode(text: &str) -> BcclResult<Snapshot> {
+        let snapshot = serde_json::from_str::<Snapshot>(text);
+        match serde_json::from_str::<Header>(text) {
+            Ok(header) if header.version != Self::FORMAT_VERSION => {
+                Err(BcclError::incompatible_snapshot(header.version, Self::FORMAT_VERSION))
+            }
+            // A truncated image still reads as far as it goes
+            Err(error) if !error.is_eof() || text.trim().is_empty() => Err(BcclError::snapshot_error(
+                "this is not a BCCL snapshot",
+                "Snapshots are JSON objects with a \"version\" field; create one with :save or Evaluator::snapshot",
+            )),
+            _ => snapshot.map_err(|error| BcclError::snapshot_error(
+                &error.to_string(),
+                "The snapshot may be truncated or edited by hand; save it again with :save or Evaluator::snapshot",
+            )),
+        }
+    }
+}
+
+/// Whether every number in `value` is finite.
+#[cfg(feature = "serde")]
+fn is_finite(value: &Value) -> bool {
+    match value {
+        Value::Number(n) => n.is_finite(),
+        Value::List(items) => items.iter().all(is_finite),
+        Value::Dictionary(dict) => dict.values().all(is_finite),
+        _ => true,
+    }
+}
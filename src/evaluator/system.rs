@@ -0,0 +1,112 @@
+//! # System Built-ins
+//!
+//! Built-ins that reach outside the interpreter. Each one requires a
+//! `Capability`, and `get_builtin_functions` only registers those whose
+//! capability is granted:
+//!
+//! - **Files**: `read_file(path)` (`fs_read`), `write_file(path, text)` (`fs_write`)
+//! - **Environment**: `getenv(name, default=)` (`env`)
+//! - **Time**: `now()` (`clock`)
+//! - **Randomness**: `random()` (`random`)
+//!
+//! I/O failures are reported as evaluation errors at the call, with the
+//! operating system's message.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::error::{BcclError, BcclResult};
+use super::builtins::{BuiltinCall, FunctionSignature};
+use super::capabilities::Capability;
+use super::value::Value;
+
+/// Returns the string argument at `position` (1-based), or a type error.
+fn string_argument<'a>(call: &'a BuiltinCall, position: usize) -> BcclResult<&'a str> {
+    let value = &call.args[position - 1];
+    value.as_string().ok_or_else(|| BcclError::function_argument_type_error_with_span(
+        call.name, position, "string", value.type_name(), &value.display(), call.spans[position - 1]
+    ))
+}
+
+/// Implementation of `read_file(path)`: the contents of a UTF-8 text file.
+pub fn builtin_read_file(call: &BuiltinCall) -> BcclResult<Value> {
+    let path = string_argument(call, 1)?;
+    std::fs::read_to_string(path)
+        .map(Value::string)
+        .map_err(|error| BcclError::evaluation_error(
+            &format!("read_file() could not read '{}': {}", path, error),
+            call.spans[0],
+            Some("Check that the file exists, is readable and contains UTF-8 text".to_string())
+        ))
+}
+
+/// Implementation of `write_file(path, text)`: creates or replaces the file
+/// and returns the number of bytes written.
+pub fn builtin_write_file(call: &BuiltinCall) -> BcclResult<Value> {
+    let path = string_argument(call, 1)?;
+    let text = string_argument(call, 2)?;
+    std::fs::write(path, text)
+        .map(|()| Value::Integer(text.len() as i64))
+        .map_err(|error| BcclError::evaluation_error(
+            &format!("write_file() could not write '{}': {}", path, error),
+            call.spans[0],
+            Some("Check that the directory exists and is writable".to_string())
+        ))
+}
+
+/// Implementation of `getenv(name, default=)`.
+///
+/// Returns the variable's value; a variable that is not set returns
+/// `default` if given and is an error otherwise.
+pub fn builtin_getenv(call: &BuiltinCall) -> BcclResult<Value> {
+    let name = string_argument(call, 1)?;
+    match std::env::var(name) {
+        Ok(value) => Ok(Value::string(value)),
+        Err(_) => match call.option("default") {
+            Some((default, _)) => Ok(default.clone()),
+            None => Err(BcclError::evaluation_error(
+                &format!("environment variable '{}' is not set", name),
+                call.spans[0],
+                Some(format!("Pass a fallback, e.g. getenv(\"{}\", default=\"\")", name))
+            )),
+        },
+    }
+}
+
+/// Implementation of `now()`: seconds since the Unix epoch, as a number.
+pub fn builtin_now(_call: &BuiltinCall) -> BcclResult<Value> {
+    let elapsed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    Ok(Value::Number(elapsed.as_secs_f64()))
+}
+
+/// Implementation of `random()`: a number in `[0, 1)`.
+///
+/// Each call hashes a counter with a randomly keyed hasher, which is good
+/// enough for scripts but not for cryptography.
+pub fn builtin_random(_call: &BuiltinCall) -> BcclResult<Value> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    // The top 53 bits fill an f64 mantissa exactly
+    Ok(Value::Number((hasher.finish() >> 11) as f64 / (1u64 << 53) as f64))
+}
+
+/// Returns the capability a system built-in requires, or `None` for any other
+/// name. Used to explain why a built-in is missing.
+pub(crate) fn required_capability(name: &str) -> Option<Capability> {
+    system_functions().into_iter()
+        .find(|(_, signature)| signature.name == name)
+        .map(|(capability, _)| capability)
+}
+
+/// Every system built-in with the capability it requires.
+pub fn system_functions() -> Vec<(Capability, FunctionSignature)> {
+    vec![
+        (Capability::FsRead, FunctionSignature::new_native("read_file", vec!["path"], builtin_read_file)),
+        (Capability::FsWrite, FunctionSignature::new_native("write_file", vec!["path", "text"], builtin_write_file)),
+        (Capability::Env, FunctionSignature::new_native("getenv", vec!["name"], builtin_getenv).with_options(vec!["default"])),
+        (Capability::Clock, FunctionSignature::new_native("now", vec![], builtin_now)),
+        (Capability::Random, FunctionSignature::new_native("random", vec![], builtin_random)),
+    ]
+}
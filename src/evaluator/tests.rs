@@ -36,13 +36,13 @@ mod tests {
     fn test_evaluate_boolean() {
         let result = evaluate_from_str("true").unwrap().unwrap();
         match result {
-            Value::Boolean(b) => assert_eq!(b, true),
+            Value::Boolean(b) => assert!(b),
             _ => panic!("Expected Boolean value"),
         }
 
         let result = evaluate_from_str("false").unwrap().unwrap();
         match result {
-            Value::Boolean(b) => assert_eq!(b, false),
+            Value::Boolean(b) => assert!(!b),
             _ => panic!("Expected Boolean value"),
         }
     }
@@ -51,7 +51,7 @@ mod tests {
     fn test_evaluate_string() {
         let result = evaluate_from_str(r#""hello world""#).unwrap().unwrap();
         match result {
-            Value::String(s) => assert_eq!(s, "hello world"),
+            Value::String(s) => assert_eq!(s.as_ref(), "hello world"),
             _ => panic!("Expected String value"),
         }
     }
@@ -138,13 +138,13 @@ mod tests {
     fn test_evaluate_unary_not() {
         let result = evaluate_from_str("not true").unwrap().unwrap();
         match result {
-            Value::Boolean(b) => assert_eq!(b, false),
+            Value::Boolean(b) => assert!(!b),
             _ => panic!("Expected Boolean value"),
         }
 
         let result = evaluate_from_str("not false").unwrap().unwrap();
         match result {
-            Value::Boolean(b) => assert_eq!(b, true),
+            Value::Boolean(b) => assert!(b),
             _ => panic!("Expected Boolean value"),
         }
     }
@@ -252,13 +252,13 @@ mod tests {
     fn test_evaluate_logical_operators() {
         let result = evaluate_from_str("true and false").unwrap().unwrap();
         match result {
-            Value::Boolean(b) => assert_eq!(b, false),
+            Value::Boolean(b) => assert!(!b),
             _ => panic!("Expected Boolean value"),
         }
 
         let result = evaluate_from_str("true or false").unwrap().unwrap();
         match result {
-            Value::Boolean(b) => assert_eq!(b, true),
+            Value::Boolean(b) => assert!(b),
             _ => panic!("Expected Boolean value"),
         }
     }
@@ -267,13 +267,13 @@ mod tests {
     fn test_evaluate_comparison_operators() {
         let result = evaluate_from_str("5 > 3").unwrap().unwrap();
         match result {
-            Value::Boolean(b) => assert_eq!(b, true),
+            Value::Boolean(b) => assert!(b),
             _ => panic!("Expected Boolean value"),
         }
 
         let result = evaluate_from_str("5 <= 3").unwrap().unwrap();
         match result {
-            Value::Boolean(b) => assert_eq!(b, false),
+            Value::Boolean(b) => assert!(!b),
             _ => panic!("Expected Boolean value"),
         }
     }
@@ -298,7 +298,7 @@ mod tests {
         match result {
             Value::Dictionary(dict) => {
                 assert_eq!(dict.len(), 2);
-                assert!(matches!(dict.get("name"), Some(Value::String(s)) if s == "John"));
+                assert!(matches!(dict.get("name"), Some(Value::String(s)) if s.as_ref() == "John"));
                 assert!(matches!(dict.get("age"), Some(Value::Integer(30))));
             }
             _ => panic!("Expected Dictionary value"),
@@ -315,7 +315,7 @@ mod tests {
 
         let result = evaluate_from_str(r#"{"key": "value"}["key"]"#).unwrap().unwrap();
         match result {
-            Value::String(s) => assert_eq!(s, "value"),
+            Value::String(s) => assert_eq!(s.as_ref(), "value"),
             _ => panic!("Expected String value"),
         }
     }
@@ -337,22 +337,22 @@ mod tests {
 
     #[test]
     fn test_evaluate_function_kwargs() {
-        // Test pure keyword arguments
-        let result = evaluate_from_str("max(a=5, b=10)").unwrap().unwrap();
+        // Test keyword option on an empty input
+        let result = evaluate_from_str("max([], default=10)").unwrap().unwrap();
         match result {
-            Value::Number(n) => assert_eq!(n, 10.0),
-            _ => panic!("Expected Number value"),
+            Value::Integer(n) => assert_eq!(n, 10),
+            _ => panic!("Expected Integer value"),
         }
 
-        // Test mixed positional and keyword arguments
-        let result = evaluate_from_str("max(5, b=10)").unwrap().unwrap();
+        // Test the variadic parameter passed by keyword as a list
+        let result = evaluate_from_str("max(values=[5, 10])").unwrap().unwrap();
         match result {
             Value::Number(n) => assert_eq!(n, 10.0),
             _ => panic!("Expected Number value"),
         }
 
-        // Test kwargs with min function using correct parameter names
-        let result = evaluate_from_str("min(a=3, b=7)").unwrap().unwrap();
+        // Test that default is ignored when there are values
+        let result = evaluate_from_str("min(3, 7, default=0)").unwrap().unwrap();
         match result {
             Value::Number(n) => assert_eq!(n, 3.0),
             _ => panic!("Expected Number value"),
@@ -6,13 +6,14 @@
 //!
 //! ## Type System
 //!
-//! BCCL supports six core value types:
+//! BCCL supports seven core value types:
 //! - **Number**: 64-bit floating point numbers (3.14, 42.0)
 //! - **Integer**: 64-bit signed integers (42, -17)
 //! - **Boolean**: true/false values
 //! - **String**: UTF-8 text ("hello world")
 //! - **List**: Ordered collections ([1, 2, 3])
 //! - **Dictionary**: Key-value mappings ({"key": "value"})
+//! - **Function**: References to built-in functions (max, str), usable as callbacks
 //!
 //! ## Type Coercion
 //!
@@ -20,28 +21,82 @@
 //! - Integers can be used as numbers (42 → 42.0)
 //! - Numbers can be integers if they have no fractional part
 //! - All types have truthiness semantics for logical operations
+//!
+//! ## Shared Storage
+//!
+//! Strings, lists and dictionaries live behind `Shared` pointers, so cloning a
+//! `Value` (reading a variable, passing an argument) is O(1) whatever its size.
+//! Scripts still see value semantics: nothing mutates shared storage in place,
+//! and Rust code that needs to change a collection goes through `list_mut` /
+//! `dict_mut`, which copy the storage first if anyone else holds it.
+//!
+//! `Shared` is an `Arc`, so values are `Send + Sync`: one precompiled chunk or
+//! one set of globals can be used by evaluators on many threads at once.
+//!
+//! ## Serialization
+//!
+//! With the `serde` feature, values serialize as `{"type": ..., "value": ...}`
+//! so integers and numbers stay distinct (`1` vs `1.0`) through JSON:
+//!
+//! | Value         | JSON                                              |
+//! |---------------|---------------------------------------------------|
+//! | `42`          | `{"type": "integer", "value": 42}`                |
+//! | `4.0`         | `{"type": "number", "value": 4.0}`                |
+//! | `true`        | `{"type": "boolean", "value": true}`              |
+//! | `"hi"`        | `{"type": "string", "value": "hi"}`               |
+//! | `[1]`         | `{"type": "list", "value": [{"type": "integer", "value": 1}]}` |
+//! | `{"k": 1}`    | `{"type": "dictionary", "value": {"k": {"type": "integer", "value": 1}}}` |
+//! | `max`         | `{"type": "function", "value": "max"}`            |
+//!
+//! Dictionary keys are written in sorted order, so the same value always
+//! produces the same JSON. Non-finite numbers have no JSON form (serde_json
+//! writes `null`, which does not deserialize); BCCL arithmetic only produces
+//! them on overflow. Exact float round trips with serde_json need its
+//! `float_roundtrip` feature.
+//!
+//! ```rust
+//! # #[cfg(feature = "serde")] {
+//! # use bccl::Value;
+//! let value = Value::list(vec![Value::Integer(1), Value::Number(1.0)]);
+//! let json = serde_json::to_string(&value).unwrap();
+//! assert_eq!(json, r#"{"type":"list","value":[{"type":"integer","value":1},{"type":"number","value":1.0}]}"#);
+//! let back: Value = serde_json::from_str(&json).unwrap();
+//! assert_eq!(serde_json::to_string(&back).unwrap(), json);
+//! # }
+//! ```
 
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Shared, atomically reference-counted storage for strings and collections.
+pub type Shared<T> = Arc<T>;
 
 /// Represents all possible runtime values in BCCL.
 /// 
 /// This enum is the core of BCCL's type system. Every expression evaluates to a `Value`,
-/// and every variable stores a `Value`. The type supports deep cloning and debug output.
+/// and every variable stores a `Value`. Cloning is cheap: strings and collections are
+/// shared, and copied only when modified through `list_mut` or `dict_mut`.
 /// 
 /// # Examples
 /// 
 /// ```rust
+/// # use std::collections::HashMap;
+/// # use bccl::Value;
 /// let num = Value::Number(3.14);
-/// let list = Value::List(vec![Value::Integer(1), Value::Integer(2)]);
-/// let dict = Value::Dictionary(HashMap::from([("key".to_string(), Value::String("value".to_string()))]));
+/// let list = Value::list(vec![Value::Integer(1), Value::Integer(2)]);
+/// let dict = Value::dict(HashMap::from([("key".to_string(), Value::string("value"))]));
 /// ```
 /// 
 /// # Type Hierarchy
 /// 
 /// - Primitive types: Number, Integer, Boolean, String
 /// - Collection types: List, Dictionary
+/// - Callable types: Function
 /// - All types support equality comparison and truthiness testing
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value", rename_all = "snake_case"))]
 pub enum Value {
     /// 64-bit floating point number (IEEE 754)
     Number(f64),
@@ -50,14 +105,75 @@ pub enum Value {
     /// Boolean true/false value
     Boolean(bool),
     /// UTF-8 string
-    String(String),
+    String(Shared<str>),
     /// Ordered list of values (can contain mixed types)
-    List(Vec<Value>),
+    List(Shared<Vec<Value>>),
     /// String-keyed dictionary/map of values
-    Dictionary(HashMap<String, Value>),
+    Dictionary(
+        #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_sorted"))]
+        Shared<HashMap<String, Value>>
+    ),
+    /// Reference to a built-in function by name (e.g. `map(str, xs)`)
+    Function(String),
+}
+
+/// Serializes a dictionary with its keys in sorted order.
+#[cfg(feature = "serde")]
+fn serialize_sorted<S: serde::Serializer>(dict: &Shared<HashMap<String, Value>>, serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::Serialize;
+    dict.iter().collect::<std::collections::BTreeMap<_, _>>().serialize(serializer)
 }
 
 impl Value {
+    /// Creates a string value.
+    /// 
+    /// ```rust
+    /// # use bccl::Value;
+    /// assert_eq!(Value::string("hi").display(), "\"hi\"");
+    /// ```
+    pub fn string(value: impl Into<Shared<str>>) -> Value {
+        Value::String(value.into())
+    }
+    
+    /// Creates a list value.
+    pub fn list(items: Vec<Value>) -> Value {
+        Value::List(Shared::new(items))
+    }
+    
+    /// Creates a dictionary value.
+    pub fn dict(entries: HashMap<String, Value>) -> Value {
+        Value::Dictionary(Shared::new(entries))
+    }
+    
+    /// Returns the items of a list for modification, copying them first if
+    /// the list is shared with another value (copy-on-write).
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// # use bccl::Value;
+    /// let original = Value::list(vec![Value::Integer(1)]);
+    /// let mut copy = original.clone();
+    /// copy.list_mut().unwrap().push(Value::Integer(2));
+    /// assert_eq!(original.display(), "[1]");
+    /// assert_eq!(copy.display(), "[1, 2]");
+    /// ```
+    pub fn list_mut(&mut self) -> Option<&mut Vec<Value>> {
+        match self {
+            Value::List(items) => Some(Shared::make_mut(items)),
+            _ => None,
+        }
+    }
+    
+    /// Returns the entries of a dictionary for modification, copying them
+    /// first if the dictionary is shared with another value (copy-on-write).
+    pub fn dict_mut(&mut self) -> Option<&mut HashMap<String, Value>> {
+        match self {
+            Value::Dictionary(dict) => Some(Shared::make_mut(dict)),
+            _ => None,
+        }
+    }
+    
     /// Attempts to convert this value to a floating-point number.
     /// 
     /// This method provides type coercion from integers to numbers,
@@ -71,6 +187,7 @@ impl Value {
     /// # Examples
     /// 
     /// ```rust
+    /// # use bccl::Value;
     /// assert_eq!(Value::Number(3.14).as_number(), Some(3.14));
     /// assert_eq!(Value::Integer(42).as_number(), Some(42.0));
     /// assert_eq!(Value::Boolean(true).as_number(), None);
@@ -96,6 +213,7 @@ impl Value {
     /// # Examples
     /// 
     /// ```rust
+    /// # use bccl::Value;
     /// assert_eq!(Value::Integer(42).as_integer(), Some(42));
     /// assert_eq!(Value::Number(42.0).as_integer(), Some(42));
     /// assert_eq!(Value::Number(3.14).as_integer(), None);
@@ -121,6 +239,7 @@ impl Value {
     /// # Examples
     /// 
     /// ```rust
+    /// # use bccl::Value;
     /// assert_eq!(Value::Boolean(true).as_boolean(), Some(true));
     /// assert_eq!(Value::Integer(1).as_boolean(), None); // Use is_truthy() instead
     /// ```
@@ -144,7 +263,8 @@ impl Value {
     /// # Examples
     /// 
     /// ```rust
-    /// assert_eq!(Value::String("hello".to_string()).as_string(), Some("hello"));
+    /// # use bccl::Value;
+    /// assert_eq!(Value::string("hello").as_string(), Some("hello"));
     /// assert_eq!(Value::Integer(42).as_string(), None); // Use display() instead
     /// ```
     pub fn as_string(&self) -> Option<&str> {
@@ -168,12 +288,14 @@ impl Value {
     /// - "string" for text values
     /// - "list" for ordered collections
     /// - "dictionary" for key-value maps
+    /// - "function" for function references
     /// 
     /// # Examples
     /// 
     /// ```rust
+    /// # use bccl::Value;
     /// assert_eq!(Value::Number(3.14).type_name(), "number");
-    /// assert_eq!(Value::List(vec![]).type_name(), "list");
+    /// assert_eq!(Value::list(vec![]).type_name(), "list");
     /// ```
     pub fn type_name(&self) -> &'static str {
         match self {
@@ -183,6 +305,7 @@ impl Value {
             Value::String(_) => "string",
             Value::List(_) => "list",
             Value::Dictionary(_) => "dictionary",
+            Value::Function(_) => "function",
         }
     }
     
@@ -198,13 +321,15 @@ impl Value {
     /// - Booleans: "true" or "false"
     /// - Strings: Quoted ("hello" → "\"hello\"")
     /// - Lists: Bracketed with comma separation ([1, 2, 3])
-    /// - Dictionaries: Braced with key-value pairs ({"a": 1, "b": 2})
+    /// - Dictionaries: Braced with key-value pairs in key order ({"a": 1, "b": 2})
+    /// - Functions: Angle-bracketed name (<function max>)
     /// 
     /// # Examples
     /// 
     /// ```rust
+    /// # use bccl::Value;
     /// assert_eq!(Value::Number(42.0).display(), "42");
-    /// assert_eq!(Value::String("hello".to_string()).display(), "\"hello\"");
+    /// assert_eq!(Value::string("hello").display(), "\"hello\"");
     /// ```
     pub fn display(&self) -> String {
         match self {
@@ -223,11 +348,15 @@ impl Value {
                 format!("[{}]", items_str.join(", "))
             }
             Value::Dictionary(dict) => {
-                let pairs: Vec<String> = dict.iter()
+                // Sorted by key so the same dictionary always displays the same way
+                let mut entries: Vec<(&String, &Value)> = dict.iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+                let pairs: Vec<String> = entries.into_iter()
                     .map(|(k, v)| format!("\"{}\": {}", k, v.display()))
                     .collect();
                 format!("{{{}}}", pairs.join(", "))
             }
+            Value::Function(name) => format!("<function {}>", name),
         }
     }
     
@@ -244,6 +373,7 @@ impl Value {
     /// - **String**: Non-empty strings are truthy, `""` is falsy
     /// - **List**: Non-empty lists are truthy, `[]` is falsy
     /// - **Dictionary**: Non-empty dictionaries are truthy, `{}` is falsy
+    /// - **Function**: Always truthy
     /// 
     /// # Returns
     /// 
@@ -252,10 +382,11 @@ impl Value {
     /// # Examples
     /// 
     /// ```rust
+    /// # use bccl::Value;
     /// assert_eq!(Value::Boolean(true).is_truthy(), true);
     /// assert_eq!(Value::Integer(0).is_truthy(), false);
-    /// assert_eq!(Value::String("".to_string()).is_truthy(), false);
-    /// assert_eq!(Value::List(vec![Value::Integer(1)]).is_truthy(), true);
+    /// assert_eq!(Value::string("").is_truthy(), false);
+    /// assert_eq!(Value::list(vec![Value::Integer(1)]).is_truthy(), true);
     /// ```
     /// 
     /// # Usage in Logical Operations
@@ -271,6 +402,36 @@ impl Value {
             Value::String(s) => !s.is_empty(),
             Value::List(items) => !items.is_empty(),
             Value::Dictionary(dict) => !dict.is_empty(),
+            Value::Function(_) => true,
+        }
+    }
+    
+    /// Orders two values for sorting and `min`/`max` style comparisons.
+    /// 
+    /// # Ordering Rules
+    /// 
+    /// - **Numbers and integers**: compared numerically, so `2 < 2.5`
+    /// - **Strings**: compared lexicographically by Unicode code point
+    /// 
+    /// # Returns
+    /// 
+    /// `Some(ordering)` for comparable values, `None` for any other combination
+    /// (including NaN).
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// # use bccl::Value;
+    /// # use std::cmp::Ordering;
+    /// assert_eq!(Value::Integer(2).compare(&Value::Number(2.5)), Some(Ordering::Less));
+    /// assert_eq!(Value::string("b").compare(&Value::string("a")), Some(Ordering::Greater));
+    /// assert_eq!(Value::Integer(1).compare(&Value::string("1")), None);
+    /// ```
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Integer(a), Value::Integer(b)) => Some(a.cmp(b)),
+            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+            _ => self.as_number()?.partial_cmp(&other.as_number()?),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,177 @@
+/// A layout document: text plus the places where it may break across lines.
+/// A `Group` is printed on one line if it fits in the width, and otherwise
+/// with every one of its own `Line`s and `SoftLine`s broken.
+#[derive(Debug, Clone)]
+pub(crate) enum Doc {
+    Text(String),
+    Concat(Vec<Doc>),
+    /// A space, or a line break when the group is broken
+    Line,
+    /// Nothing, or a line break when the group is broken
+    SoftLine,
+    /// Always a line break
+    HardLine,
+    /// An empty line, kept from the source between statements
+    BlankLine,
+    /// A comment on a line of its own
+    Comment(String),
+    /// A comment after code on the same line. The line always breaks right
+    /// after it, so it stays after the code it followed
+    TrailingComment(String),
+    /// Breaks inside are indented one level deeper
+    Nest(Box<Doc>),
+    Group { doc: Box<Doc>, broken: bool },
+}
+
+impl Doc {
+    pub(crate) fn text(text: impl Into<String>) -> Doc {
+        Doc::Text(text.into())
+    }
+
+    pub(crate) fn nest(doc: Doc) -> Doc {
+        Doc::Nest(Box::new(doc))
+    }
+
+    /// Groups `doc`. A group holding a comment or a hard line break can never
+    /// be flat.
+    pub(crate) fn group(doc: Doc) -> Doc {
+        let broken = doc.forces_break();
+        Doc::Group { doc: Box::new(doc), broken }
+    }
+
+    fn forces_break(&self) -> bool {
+        match self {
+            Doc::HardLine | Doc::BlankLine | Doc::Comment(_) | Doc::TrailingComment(_) => true,
+            Doc::Concat(docs) => docs.iter().any(Doc::forces_break),
+            Doc::Nest(doc) => doc.forces_break(),
+            Doc::Group { broken, .. } => *broken,
+            Doc::Text(_) | Doc::Line | Doc::SoftLine => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// Prints a document within `width` columns, indenting `indent` spaces per
+/// level. Lines never end in spaces, and the output ends with a single line
+/// break unless it is empty.
+pub(crate) fn print(doc: &Doc, width: usize, indent: usize) -> String {
+    let mut printer = Printer { out: String::new(), column: 0, line_start: true, after_comment: false };
+    let mut stack = vec![(0, Mode::Break, doc)];
+
+    while let Some((level, mode, doc)) = stack.pop() {
+        match doc {
+            // Spacing that would have followed the code before the comment
+            Doc::Text(text) if printer.after_comment && text.trim().is_empty() => {}
+            Doc::Text(text) => {
+                if printer.after_comment {
+                    printer.newline(level);
+                }
+                printer.write(text);
+            }
+            Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (level, mode, doc))),
+            Doc::Line | Doc::SoftLine if mode == Mode::Break => printer.newline(level),
+            Doc::Line if printer.after_comment => {}
+            Doc::Line => printer.write(" "),
+            Doc::SoftLine => {}
+            Doc::HardLine => printer.newline(level),
+            Doc::BlankLine => {
+                if !printer.line_start {
+                    printer.newline(level);
+                }
+                printer.newline(level);
+            }
+            Doc::Comment(text) => {
+                if !printer.line_start {
+                    printer.newline(level);
+                }
+                printer.write(text);
+                printer.newline(level);
+            }
+            Doc::TrailingComment(text) => {
+                if !printer.line_start {
+                    printer.write("  ");
+                }
+                printer.write(text);
+                printer.after_comment = true;
+            }
+            Doc::Nest(doc) => stack.push((level + indent, mode, doc)),
+            Doc::Group { doc, broken } => {
+                let flat = !broken && (mode == Mode::Flat || fits(doc, &stack, width.saturating_sub(printer.column)));
+                stack.push((level, if flat { Mode::Flat } else { Mode::Break }, doc));
+            }
+        }
+    }
+
+    let mut out = printer.out.trim_end().to_string();
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+/// Whether `doc` printed flat, followed by the rest of the current line,
+/// takes at most `remaining` columns.
+fn fits(doc: &Doc, rest: &[(usize, Mode, &Doc)], remaining: usize) -> bool {
+    let mut remaining = remaining as isize;
+    let mut pending = vec![(Mode::Flat, doc)];
+    let mut rest = rest.iter().rev();
+
+    loop {
+        let (mode, doc) = match pending.pop() {
+            Some(next) => next,
+            None => match rest.next() {
+                Some((_, mode, doc)) => (*mode, *doc),
+                None => return true,
+            },
+        };
+        match doc {
+            Doc::Text(text) => {
+                remaining -= text.chars().count() as isize;
+                if remaining < 0 {
+                    return false;
+                }
+            }
+            Doc::Concat(docs) => pending.extend(docs.iter().rev().map(|doc| (mode, doc))),
+            Doc::Line if mode == Mode::Flat => remaining -= 1,
+            Doc::SoftLine => {}
+            Doc::Line | Doc::HardLine | Doc::BlankLine | Doc::Comment(_) | Doc::TrailingComment(_) => return true,
+            Doc::Nest(doc) => pending.push((mode, doc)),
+            Doc::Group { doc, broken } => pending.push((if *broken { Mode::Break } else { mode }, doc)),
+        }
+    }
+}
+
+struct Printer {
+    out: String,
+    column: usize,
+    /// Nothing but indentation has been written on the current line
+    line_start: bool,
+    /// The line ends in a trailing comment, so any more text goes on the next
+    /// line
+    after_comment: bool,
+}
+
+impl Printer {
+    fn write(&mut self, text: &str) {
+        if !text.is_empty() {
+            self.out.push_str(text);
+            self.column += text.chars().count();
+            self.line_start = false;
+        }
+    }
+
+    fn newline(&mut self, level: usize) {
+        self.after_comment = false;
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+        self.out.push('\n');
+        self.out.push_str(&" ".repeat(level));
+        self.column = level;
+        self.line_start = true;
+    }
+}
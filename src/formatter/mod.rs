@@ -0,0 +1,359 @@
+//! # Formatter Module
+//!
+//! Canonical pretty-printing of BCCL source, as used by `bccl fmt`.
+//!
+//! ## Style
+//!
+//! - One statement per line; `;` separators become line breaks, and at most
+//!   one blank line is kept between statements
+//! - One space around binary operators and `=`, after `,` and `:`, and after
+//!   `not`; none inside brackets, after `-`/`+`/`*`/`**`, or around `=` in
+//!   keyword arguments
+//! - A list, dictionary or call that does not fit in `FormatOptions::width`
+//!   gets one element per line, indented; a long chain of binary operators
+//!   breaks after its operators
+//! - Literals keep their spelling (`1.50` stays `1.50`) and parentheses are
+//!   kept as written
+//!
+//! ## Comments
+//!
+//! The formatter works on the lossless `SyntaxTree`, so every comment is kept.
+//! A comment on a line of its own stays on its own line, indented with the
+//! code that follows it; a comment after code stays right after that code,
+//! and the line breaks there. A trailing comment inside an expression
+//! therefore forces the enclosing group onto several lines, and code that
+//! followed it on the next line, such as the operand of `not` or a closing
+//! `]`, stays on the next line.
+//!
+//! ## Guarantees
+//!
+//! The output parses to the same program as the input, spans aside, and
+//! formatting it again changes nothing. Input with syntax errors is rejected
+//! rather than guessed at.
+
+use crate::error::{BcclError, BcclResult};
+use crate::lexer::TokenType;
+use crate::syntax::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken, SyntaxTree, TriviaKind};
+
+mod doc;
+
+use doc::Doc;
+
+/// Layout settings for `format_source`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Preferred maximum line length, in characters
+    pub width: usize,
+    /// Spaces per indentation level
+    pub indent: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self { width: 80, indent: 4 }
+    }
+}
+
+/// Formats BCCL source in the canonical style described in the module docs.
+///
+/// # Arguments
+///
+/// * `source` - The program to format
+/// * `options` - Line width and indentation
+///
+/// # Returns
+///
+/// * `Ok(text)` - The formatted program, ending in a line break unless empty
+/// * `Err(error)` - If the source has lexical or syntax errors (several are
+///   returned as `BcclError::Multiple`)
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::{format_source, FormatOptions};
+/// let formatted = format_source("x=1;y = [x,2 ]# pair", &FormatOptions::default()).unwrap();
+/// assert_eq!(formatted, "x = 1\ny = [x, 2]  # pair\n");
+/// ```
+pub fn format_source(source: &str, options: &FormatOptions) -> BcclResult<String> {
+    let tree = SyntaxTree::parse(source);
+    if !tree.errors().is_empty() {
+        return Err(BcclError::multiple(tree.into_errors()));
+    }
+    let mut formatter = Formatter { tokens: tree.root().tokens(), next: 0, statement_breaks: None };
+    let doc = formatter.program(tree.root());
+    Ok(doc::print(&doc, options.width, options.indent))
+}
+
+/// Turns a syntax tree into a layout document. Every token is visited once,
+/// in order, through `token` or `separator`, and brings its comments along:
+/// a comment with no line break between it and the previous token trails
+/// that token, any other comment leads the token after it.
+struct Formatter<'a> {
+    /// Every token of the tree, to look ahead for trailing comments
+    tokens: Vec<&'a SyntaxToken>,
+    /// Index in `tokens` of the next token to lay out
+    next: usize,
+    /// Line breaks before the statement being started, for blank lines
+    statement_breaks: Option<usize>,
+}
+
+impl Formatter<'_> {
+    fn program(&mut self, root: &SyntaxNode) -> Doc {
+        let mut docs = Vec::new();
+        let mut breaks = 0;
+        for child in root.children() {
+            match child {
+                SyntaxElement::Node(statement) => {
+                    if self.next > 0 {
+                        docs.push(Doc::HardLine);
+                    }
+                    self.statement_breaks = Some(breaks);
+                    docs.push(self.statement(statement));
+                    breaks = 0;
+                }
+                // Separators and the end of input only bring their comments
+                SyntaxElement::Token(token) => {
+                    let is_eof = token.kind == TokenType::Eof;
+                    docs.push(self.separator(token, is_eof.then_some(breaks)));
+                    if token.kind == TokenType::Newline {
+                        breaks += 1;
+                    }
+                }
+            }
+        }
+        Doc::Concat(docs)
+    }
+
+    fn token(&mut self, token: &SyntaxToken) -> Doc {
+        let breaks = self.statement_breaks.take();
+        let leading = self.leading(token, breaks);
+        let trailing = self.trailing(token);
+        Doc::Concat(vec![leading, Doc::text(&token.text), trailing])
+    }
+
+    /// A `;`, line break or end of input, which the layout replaces: only
+    /// its comments are kept.
+    fn separator(&mut self, token: &SyntaxToken, breaks: Option<usize>) -> Doc {
+        let leading = self.leading(token, breaks);
+        let trailing = self.trailing(token);
+        Doc::Concat(vec![leading, trailing])
+    }
+
+    /// The comments leading `token`. With `breaks`, the number of line breaks
+    /// since the previous statement, blank lines in front of the token and
+    /// its comments are kept too.
+    fn leading(&mut self, token: &SyntaxToken, breaks: Option<usize>) -> Doc {
+        let previous = self.next.checked_sub(1).map(|index| self.tokens[index]);
+        let mut written = previous.is_some();
+        let mut newlines = breaks.unwrap_or(0);
+        let mut docs = Vec::new();
+        for trivia in &token.leading_trivia {
+            match trivia.kind {
+                TriviaKind::Whitespace => newlines += trivia.text.matches('\n').count(),
+                TriviaKind::Comment if newlines == 0 && previous.is_some_and(is_trailed) => {
+                    // Comments inside a statement were written by `trailing`;
+                    // at its end they go after everything, outside any group
+                    if is_separator(token) {
+                        docs.push(Doc::TrailingComment(trivia.text.trim_end().to_string()));
+                    }
+                }
+                TriviaKind::Comment => {
+                    if written && breaks.is_some() && newlines >= 2 {
+                        docs.push(Doc::BlankLine);
+                    }
+                    docs.push(Doc::Comment(trivia.text.trim_end().to_string()));
+                    written = true;
+                    newlines = 0;
+                }
+            }
+        }
+        if written && breaks.is_some() && newlines >= 2 {
+            docs.push(Doc::BlankLine);
+        }
+        Doc::Concat(docs)
+    }
+
+    /// The comments trailing `token` within a statement, which sit in the next
+    /// token's trivia, and moves on to the next token.
+    fn trailing(&mut self, token: &SyntaxToken) -> Doc {
+        self.next += 1;
+        let mut docs = Vec::new();
+        if is_trailed(token) && let Some(next) = self.tokens.get(self.next) && !is_separator(next) {
+            for trivia in &next.leading_trivia {
+                match trivia.kind {
+                    TriviaKind::Whitespace if trivia.text.contains('\n') => break,
+                    TriviaKind::Whitespace => {}
+                    TriviaKind::Comment => docs.push(Doc::TrailingComment(trivia.text.trim_end().to_string())),
+                }
+            }
+        }
+        Doc::Concat(docs)
+    }
+
+    fn statement(&mut self, node: &SyntaxNode) -> Doc {
+        match node.kind() {
+            SyntaxKind::AssignmentStmt | SyntaxKind::CompoundAssignmentStmt => {
+                let mut docs = Vec::new();
+                for child in node.children() {
+                    match child {
+                        SyntaxElement::Token(token) if docs.is_empty() => docs.push(self.token(token)),
+                        SyntaxElement::Token(token) => {
+                            docs.push(Doc::text(" "));
+                            docs.push(self.token(token));
+                            docs.push(Doc::text(" "));
+                        }
+                        SyntaxElement::Node(value) => docs.push(self.expression(value)),
+                    }
+                }
+                Doc::Concat(docs)
+            }
+            _ => self.children(node),
+        }
+    }
+
+    /// Every child of `node` in order, with no space in between.
+    fn children(&mut self, node: &SyntaxNode) -> Doc {
+        let docs = node.children().iter().map(|child| self.element(child)).collect();
+        Doc::Concat(docs)
+    }
+
+    fn element(&mut self, element: &SyntaxElement) -> Doc {
+        match element {
+            SyntaxElement::Node(node) => self.expression(node),
+            SyntaxElement::Token(token) => self.token(token),
+        }
+    }
+
+    fn expression(&mut self, node: &SyntaxNode) -> Doc {
+        match node.kind() {
+            SyntaxKind::Binary => self.binary(node),
+            SyntaxKind::Unary => {
+                let mut docs = Vec::new();
+                for child in node.children() {
+                    docs.push(self.element(child));
+                    if matches!(child, SyntaxElement::Token(token) if token.kind == TokenType::Not) {
+                        docs.push(Doc::text(" "));
+                    }
+                }
+                Doc::Concat(docs)
+            }
+            SyntaxKind::Call | SyntaxKind::List | SyntaxKind::Dictionary => self.bracketed(node),
+            SyntaxKind::DictEntry | SyntaxKind::Annotation => {
+                let docs = node.children().iter()
+                    .map(|child| match child {
+                        SyntaxElement::Token(token) if token.kind == TokenType::Colon => {
+                            Doc::Concat(vec![self.token(token), Doc::text(" ")])
+                        }
+                        child => self.element(child),
+                    })
+                    .collect();
+                Doc::Concat(docs)
+            }
+            // Literals, names, parentheses, indexing and arguments
+            _ => self.children(node),
+        }
+    }
+
+    /// A chain of binary operators of the same precedence, flattened along its
+    /// left operands so the whole chain breaks together, after its operators:
+    /// `a +` / `b -` / `c`.
+    fn binary(&mut self, node: &SyntaxNode) -> Doc {
+        let mut chain = vec![node];
+        while let Some(left) = chain.last().and_then(|link| link.child_nodes().next())
+            && left.kind() == SyntaxKind::Binary
+            && precedence(left) == precedence(node)
+        {
+            chain.push(left);
+        }
+        let innermost = chain.last().and_then(|link| link.child_nodes().next()).expect("a left operand");
+        let first = self.expression(innermost);
+
+        let mut rest = Vec::new();
+        for link in chain.into_iter().rev() {
+            // The left operand comes first and is already laid out
+            for child in link.children().iter().skip(1) {
+                match child {
+                    SyntaxElement::Token(operator) => {
+                        rest.push(Doc::text(" "));
+                        rest.push(self.token(operator));
+                    }
+                    SyntaxElement::Node(right) => {
+                        rest.push(Doc::Line);
+                        rest.push(self.expression(right));
+                    }
+                }
+            }
+        }
+        Doc::group(Doc::Concat(vec![first, Doc::nest(Doc::Concat(rest))]))
+    }
+
+    /// A call, list or dictionary: everything up to the opening bracket, then
+    /// the elements, one per line if they do not fit on one.
+    fn bracketed(&mut self, node: &SyntaxNode) -> Doc {
+        let children = node.children();
+        let open = children.iter()
+            .position(|child| matches!(child, SyntaxElement::Token(token) if matches!(
+                token.kind,
+                TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftBrace
+            )))
+            .expect("an opening bracket");
+        let (head, rest) = children.split_at(open + 1);
+        let (elements, close) = rest.split_at(rest.len() - 1);
+
+        let head: Vec<Doc> = head.iter().map(|child| self.element(child)).collect();
+        let mut inner = Vec::new();
+        for element in elements {
+            match element {
+                SyntaxElement::Token(comma) => {
+                    inner.push(self.token(comma));
+                    inner.push(Doc::Line);
+                }
+                SyntaxElement::Node(node) => inner.push(self.argument(node)),
+            }
+        }
+        let close = self.element(&close[0]);
+
+        if inner.is_empty() {
+            return Doc::Concat(vec![Doc::Concat(head), close]);
+        }
+        Doc::group(Doc::Concat(vec![
+            Doc::Concat(head),
+            Doc::nest(Doc::Concat(vec![Doc::SoftLine, Doc::Concat(inner)])),
+            Doc::SoftLine,
+            close,
+        ]))
+    }
+
+    /// An element of a list, entry of a dictionary or argument of a call.
+    fn argument(&mut self, node: &SyntaxNode) -> Doc {
+        if node.kind() != SyntaxKind::Argument {
+            return self.expression(node);
+        }
+        // `*xs`, `**d` and `name=value` are written without spaces
+        self.children(node)
+    }
+}
+
+/// Whether a comment right after `token` on the same line trails it. After
+/// a line break token the comment is on a line of its own.
+fn is_trailed(token: &SyntaxToken) -> bool {
+    token.kind != TokenType::Newline
+}
+
+/// Whether `token` separates statements, so that the layout replaces it.
+fn is_separator(token: &SyntaxToken) -> bool {
+    matches!(token.kind, TokenType::Semicolon | TokenType::Newline | TokenType::Eof)
+}
+
+/// Binding strength of a binary node's operator, lowest first.
+fn precedence(node: &SyntaxNode) -> u8 {
+    match node.child_tokens().next().map(|token| &token.kind) {
+        Some(TokenType::Or) => 0,
+        Some(TokenType::And) => 1,
+        Some(TokenType::Equal | TokenType::NotEqual) => 2,
+        Some(TokenType::Less | TokenType::Greater | TokenType::LessEqual | TokenType::GreaterEqual) => 3,
+        Some(TokenType::In | TokenType::Not) => 4,
+        Some(TokenType::Plus | TokenType::Minus) => 5,
+        _ => 6,
+    }
+}
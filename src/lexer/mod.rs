@@ -12,17 +12,24 @@ pub struct Lexer {
     input: Vec<char>,
     position: usize,
     current_char: Option<char>,
+    /// Open brackets; line breaks inside brackets never end a statement
+    depth: usize,
+    /// Whether the last token can end a statement (a value or closing
+    /// bracket), so that a line break after it is significant
+    ends_statement: bool,
 }
 
 impl Lexer {
     pub fn new(input: &str) -> Self {
         let chars: Vec<char> = input.chars().collect();
-        let current_char = chars.get(0).copied();
+        let current_char = chars.first().copied();
         
         Self {
             input: chars,
             position: 0,
             current_char,
+            depth: 0,
+            ends_statement: false,
         }
     }
     
@@ -31,13 +38,20 @@ impl Lexer {
         self.current_char = self.input.get(self.position).copied();
     }
     
-    fn peek(&self) -> Option<char> {
-        self.input.get(self.position + 1).copied()
-    }
-    
+    /// Skips whitespace and `#` comments, stopping at a line break that ends a
+    /// statement.
     fn skip_whitespace(&mut self) {
         while let Some(ch) = self.current_char {
-            if ch.is_whitespace() {
+            if ch == '\n' && self.depth == 0 && self.ends_statement {
+                break;
+            }
+            if ch == '#' {
+                // A comment runs to the end of the line; the line break itself
+                // may still end the statement
+                while self.current_char.is_some_and(|ch| ch != '\n') {
+                    self.advance();
+                }
+            } else if ch.is_whitespace() {
                 self.advance();
             } else {
                 break;
@@ -76,6 +90,9 @@ impl Lexer {
                 if let Some('=') = self.current_char {
                     self.advance();
                     Token::new(TokenType::MultiplyAssign, Span::new(start_pos, self.position))
+                } else if let Some('*') = self.current_char {
+                    self.advance();
+                    Token::new(TokenType::DoubleStar, Span::new(start_pos, self.position))
                 } else {
                     Token::new(TokenType::Multiply, Span::single(start_pos))
                 }
@@ -177,6 +194,12 @@ impl Lexer {
                 self.advance();
                 Token::new(TokenType::Semicolon, span)
             }
+            // Only reached when the line break ends a statement
+            Some('\n') => {
+                let span = Span::single(self.position);
+                self.advance();
+                Token::new(TokenType::Newline, span)
+            }
             
             // String literals
             Some('"') => {
@@ -207,9 +230,30 @@ impl Lexer {
             None => Token::new(TokenType::Eof, Span::single(self.position))
         };
         
+        self.track(&token.token_type);
         Ok(token)
     }
     
+    /// Updates the bracket depth and whether a line break would end the
+    /// statement, after lexing a token of type `token_type`.
+    fn track(&mut self, token_type: &TokenType) {
+        match token_type {
+            TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftBrace => self.depth += 1,
+            TokenType::RightParen | TokenType::RightBracket | TokenType::RightBrace => {
+                self.depth = self.depth.saturating_sub(1);
+            }
+            _ => {}
+        }
+        // Operators, commas, separators and opening brackets continue the line
+        self.ends_statement = matches!(
+            token_type,
+            TokenType::Number(_) | TokenType::Integer(_) | TokenType::Boolean(_) |
+            TokenType::String(_) | TokenType::Identifier(_) |
+            TokenType::RightParen | TokenType::RightBracket | TokenType::RightBrace
+        );
+    }
+    
+    /// Tokenizes the whole input, stopping at the first lexical error.
     pub fn tokenize(&mut self) -> BcclResult<Vec<Token>> {
         let mut tokens = Vec::new();
         
@@ -225,4 +269,55 @@ impl Lexer {
         
         Ok(tokens)
     }
-}
\ No newline at end of file
+    
+    /// Tokenizes the whole input without stopping at lexical errors.
+    /// 
+    /// Each invalid character, malformed number or unterminated string becomes
+    /// a `TokenType::Error` token carrying the error's message and span, and
+    /// lexing continues after it. The parser reports error tokens as lexical
+    /// errors, so `Parser::parse_with_recovery` on these tokens yields a
+    /// partial tree plus every lexical and syntax problem at once.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust
+    /// # use bccl::{Lexer, TokenType};
+    /// let tokens = Lexer::new("x = 1 @ 2.3.4").tokenize_with_recovery();
+    /// let errors: Vec<_> = tokens.iter()
+    ///     .filter(|token| matches!(token.token_type, TokenType::Error(_)))
+    ///     .collect();
+    /// assert_eq!(errors.len(), 2);
+    /// assert_eq!(errors[1].token_type, TokenType::Error("Malformed number '2.3.4'".to_string()));
+    /// ```
+    pub fn tokenize_with_recovery(&mut self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        
+        loop {
+            // Every lexical error consumes at least one character, so this ends
+            let token = self.next_token().unwrap_or_else(|error| {
+                // Bad text stands in for a value, so a line break after it
+                // still ends the statement
+                self.ends_statement = true;
+                error_token(error)
+            });
+            let is_eof = matches!(token.token_type, TokenType::Eof);
+            tokens.push(token);
+            
+            if is_eof {
+                break;
+            }
+        }
+        
+        tokens
+    }
+}
+
+/// Turns a lexical error into an error token covering the same text.
+fn error_token(error: BcclError) -> Token {
+    match error {
+        BcclError::LexError { message, span } | BcclError::MalformedNumber { message, span } => {
+            Token::new(TokenType::Error(message), Span::new(span.offset(), span.offset() + span.len()))
+        }
+        other => Token::new(TokenType::Error(other.to_string()), Span::single(0)),
+    }
+}
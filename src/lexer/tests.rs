@@ -152,4 +152,113 @@ fn test_tokenize_collections() {
     assert_eq!(tokens[10].token_type, TokenType::String("value".to_string()));
     assert_eq!(tokens[11].token_type, TokenType::RightBrace);
     assert!(matches!(tokens[12].token_type, TokenType::Eof));
-}
\ No newline at end of file
+}
+#[test]
+fn test_tokenize_unpacking_operators() {
+    let mut lexer = Lexer::new("f(*xs, **d)");
+    let tokens = lexer.tokenize().unwrap();
+    
+    assert_eq!(tokens[2].token_type, TokenType::Multiply);
+    assert_eq!(tokens[5].token_type, TokenType::DoubleStar);
+    assert_eq!(tokens[5].span, Span::new(7, 9));
+    assert!(matches!(tokens[8].token_type, TokenType::Eof));
+}
+
+#[test]
+fn test_tokenize_with_recovery_emits_error_tokens() {
+    let mut lexer = Lexer::new("x = 1 @ 2.3.4 + \"open");
+    let tokens = lexer.tokenize_with_recovery();
+    
+    let kinds: Vec<&TokenType> = tokens.iter().map(|token| &token.token_type).collect();
+    assert_eq!(kinds, vec![
+        &TokenType::Identifier("x".to_string()),
+        &TokenType::Assign,
+        &TokenType::Integer(1),
+        &TokenType::Error("Invalid character '@'".to_string()),
+        &TokenType::Error("Malformed number '2.3.4'".to_string()),
+        &TokenType::Plus,
+        &TokenType::Error("Malformed number 'Unterminated string literal'".to_string()),
+        &TokenType::Eof,
+    ]);
+    assert_eq!(tokens[3].span, Span::new(6, 7));
+    assert_eq!(tokens[4].span, Span::new(8, 13));
+    assert_eq!(tokens[6].span, Span::new(16, 21));
+}
+
+#[test]
+fn test_tokenize_with_recovery_matches_strict_on_valid_input() {
+    let source = r#"total = sum(*[1, 2.5]) + len({"a": true}); total >= 3"#;
+    let strict = Lexer::new(source).tokenize().unwrap();
+    assert_eq!(Lexer::new(source).tokenize_with_recovery(), strict);
+    
+    // The strict entry point still stops at the first problem
+    assert!(matches!(Lexer::new("1 @ 2").tokenize(), Err(BcclError::LexError { .. })));
+}
+
+#[test]
+fn test_newlines_end_statements() {
+    let mut lexer = Lexer::new("x = 1\n\n\ny = f(2)\n");
+    let tokens = lexer.tokenize().unwrap();
+    
+    let kinds: Vec<&TokenType> = tokens.iter().map(|token| &token.token_type).collect();
+    assert_eq!(kinds, vec![
+        &TokenType::Identifier("x".to_string()),
+        &TokenType::Assign,
+        &TokenType::Integer(1),
+        &TokenType::Newline,
+        &TokenType::Identifier("y".to_string()),
+        &TokenType::Assign,
+        &TokenType::Identifier("f".to_string()),
+        &TokenType::LeftParen,
+        &TokenType::Integer(2),
+        &TokenType::RightParen,
+        &TokenType::Newline,
+        &TokenType::Eof,
+    ]);
+    assert_eq!(tokens[3].span, Span::single(5));
+}
+
+#[test]
+fn test_newlines_continue_inside_brackets_and_after_operators() {
+    let sources = [
+        "total = [1,\n  2,\n  3]",
+        "total = (1\n  + 2)",
+        "total = 1 +\n  2",
+        "total =\n  sum(1,\n 2)",
+        "ok = 1 in\n [1] and\n not\n false",
+        "d = {\n  \"a\": 1\n}",
+    ];
+    for source in sources {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        assert!(
+            !tokens.iter().any(|token| token.token_type == TokenType::Newline),
+            "unexpected newline token in {:?}", source
+        );
+    }
+    
+    // Leading blank lines and lines after `;` produce nothing either
+    let tokens = Lexer::new("\n\n x = 1;\n y = 2").tokenize().unwrap();
+    assert!(!tokens.iter().any(|token| token.token_type == TokenType::Newline));
+}
+
+#[test]
+fn test_comments_are_skipped() {
+    let tokens = Lexer::new("x = 1 # set x\n# a whole line\ny = 2 #").tokenize().unwrap();
+    let types: Vec<&TokenType> = tokens.iter().map(|t| &t.token_type).collect();
+    assert_eq!(types, vec![
+        &TokenType::Identifier("x".to_string()),
+        &TokenType::Assign,
+        &TokenType::Integer(1),
+        &TokenType::Newline,
+        &TokenType::Identifier("y".to_string()),
+        &TokenType::Assign,
+        &TokenType::Integer(2),
+        &TokenType::Eof,
+    ]);
+    // The line break after a comment still ends the statement
+    assert_eq!(tokens[3].span, Span::single(13));
+    
+    // A `#` inside a string is not a comment
+    let tokens = Lexer::new("\"# not a comment\"").tokenize().unwrap();
+    assert_eq!(tokens[0].token_type, TokenType::String("# not a comment".to_string()));
+}
@@ -1,6 +1,7 @@
 use crate::error::Span;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TokenType {
     // Literals
     Number(f64),
@@ -14,6 +15,7 @@ pub enum TokenType {
     Minus,
     Multiply,
     Divide,
+    DoubleStar, // ** (keyword unpacking in calls)
     
     // Comparison
     Equal,
@@ -46,12 +48,19 @@ pub enum TokenType {
     Comma,
     Colon,
     Semicolon,
+    /// A line break that ends a statement. Line breaks inside brackets or
+    /// after an operator continue the statement and produce no token.
+    Newline,
     
     // Special
     Eof,
+    /// Text that could not be lexed, with the lexical error's message. Only
+    /// produced by `Lexer::tokenize_with_recovery`.
+    Error(String),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Token {
     pub token_type: TokenType,
     pub span: Span,
@@ -2,6 +2,15 @@ pub mod error;
 pub mod lexer;
 pub mod parser;
 pub mod evaluator;
+pub mod vm;
+pub mod optimizer;
+pub mod resolver;
+pub mod syntax;
+pub mod formatter;
+pub mod lint;
+pub mod checker;
+#[cfg(feature = "lsp")]
+pub mod lsp;
 
 #[cfg(test)]
 mod test_errors;
@@ -9,9 +18,21 @@ mod test_errors;
 #[cfg(test)]
 mod verification_tests;
 
+#[cfg(test)]
+mod test_support;
+
 pub mod demo_errors;
 
 pub use error::*;
 pub use lexer::*;
 pub use parser::*;
-pub use evaluator::*;
\ No newline at end of file
+pub use evaluator::*;
+pub use vm::*;
+pub use optimizer::*;
+pub use resolver::*;
+pub use syntax::*;
+pub use formatter::*;
+pub use lint::*;
+pub use checker::*;
+#[cfg(feature = "lsp")]
+pub use lsp::*;
@@ -0,0 +1,320 @@
+//! # Lint Module
+//!
+//! Static checks for code that runs but is probably not what was meant, as
+//! used by `bccl lint`. Each finding is a `BcclError::LintWarning`, rendered
+//! by miette like any other diagnostic but with warning severity.
+//!
+//! ## Rules
+//!
+//! | Rule                 | Finds                                                 |
+//! |----------------------|-------------------------------------------------------|
+//! | `unused-variable`    | a variable that is assigned but never read            |
+//! | `unread-assignment`  | a value that is overwritten, or never read, after it is assigned |
+//! | `shadowed-builtin`   | an assignment that hides a built-in, e.g. `max = 3`   |
+//! | `self-comparison`    | an expression compared with itself, e.g. `x == x`     |
+//! | `constant-condition` | a left `and`/`or` operand that does not depend on any variable, or `true`/`false` on the right |
+//! | `float-equality`     | `==`/`!=` on fractional or divided numbers            |
+//! | `unknown-rule`       | a `# bccl: allow(...)` comment naming no rule         |
+//!
+//! BCCL has no `return` or conditional statements yet, so there is no rule
+//! for unreachable code; code after a `return` will need one when functions
+//! are added.
+//!
+//! ## Turning Rules Off
+//!
+//! Every rule is on by default. A `LintConfig` turns rules off for a whole
+//! run; `bccl lint` builds one from `--allow LIST` and from a `--config FILE`
+//! with lines like `allow(float-equality)` or `warn(unused-variable)`.
+//!
+//! A comment `# bccl: allow(rule, ...)` silences rules on one line: the line
+//! it ends, or, on a line of its own, the line after it. A finding is
+//! silenced if that line is any line it spans, or the last line of the
+//! statement it is in, so a comment after the closing bracket of a long list
+//! covers the whole assignment. A name in it that is not a rule is reported
+//! as `unknown-rule`, so a typo does not go unnoticed.
+
+use std::collections::HashSet;
+use std::fmt;
+use crate::error::{BcclError, BcclResult, Span};
+use crate::syntax::{SyntaxTree, TriviaKind};
+
+mod rules;
+
+#[cfg(test)]
+mod tests;
+
+pub use rules::Linter;
+
+/// A check that `bccl lint` can run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintRule {
+    UnusedVariable,
+    UnreadAssignment,
+    ShadowedBuiltin,
+    SelfComparison,
+    ConstantCondition,
+    FloatEquality,
+    UnknownRule,
+}
+
+impl LintRule {
+    /// Every rule, in declaration order.
+    pub const ALL: [LintRule; 7] = [
+        LintRule::UnusedVariable,
+        LintRule::UnreadAssignment,
+        LintRule::ShadowedBuiltin,
+        LintRule::SelfComparison,
+        LintRule::ConstantCondition,
+        LintRule::FloatEquality,
+        LintRule::UnknownRule,
+    ];
+
+    /// The name used in warnings, configuration and `allow` comments.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LintRule::UnusedVariable => "unused-variable",
+            LintRule::UnreadAssignment => "unread-assignment",
+            LintRule::ShadowedBuiltin => "shadowed-builtin",
+            LintRule::SelfComparison => "self-comparison",
+            LintRule::ConstantCondition => "constant-condition",
+            LintRule::FloatEquality => "float-equality",
+            LintRule::UnknownRule => "unknown-rule",
+        }
+    }
+
+    /// Parses a rule name such as `unused-variable`.
+    pub fn from_name(name: &str) -> Option<LintRule> {
+        LintRule::ALL.into_iter().find(|rule| rule.name() == name)
+    }
+}
+
+impl fmt::Display for LintRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The rules a lint run checks.
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::{LintConfig, LintRule};
+/// let config = LintConfig::parse("# floats are compared exactly on purpose\nallow(float-equality)").unwrap();
+/// assert!(!config.is_enabled(LintRule::FloatEquality));
+/// assert!(config.is_enabled(LintRule::UnusedVariable));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintConfig {
+    enabled: HashSet<LintRule>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self { enabled: LintRule::ALL.into_iter().collect() }
+    }
+}
+
+impl LintConfig {
+    /// Every rule on.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns this configuration with `rule` turned off.
+    pub fn allow(mut self, rule: LintRule) -> Self {
+        self.enabled.remove(&rule);
+        self
+    }
+
+    /// Returns this configuration with `rule` turned on.
+    pub fn warn(mut self, rule: LintRule) -> Self {
+        self.enabled.insert(rule);
+        self
+    }
+
+    /// Returns whether `rule` is checked.
+    pub fn is_enabled(&self, rule: LintRule) -> bool {
+        self.enabled.contains(&rule)
+    }
+
+    /// Applies a configuration file to the default configuration. Each line
+    /// is `allow(rule, ...)` or `warn(rule, ...)`, applied in order; blank
+    /// lines and `#` comments are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(config)` - The resulting configuration
+    /// * `Err(entry)` - The first line or rule name that is not understood
+    pub fn parse(text: &str) -> Result<Self, String> {
+        text.lines()
+            .map(|line| line.split('#').next().unwrap_or("").trim())
+            .filter(|line| !line.is_empty())
+            .try_fold(Self::default(), |config, line| {
+                let (action, rules) = directive(line).ok_or_else(|| line.to_string())?;
+                rules.into_iter().try_fold(config, |config, name| {
+                    let rule = LintRule::from_name(name).ok_or_else(|| name.to_string())?;
+                    Ok(if action == "allow" { config.allow(rule) } else { config.warn(rule) })
+                })
+            })
+    }
+}
+
+/// Checks BCCL source with the rules `config` turns on, honouring
+/// `# bccl: allow(rule)` comments.
+///
+/// # Arguments
+///
+/// * `source` - The program to check
+/// * `config` - The rules to run
+///
+/// # Returns
+///
+/// * `Ok(warnings)` - One `BcclError::LintWarning` per finding, in source order
+/// * `Err(error)` - If the source has lexical or syntax errors (several are
+///   returned as `BcclError::Multiple`)
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::{lint_source, LintConfig};
+/// let warnings = lint_source("max = 3\nmax", &LintConfig::new()).unwrap();
+/// assert_eq!(warnings.len(), 1);
+/// assert!(warnings[0].to_string().contains("hides the built-in"));
+///
+/// let allowed = lint_source("max = 3  # bccl: allow(shadowed-builtin)\nmax", &LintConfig::new()).unwrap();
+/// assert!(allowed.is_empty());
+/// ```
+pub fn lint_source(source: &str, config: &LintConfig) -> BcclResult<Vec<BcclError>> {
+    let tree = SyntaxTree::parse(source);
+    if !tree.errors().is_empty() {
+        return Err(BcclError::multiple(tree.into_errors()));
+    }
+    let lines = Lines::new(source);
+    let program = tree.lower();
+    let (allowed, unknown) = allow_comments(source, &lines, &tree);
+    let mut findings = Linter::new(config.clone()).findings(&program);
+    findings.extend(unknown.into_iter().filter(|finding| config.is_enabled(finding.rule)));
+    findings.sort_by_key(|finding| (finding.span.start, finding.span.end));
+
+    // A comment on any line of the finding, or at the end of the statement
+    // it is in, silences it
+    let statements: Vec<Span> = program.statements.iter().map(|stmt| stmt.span()).collect();
+    let warnings = findings.into_iter()
+        .filter(|finding| {
+            let start = finding.span.start;
+            let containing = statements.partition_point(|span| span.start <= start).checked_sub(1)
+                .map(|index| statements[index])
+                .filter(|span| start < span.end);
+            let end = containing.map_or(finding.span.end, |span| span.end.max(finding.span.end));
+            (lines.line(start)..=lines.line(end)).all(|line| !allowed.contains(&(line, finding.rule)))
+        })
+        .map(|finding| finding.into_warning())
+        .collect();
+    Ok(warnings)
+}
+
+/// Splits `allow(a, b)` into `("allow", ["a", "b"])`; the action is `allow`
+/// or `warn`.
+fn directive(text: &str) -> Option<(&str, Vec<&str>)> {
+    let (action, rest) = text.trim().split_once('(')?;
+    let action = action.trim();
+    let rules = rest.trim_end().strip_suffix(')')?;
+    if action != "allow" && action != "warn" {
+        return None;
+    }
+    Some((action, rules.split(',').map(str::trim).filter(|rule| !rule.is_empty()).collect()))
+}
+
+/// The `(line, rule)` pairs that `# bccl: allow(...)` comments silence, and
+/// an `unknown-rule` finding for each name in them that is not a rule.
+fn allow_comments(source: &str, lines: &Lines, tree: &SyntaxTree) -> (HashSet<(usize, LintRule)>, Vec<Finding>) {
+    let chars: Vec<char> = source.chars().collect();
+    let mut allowed = HashSet::new();
+    let mut unknown = Vec::new();
+    for token in tree.root().tokens() {
+        // Trivia runs up to the token, so the comments' offsets are found by
+        // counting back from its start
+        let mut offset = token.span.start - token.leading_trivia.iter().map(|trivia| trivia.text.chars().count()).sum::<usize>();
+        for trivia in &token.leading_trivia {
+            let start = offset;
+            offset += trivia.text.chars().count();
+            if trivia.kind != TriviaKind::Comment {
+                continue;
+            }
+            let Some(("allow", rules)) = trivia.text.trim_start_matches('#').trim()
+                .strip_prefix("bccl:")
+                .and_then(directive)
+            else {
+                continue;
+            };
+            let line = lines.line(start);
+            // A comment alone on its line speaks for the next one
+            let own_line = chars[lines.start(line)..start].iter().all(|c| c.is_whitespace());
+            let target = if own_line { line + 1 } else { line };
+            for name in rules {
+                match LintRule::from_name(name) {
+                    Some(rule) => {
+                        allowed.insert((target, rule));
+                    }
+                    None => {
+                        // `directive` returns slices of the comment, so the
+                        // name's position is its distance from the start
+                        let bytes = name.as_ptr() as usize - trivia.text.as_ptr() as usize;
+                        let name_start = start + trivia.text[..bytes].chars().count();
+                        unknown.push(Finding {
+                            rule: LintRule::UnknownRule,
+                            span: Span::new(name_start, name_start + name.chars().count()),
+                            message: format!("`{}` is not a lint rule, so this comment silences nothing", name),
+                            suggestion: format!(
+                                "The rules are {}",
+                                LintRule::ALL.iter().map(LintRule::name).collect::<Vec<_>>().join(", ")
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    (allowed, unknown)
+}
+
+/// The character offset at which each line starts, so that finding the line
+/// of an offset does not rescan the source.
+struct Lines {
+    starts: Vec<usize>,
+}
+
+impl Lines {
+    fn new(source: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(source.chars().enumerate().filter(|(_, c)| *c == '\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { starts }
+    }
+
+    /// The 0-based line of the character at `offset`.
+    fn line(&self, offset: usize) -> usize {
+        self.starts.partition_point(|&start| start <= offset) - 1
+    }
+
+    /// The offset at which `line` starts.
+    fn start(&self, line: usize) -> usize {
+        self.starts[line]
+    }
+}
+
+/// A problem found by a rule, before it becomes a warning.
+#[derive(Debug, Clone)]
+struct Finding {
+    rule: LintRule,
+    span: Span,
+    message: String,
+    suggestion: String,
+}
+
+impl Finding {
+    fn into_warning(self) -> BcclError {
+        BcclError::lint_warning(self.rule.name(), &self.message, self.span, &self.suggestion)
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::{HashMap, HashSet};
+use crate::error::{BcclError, Span};
+use crate::evaluator::{get_builtin_functions, Capabilities};
+use crate::parser::{BinaryOp, Expr, Program, Stmt, UnaryOp, Visitor, walk_expr};
+use super::{Finding, LintConfig, LintRule};
+
+/// Runs lint rules over a parsed program.
+///
+/// `lint_source` is usually more convenient: working on the AST alone, a
+/// `Linter` cannot see `# bccl: allow(...)` comments.
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::{Lexer, LintConfig, Linter, Parser};
+/// let program = Parser::new(Lexer::new("total = 0.1 + 0.2\ntotal == 0.3").tokenize().unwrap()).parse().unwrap();
+/// let warnings = Linter::new(LintConfig::new()).lint(&program);
+/// assert_eq!(warnings.len(), 1);
+/// assert!(warnings[0].to_string().contains("floating-point"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Linter {
+    config: LintConfig,
+    /// Names of the built-in functions that assignments should not hide
+    functions: HashSet<String>,
+}
+
+impl Linter {
+    /// Creates a linter that knows every built-in, whatever its capability.
+    pub fn new(config: LintConfig) -> Self {
+        let functions = get_builtin_functions(Capabilities::all()).into_keys().collect();
+        Self { config, functions }
+    }
+
+    /// Sets the built-in functions `shadowed-builtin` protects, for hosts
+    /// that register their own.
+    pub fn with_functions<I: IntoIterator<Item = String>>(mut self, names: I) -> Self {
+        self.functions = names.into_iter().collect();
+        self
+    }
+
+    /// Checks `program`, returning one `BcclError::LintWarning` per finding in
+    /// source order.
+    pub fn lint(&self, program: &Program) -> Vec<BcclError> {
+        self.findings(program).into_iter().map(|finding| finding.into_warning()).collect()
+    }
+
+    pub(super) fn findings(&self, program: &Program) -> Vec<Finding> {
+        let mut findings = self.variable_findings(program);
+        let mut expressions = ExpressionRules { findings: Vec::new() };
+        expressions.visit_program(program);
+        findings.extend(expressions.findings);
+        findings.retain(|finding| self.config.is_enabled(finding.rule));
+        findings.sort_by_key(|finding| (finding.span.start, finding.span.end));
+        findings
+    }
+
+    /// `unused-variable`, `unread-assignment` and `shadowed-builtin`, which
+    /// follow variables from statement to statement.
+    fn variable_findings(&self, program: &Program) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        // Names some expression reads; a compound assignment reading its own
+        // target does not count as a use
+        let read: HashSet<&str> = program.statements.iter()
+            .flat_map(|stmt| statement_value(stmt).referenced_names())
+            .collect();
+        let mut shadowed = HashSet::new();
+        let mut unused = HashSet::new();
+        // The latest assignment to each variable whose value nothing has read
+        let mut pending: HashMap<&str, Span> = HashMap::new();
+
+        for stmt in &program.statements {
+            for name in statement_value(stmt).referenced_names() {
+                pending.remove(name);
+            }
+            let (name, span) = match stmt {
+                Stmt::Assignment { name, span, .. } => (name.as_str(), *span),
+                Stmt::CompoundAssignment { name, span, .. } => {
+                    pending.remove(name.as_str());
+                    (name.as_str(), *span)
+                }
+                Stmt::Expression { .. } => continue,
+            };
+
+            if self.functions.contains(name) && shadowed.insert(name) {
+                findings.push(Finding {
+                    rule: LintRule::ShadowedBuiltin,
+                    span,
+                    message: format!("'{}' hides the built-in function {}()", name, name),
+                    suggestion: format!("Rename the variable; while it exists, {}(...) calls it instead of the built-in", name),
+                });
+            }
+            if !read.contains(name) {
+                if unused.insert(name) {
+                    findings.push(Finding {
+                        rule: LintRule::UnusedVariable,
+                        span,
+                        message: format!("variable '{}' is assigned but never used", name),
+                        suggestion: "Remove the assignment, or use the variable".to_string(),
+                    });
+                }
+                continue;
+            }
+            if let Some(overwritten) = pending.insert(name, span) {
+                findings.push(Finding {
+                    rule: LintRule::UnreadAssignment,
+                    span: overwritten,
+                    message: format!("the value assigned to '{}' is overwritten before it is read", name),
+                    suggestion: "Remove this assignment, or read the value before assigning again".to_string(),
+                });
+            }
+        }
+
+        findings.extend(pending.into_iter().map(|(name, span)| Finding {
+            rule: LintRule::UnreadAssignment,
+            span,
+            message: format!("the value assigned to '{}' is never read", name),
+            suggestion: "Remove this assignment, or use the value after it".to_string(),
+        }));
+        findings
+    }
+}
+
+/// The expression a statement evaluates.
+fn statement_value(stmt: &Stmt) -> &Expr {
+    match stmt {
+        Stmt::Expression { expr, .. } => expr,
+        Stmt::Assignment { value, .. } | Stmt::CompoundAssignment { value, .. } => value,
+    }
+}
+
+/// `self-comparison`, `constant-condition` and `float-equality`, which look
+/// at one expression at a time.
+struct ExpressionRules {
+    findings: Vec<Finding>,
+}
+
+impl Visitor<'_> for ExpressionRules {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let Expr::Binary { left, operator, right, span } = expr {
+            self.binary(expr, left, *operator, right, *span);
+        }
+        walk_expr(self, expr);
+    }
+}
+
+impl ExpressionRules {
+    fn binary(&mut self, expr: &Expr, left: &Expr, operator: BinaryOp, right: &Expr, span: Span) {
+        let comparison = matches!(
+            operator,
+            BinaryOp::Equal | BinaryOp::NotEqual | BinaryOp::Less | BinaryOp::Greater | BinaryOp::LessEqual | BinaryOp::GreaterEqual
+        );
+        // Calls may return something new each time, e.g. random()
+        if comparison && left.to_sexp() == right.to_sexp() && !has_call(left) {
+            let always = matches!(operator, BinaryOp::Equal | BinaryOp::LessEqual | BinaryOp::GreaterEqual);
+            self.findings.push(Finding {
+                rule: LintRule::SelfComparison,
+                span,
+                message: format!("`{}` compares an expression with itself", expr),
+                suggestion: format!("This is always {}; compare with something else or use `{}`", always, always),
+            });
+        }
+
+        // `or` returns its right operand when the left one is falsy, so a
+        // constant there is the default-value idiom, `getenv("PORT") or "8080"`;
+        // only `true`/`false` on the right is a constant condition
+        if matches!(operator, BinaryOp::And | BinaryOp::Or) {
+            let right_is_condition = matches!(right, Expr::Boolean { .. });
+            for operand in [left].into_iter().chain(right_is_condition.then_some(right)) {
+                if operand.referenced_names().is_empty() {
+                    self.findings.push(Finding {
+                        rule: LintRule::ConstantCondition,
+                        span: operand.span(),
+                        message: format!("operand `{}` of `{}` is constant", operand, operator.symbol()),
+                        suggestion: "Its value never changes, so the condition can be simplified".to_string(),
+                    });
+                }
+            }
+        }
+
+        if matches!(operator, BinaryOp::Equal | BinaryOp::NotEqual) && (is_inexact(left) || is_inexact(right)) {
+            self.findings.push(Finding {
+                rule: LintRule::FloatEquality,
+                span,
+                message: format!("`{}` on floating-point numbers only tolerates a difference below 2.2e-16", operator.symbol()),
+                suggestion: "Rounding errors grow with the size of the numbers and can exceed that; compare with a tolerance that suits the values instead, e.g. max(a - b, b - a) < 0.000001".to_string(),
+            });
+        }
+    }
+}
+
+/// Whether `expr` calls a function anywhere.
+fn has_call(expr: &Expr) -> bool {
+    struct Calls(bool);
+
+    impl Visitor<'_> for Calls {
+        fn visit_expr(&mut self, expr: &Expr) {
+            self.0 |= matches!(expr, Expr::FunctionCall { .. });
+            walk_expr(self, expr);
+        }
+    }
+
+    let mut calls = Calls(false);
+    calls.visit_expr(expr);
+    calls.0
+}
+
+/// Whether `expr` is a number that is likely to carry a rounding error: a
+/// fractional literal, a division, or arithmetic on either.
+fn is_inexact(expr: &Expr) -> bool {
+    match expr {
+        Expr::Number { value, .. } => value.fract() != 0.0,
+        Expr::Unary { operator: UnaryOp::Minus | UnaryOp::Plus, operand, .. } => is_inexact(operand),
+        Expr::Binary { operator: BinaryOp::Divide, .. } => true,
+        Expr::Binary { left, operator: BinaryOp::Add | BinaryOp::Subtract | BinaryOp::Multiply, right, .. } => {
+            is_inexact(left) || is_inexact(right)
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,173 @@
+use super::*;
+use miette::Diagnostic;
+use crate::{Lexer, Parser};
+
+/// The `(rule, line)` of every warning for `source`, lines counted from 1.
+fn lint(source: &str, config: &LintConfig) -> Vec<(String, usize)> {
+    let lines = Lines::new(source);
+    lint_source(source, config).unwrap().into_iter()
+        .map(|warning| match warning {
+            BcclError::LintWarning { rule, span, .. } => (rule, lines.line(span.offset()) + 1),
+            other => panic!("Expected a lint warning, got {:?}", other),
+        })
+        .collect()
+}
+
+fn rules(source: &str) -> Vec<String> {
+    lint(source, &LintConfig::new()).into_iter().map(|(rule, _)| rule).collect()
+}
+
+#[test]
+fn test_unused_variable() {
+    assert_eq!(rules("x = 1\ny = 2\nx"), ["unused-variable"]);
+    // Only updating a variable does not use it
+    assert_eq!(rules("count = 0\ncount += 1"), ["unused-variable"]);
+    // Calling a variable reads it
+    assert!(rules("f = max\nf(1, 2)").is_empty());
+    assert!(rules("xs = [1]\nlen(xs)").is_empty());
+}
+
+#[test]
+fn test_unread_assignment() {
+    assert_eq!(lint("x = 1\nx = 2\nx", &LintConfig::new()), [("unread-assignment".to_string(), 1)]);
+    assert_eq!(lint("x = 1\nx\nx = 2", &LintConfig::new()), [("unread-assignment".to_string(), 3)]);
+    // The right-hand side is read before the assignment happens
+    assert!(rules("x = 1\nx = x + 1\nx").is_empty());
+    // A compound assignment reads its target
+    assert!(rules("x = 1\nx *= 2\nx").is_empty());
+    assert_eq!(rules("x = 1\nx\nx *= 2"), ["unread-assignment"]);
+}
+
+#[test]
+fn test_shadowed_builtin() {
+    assert_eq!(lint("max = 3\nmax\nmax = 4\nmax", &LintConfig::new()), [("shadowed-builtin".to_string(), 1)]);
+    assert!(rules("maximum = 3\nmaximum").is_empty());
+    // Built-ins behind a capability are protected too
+    assert_eq!(rules("now = 1\nnow"), ["shadowed-builtin"]);
+
+    let program = Parser::new(Lexer::new("max = 1\nmax").tokenize().unwrap()).parse().unwrap();
+    let linter = Linter::new(LintConfig::new()).with_functions(["min".to_string()]);
+    assert!(linter.lint(&program).is_empty());
+}
+
+#[test]
+fn test_self_comparison() {
+    assert_eq!(rules("x = 1\nx == x"), ["self-comparison"]);
+    assert_eq!(rules("x = [1]\nx[0] + 1 >= (x[0] + 1)"), ["self-comparison"]);
+    assert!(rules("x = 1\ny = 2\nx == y").is_empty());
+    // A call may give a different value each time
+    assert!(rules("random() == random()").is_empty());
+}
+
+#[test]
+fn test_constant_condition() {
+    assert_eq!(rules("x = 1\nx > 0 and true"), ["constant-condition"]);
+    assert_eq!(rules("x = 1\n[] or x"), ["constant-condition"]);
+    assert_eq!(rules("1 < 2 or false"), ["constant-condition", "constant-condition"]);
+
+    // A constant on the right of `or` is a default value, not a condition
+    assert!(rules("port = 0\nport = port or \"8080\"\nport").is_empty());
+    assert!(rules("x = []\nx and [1] or [2]").is_empty());
+    assert!(rules("x = 1\ny = 2\nx and y").is_empty());
+    // Only `and` and `or` are conditions
+    assert!(rules("x = 1\nx + 1").is_empty());
+}
+
+#[test]
+fn test_float_equality() {
+    assert_eq!(rules("x = 1\nx == 0.5"), ["float-equality"]);
+    assert_eq!(rules("x = 1\nx / 3 != 2"), ["float-equality"]);
+    assert_eq!(rules("x = 1\n-(x * 0.1) == x"), ["float-equality"]);
+    assert!(rules("x = 1\nx == 2").is_empty());
+    assert!(rules("x = 1\nx == 2.0").is_empty());
+    assert!(rules("x = 1\nx < 0.5").is_empty());
+}
+
+#[test]
+fn test_config_turns_rules_off() {
+    let source = "x = 0.5 == 0.5\ny = 1";
+    assert_eq!(rules(source), ["unused-variable", "self-comparison", "float-equality", "unused-variable"]);
+    let config = LintConfig::new().allow(LintRule::UnusedVariable).allow(LintRule::SelfComparison);
+    assert_eq!(lint(source, &config), [("float-equality".to_string(), 1)]);
+    assert!(lint(source, &config.clone().allow(LintRule::FloatEquality)).is_empty());
+    assert_eq!(lint(source, &config.warn(LintRule::SelfComparison)).len(), 2);
+}
+
+#[test]
+fn test_config_files() {
+    let config = LintConfig::parse("allow(unused-variable, float-equality)  # noisy\n\nwarn(float-equality)\n").unwrap();
+    assert!(!config.is_enabled(LintRule::UnusedVariable));
+    assert!(config.is_enabled(LintRule::FloatEquality));
+    assert_eq!(LintConfig::parse("").unwrap(), LintConfig::new());
+    assert_eq!(LintConfig::parse("allow(unused)"), Err("unused".to_string()));
+    assert_eq!(LintConfig::parse("deny(unused-variable)"), Err("deny(unused-variable)".to_string()));
+    assert_eq!(LintConfig::parse("allow unused-variable"), Err("allow unused-variable".to_string()));
+}
+
+#[test]
+fn test_allow_comments() {
+    // A trailing comment covers its own line only
+    assert_eq!(
+        lint("a = 1  # bccl: allow(unused-variable)\nb = 2", &LintConfig::new()),
+        [("unused-variable".to_string(), 2)]
+    );
+    // A comment on its own line covers the next line
+    assert_eq!(
+        lint("#bccl: allow(unused-variable, float-equality)\na = 1 == 0.5\nb = 2", &LintConfig::new()),
+        [("unused-variable".to_string(), 3)]
+    );
+    // Other rules and other comments change nothing, and unknown rules are reported
+    assert_eq!(rules("a = 1  # bccl: allow(float-equality, nonsense)"), ["unused-variable", "unknown-rule"]);
+    assert_eq!(rules("a = 1  # allow(unused-variable)"), ["unused-variable"]);
+    assert_eq!(rules("a = \"# bccl: allow(unused-variable)\""), ["unused-variable"]);
+    // Offsets are counted in characters
+    assert!(rules("s = \"日本語\"  # bccl: allow(unused-variable)").is_empty());
+    // A statement over several lines is covered by a comment on any of them,
+    // including its last
+    assert!(rules("values = [\n    1,\n    2\n]  # bccl: allow(unused-variable)").is_empty());
+    assert!(rules("x = 0.5\nx == 0.1 or\n    x > 1  # bccl: allow(float-equality)").is_empty());
+    assert_eq!(
+        lint("values = [\n    1\n]  # bccl: allow(unused-variable)\nb = 2", &LintConfig::new()),
+        [("unused-variable".to_string(), 4)]
+    );
+}
+
+#[test]
+fn test_unknown_rules_in_allow_comments() {
+    let source = "# bccl: allow(unused-varible)\nx = 1\ny = 2  # bccl: allow(unused-variable, nonsense)";
+    let warnings = lint_source(source, &LintConfig::new()).unwrap();
+    let unknown: Vec<_> = warnings.iter()
+        .filter(|warning| matches!(warning, BcclError::LintWarning { rule, .. } if rule == "unknown-rule"))
+        .collect();
+    let names: Vec<_> = unknown.iter()
+        .map(|warning| {
+            let label = warning.labels().unwrap().next().unwrap();
+            source.chars().skip(label.offset()).take(label.len()).collect::<String>()
+        })
+        .collect();
+    assert_eq!(names, ["unused-varible", "nonsense"]);
+    assert!(unknown[0].to_string().contains("`unused-varible` is not a lint rule"));
+    assert!(unknown[0].help().unwrap().to_string().contains("unused-variable, unread-assignment"));
+    // The misspelled rule silences nothing, and the report can be turned off
+    assert_eq!(lint(source, &LintConfig::new().allow(LintRule::UnknownRule)), [("unused-variable".to_string(), 2)]);
+}
+
+#[test]
+fn test_warnings_are_diagnostics() {
+    let warnings = lint_source("y = 2\nx = 1 == 1.5", &LintConfig::new()).unwrap();
+    let starts: Vec<_> = warnings.iter()
+        .map(|warning| warning.labels().unwrap().next().unwrap().offset())
+        .collect();
+    assert_eq!(starts, [0, 6, 10]);
+    for warning in &warnings {
+        assert_eq!(warning.severity(), Some(miette::Severity::Warning));
+        assert_eq!(warning.code().unwrap().to_string(), "bccl::lint::warning");
+        assert!(warning.help().unwrap().to_string().contains("# bccl: allow("));
+    }
+}
+
+#[test]
+fn test_rejects_invalid_source() {
+    assert!(lint_source("x = [1, 2", &LintConfig::new()).is_err());
+    assert!(lint_source("", &LintConfig::new()).unwrap().is_empty());
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use miette::{Diagnostic, Severity};
+use serde_json::{json, Value as Json};
+use crate::checker::{Type, TypeChecker};
+use crate::error::{BcclError, Span};
+use crate::evaluator::{required_capability, Evaluator, EvaluatorLimits, FunctionSignature};
+use crate::lexer::TokenType;
+use crate::lint::{lint_source, LintConfig};
+use crate::parser::{Program, Stmt};
+use crate::syntax::SyntaxTree;
+use super::position::LineIndex;
+
+/// Bounds on the run that finds runtime errors, so a loop-free but huge
+/// program cannot stall the editor.
+const RUN_STEPS: u64 = 100_000;
+const RUN_ELEMENTS: usize = 100_000;
+const RUN_TIME: Duration = Duration::from_millis(200);
+
+/// Names completed everywhere, next to variables and functions.
+const KEYWORDS: [&str; 6] = ["true", "false", "and", "or", "not", "in"];
+
+/// An open document and what the server knows about it. Everything is
+/// recomputed when the text changes.
+pub(super) struct Document {
+    text: String,
+    index: LineIndex,
+    tree: SyntaxTree,
+    /// The program as far as it parses
+    program: Program,
+}
+
+/// The call a cursor is in the argument list of.
+#[derive(Debug, PartialEq)]
+pub(super) struct CallContext {
+    pub function: String,
+    /// Commas before the cursor, i.e. the index of the current argument
+    pub commas: usize,
+    /// Set when the current argument is `name=...`
+    pub keyword: Option<String>,
+}
+
+impl Document {
+    pub fn new(text: String) -> Self {
+        let tree = SyntaxTree::parse(&text);
+        let program = tree.lower();
+        Self { index: LineIndex::new(&text), text, tree, program }
+    }
+
+    /// The character offset of an LSP `Position`.
+    pub fn offset(&self, position: &Json) -> Option<usize> {
+        let line = position["line"].as_u64()?;
+        let column = position["character"].as_u64()?;
+        Some(self.index.offset(line as u32, column as u32))
+    }
+
+    /// Lexical and syntax errors; without those, type errors or the first
+    /// runtime error, followed by lint warnings.
+    pub fn diagnostics(&self, functions: &HashMap<String, FunctionSignature>) -> Vec<Json> {
+        if !self.tree.errors().is_empty() {
+            return self.tree.errors().iter().map(|error| self.diagnostic(error)).collect();
+        }
+        let mut problems = Vec::new();
+        match TypeChecker::new().with_functions(functions).check(&self.program) {
+            Err(error) => problems.extend(error.into_errors()),
+            Ok(()) => problems.extend(self.runtime_error()),
+        }
+        if let Ok(warnings) = lint_source(&self.text, &LintConfig::new()) {
+            problems.extend(warnings);
+        }
+        problems.iter().map(|problem| self.diagnostic(problem)).collect()
+    }
+
+    /// Runs the program without capabilities and within small limits. Only
+    /// errors the program would have in any host are returned: not reaching a
+    /// limit, and not a missing capability that the host may grant.
+    fn runtime_error(&self) -> Option<BcclError> {
+        let mut evaluator = Evaluator::new();
+        evaluator.set_limits(EvaluatorLimits::new().with_max_steps(RUN_STEPS).with_max_total_elements(RUN_ELEMENTS));
+        evaluator.set_deadline(Some(Instant::now() + RUN_TIME));
+        match evaluator.evaluate_program(&self.program) {
+            Err(BcclError::MissingCapability { .. } | BcclError::LimitExceeded { .. } | BcclError::Cancelled { .. }) => None,
+            Err(error) => Some(error),
+            Ok(_) => None,
+        }
+    }
+
+    /// An LSP `Diagnostic` at the first label of `error`, with its help text
+    /// after the message.
+    fn diagnostic(&self, error: &BcclError) -> Json {
+        let span = error.labels()
+            .and_then(|mut labels| labels.next())
+            .map(|label| Span::new(label.offset(), label.offset() + label.len()))
+            .unwrap_or(Span::zero_width(0));
+        let severity = match error.severity() {
+            Some(Severity::Warning) => 2,
+            Some(Severity::Advice) => 3,
+            _ => 1,
+        };
+        let mut message = error.to_string();
+        if let Some(help) = error.help() {
+            message.push_str(&format!("\nhelp: {}", help));
+        }
+        json!({
+            "range": self.range(span),
+            "severity": severity,
+            "code": error.code().map(|code| code.to_string()),
+            "source": "bccl",
+            "message": message,
+        })
+    }
+
+    /// Completion items: keyword arguments of the call the cursor is in,
+    /// variables, functions and keywords.
+    pub fn completion(&self, offset: usize, functions: &HashMap<String, FunctionSignature>) -> Vec<Json> {
+        let mut items = Vec::new();
+        if let Some(signature) = self.call_at(offset).and_then(|call| self.function(&call.function, functions)) {
+            for name in keyword_names(signature) {
+                items.push(json!({
+                    "label": format!("{}=", name),
+                    "kind": 5,
+                    "detail": format!("argument of {}()", signature.name),
+                    // Listed first: they are only offered where they fit
+                    "sortText": format!("0{}", name),
+                }));
+            }
+        }
+
+        let types = self.types(functions);
+        let mut variables: Vec<_> = self.program.assigned_names().into_iter().collect();
+        variables.sort();
+        for name in variables {
+            let ty = types.get(name).copied().unwrap_or(Type::Any);
+            items.push(json!({ "label": name, "kind": 6, "detail": ty.name(), "sortText": format!("1{}", name) }));
+        }
+
+        let mut names: Vec<_> = functions.keys().collect();
+        names.sort();
+        for name in names {
+            let (label, _) = signature_label(&functions[name]);
+            items.push(json!({ "label": name, "kind": 3, "detail": label, "sortText": format!("2{}", name) }));
+        }
+
+        for keyword in KEYWORDS {
+            items.push(json!({ "label": keyword, "kind": 14, "sortText": format!("3{}", keyword) }));
+        }
+        items
+    }
+
+    /// Markdown for the variable or function under the cursor.
+    pub fn hover(&self, offset: usize, functions: &HashMap<String, FunctionSignature>) -> Option<Json> {
+        let (name, span) = self.identifier_at(offset)?;
+        let value = if let Some(definition) = self.definition_span(&name) {
+            let ty = self.types(functions).get(&name).copied().unwrap_or(Type::Any);
+            let (line, _) = self.index.position(definition.start);
+            format!("```bccl\n{}: {}\n```\nVariable, first assigned on line {}", name, ty, line + 1)
+        } else {
+            let signature = functions.get(&name)?;
+            let mut value = format!("```bccl\n{}\n```\nBuilt-in function", signature_label(signature).0);
+            if let Some(capability) = required_capability(&name) {
+                value.push_str(&format!("; requires the `{}` capability", capability));
+            }
+            value
+        };
+        Some(json!({ "contents": { "kind": "markdown", "value": value }, "range": self.range(span) }))
+    }
+
+    /// Where the variable under the cursor is first assigned.
+    pub fn definition(&self, offset: usize) -> Option<Json> {
+        let (name, _) = self.identifier_at(offset)?;
+        self.definition_span(&name).map(|span| self.range(span))
+    }
+
+    /// The signature of the call the cursor is in, with the parameter the
+    /// current argument binds to.
+    pub fn signature_help(&self, offset: usize, functions: &HashMap<String, FunctionSignature>) -> Option<Json> {
+        let call = self.call_at(offset)?;
+        let signature = self.function(&call.function, functions)?;
+        let (label, parameters) = signature_label(signature);
+        let positional = signature.parameters.to_parameters().positional.len();
+        let active = match &call.keyword {
+            Some(keyword) => parameters.iter().position(|(name, _)| name == keyword),
+            None if call.commas < positional => Some(call.commas),
+            // Extra arguments go to `*rest`, if there is one
+            None => parameters.iter().position(|(_, label)| label.starts_with('*') && !label.starts_with("**")),
+        };
+        let parameters: Vec<Json> = parameters.into_iter().map(|(_, label)| json!({ "label": label })).collect();
+        Some(json!({
+            "signatures": [{ "label": label, "parameters": parameters }],
+            "activeSignature": 0,
+            // Out of range means no parameter is highlighted
+            "activeParameter": active.unwrap_or(parameters.len()),
+        }))
+    }
+
+    /// The function `name(...)` calls, unless a variable hides it.
+    fn function<'a>(&self, name: &str, functions: &'a HashMap<String, FunctionSignature>) -> Option<&'a FunctionSignature> {
+        if self.definition_span(name).is_some() {
+            return None;
+        }
+        functions.get(name)
+    }
+
+    fn types(&self, functions: &HashMap<String, FunctionSignature>) -> HashMap<String, Type> {
+        TypeChecker::new().with_functions(functions).infer(&self.program)
+    }
+
+    /// The identifier the cursor is on or just after.
+    fn identifier_at(&self, offset: usize) -> Option<(String, Span)> {
+        self.tree.root().tokens().into_iter().find_map(|token| match &token.kind {
+            TokenType::Identifier(name) if token.span.start <= offset && offset <= token.span.end => {
+                Some((name.clone(), token.span))
+            }
+            _ => None,
+        })
+    }
+
+    /// The name in the first statement that assigns `name`.
+    fn definition_span(&self, name: &str) -> Option<Span> {
+        self.program.statements.iter().find_map(|stmt| match stmt {
+            Stmt::Assignment { name: assigned, span, .. } | Stmt::CompoundAssignment { name: assigned, span, .. }
+                if assigned == name => Some(Span::new(span.start, span.start + name.chars().count())),
+            _ => None,
+        })
+    }
+
+    /// The innermost call whose argument list contains `offset`, found from
+    /// the tokens alone so that it works while the call is being typed.
+    pub fn call_at(&self, offset: usize) -> Option<CallContext> {
+        // One entry per open bracket; `None` for lists, dictionaries and
+        // parentheses that do not start a call
+        let mut open: Vec<Option<CallContext>> = Vec::new();
+        let mut previous: [Option<&TokenType>; 2] = [None, None];
+        for token in self.tree.root().tokens() {
+            if token.span.end > offset || token.kind == TokenType::Eof {
+                break;
+            }
+            match &token.kind {
+                TokenType::LeftParen => open.push(match previous[1] {
+                    Some(TokenType::Identifier(name)) => Some(CallContext { function: name.clone(), commas: 0, keyword: None }),
+                    _ => None,
+                }),
+                TokenType::LeftBracket | TokenType::LeftBrace => open.push(None),
+                TokenType::RightParen | TokenType::RightBracket | TokenType::RightBrace => {
+                    open.pop();
+                }
+                TokenType::Comma => {
+                    if let Some(Some(call)) = open.last_mut() {
+                        call.commas += 1;
+                        call.keyword = None;
+                    }
+                }
+                TokenType::Assign => {
+                    if let (Some(Some(call)), [Some(TokenType::LeftParen | TokenType::Comma), Some(TokenType::Identifier(name))]) = (open.last_mut(), previous) {
+                        call.keyword = Some(name.clone());
+                    }
+                }
+                TokenType::Newline | TokenType::Semicolon => open.clear(),
+                _ => {}
+            }
+            previous = [previous[1], Some(&token.kind)];
+        }
+        open.into_iter().rev().flatten().next()
+    }
+
+    fn range(&self, span: Span) -> Json {
+        json!({ "start": self.position(span.start), "end": self.position(span.end) })
+    }
+
+    fn position(&self, offset: usize) -> Json {
+        let (line, character) = self.index.position(offset);
+        json!({ "line": line, "character": character })
+    }
+}
+
+/// The names a call to `signature` can pass by keyword.
+fn keyword_names(signature: &FunctionSignature) -> Vec<String> {
+    let parameters = signature.parameters.to_parameters();
+    parameters.positional.iter().map(|parameter| parameter.name.clone())
+        .chain(parameters.rest)
+        .chain(parameters.keyword_only.iter().map(|parameter| parameter.name.clone()))
+        .chain(signature.options.iter().cloned())
+        .collect()
+}
+
+/// The signature as written in documentation, e.g.
+/// `sorted(values, *, reverse=false, key=...)`, and the name and label of
+/// each parameter. Options, which have no default, are shown as `name=...`.
+fn signature_label(signature: &FunctionSignature) -> (String, Vec<(String, String)>) {
+    let parameters = signature.parameters.to_parameters();
+    let with_default = |name: &str, default: &Option<crate::evaluator::Value>| match default {
+        Some(default) => format!("{}={}", name, default.display()),
+        None => name.to_string(),
+    };
+    let mut labelled: Vec<(String, String)> = parameters.positional.iter()
+        .map(|parameter| (parameter.name.clone(), with_default(&parameter.name, &parameter.default)))
+        .collect();
+    if let Some(rest) = &parameters.rest {
+        labelled.push((rest.clone(), format!("*{}", rest)));
+    }
+    let keyword_only: Vec<(String, String)> = parameters.keyword_only.iter()
+        .map(|parameter| (parameter.name.clone(), with_default(&parameter.name, &parameter.default)))
+        .chain(signature.options.iter().map(|option| (option.clone(), format!("{}=...", option))))
+        .collect();
+
+    let mut parts: Vec<&str> = labelled.iter().map(|(_, label)| label.as_str()).collect();
+    // A bare `*` marks where keyword-only parameters start
+    if parameters.rest.is_none() && !keyword_only.is_empty() {
+        parts.push("*");
+    }
+    parts.extend(keyword_only.iter().map(|(_, label)| label.as_str()));
+    let var_keyword = parameters.var_keyword.map(|name| (name.clone(), format!("**{}", name)));
+    parts.extend(var_keyword.iter().map(|(_, label)| label.as_str()));
+    let label = format!("{}({})", signature.name, parts.join(", "));
+
+    labelled.extend(keyword_only);
+    labelled.extend(var_keyword);
+    (label, labelled)
+}
@@ -0,0 +1,288 @@
+//! # LSP Module
+//!
+//! A Language Server Protocol server for BCCL, run by the `bccl-lsp` binary
+//! over standard input and output.
+//!
+//! ## Features
+//!
+//! - **Diagnostics**: lexer and parser errors; for a program that parses,
+//!   type errors, or else the first runtime error, plus lint warnings
+//! - **Completion**: variables, built-in functions and keywords, and the
+//!   keyword arguments of the call being typed
+//! - **Hover**: the inferred type of a variable, or a built-in's signature
+//! - **Go to definition**: the first assignment to a variable
+//! - **Signature help**: the signature of the enclosing call, with the
+//!   parameter the current argument binds to, by position or by keyword
+//!
+//! ## Positions
+//!
+//! Spans count characters, while LSP positions count UTF-16 code units per
+//! line; `LineIndex` converts between them.
+//!
+//! ## Runtime errors
+//!
+//! To find runtime errors the program is run without capabilities and with
+//! small limits. Errors that depend on those, a missing capability or a limit
+//! reached, are not reported: the host that runs the program for real may
+//! grant more.
+
+mod position;
+mod analysis;
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use serde_json::{json, Value as Json};
+use crate::evaluator::{get_builtin_functions, Capabilities, FunctionSignature};
+use analysis::Document;
+
+pub use position::LineIndex;
+
+/// JSON-RPC error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// A language server: the open documents and the functions they can call.
+///
+/// `handle` answers one decoded message; `run` reads and writes framed
+/// messages until the client says `exit`.
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::LanguageServer;
+/// # use serde_json::json;
+/// let mut server = LanguageServer::new();
+/// let replies = server.handle(&json!({
+///     "jsonrpc": "2.0",
+///     "method": "textDocument/didOpen",
+///     "params": { "textDocument": { "uri": "file:///a.bccl", "text": "x = 1 +" } },
+/// }));
+/// let diagnostics = &replies[0]["params"]["diagnostics"];
+/// assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+/// ```
+pub struct LanguageServer {
+    /// Every built-in, including those behind capabilities: a document may
+    /// be run by a host that grants them
+    functions: HashMap<String, FunctionSignature>,
+    documents: HashMap<String, Document>,
+    shutdown: bool,
+}
+
+impl Default for LanguageServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LanguageServer {
+    /// Creates a server with no open documents.
+    pub fn new() -> Self {
+        Self {
+            functions: get_builtin_functions(Capabilities::all()),
+            documents: HashMap::new(),
+            shutdown: false,
+        }
+    }
+
+    /// Serves messages framed with `Content-Length` headers from `input`,
+    /// writing replies and notifications to `output`.
+    ///
+    /// # Returns
+    ///
+    /// The exit code: 0 after `shutdown` then `exit`, 1 if the client exits
+    /// without shutting down or `input` ends
+    pub fn run<R: BufRead, W: Write>(&mut self, mut input: R, mut output: W) -> io::Result<i32> {
+        while let Some(frame) = read_message(&mut input)? {
+            let body = match frame {
+                Frame::Message(body) => body,
+                Frame::Malformed(message) => {
+                    write_message(&mut output, &error_response(Json::Null, INVALID_REQUEST, &message))?;
+                    continue;
+                }
+            };
+            let message = match serde_json::from_slice::<Json>(&body) {
+                Ok(message) => message,
+                Err(error) => {
+                    write_message(&mut output, &error_response(Json::Null, PARSE_ERROR, &error.to_string()))?;
+                    continue;
+                }
+            };
+            if message["method"] == "exit" {
+                return Ok(if self.shutdown { 0 } else { 1 });
+            }
+            for reply in self.handle(&message) {
+                write_message(&mut output, &reply)?;
+            }
+        }
+        Ok(1)
+    }
+
+    /// Handles one request or notification.
+    ///
+    /// # Returns
+    ///
+    /// The messages to send back: the response to a request, and any
+    /// `textDocument/publishDiagnostics` notifications
+    pub fn handle(&mut self, message: &Json) -> Vec<Json> {
+        let Some(method) = message["method"].as_str() else {
+            // A response to a request of ours; the server sends none
+            return Vec::new();
+        };
+        let params = &message["params"];
+        let Some(id) = message.get("id").cloned() else {
+            return self.notification(method, params);
+        };
+        if self.shutdown {
+            return vec![error_response(id, INVALID_REQUEST, "the server is shutting down")];
+        }
+        let result = match method {
+            "initialize" => Some(initialize_result()),
+            "shutdown" => {
+                self.shutdown = true;
+                Some(Json::Null)
+            }
+            "textDocument/completion" => self.at_position(params, |document, offset, functions| {
+                json!(document.completion(offset, functions))
+            }),
+            "textDocument/hover" => self.at_position(params, |document, offset, functions| {
+                json!(document.hover(offset, functions))
+            }),
+            "textDocument/definition" => self.at_position(params, |document, offset, _| {
+                document.definition(offset).map_or(Json::Null, |range| {
+                    json!({ "uri": params["textDocument"]["uri"], "range": range })
+                })
+            }),
+            "textDocument/signatureHelp" => self.at_position(params, |document, offset, functions| {
+                json!(document.signature_help(offset, functions))
+            }),
+            _ => return vec![error_response(id, METHOD_NOT_FOUND, &format!("unknown method '{}'", method))],
+        };
+        match result {
+            Some(result) => vec![json!({ "jsonrpc": "2.0", "id": id, "result": result })],
+            None => vec![error_response(id, INVALID_PARAMS, "expected an open document and a position")],
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &Json) -> Vec<Json> {
+        let Some(uri) = params["textDocument"]["uri"].as_str() else {
+            return Vec::new();
+        };
+        match method {
+            "textDocument/didOpen" => {
+                let Some(text) = params["textDocument"]["text"].as_str() else {
+                    return Vec::new();
+                };
+                self.documents.insert(uri.to_string(), Document::new(text.to_string()));
+            }
+            "textDocument/didChange" => {
+                // Only full synchronization is offered, so the last change
+                // holds the whole text
+                let Some(text) = params["contentChanges"].as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str()) else {
+                    return Vec::new();
+                };
+                self.documents.insert(uri.to_string(), Document::new(text.to_string()));
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                return vec![publish_diagnostics(uri, Vec::new())];
+            }
+            _ => return Vec::new(),
+        }
+        vec![publish_diagnostics(uri, self.documents[uri].diagnostics(&self.functions))]
+    }
+
+    /// Runs `f` on the document and offset a `TextDocumentPositionParams`
+    /// names, or returns `None` if they do not exist.
+    fn at_position(
+        &self,
+        params: &Json,
+        f: impl FnOnce(&Document, usize, &HashMap<String, FunctionSignature>) -> Json,
+    ) -> Option<Json> {
+        let document = self.documents.get(params["textDocument"]["uri"].as_str()?)?;
+        let offset = document.offset(&params["position"])?;
+        Some(f(document, offset, &self.functions))
+    }
+}
+
+fn initialize_result() -> Json {
+    json!({
+        "capabilities": {
+            "textDocumentSync": 1,
+            "completionProvider": { "triggerCharacters": [] },
+            "hoverProvider": true,
+            "definitionProvider": true,
+            "signatureHelpProvider": { "triggerCharacters": ["(", ","] },
+        },
+        "serverInfo": { "name": "bccl-lsp", "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Json>) -> Json {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+fn error_response(id: Json, code: i64, message: &str) -> Json {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// One framed message read by `read_message`.
+enum Frame {
+    /// The body of a message
+    Message(Vec<u8>),
+    /// A message whose headers could not be read, already skipped
+    Malformed(String),
+}
+
+/// Reads the headers and body of one message, or `None` at the end of input.
+fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<Frame>> {
+    let mut length = None;
+    let mut malformed = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            // Blank lines before the headers are tolerated
+            if length.is_some() || malformed.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            match value.trim().parse::<usize>() {
+                Ok(value) => length = Some(value),
+                Err(_) => malformed = Some(format!("bad Content-Length '{}'", value.trim())),
+            }
+        }
+    }
+    if let Some(message) = malformed {
+        // Without a length the body can only be skipped by reading it as JSON;
+        // if that fails too, the next header line resynchronizes
+        let _ = serde_json::Deserializer::from_reader(&mut *input).into_iter::<Json>().next();
+        return Ok(Some(Frame::Malformed(message)));
+    }
+    let mut body = vec![0; length.unwrap_or(0)];
+    input.read_exact(&mut body)?;
+    Ok(Some(Frame::Message(body)))
+}
+
+fn write_message<W: Write>(output: &mut W, message: &Json) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
@@ -0,0 +1,57 @@
+/// Converts between the character offsets of `Span` and LSP positions.
+///
+/// An LSP position is a 0-based line and a column counted in UTF-16 code
+/// units, so a character outside the Basic Multilingual Plane, such as an
+/// emoji, takes two columns.
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::LineIndex;
+/// let index = LineIndex::new("x = 1\ns = \"😀\" + y");
+/// // `y` is character 16, after the two-column emoji
+/// assert_eq!(index.position(16), (1, 11));
+/// assert_eq!(index.offset(1, 11), 16);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    chars: Vec<char>,
+    /// Character offset at which each line starts
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    /// Indexes the lines of `text`.
+    pub fn new(text: &str) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let line_starts = std::iter::once(0)
+            .chain(chars.iter().enumerate().filter(|(_, c)| **c == '\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { chars, line_starts }
+    }
+
+    /// The `(line, column)` of the character at `offset`. Offsets past the
+    /// end are clamped to the end of the text.
+    pub fn position(&self, offset: usize) -> (u32, u32) {
+        let offset = offset.min(self.chars.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let column: usize = self.chars[self.line_starts[line]..offset].iter().map(|c| c.len_utf16()).sum();
+        (line as u32, column as u32)
+    }
+
+    /// The character offset of `(line, column)`. A column past the end of its
+    /// line means the end of the line, and a line past the end of the text
+    /// means the end of the text.
+    pub fn offset(&self, line: u32, column: u32) -> usize {
+        let Some(&start) = self.line_starts.get(line as usize) else {
+            return self.chars.len();
+        };
+        let mut offset = start;
+        let mut units = 0;
+        while offset < self.chars.len() && self.chars[offset] != '\n' && units < column as usize {
+            units += self.chars[offset].len_utf16();
+            offset += 1;
+        }
+        offset
+    }
+}
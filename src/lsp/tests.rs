@@ -0,0 +1,204 @@
+use super::*;
+
+const URI: &str = "file:///test.bccl";
+
+/// A server with `text` open.
+fn open(text: &str) -> (LanguageServer, Vec<Json>) {
+    let mut server = LanguageServer::new();
+    let replies = server.handle(&json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": { "textDocument": { "uri": URI, "languageId": "bccl", "version": 1, "text": text } },
+    }));
+    (server, replies)
+}
+
+/// The result of a position request at `(line, character)`.
+fn request(server: &mut LanguageServer, method: &str, line: u32, character: u32) -> Json {
+    let replies = server.handle(&json!({
+        "jsonrpc": "2.0",
+        "id": 7,
+        "method": method,
+        "params": { "textDocument": { "uri": URI }, "position": { "line": line, "character": character } },
+    }));
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0]["id"], 7);
+    replies[0]["result"].clone()
+}
+
+/// The `(line, message)` of each diagnostic in a publish notification.
+fn diagnostics(notification: &Json) -> Vec<(u64, String)> {
+    assert_eq!(notification["method"], "textDocument/publishDiagnostics");
+    notification["params"]["diagnostics"].as_array().unwrap().iter()
+        .map(|diagnostic| (diagnostic["range"]["start"]["line"].as_u64().unwrap(), diagnostic["message"].as_str().unwrap().to_string()))
+        .collect()
+}
+
+#[test]
+fn test_line_index() {
+    let index = LineIndex::new("ab\n\ncd");
+    assert_eq!(index.position(0), (0, 0));
+    assert_eq!(index.position(2), (0, 2));
+    assert_eq!(index.position(3), (1, 0));
+    assert_eq!(index.position(5), (2, 1));
+    // Past the end is the end
+    assert_eq!(index.position(99), (2, 2));
+    assert_eq!(index.offset(2, 1), 5);
+    assert_eq!(index.offset(0, 99), 2);
+    assert_eq!(index.offset(9, 0), 6);
+}
+
+#[test]
+fn test_diagnostics() {
+    // Syntax errors hide everything else
+    let (_, replies) = open("x = 1 +\ny = \"a\" * 2");
+    let found = diagnostics(&replies[0]);
+    assert_eq!(found.len(), 1);
+    assert!(found[0].1.starts_with("Syntax error"), "{:?}", found);
+
+    let (_, replies) = open("x = 1\ny = x + \"a\"");
+    let found = diagnostics(&replies[0]);
+    assert_eq!(found[0].0, 1);
+    assert!(found[0].1.contains("Type error") && found[0].1.contains("\nhelp: "), "{:?}", found);
+
+    // A well-typed program is run for its runtime errors
+    let (_, replies) = open("x = [1, 2]\nx[5]");
+    assert_eq!(diagnostics(&replies[0]).len(), 1);
+    assert_eq!(diagnostics(&replies[0])[0].0, 1);
+    assert_eq!(replies[0]["params"]["diagnostics"][0]["severity"], 1);
+
+    // ... but not for errors that depend on the host or on limits
+    let (_, replies) = open("print_me = read_file(\"a\")\nprint_me\nn = 0\nbig = [0] * 1000000\nbig");
+    assert!(diagnostics(&replies[0]).iter().all(|(_, message)| !message.contains("capability") && !message.contains("limit")));
+
+    let (_, replies) = open("unused = 1");
+    let diagnostic = &replies[0]["params"]["diagnostics"][0];
+    assert_eq!(diagnostic["severity"], 2);
+    assert_eq!(diagnostic["source"], "bccl");
+}
+
+#[test]
+fn test_document_sync() {
+    let (mut server, _) = open("x = 1");
+    let replies = server.handle(&json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didChange",
+        "params": { "textDocument": { "uri": URI, "version": 2 }, "contentChanges": [{ "text": "x = (" }] },
+    }));
+    assert_eq!(diagnostics(&replies[0]).len(), 1);
+    let replies = server.handle(&json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didClose",
+        "params": { "textDocument": { "uri": URI } },
+    }));
+    assert!(diagnostics(&replies[0]).is_empty());
+    // A closed document cannot be queried
+    let replies = server.handle(&json!({
+        "jsonrpc": "2.0", "id": 1, "method": "textDocument/hover",
+        "params": { "textDocument": { "uri": URI }, "position": { "line": 0, "character": 0 } },
+    }));
+    assert_eq!(replies[0]["error"]["code"], INVALID_PARAMS);
+}
+
+#[test]
+fn test_completion() {
+    let (mut server, _) = open("total = 1\nsorted([total], ");
+    let items = request(&mut server, "textDocument/completion", 1, 16);
+    let items = items.as_array().unwrap();
+    let find = |label: &str| items.iter().find(|item| item["label"] == label).cloned();
+    assert_eq!(find("total").unwrap()["detail"], "int");
+    assert_eq!(find("max").unwrap()["detail"], "max(*values, key=..., default=...)");
+    assert!(find("read_file").is_some());
+    assert!(find("true").is_some());
+    assert!(find("reverse=").is_some() && find("key=").is_some());
+    // Outside a call there are no keyword arguments
+    let items = request(&mut server, "textDocument/completion", 0, 0);
+    assert!(items.as_array().unwrap().iter().all(|item| !item["label"].as_str().unwrap().ends_with('=')));
+}
+
+#[test]
+fn test_hover_and_definition() {
+    let (mut server, _) = open("name = \"😀\"\n\nname: str = name + \"!\"\nprint = sorted(\"ba\")");
+    let hover = request(&mut server, "textDocument/hover", 2, 14);
+    assert_eq!(hover["contents"]["value"], "```bccl\nname: str\n```\nVariable, first assigned on line 1");
+    assert_eq!(hover["range"], json!({ "start": { "line": 2, "character": 12 }, "end": { "line": 2, "character": 16 } }));
+
+    let hover = request(&mut server, "textDocument/hover", 3, 9);
+    assert!(hover["contents"]["value"].as_str().unwrap().contains("sorted(values, *, reverse=false, key=...)"));
+    assert_eq!(request(&mut server, "textDocument/hover", 1, 0), Json::Null);
+
+    let definition = request(&mut server, "textDocument/definition", 2, 14);
+    assert_eq!(definition["uri"], URI);
+    assert_eq!(definition["range"], json!({ "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 4 } }));
+    // Built-ins have no definition
+    assert_eq!(request(&mut server, "textDocument/definition", 3, 9), Json::Null);
+}
+
+#[test]
+fn test_signature_help() {
+    let (mut server, _) = open("sorted([3, (1)], reverse=true)\nmax(1, 2, 3)\nx = [int(\"1\"), 2]\nf = 3\nf(");
+    let help = request(&mut server, "textDocument/signatureHelp", 0, 25);
+    assert_eq!(help["signatures"][0]["label"], "sorted(values, *, reverse=false, key=...)");
+    // The keyword picks the parameter, whatever its position
+    assert_eq!(help["activeParameter"], 1);
+    // Before the first comma: the first parameter
+    assert_eq!(request(&mut server, "textDocument/signatureHelp", 0, 8)["activeParameter"], 0);
+    // Extra positional arguments go to `*values`
+    assert_eq!(request(&mut server, "textDocument/signatureHelp", 1, 10)["activeParameter"], 0);
+    // Inside the nested call, then after it
+    assert_eq!(request(&mut server, "textDocument/signatureHelp", 2, 9)["signatures"][0]["label"], "int(value)");
+    assert_eq!(request(&mut server, "textDocument/signatureHelp", 2, 15), Json::Null);
+    // A variable hides a built-in of the same name, and is not a function
+    assert_eq!(request(&mut server, "textDocument/signatureHelp", 4, 2), Json::Null);
+}
+
+#[test]
+fn test_protocol_errors() {
+    let mut server = LanguageServer::new();
+    let replies = server.handle(&json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }));
+    assert_eq!(replies[0]["result"]["serverInfo"]["name"], "bccl-lsp");
+    let replies = server.handle(&json!({ "jsonrpc": "2.0", "id": 2, "method": "workspace/symbol", "params": {} }));
+    assert_eq!(replies[0]["error"]["code"], METHOD_NOT_FOUND);
+    // Unknown notifications are ignored
+    assert!(server.handle(&json!({ "jsonrpc": "2.0", "method": "$/setTrace" })).is_empty());
+    let replies = server.handle(&json!({ "jsonrpc": "2.0", "id": 3, "method": "shutdown" }));
+    assert_eq!(replies[0]["result"], Json::Null);
+    let replies = server.handle(&json!({ "jsonrpc": "2.0", "id": 4, "method": "initialize" }));
+    assert_eq!(replies[0]["error"]["code"], INVALID_REQUEST);
+}
+
+#[test]
+fn test_framing() {
+    let frame = |body: &str| format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+    let input = [
+        frame("{\"jsonrpc\": \"2.0\", \"id\": 1, \"method\": \"shutdown\"}"),
+        frame("{not json"),
+        frame("{\"jsonrpc\": \"2.0\", \"method\": \"exit\"}"),
+    ].concat();
+    let mut output = Vec::new();
+    assert_eq!(LanguageServer::new().run(input.as_bytes(), &mut output).unwrap(), 0);
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("Content-Length: 38\r\n\r\n{\"id\":1,\"jsonrpc\":\"2.0\",\"result\":null}"), "{}", output);
+    assert!(output.contains(&format!("\"code\":{}", PARSE_ERROR)));
+
+    // Exiting without a shutdown, or running out of input, is a failure
+    let exit = frame("{\"jsonrpc\": \"2.0\", \"method\": \"exit\"}");
+    assert_eq!(LanguageServer::new().run(exit.as_bytes(), Vec::new()).unwrap(), 1);
+    assert_eq!(LanguageServer::new().run(&b""[..], Vec::new()).unwrap(), 1);
+}
+
+#[test]
+fn test_bad_content_length_skips_the_frame() {
+    let frame = |body: &str| format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+    let input = [
+        "Content-Length: twelve\r\n\r\n{\"jsonrpc\": \"2.0\", \"id\": 1, \"method\": \"initialize\"}".to_string(),
+        frame("{\"jsonrpc\": \"2.0\", \"id\": 2, \"method\": \"shutdown\"}"),
+        frame("{\"jsonrpc\": \"2.0\", \"method\": \"exit\"}"),
+    ].concat();
+    let mut output = Vec::new();
+    assert_eq!(LanguageServer::new().run(input.as_bytes(), &mut output).unwrap(), 0);
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(&format!("\"code\":{},\"message\":\"bad Content-Length 'twelve'\"", INVALID_REQUEST)), "{}", output);
+    assert!(output.contains("{\"id\":2,\"jsonrpc\":\"2.0\",\"result\":null}"), "{}", output);
+    assert!(!output.contains("\"id\":1"), "{}", output);
+}
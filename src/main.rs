@@ -1,5 +1,7 @@
 use std::io::{self, Write};
-use bccl::{Lexer, Parser, Evaluator, ErrorContext};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use bccl::{Lexer, Parser, Evaluator, ErrorContext, Backend, Capabilities, BcclError, Snapshot, FormatOptions, format_source, LintConfig, LintRule, lint_source, TypeChecker};
 use miette::{IntoDiagnostic, Result, GraphicalReportHandler, GraphicalTheme};
 
 fn strip_ansi_codes(input: &str) -> String {
@@ -8,9 +10,35 @@ fn strip_ansi_codes(input: &str) -> String {
     re.replace_all(input, "").to_string()
 }
 
+const USAGE: &str = "Usage: bccl [--backend tree|vm] [--no-opt] [--check] [--capabilities LIST]
+       bccl --dump-tokens|--dump-ast [FILE]
+       bccl fmt [--check] [--width N] FILE...
+       bccl lint [--allow LIST] [--config FILE] FILE...
+       bccl check FILE...";
+
 fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // `bccl fmt [--check] [--width N] FILE...` formats files instead of
+    // starting the REPL
+    if args.first().is_some_and(|arg| arg == "fmt") {
+        std::process::exit(format_files(&args[1..]));
+    }
+    // `bccl lint [--allow LIST] [--config FILE] FILE...` reports warnings
+    if args.first().is_some_and(|arg| arg == "lint") {
+        std::process::exit(lint_files(&args[1..]));
+    }
+    // `bccl check FILE...` reports type errors without running anything
+    if args.first().is_some_and(|arg| arg == "check") {
+        std::process::exit(check_files(&args[1..]));
+    }
+    // `--dump-tokens` and `--dump-ast` print what the lexer and parser make
+    // of a file, or of standard input without one
+    if let Some(mode) = args.first().filter(|arg| *arg == "--dump-tokens" || *arg == "--dump-ast") {
+        std::process::exit(dump(mode, args.get(1)));
+    }
+    
     println!("BCCL Interpreter v0.2.0 - Enhanced Error Diagnostics");
-    println!("Type expressions and assignments. Use Ctrl+C to exit.\n");
+    println!("Type expressions and assignments. Ctrl+C stops a running evaluation; at the prompt it exits.\n");
     
     // Show some examples
     println!("Examples:");
@@ -20,7 +48,61 @@ fn main() -> Result<()> {
     println!("  z            # Show variable value");
     println!();
     
-    let mut evaluator = Evaluator::new();
+    // `--backend vm` runs everything on the bytecode VM from the start
+    let mut backend = Backend::TreeWalk;
+    if let Some(position) = args.iter().position(|arg| arg == "--backend") {
+        match args.get(position + 1).and_then(|name| Backend::from_name(name)) {
+            Some(selected) => backend = selected,
+            None => {
+                eprintln!("{}", USAGE);
+                std::process::exit(2);
+            }
+        }
+    }
+    
+    // The REPL trusts its user with every capability unless
+    // `--capabilities fs_read,clock` (or `none`) narrows them
+    let mut capabilities = Capabilities::all();
+    if let Some(position) = args.iter().position(|arg| arg == "--capabilities") {
+        match args.get(position + 1).map(|list| Capabilities::parse(list)) {
+            Some(Ok(selected)) => capabilities = selected,
+            Some(Err(name)) => {
+                eprintln!("Unknown capability '{}'. Use fs_read, fs_write, env, clock, random, process, all or none.", name);
+                std::process::exit(2);
+            }
+            None => {
+                eprintln!("{}", USAGE);
+                std::process::exit(2);
+            }
+        }
+    }
+    
+    let mut evaluator = Evaluator::with_capabilities(capabilities);
+    evaluator.set_backend(backend);
+    // `--no-opt` evaluates programs exactly as parsed
+    if args.iter().any(|arg| arg == "--no-opt") {
+        evaluator.set_optimize(false);
+    }
+    // `--check` type-checks each input before it runs
+    if args.iter().any(|arg| arg == "--check") {
+        evaluator.set_type_check(true);
+    }
+    
+    // Ctrl+C cancels the running evaluation, or exits when nothing is running
+    let cancellation = evaluator.cancellation_token();
+    let evaluating = Arc::new(AtomicBool::new(false));
+    {
+        let cancellation = cancellation.clone();
+        let evaluating = evaluating.clone();
+        ctrlc::set_handler(move || {
+            if evaluating.load(Ordering::SeqCst) {
+                cancellation.cancel();
+            } else {
+                println!();
+                std::process::exit(130);
+            }
+        }).into_diagnostic()?;
+    }
     
     loop {
         print!("> ");
@@ -44,11 +126,46 @@ fn main() -> Result<()> {
                     show_variables(&evaluator);
                     continue;
                 }
+                if input == ":backend" {
+                    println!("Backend: {}", evaluator.backend().name());
+                    continue;
+                }
+                if let Some(name) = input.strip_prefix(":backend ") {
+                    match Backend::from_name(name.trim()) {
+                        Some(selected) => {
+                            evaluator.set_backend(selected);
+                            println!("Backend: {}", selected.name());
+                        }
+                        None => println!("Unknown backend '{}'. Use 'tree' or 'vm'.", name.trim()),
+                    }
+                    continue;
+                }
                 if input == ":clear" {
-                    evaluator = Evaluator::new();
+                    let optimize = evaluator.optimize_enabled();
+                    let type_check = evaluator.type_check_enabled();
+                    let backend = evaluator.backend();
+                    evaluator = Evaluator::with_capabilities(evaluator.capabilities());
+                    evaluator.set_backend(backend);
+                    evaluator.set_optimize(optimize);
+                    evaluator.set_type_check(type_check);
+                    evaluator.set_cancellation_token(cancellation.clone());
                     println!("Variables cleared.");
                     continue;
                 }
+                if let Some(path) = input.strip_prefix(":save ") {
+                    match save_session(path.trim(), &evaluator) {
+                        Ok(count) => println!("Saved {} variables to {}.", count, path.trim()),
+                        Err(error) => report_error(error, input),
+                    }
+                    continue;
+                }
+                if let Some(path) = input.strip_prefix(":load ") {
+                    match load_session(path.trim(), &mut evaluator) {
+                        Ok(count) => println!("Loaded {} variables from {}.", count, path.trim()),
+                        Err(error) => report_error(error, input),
+                    }
+                    continue;
+                }
                 if input == ":quit" || input == ":exit" {
                     break;
                 }
@@ -57,31 +174,29 @@ fn main() -> Result<()> {
                     continue;
                 }
                 
-                // Create error context for rich diagnostics
-                let context = ErrorContext::new(input.to_string());
+                // `:tokens <code>` and `:ast <code>` show what would be
+                // evaluated
+                let (source, result) = if let Some(code) = command_argument(input, ":tokens") {
+                    (code, show_tokens(code))
+                } else if let Some(code) = command_argument(input, ":ast") {
+                    (code, show_ast(code, &evaluator))
+                } else if input == ":save" || input == ":load" {
+                    println!("Usage: {} FILE", input);
+                    continue;
+                } else if input.starts_with(':') {
+                    let command = input.split_whitespace().next().unwrap_or(input);
+                    println!("Unknown command '{}'. Type :help for the list of commands.", command);
+                    continue;
+                } else {
+                    cancellation.reset();
+                    evaluating.store(true, Ordering::SeqCst);
+                    let result = evaluate_input(input, &mut evaluator);
+                    evaluating.store(false, Ordering::SeqCst);
+                    (input, result)
+                };
                 
-                // Evaluate the input with comprehensive error handling
-                if let Err(error) = evaluate_input(input, &mut evaluator) {
-                    // Use miette to display rich error diagnostics with proper formatting
-                    let report = miette::Report::new(error).with_source_code(context.source);
-                    
-                    // Configure miette for consistent output regardless of execution context
-                    // Always use ASCII theme for consistent display across different execution contexts
-                    let theme = GraphicalTheme::ascii();
-                    
-                    let handler = GraphicalReportHandler::new_themed(theme);
-                    let mut output = String::new();
-                    match handler.render_report(&mut output, report.as_ref()) {
-                        Ok(()) => {
-                            // Strip ANSI color codes for consistent output
-                            let clean_output = strip_ansi_codes(&output);
-                            eprint!("{}", clean_output);
-                        }
-                        Err(_) => {
-                            // Simple fallback that should always work
-                            eprintln!("{}", report);
-                        }
-                    }
+                if let Err(error) = result {
+                    report_error(error, source);
                 }
             }
             Err(e) => {
@@ -94,6 +209,273 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Runs `bccl fmt`: rewrites each file in the canonical style, or with
+/// `--check` only lists the files that are not formatted. Returns the exit
+/// code: 0 on success, 1 if a file has errors or (with `--check`) needs
+/// formatting, 2 for bad arguments.
+fn format_files(args: &[String]) -> i32 {
+    const USAGE: &str = "Usage: bccl fmt [--check] [--width N] FILE...";
+    let mut options = FormatOptions::default();
+    let mut check = false;
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--width" => match args.next().and_then(|width| width.parse().ok()) {
+                Some(width) => options.width = width,
+                None => {
+                    eprintln!("{}", USAGE);
+                    return 2;
+                }
+            },
+            _ => files.push(arg),
+        }
+    }
+    if files.is_empty() {
+        eprintln!("{}", USAGE);
+        return 2;
+    }
+    
+    let mut status = 0;
+    for path in files {
+        let source = match std::fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(error) => {
+                eprintln!("{}: {}", path, error);
+                status = 1;
+                continue;
+            }
+        };
+        let formatted = match format_source(&source, &options) {
+            Ok(formatted) => formatted,
+            Err(error) => {
+                eprintln!("{}: not formatted because of errors", path);
+                report_error(error, &source);
+                status = 1;
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+        if check {
+            println!("{} would be reformatted", path);
+            status = 1;
+        } else if let Err(error) = std::fs::write(path, formatted) {
+            eprintln!("{}: {}", path, error);
+            status = 1;
+        }
+    }
+    status
+}
+
+/// Runs `bccl lint`: reports the lint warnings of each file. `--allow`
+/// turns off a comma-separated list of rules, and `--config` applies a file
+/// of `allow(rule)` / `warn(rule)` lines. Returns the exit code: 0 if there
+/// are no warnings, 1 if there are warnings or a file has errors, 2 for bad
+/// arguments.
+fn lint_files(args: &[String]) -> i32 {
+    const USAGE: &str = "Usage: bccl lint [--allow LIST] [--config FILE] FILE...";
+    let mut config = LintConfig::new();
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--allow" => {
+                let Some(list) = args.next() else {
+                    eprintln!("{}", USAGE);
+                    return 2;
+                };
+                for name in list.split(',').map(str::trim) {
+                    match LintRule::from_name(name) {
+                        Some(rule) => config = config.allow(rule),
+                        None => {
+                            eprintln!("Unknown lint rule '{}'. Use one of: {}", name, lint_rule_names());
+                            return 2;
+                        }
+                    }
+                }
+            }
+            "--config" => {
+                let Some(path) = args.next() else {
+                    eprintln!("{}", USAGE);
+                    return 2;
+                };
+                let parsed = std::fs::read_to_string(path)
+                    .map_err(|error| error.to_string())
+                    .and_then(|text| LintConfig::parse(&text).map_err(|entry| {
+                        format!("'{}' is not allow(rule) or warn(rule) with rules from: {}", entry, lint_rule_names())
+                    }));
+                match parsed {
+                    Ok(parsed) => config = parsed,
+                    Err(message) => {
+                        eprintln!("{}: {}", path, message);
+                        return 2;
+                    }
+                }
+            }
+            _ => files.push(arg),
+        }
+    }
+    if files.is_empty() {
+        eprintln!("{}", USAGE);
+        return 2;
+    }
+    
+    let mut status = 0;
+    for path in files {
+        let source = match std::fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(error) => {
+                eprintln!("{}: {}", path, error);
+                status = 1;
+                continue;
+            }
+        };
+        match lint_source(&source, &config) {
+            Ok(warnings) => {
+                if !warnings.is_empty() {
+                    eprintln!("{}: {} warning{}", path, warnings.len(), if warnings.len() == 1 { "" } else { "s" });
+                    status = 1;
+                }
+                for warning in warnings {
+                    report_error(warning, &source);
+                }
+            }
+            Err(error) => {
+                eprintln!("{}: not linted because of errors", path);
+                report_error(error, &source);
+                status = 1;
+            }
+        }
+    }
+    status
+}
+
+/// Runs `bccl check`: reports the type errors of each file without running
+/// it. Returns the exit code: 0 if there are none, 1 if a file has errors, 2
+/// for bad arguments.
+fn check_files(args: &[String]) -> i32 {
+    if args.is_empty() || args.iter().any(|arg| arg.starts_with("--")) {
+        eprintln!("Usage: bccl check FILE...");
+        return 2;
+    }
+    
+    let checker = TypeChecker::new();
+    let mut status = 0;
+    for path in args {
+        let source = match std::fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(error) => {
+                eprintln!("{}: {}", path, error);
+                status = 1;
+                continue;
+            }
+        };
+        let result = Lexer::new(&source).tokenize()
+            .and_then(|tokens| Parser::new(tokens).parse())
+            .and_then(|program| checker.check(&program));
+        if let Err(error) = result {
+            let count = error_count(&error);
+            eprintln!("{}: {} error{}", path, count, if count == 1 { "" } else { "s" });
+            report_error(error, &source);
+            status = 1;
+        }
+    }
+    status
+}
+
+fn error_count(error: &BcclError) -> usize {
+    match error {
+        BcclError::Multiple { errors } => errors.len(),
+        _ => 1,
+    }
+}
+
+fn lint_rule_names() -> String {
+    LintRule::ALL.iter().map(LintRule::name).collect::<Vec<_>>().join(", ")
+}
+
+/// Runs `bccl --dump-tokens` or `bccl --dump-ast`: prints the tokens or the
+/// S-expression tree of `path`, or of standard input when there is none.
+/// Returns the exit code: 0 on success, 1 if the input has errors.
+fn dump(mode: &str, path: Option<&String>) -> i32 {
+    let source = match path {
+        Some(path) => std::fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error)),
+        None => io::read_to_string(io::stdin()).map_err(|error| format!("Error reading input: {}", error)),
+    };
+    let source = match source {
+        Ok(source) => source,
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    };
+    let result = if mode == "--dump-tokens" {
+        show_tokens(&source)
+    } else {
+        Lexer::new(&source).tokenize()
+            .and_then(|tokens| Parser::new(tokens).parse())
+            .map(|program| println!("{}", program.to_sexp()))
+    };
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            report_error(error, &source);
+            1
+        }
+    }
+}
+
+fn report_error(error: BcclError, source: &str) {
+    // Create error context for rich diagnostics
+    let context = ErrorContext::new(source.to_string());
+    
+    // Use miette to display rich error diagnostics with proper formatting
+    let report = miette::Report::new(error).with_source_code(context.source);
+    
+    // Configure miette for consistent output regardless of execution context
+    // Always use ASCII theme for consistent display across different execution contexts
+    let theme = GraphicalTheme::ascii();
+    
+    let handler = GraphicalReportHandler::new_themed(theme);
+    let mut output = String::new();
+    match handler.render_report(&mut output, report.as_ref()) {
+        Ok(()) => {
+            // Strip ANSI color codes for consistent output
+            let clean_output = strip_ansi_codes(&output);
+            eprint!("{}", clean_output);
+        }
+        Err(_) => {
+            // Simple fallback that should always work
+            eprintln!("{}", report);
+        }
+    }
+}
+
+/// Writes the session's snapshot to `path`, returning the number of variables.
+fn save_session(path: &str, evaluator: &Evaluator) -> bccl::BcclResult<usize> {
+    let snapshot = evaluator.snapshot();
+    std::fs::write(path, snapshot.encode()?).map_err(|error| BcclError::snapshot_error(
+        &format!("could not write '{}': {}", path, error),
+        "Check that the directory exists and is writable",
+    ))?;
+    Ok(snapshot.variables.len())
+}
+
+/// Replaces the session's variables with the snapshot in `path`, returning the
+/// number of variables loaded.
+fn load_session(path: &str, evaluator: &mut Evaluator) -> bccl::BcclResult<usize> {
+    let text = std::fs::read_to_string(path).map_err(|error| BcclError::snapshot_error(
+        &format!("could not read '{}': {}", path, error),
+        "Check the path; snapshots are written with :save",
+    ))?;
+    let snapshot = Snapshot::decode(&text)?;
+    evaluator.restore(&snapshot)?;
+    Ok(snapshot.variables.len())
+}
+
 fn evaluate_input(input: &str, evaluator: &mut Evaluator) -> bccl::BcclResult<()> {
     // Tokenize
     let mut lexer = Lexer::new(input);
@@ -104,10 +486,46 @@ fn evaluate_input(input: &str, evaluator: &mut Evaluator) -> bccl::BcclResult<()
     let program = parser.parse()?;
     
     // Evaluate
-    match evaluator.evaluate_program(&program)? {
-        Some(value) => println!("{}", value.display()),
-        None => {}
+    if let Some(value) = evaluator.evaluate_program(&program)? {
+        println!("{}", value.display());
+    }
+    
+    Ok(())
+}
+
+/// The argument of a REPL command: `Some("")` for the bare command,
+/// `Some(argument)` when a space follows it, and `None` for any other input,
+/// so `:astral` is not `:ast`.
+fn command_argument<'a>(input: &'a str, command: &str) -> Option<&'a str> {
+    match input.strip_prefix(command)? {
+        "" => Some(""),
+        rest if rest.starts_with(char::is_whitespace) => Some(rest.trim()),
+        _ => None,
+    }
+}
+
+/// Prints one token per line with its character span.
+fn show_tokens(input: &str) -> bccl::BcclResult<()> {
+    let mut lexer = Lexer::new(input);
+    for token in lexer.tokenize()? {
+        println!("{:>4}..{:<4} {:?}", token.span.start, token.span.end, token.token_type);
+    }
+    Ok(())
+}
+
+fn show_ast(input: &str, evaluator: &Evaluator) -> bccl::BcclResult<()> {
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize()?;
+    let mut parser = Parser::new(tokens);
+    let mut program = parser.parse()?;
+    
+    // Show the optimized tree unless the optimizer is off, as source and
+    // as an S-expression
+    if evaluator.optimize_enabled() {
+        program = evaluator.optimize(&program);
     }
+    println!("{}", program);
+    println!("{}", program.to_sexp());
     
     Ok(())
 }
@@ -117,7 +535,12 @@ fn show_help() {
     println!("  :help     - Show this help message");
     println!("  :vars     - Show all defined variables");
     println!("  :clear    - Clear all variables");
+    println!("  :save     - Save all variables to a file, e.g. :save session.json");
+    println!("  :load     - Replace all variables with a saved file, e.g. :load session.json");
     println!("  :demo     - Show error formatting examples");
+    println!("  :backend  - Show or set the backend (:backend tree, :backend vm)");
+    println!("  :tokens   - Show the tokens of an input, e.g. :tokens 1 + 2");
+    println!("  :ast      - Show the (optimized) syntax tree of an input, e.g. :ast 1 + 2");
     println!("  :quit     - Exit the interpreter");
     println!("  :exit     - Exit the interpreter");
     println!();
@@ -125,6 +548,7 @@ fn show_help() {
     println!("  Numbers:     42, 3.14, -5");
     println!("  Variables:   x, my_var, _private");
     println!("  Assignment:  x = 10");
+    println!("  Annotation:  x: int = 10   (int, float, bool, str, list, dict, function, any)");
     println!("  Operators:   +, -, *, /");
     println!("  Grouping:    (expression)");
     println!();
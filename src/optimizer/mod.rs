@@ -0,0 +1,199 @@
+//! # Optimizer Module
+//!
+//! A rewriting pass over the AST that runs between parsing and evaluation, on
+//! either backend. It never changes what a program does, including which
+//! errors it reports and where.
+//!
+//! ## Passes
+//!
+//! - **Constant folding**: an operator whose operands are all constant
+//!   (literals, or lists and dictionaries of constants) is computed once and
+//!   replaced by a literal carrying the operator's span, e.g.
+//!   `60 * 60 * 24` → `86400` and `not ("a" < "b")` → `false`
+//! - **Builtin resolution**: a call `max(...)` is marked `builtin` when `max`
+//!   is a registered function that no variable can shadow, so evaluation
+//!   skips the variable lookup
+//!
+//! ## Errors Stay at Runtime
+//!
+//! Folding reuses the evaluator's own `operations`. When an operation fails,
+//! like `1 / 0`, the expression is left as written so that the error is
+//! raised when (and if) it is evaluated, with its original span and after
+//! any earlier errors in the program.
+//!
+//! ## Limits
+//!
+//! Folding must not hide work that `EvaluatorLimits` would have stopped, so
+//! the optimizer takes the evaluator's limits (`with_limits`):
+//!
+//! - A constant whose string or collection is over `max_string_length` or
+//!   `max_collection_size`, as an operand or a result, is not folded; the
+//!   expression raises `LimitExceeded` at runtime as written
+//! - With `max_total_elements` set, lists and dictionaries are not folded
+//!   through, since each one counts towards the run's total
+//! - With `max_steps` set, nothing is folded, since a folded expression takes
+//!   fewer steps than the one it replaces
+//!
+//! ## Dead Branches
+//!
+//! BCCL has no conditionals yet, and `and`/`or` evaluate both operands, so
+//! there are no branches to drop: `false and x` still looks up `x`. An
+//! `and`/`or` is folded only when both sides are constant.
+
+use std::collections::HashSet;
+use crate::parser::{Expr, Program, VisitorMut, walk_expr_mut};
+use crate::evaluator::{EvaluatorLimits, Value};
+use crate::evaluator::operations;
+use crate::error::Span;
+
+#[cfg(test)]
+mod tests;
+
+/// Rewrites programs into equivalent, cheaper programs.
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::{Lexer, Optimizer, Parser, Expr, Stmt};
+/// let tokens = Lexer::new("2 * 3 + 1").tokenize().unwrap();
+/// let program = Parser::new(tokens).parse().unwrap();
+/// let optimized = Optimizer::new().optimize(&program);
+/// match &optimized.statements[0] {
+///     Stmt::Expression { expr: Expr::Number { value, .. }, .. } => assert_eq!(*value, 7.0),
+///     other => panic!("not folded: {:?}", other),
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Optimizer {
+    /// Names of registered functions that are not currently variables
+    functions: HashSet<String>,
+    /// The limits programs will run under
+    limits: EvaluatorLimits,
+}
+
+impl Optimizer {
+    /// Creates an optimizer that folds constants but resolves no calls.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Sets the registered functions that calls may be resolved to.
+    ///
+    /// Leave out any name that is currently a variable: a variable always
+    /// wins over a function of the same name. `Evaluator::optimize` does this.
+    pub fn with_functions<I: IntoIterator<Item = String>>(mut self, names: I) -> Self {
+        self.functions = names.into_iter().collect();
+        self
+    }
+    
+    /// Sets the limits programs will run under, so that folding never skips
+    /// a check. `Evaluator::optimize` passes the evaluator's own.
+    pub fn with_limits(mut self, limits: EvaluatorLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+    
+    /// Returns an optimized copy of `program`.
+    pub fn optimize(&self, program: &Program) -> Program {
+        // A name assigned anywhere in the program may shadow a function by the
+        // time a call runs, so calls to it keep their runtime lookup
+        let assigned = program.assigned_names();
+        let resolvable = self.functions.iter()
+            .map(|name| name.as_str())
+            .filter(|name| !assigned.contains(name))
+            .collect();
+        
+        let mut optimized = program.clone();
+        Folder { resolvable, limits: &self.limits }.visit_program_mut(&mut optimized);
+        optimized
+    }
+}
+
+/// Rewrites a tree bottom-up: each expression's children are optimized before
+/// the expression itself is folded.
+struct Folder<'a> {
+    /// Functions that calls can be resolved to
+    resolvable: HashSet<&'a str>,
+    /// The limits that folded values must stay within
+    limits: &'a EvaluatorLimits,
+}
+
+impl VisitorMut for Folder<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr);
+        if let Expr::FunctionCall { name, builtin, .. } = expr {
+            *builtin = *builtin || self.resolvable.contains(name.as_str());
+        } else if self.limits.max_steps.is_none() && let Some(folded) = self.fold(expr) {
+            *expr = folded;
+        }
+    }
+}
+
+impl Folder<'_> {
+    /// The literal `expr` computes if it is an operator on constants.
+    fn fold(&self, expr: &Expr) -> Option<Expr> {
+        match expr {
+            Expr::Binary { .. } | Expr::Unary { .. } | Expr::Index { .. } => literal(self.constant_value(expr)?, expr.span()),
+            _ => None,
+        }
+    }
+
+    /// The value of a constant expression: a literal, a list or dictionary
+    /// whose elements are all constant, or an operator on constants that
+    /// succeeds. Values the limits would reject are not constant.
+    ///
+    /// Operators that were not replaced by a literal (because they produce a
+    /// collection, e.g. `{"a": [1, 2]}["a"]`) are computed here so that an
+    /// enclosing expression such as `{"a": [1, 2]}["a"][1]` can still fold.
+    fn constant_value(&self, expr: &Expr) -> Option<Value> {
+        let value = match expr {
+            Expr::Binary { left, operator, right, span } => {
+                operations::binary(operator, self.constant_value(left)?, left.span(), self.constant_value(right)?, right.span(), *span).ok()?
+            }
+            Expr::Unary { operator, operand, .. } => {
+                operations::unary(operator, self.constant_value(operand)?, operand.span()).ok()?
+            }
+            Expr::Index { object, index, span } => {
+                operations::index(&self.constant_value(object)?, object.span(), &self.constant_value(index)?, index.span(), *span).ok()?
+            }
+            Expr::Number { value, .. } => Value::Number(*value),
+            Expr::Integer { value, .. } => Value::Integer(*value),
+            Expr::Boolean { value, .. } => Value::Boolean(*value),
+            Expr::String { value, .. } => Value::string(value.clone()),
+            Expr::List { elements, .. } => elements.iter()
+                .map(|element| self.constant_value(element))
+                .collect::<Option<Vec<_>>>()
+                .map(Value::list)?,
+            Expr::Dictionary { pairs, .. } => pairs.iter()
+                .map(|(key, value)| self.constant_value(value).map(|v| (key.clone(), v)))
+                .collect::<Option<_>>()
+                .map(Value::dict)?,
+            _ => return None,
+        };
+        self.within_limits(&value).then_some(value)
+    }
+
+    /// Whether creating `value` at runtime would pass the size checks of
+    /// `Usage::allocate` whatever else the run allocates.
+    fn within_limits(&self, value: &Value) -> bool {
+        let size = match value {
+            Value::String(s) => return self.limits.max_string_length.is_none_or(|max| s.len() <= max),
+            Value::List(items) => items.len(),
+            Value::Dictionary(dict) => dict.len(),
+            _ => return true,
+        };
+        self.limits.max_total_elements.is_none() && self.limits.max_collection_size.is_none_or(|max| size <= max)
+    }
+}
+
+/// The literal expression for a folded scalar. Collections and functions
+/// have no literal form, so expressions producing them are left unfolded.
+fn literal(value: Value, span: Span) -> Option<Expr> {
+    match value {
+        Value::Number(value) => Some(Expr::Number { value, span }),
+        Value::Integer(value) => Some(Expr::Integer { value, span }),
+        Value::Boolean(value) => Some(Expr::Boolean { value, span }),
+        Value::String(value) => Some(Expr::String { value: value.to_string(), span }),
+        Value::List(_) | Value::Dictionary(_) | Value::Function(_) => None,
+    }
+}
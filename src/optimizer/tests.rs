@@ -0,0 +1,81 @@
+use super::*;
+use crate::{Lexer, Parser, Evaluator, Stmt};
+
+fn parse(code: &str) -> Program {
+    let tokens = Lexer::new(code).tokenize().unwrap();
+    Parser::new(tokens).parse().unwrap()
+}
+
+/// The first statement's expression after optimizing with the builtins registered.
+fn optimized(code: &str) -> Expr {
+    let program = Evaluator::new().optimize(&parse(code));
+    match &program.statements[0] {
+        Stmt::Expression { expr, .. } | Stmt::Assignment { value: expr, .. } => expr.clone(),
+        Stmt::CompoundAssignment { value, .. } => value.clone(),
+    }
+}
+
+#[test]
+fn test_folds_arithmetic() {
+    match optimized("60 * 60 * 24") {
+        Expr::Number { value, span } => {
+            assert_eq!(value, 86400.0);
+            assert_eq!(span, Span::new(0, 12));
+        }
+        other => panic!("Expected folded number, got {:?}", other),
+    }
+    assert!(matches!(optimized("-(2 + 3)"), Expr::Number { value, .. } if value == -5.0));
+}
+
+#[test]
+fn test_folds_booleans_strings_and_collections() {
+    assert!(matches!(optimized("not (\"a\" < \"b\")"), Expr::Boolean { value: false, .. }));
+    assert!(matches!(optimized("\"x\" == \"x\" and 1"), Expr::Integer { value: 1, .. }));
+    assert!(matches!(optimized("2 in [1, 2, 3]"), Expr::Boolean { value: true, .. }));
+    assert!(matches!(optimized("{\"a\": [1, 2]}[\"a\"][1]"), Expr::Integer { value: 2, .. }));
+}
+
+#[test]
+fn test_leaves_failing_and_non_constant_expressions() {
+    // Errors are raised at runtime, with the original spans
+    assert!(matches!(optimized("1 / 0"), Expr::Binary { .. }));
+    assert!(matches!(optimized("[1][5]"), Expr::Index { .. }));
+    assert!(matches!(optimized("-\"x\""), Expr::Unary { .. }));
+    // Variables are not constant, but constant operands inside are folded
+    match optimized("x + 2 * 3") {
+        Expr::Binary { right, .. } => assert!(matches!(*right, Expr::Number { value, .. } if value == 6.0)),
+        other => panic!("Expected binary, got {:?}", other),
+    }
+    // Both sides of `and` are always evaluated, so nothing is dropped
+    assert!(matches!(optimized("false and x"), Expr::Binary { .. }));
+    // Collections have no literal form
+    assert!(matches!(optimized("[1, 2] or 0"), Expr::Binary { .. }));
+}
+
+#[test]
+fn test_resolves_builtin_calls() {
+    assert!(matches!(optimized("max(1, 2)"), Expr::FunctionCall { builtin: true, .. }));
+    assert!(matches!(optimized("nope(1)"), Expr::FunctionCall { builtin: false, .. }));
+    // A name assigned anywhere in the program keeps its runtime lookup
+    assert!(matches!(optimized("max(1, 2); max = str; max(3)"), Expr::FunctionCall { builtin: false, .. }));
+
+    // So does a name that is currently a variable
+    let mut evaluator = Evaluator::new();
+    evaluator.evaluate_program(&parse("min = max")).unwrap();
+    let program = evaluator.optimize(&parse("min(1, 2)"));
+    match &program.statements[0] {
+        Stmt::Expression { expr, .. } => assert!(matches!(expr, Expr::FunctionCall { builtin: false, .. })),
+        other => panic!("Expected expression, got {:?}", other),
+    }
+    assert_eq!(evaluator.evaluate_program(&parse("min(1, 2)")).unwrap().unwrap().display(), "2");
+}
+
+#[test]
+fn test_runtime_error_keeps_original_span() {
+    let mut evaluator = Evaluator::new();
+    let error = evaluator.evaluate_program(&parse("x = 1 + 2; 10 / (x - 3)")).unwrap_err();
+    match error {
+        crate::BcclError::DivisionByZero { span, .. } => assert_eq!(span.offset(), 11),
+        other => panic!("Expected DivisionByZero, got {:?}", other),
+    }
+}
@@ -1,12 +1,18 @@
 use crate::error::Span;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expr {
     Number { value: f64, span: Span },
     Integer { value: i64, span: Span },
     Boolean { value: bool, span: Span },
     String { value: String, span: Span },
-    Identifier { name: String, span: Span },
+    Identifier {
+        name: String,
+        span: Span,
+        /// Storage slot assigned by the resolver; `None` means look up by name
+        slot: Option<Slot>,
+    },
     Binary {
         left: Box<Expr>,
         operator: BinaryOp,
@@ -20,8 +26,20 @@ pub enum Expr {
     },
     FunctionCall {
         name: String,
+        /// Positional arguments; `*xs` appears as `Expr::Unpack`
         args: Vec<Expr>,
-        kwargs: Vec<(String, Expr)>,
+        /// Keyword arguments; `**d` appears with no name
+        kwargs: Vec<(Option<String>, Expr)>,
+        span: Span,
+        /// Set by the optimizer when `name` can only refer to a registered
+        /// function, so the variable lookup can be skipped
+        builtin: bool,
+        /// Slot of a variable named `name`, assigned by the resolver
+        slot: Option<Slot>,
+    },
+    /// `*xs` inside call arguments: spreads a list into positional arguments
+    Unpack {
+        value: Box<Expr>,
         span: Span,
     },
     List {
@@ -50,6 +68,7 @@ impl Expr {
             Expr::Binary { span, .. } => *span,
             Expr::Unary { span, .. } => *span,
             Expr::FunctionCall { span, .. } => *span,
+            Expr::Unpack { span, .. } => *span,
             Expr::List { span, .. } => *span,
             Expr::Dictionary { span, .. } => *span,
             Expr::Index { span, .. } => *span,
@@ -67,7 +86,8 @@ impl Stmt {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BinaryOp {
     // Arithmetic
     Add,
@@ -92,26 +112,60 @@ pub enum BinaryOp {
     NotIn,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnaryOp {
     Minus,
     Plus,
     Not,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Stmt {
     Expression { expr: Expr, span: Span },
-    Assignment { name: String, value: Expr, span: Span },
+    Assignment {
+        name: String,
+        value: Expr,
+        span: Span,
+        slot: Option<Slot>,
+        /// The declared type in `name: type = value`; only the `TypeChecker`
+        /// looks at it
+        annotation: Option<TypeAnnotation>,
+    },
     CompoundAssignment { 
         name: String, 
         operator: CompoundOp, 
         value: Expr, 
-        span: Span 
+        span: Span,
+        slot: Option<Slot>,
     },
 }
 
-#[derive(Debug, Clone)]
+/// A type written in source, e.g. `int` in `x: int = 3`. The name is not
+/// checked by the parser: the `TypeChecker` reports names it does not know.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TypeAnnotation {
+    pub name: String,
+    pub span: Span,
+}
+
+/// Where a variable is stored, as assigned by the `Resolver`.
+/// 
+/// `depth` counts environments outward from the one being executed and
+/// `index` is the position within that environment. BCCL has a single scope,
+/// so `depth` is 0 except for reads of shared globals, which live in a base
+/// environment (`Environment::with_base`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Slot {
+    pub depth: u32,
+    pub index: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CompoundOp {
     Add,
     Subtract,
@@ -119,7 +173,8 @@ pub enum CompoundOp {
     Divide,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Program {
     pub statements: Vec<Stmt>,
 }
\ No newline at end of file
@@ -1,112 +1,91 @@
-use crate::lexer::{Token, TokenType};
-use crate::error::{BcclError, BcclResult, Span};
+use crate::lexer::TokenType;
+use crate::error::{BcclError, BcclResult};
+use crate::syntax::{lower_expression, SyntaxKind, SyntaxNode};
 use super::ast::Expr;
-use super::Parser;
+use super::{fail, push, NodeResult, Parser};
 
 impl Parser {
+    /// Parses a list literal, `[a, b, ...]`.
     pub fn parse_list(&mut self) -> BcclResult<Expr> {
-        let left_bracket = self.expect_token(TokenType::LeftBracket)?;
-        let start_span = left_bracket.span;
-        
-        let mut elements = Vec::new();
-        
-        // Handle empty list
-        if let Some(Token { token_type: TokenType::RightBracket, .. }) = self.current_token() {
-            let right_bracket = self.expect_token(TokenType::RightBracket)?;
-            let span = start_span.combine(&right_bracket.span);
-            return Ok(Expr::List { elements, span });
-        }
-        
-        // Parse list elements
-        loop {
-            elements.push(self.parse_expression()?);
-            
-            match self.current_token() {
-                Some(Token { token_type: TokenType::Comma, .. }) => {
-                    self.advance();
-                    continue;
-                }
-                Some(Token { token_type: TokenType::RightBracket, .. }) => break,
-                Some(token) => {
-                    let found_str = self.token_type_name(&token.token_type);
-                    return Err(BcclError::unexpected_token(&found_str, token.span, 
-                        vec![",".to_string(), "]".to_string()]));
-                }
-                None => {
-                    return Err(BcclError::unexpected_eof(start_span, 
-                        vec![",".to_string(), "]".to_string()]));
+        self.list().map(|node| lower_expression(&node)).map_err(|failure| *failure.error)
+    }
+
+    /// Parses a dictionary literal, `{"key": value, ...}`.
+    pub fn parse_dictionary(&mut self) -> BcclResult<Expr> {
+        self.dictionary().map(|node| lower_expression(&node)).map_err(|failure| *failure.error)
+    }
+
+    pub(super) fn list(&mut self) -> NodeResult {
+        let mut children = Vec::new();
+        self.expect_token(&mut children, TokenType::LeftBracket)?;
+
+        // Parse list elements, unless the list is empty
+        if !matches!(self.current_kind(), TokenType::RightBracket) {
+            loop {
+                let element = self.expression();
+                push(&mut children, element)?;
+
+                let token = self.current_token();
+                match &token.kind {
+                    TokenType::Comma => children.push(self.bump()),
+                    TokenType::RightBracket => break,
+                    kind => {
+                        let found_str = self.token_type_name(kind);
+                        let error = BcclError::unexpected_token(&found_str, token.span,
+                            vec![",".to_string(), "]".to_string()]);
+                        return Err(fail(&mut children, error));
+                    }
                 }
             }
         }
-        
-        let right_bracket = self.expect_token(TokenType::RightBracket)?;
-        let span = start_span.combine(&right_bracket.span);
-        
-        Ok(Expr::List { elements, span })
+
+        self.expect_token(&mut children, TokenType::RightBracket)?;
+        Ok(SyntaxNode::new(SyntaxKind::List, children))
     }
-    
-    pub fn parse_dictionary(&mut self) -> BcclResult<Expr> {
-        let left_brace = self.expect_token(TokenType::LeftBrace)?;
-        let start_span = left_brace.span;
-        
-        let mut pairs = Vec::new();
-        
-        // Handle empty dictionary
-        if let Some(Token { token_type: TokenType::RightBrace, .. }) = self.current_token() {
-            let right_brace = self.expect_token(TokenType::RightBrace)?;
-            let span = start_span.combine(&right_brace.span);
-            return Ok(Expr::Dictionary { pairs, span });
-        }
-        
-        // Parse dictionary key-value pairs
-        loop {
-            // Parse key (must be a string)
-            let key = match self.current_token() {
-                Some(Token { token_type: TokenType::String(s), .. }) => {
-                    let key = s.clone();
-                    self.advance();
-                    key
-                }
-                Some(token) => {
-                    let found_str = self.token_type_name(&token.token_type);
-                    return Err(BcclError::unexpected_token(&found_str, token.span, 
-                        vec!["string".to_string()]));
-                }
-                None => {
-                    return Err(BcclError::unexpected_eof(start_span, 
-                        vec!["string".to_string()]));
-                }
-            };
-            
-            // Expect colon
-            self.expect_token(TokenType::Colon)?;
-            
-            // Parse value
-            let value = self.parse_expression()?;
-            
-            pairs.push((key, value));
-            
-            match self.current_token() {
-                Some(Token { token_type: TokenType::Comma, .. }) => {
-                    self.advance();
-                    continue;
-                }
-                Some(Token { token_type: TokenType::RightBrace, .. }) => break,
-                Some(token) => {
-                    let found_str = self.token_type_name(&token.token_type);
-                    return Err(BcclError::unexpected_token(&found_str, token.span, 
-                        vec![",".to_string(), "}".to_string()]));
-                }
-                None => {
-                    return Err(BcclError::unexpected_eof(start_span, 
-                        vec![",".to_string(), "}".to_string()]));
+
+    pub(super) fn dictionary(&mut self) -> NodeResult {
+        let mut children = Vec::new();
+        self.expect_token(&mut children, TokenType::LeftBrace)?;
+
+        // Parse dictionary key-value pairs, unless the dictionary is empty
+        if !matches!(self.current_kind(), TokenType::RightBrace) {
+            loop {
+                let entry = self.dict_entry();
+                push(&mut children, entry)?;
+
+                let token = self.current_token();
+                match &token.kind {
+                    TokenType::Comma => children.push(self.bump()),
+                    TokenType::RightBrace => break,
+                    kind => {
+                        let found_str = self.token_type_name(kind);
+                        let error = BcclError::unexpected_token(&found_str, token.span,
+                            vec![",".to_string(), "}".to_string()]);
+                        return Err(fail(&mut children, error));
+                    }
                 }
             }
         }
-        
-        let right_brace = self.expect_token(TokenType::RightBrace)?;
-        let span = start_span.combine(&right_brace.span);
-        
-        Ok(Expr::Dictionary { pairs, span })
+
+        self.expect_token(&mut children, TokenType::RightBrace)?;
+        Ok(SyntaxNode::new(SyntaxKind::Dictionary, children))
+    }
+
+    /// `"key": value`; keys must be strings.
+    fn dict_entry(&mut self) -> NodeResult {
+        let mut children = Vec::new();
+        let token = self.current_token();
+        if !matches!(token.kind, TokenType::String(_)) {
+            let found_str = self.token_type_name(&token.kind);
+            let error = BcclError::unexpected_token(&found_str, token.span, vec!["string".to_string()]);
+            return Err(fail(&mut children, error));
+        }
+        children.push(self.bump());
+
+        self.expect_token(&mut children, TokenType::Colon)?;
+        let value = self.expression();
+        push(&mut children, value)?;
+
+        Ok(SyntaxNode::new(SyntaxKind::DictEntry, children))
     }
-}
\ No newline at end of file
+}
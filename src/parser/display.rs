@@ -0,0 +1,311 @@
+//! Printing the AST back out: `Display` gives BCCL source with only the
+//! parentheses the grammar needs, and `to_sexp` gives an S-expression dump for
+//! debugging. Neither shows spans or resolver slots.
+//!
+//! Printed source parses back to the same tree, spans aside, with one
+//! exception: the optimizer can fold `-3` into a negative literal, which has
+//! no syntax of its own and prints as the negation `-3`.
+
+use std::fmt;
+use super::ast::{BinaryOp, CompoundOp, Expr, Program, Stmt, UnaryOp};
+
+/// Binding strength of an operator, lowest first; operands of unary operators
+/// and indexed objects must be `POSTFIX`.
+const UNARY: u8 = 8;
+const POSTFIX: u8 = 9;
+
+impl BinaryOp {
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::And => 2,
+            BinaryOp::Equal | BinaryOp::NotEqual => 3,
+            BinaryOp::Less | BinaryOp::Greater | BinaryOp::LessEqual | BinaryOp::GreaterEqual => 4,
+            BinaryOp::In | BinaryOp::NotIn => 5,
+            BinaryOp::Add | BinaryOp::Subtract => 6,
+            BinaryOp::Multiply | BinaryOp::Divide => 7,
+        }
+    }
+
+    /// The operator as written in source.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Subtract => "-",
+            BinaryOp::Multiply => "*",
+            BinaryOp::Divide => "/",
+            BinaryOp::Equal => "==",
+            BinaryOp::NotEqual => "!=",
+            BinaryOp::Less => "<",
+            BinaryOp::Greater => ">",
+            BinaryOp::LessEqual => "<=",
+            BinaryOp::GreaterEqual => ">=",
+            BinaryOp::And => "and",
+            BinaryOp::Or => "or",
+            BinaryOp::In => "in",
+            BinaryOp::NotIn => "not in",
+        }
+    }
+}
+
+impl UnaryOp {
+    /// The operator as written in source.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            UnaryOp::Minus => "-",
+            UnaryOp::Plus => "+",
+            UnaryOp::Not => "not",
+        }
+    }
+}
+
+impl CompoundOp {
+    /// The operator as written in source, e.g. `+=`.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            CompoundOp::Add => "+=",
+            CompoundOp::Subtract => "-=",
+            CompoundOp::Multiply => "*=",
+            CompoundOp::Divide => "/=",
+        }
+    }
+}
+
+impl Expr {
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary { operator, .. } => operator.precedence(),
+            Expr::Unary { .. } => UNARY,
+            // Printed with a leading `-`, so they bind like a negation
+            Expr::Number { value, .. } if value.is_sign_negative() => UNARY,
+            Expr::Integer { value, .. } if *value < 0 => UNARY,
+            // Only found in call arguments, where anything goes
+            Expr::Unpack { .. } => 0,
+            _ => POSTFIX,
+        }
+    }
+
+    /// Writes `self`, in parentheses if it binds looser than `min`.
+    fn write(&self, f: &mut fmt::Formatter<'_>, min: u8) -> fmt::Result {
+        if self.precedence() < min {
+            write!(f, "(")?;
+            self.write(f, 0)?;
+            return write!(f, ")");
+        }
+        match self {
+            Expr::Number { value, .. } => write!(f, "{}", number_literal(*value)),
+            Expr::Integer { value, .. } => write!(f, "{}", value),
+            Expr::Boolean { value, .. } => write!(f, "{}", value),
+            Expr::String { value, .. } => write!(f, "{}", string_literal(value)),
+            Expr::Identifier { name, .. } => write!(f, "{}", name),
+            // Operators are left-associative, so a right operand of the same
+            // precedence needs parentheses
+            Expr::Binary { left, operator, right, .. } => {
+                left.write(f, operator.precedence())?;
+                write!(f, " {} ", operator.symbol())?;
+                right.write(f, operator.precedence() + 1)
+            }
+            Expr::Unary { operator, operand, .. } => {
+                write!(f, "{}", operator.symbol())?;
+                if *operator == UnaryOp::Not {
+                    write!(f, " ")?;
+                }
+                operand.write(f, POSTFIX)
+            }
+            Expr::FunctionCall { name, args, kwargs, .. } => {
+                write!(f, "{}(", name)?;
+                let mut first = true;
+                for arg in args {
+                    separate(f, &mut first)?;
+                    arg.write(f, 0)?;
+                }
+                for (name, value) in kwargs {
+                    separate(f, &mut first)?;
+                    match name {
+                        Some(name) => write!(f, "{}=", name)?,
+                        None => write!(f, "**")?,
+                    }
+                    value.write(f, 0)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Unpack { value, .. } => {
+                write!(f, "*")?;
+                value.write(f, 0)
+            }
+            Expr::List { elements, .. } => {
+                write!(f, "[")?;
+                let mut first = true;
+                for element in elements {
+                    separate(f, &mut first)?;
+                    element.write(f, 0)?;
+                }
+                write!(f, "]")
+            }
+            Expr::Dictionary { pairs, .. } => {
+                write!(f, "{{")?;
+                let mut first = true;
+                for (key, value) in pairs {
+                    separate(f, &mut first)?;
+                    write!(f, "{}: ", string_literal(key))?;
+                    value.write(f, 0)?;
+                }
+                write!(f, "}}")
+            }
+            Expr::Index { object, index, .. } => {
+                object.write(f, POSTFIX)?;
+                write!(f, "[")?;
+                index.write(f, 0)?;
+                write!(f, "]")
+            }
+        }
+    }
+
+    /// The expression as an S-expression, e.g. `(+ 1 (* x 2))`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bccl::{Lexer, Parser, Stmt};
+    /// let program = Parser::new(Lexer::new("max(*xs, default=-1)[0]").tokenize().unwrap()).parse().unwrap();
+    /// let Stmt::Expression { expr, .. } = &program.statements[0] else { unreachable!() };
+    /// assert_eq!(expr.to_sexp(), "(index (call max (unpack xs) (kw default (- 1))) 0)");
+    /// ```
+    pub fn to_sexp(&self) -> String {
+        match self {
+            Expr::Number { value, .. } => number_literal(*value),
+            Expr::Integer { value, .. } => value.to_string(),
+            Expr::Boolean { value, .. } => value.to_string(),
+            Expr::String { value, .. } => string_literal(value),
+            Expr::Identifier { name, .. } => name.clone(),
+            Expr::Binary { left, operator, right, .. } => {
+                let operator = if *operator == BinaryOp::NotIn { "not-in" } else { operator.symbol() };
+                format!("({} {} {})", operator, left.to_sexp(), right.to_sexp())
+            }
+            Expr::Unary { operator, operand, .. } => format!("({} {})", operator.symbol(), operand.to_sexp()),
+            Expr::FunctionCall { name, args, kwargs, .. } => {
+                let mut parts = vec!["call".to_string(), name.clone()];
+                parts.extend(args.iter().map(Expr::to_sexp));
+                parts.extend(kwargs.iter().map(|(name, value)| match name {
+                    Some(name) => format!("(kw {} {})", name, value.to_sexp()),
+                    None => format!("(unpack-kw {})", value.to_sexp()),
+                }));
+                format!("({})", parts.join(" "))
+            }
+            Expr::Unpack { value, .. } => format!("(unpack {})", value.to_sexp()),
+            Expr::List { elements, .. } => {
+                let mut parts = vec!["list".to_string()];
+                parts.extend(elements.iter().map(Expr::to_sexp));
+                format!("({})", parts.join(" "))
+            }
+            Expr::Dictionary { pairs, .. } => {
+                let mut parts = vec!["dict".to_string()];
+                parts.extend(pairs.iter().map(|(key, value)| format!("({} {})", string_literal(key), value.to_sexp())));
+                format!("({})", parts.join(" "))
+            }
+            Expr::Index { object, index, .. } => format!("(index {} {})", object.to_sexp(), index.to_sexp()),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+impl Stmt {
+    /// The statement as an S-expression: `(= x 1)`, `(= (: x int) 1)`,
+    /// `(+= x 1)`, or the expression's own.
+    pub fn to_sexp(&self) -> String {
+        match self {
+            Stmt::Expression { expr, .. } => expr.to_sexp(),
+            Stmt::Assignment { name, value, annotation: Some(annotation), .. } => {
+                format!("(= (: {} {}) {})", name, annotation.name, value.to_sexp())
+            }
+            Stmt::Assignment { name, value, .. } => format!("(= {} {})", name, value.to_sexp()),
+            Stmt::CompoundAssignment { name, operator, value, .. } => {
+                format!("({} {} {})", operator.symbol(), name, value.to_sexp())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Stmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stmt::Expression { expr, .. } => write!(f, "{}", expr),
+            Stmt::Assignment { name, value, annotation: Some(annotation), .. } => {
+                write!(f, "{}: {} = {}", name, annotation.name, value)
+            }
+            Stmt::Assignment { name, value, .. } => write!(f, "{} = {}", name, value),
+            Stmt::CompoundAssignment { name, operator, value, .. } => write!(f, "{} {} {}", name, operator.symbol(), value),
+        }
+    }
+}
+
+impl Program {
+    /// One S-expression per statement, one per line.
+    pub fn to_sexp(&self) -> String {
+        self.statements.iter().map(Stmt::to_sexp).collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// One statement per line.
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::{Lexer, Parser};
+/// let program = Parser::new(Lexer::new("x = ((1 + 2)) * 3; y=-(-x)[0]").tokenize().unwrap()).parse().unwrap();
+/// assert_eq!(program.to_string(), "x = (1 + 2) * 3\ny = -(-x)[0]");
+/// ```
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, statement) in self.statements.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", statement)?;
+        }
+        Ok(())
+    }
+}
+
+fn separate(f: &mut fmt::Formatter<'_>, first: &mut bool) -> fmt::Result {
+    if !std::mem::take(first) {
+        write!(f, ", ")?;
+    }
+    Ok(())
+}
+
+/// A number as the lexer reads it back: always with a `.` so it stays a
+/// number rather than an integer, and never with an exponent, which the lexer
+/// does not read.
+fn number_literal(value: f64) -> String {
+    let shortest = format!("{:?}", value);
+    if !value.is_finite() || !shortest.contains('e') {
+        return shortest;
+    }
+    let mut plain = format!("{}", value);
+    if !plain.contains('.') {
+        plain.push_str(".0");
+    }
+    plain
+}
+
+fn string_literal(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -1,414 +1,188 @@
-use crate::lexer::{Token, TokenType};
+use crate::lexer::TokenType;
 use crate::error::{BcclError, BcclResult, Span};
-use super::ast::{Expr, BinaryOp, UnaryOp};
-use super::Parser;
+use crate::syntax::{lower_expression, SyntaxElement, SyntaxKind, SyntaxNode};
+use super::ast::Expr;
+use super::{fail, push, NodeResult, Parser};
+
+/// Number of binary precedence levels handled by `Parser::binary`
+const BINARY_LEVELS: usize = 7;
 
 impl Parser {
+    /// Parses one expression.
     pub fn parse_expression(&mut self) -> BcclResult<Expr> {
-        self.parse_logical_or()
-    }
-    
-    pub fn parse_logical_or(&mut self) -> BcclResult<Expr> {
-        let mut left = self.parse_logical_and()?;
-        
-        while let Some(token) = self.current_token() {
-            let op = match token.token_type {
-                TokenType::Or => BinaryOp::Or,
-                _ => break,
-            };
-            
-            self.advance();
-            let right = self.parse_logical_and()?;
-            let span = left.span().combine(&right.span());
-            
-            left = Expr::Binary {
-                left: Box::new(left),
-                operator: op,
-                right: Box::new(right),
-                span,
-            };
-        }
-        
-        Ok(left)
+        self.expression().map(|node| lower_expression(&node)).map_err(|failure| *failure.error)
     }
-    
-    pub fn parse_logical_and(&mut self) -> BcclResult<Expr> {
-        let mut left = self.parse_equality()?;
-        
-        while let Some(token) = self.current_token() {
-            let op = match token.token_type {
-                TokenType::And => BinaryOp::And,
-                _ => break,
-            };
-            
-            self.advance();
-            let right = self.parse_equality()?;
-            let span = left.span().combine(&right.span());
-            
-            left = Expr::Binary {
-                left: Box::new(left),
-                operator: op,
-                right: Box::new(right),
-                span,
-            };
-        }
-        
-        Ok(left)
-    }
-    
-    pub fn parse_equality(&mut self) -> BcclResult<Expr> {
-        let mut left = self.parse_comparison()?;
-        
-        while let Some(token) = self.current_token() {
-            let op = match token.token_type {
-                TokenType::Equal => BinaryOp::Equal,
-                TokenType::NotEqual => BinaryOp::NotEqual,
-                _ => break,
-            };
-            
-            self.advance();
-            let right = self.parse_comparison()?;
-            let span = left.span().combine(&right.span());
-            
-            left = Expr::Binary {
-                left: Box::new(left),
-                operator: op,
-                right: Box::new(right),
-                span,
-            };
-        }
-        
-        Ok(left)
+
+    pub(super) fn expression(&mut self) -> NodeResult {
+        self.binary(0)
     }
-    
-    pub fn parse_comparison(&mut self) -> BcclResult<Expr> {
-        let mut left = self.parse_membership()?;
-        
-        while let Some(token) = self.current_token() {
-            let op = match token.token_type {
-                TokenType::Less => BinaryOp::Less,
-                TokenType::Greater => BinaryOp::Greater,
-                TokenType::LessEqual => BinaryOp::LessEqual,
-                TokenType::GreaterEqual => BinaryOp::GreaterEqual,
-                _ => break,
-            };
-            
-            self.advance();
-            let right = self.parse_membership()?;
-            let span = left.span().combine(&right.span());
-            
-            left = Expr::Binary {
-                left: Box::new(left),
-                operator: op,
-                right: Box::new(right),
-                span,
-            };
+
+    /// One precedence level of left-associative binary operators, lowest
+    /// first: `or`, `and`, equality, comparison, membership, additive,
+    /// multiplicative.
+    fn binary(&mut self, level: usize) -> NodeResult {
+        if level == BINARY_LEVELS {
+            return self.unary();
         }
-        
-        Ok(left)
-    }
-    
-    pub fn parse_membership(&mut self) -> BcclResult<Expr> {
-        let mut left = self.parse_additive()?;
-        
-        while let Some(token) = self.current_token() {
-            let op = match token.token_type {
-                TokenType::In => BinaryOp::In,
-                TokenType::Not => {
-                    // Check for "not in"
-                    if let Some(next_token) = self.peek_token() {
-                        if matches!(next_token.token_type, TokenType::In) {
-                            self.advance(); // consume "not"
-                            self.advance(); // consume "in"
-                            BinaryOp::NotIn
-                        } else {
-                            break;
-                        }
-                    } else {
-                        break;
-                    }
-                }
-                _ => break,
-            };
-            
-            if !matches!(op, BinaryOp::NotIn) {
-                self.advance();
+        let mut left = self.binary(level + 1)?;
+
+        while let Some(operator_tokens) = self.binary_operator(level) {
+            let mut children = vec![SyntaxElement::Node(left)];
+            for _ in 0..operator_tokens {
+                children.push(self.bump());
             }
-            let right = self.parse_additive()?;
-            let span = left.span().combine(&right.span());
-            
-            left = Expr::Binary {
-                left: Box::new(left),
-                operator: op,
-                right: Box::new(right),
-                span,
-            };
-        }
-        
-        Ok(left)
-    }
-    
-    pub fn parse_additive(&mut self) -> BcclResult<Expr> {
-        let mut left = self.parse_multiplicative()?;
-        
-        while let Some(token) = self.current_token() {
-            let op = match token.token_type {
-                TokenType::Plus => BinaryOp::Add,
-                TokenType::Minus => BinaryOp::Subtract,
-                _ => break,
-            };
-            
-            let op_span = token.span;
-            self.advance();
-            let right = self.parse_multiplicative()?;
-            let span = left.span().combine(&right.span());
-            
-            left = Expr::Binary {
-                left: Box::new(left),
-                operator: op,
-                right: Box::new(right),
-                span,
-            };
+            let right = self.binary(level + 1);
+            push(&mut children, right)?;
+            left = SyntaxNode::new(SyntaxKind::Binary, children);
         }
-        
-        Ok(left)
-    }
-    
-    pub fn parse_multiplicative(&mut self) -> BcclResult<Expr> {
-        let mut left = self.parse_unary()?;
-        
-        while let Some(token) = self.current_token() {
-            let op = match token.token_type {
-                TokenType::Multiply => BinaryOp::Multiply,
-                TokenType::Divide => BinaryOp::Divide,
-                _ => break,
-            };
-            
-            let op_span = token.span;
-            self.advance();
-            let right = self.parse_unary()?;
-            let span = left.span().combine(&right.span());
-            
-            left = Expr::Binary {
-                left: Box::new(left),
-                operator: op,
-                right: Box::new(right),
-                span,
-            };
-        }
-        
+
         Ok(left)
     }
-    
-    pub fn parse_unary(&mut self) -> BcclResult<Expr> {
-        if let Some(token) = self.current_token() {
-            match token.token_type {
-                TokenType::Minus => {
-                    let op_span = token.span;
-                    self.advance();
-                    let operand = self.parse_postfix()?;
-                    let span = op_span.combine(&operand.span());
-                    return Ok(Expr::Unary {
-                        operator: UnaryOp::Minus,
-                        operand: Box::new(operand),
-                        span,
-                    });
-                }
-                TokenType::Plus => {
-                    let op_span = token.span;
-                    self.advance();
-                    let operand = self.parse_postfix()?;
-                    let span = op_span.combine(&operand.span());
-                    return Ok(Expr::Unary {
-                        operator: UnaryOp::Plus,
-                        operand: Box::new(operand),
-                        span,
-                    });
-                }
-                TokenType::Not => {
-                    let op_span = token.span;
-                    self.advance();
-                    let operand = self.parse_postfix()?;
-                    let span = op_span.combine(&operand.span());
-                    return Ok(Expr::Unary {
-                        operator: UnaryOp::Not,
-                        operand: Box::new(operand),
-                        span,
-                    });
+
+    /// How many tokens the binary operator at the current position has, if
+    /// there is one at this precedence level.
+    fn binary_operator(&self, level: usize) -> Option<usize> {
+        let current = self.current_kind();
+        let single = match level {
+            0 => matches!(current, TokenType::Or),
+            1 => matches!(current, TokenType::And),
+            2 => matches!(current, TokenType::Equal | TokenType::NotEqual),
+            3 => matches!(current, TokenType::Less | TokenType::Greater | TokenType::LessEqual | TokenType::GreaterEqual),
+            4 => {
+                // "not in"
+                if matches!(current, TokenType::Not) && matches!(self.peek_kind(), Some(TokenType::In)) {
+                    return Some(2);
                 }
-                _ => {}
+                matches!(current, TokenType::In)
             }
+            5 => matches!(current, TokenType::Plus | TokenType::Minus),
+            _ => matches!(current, TokenType::Multiply | TokenType::Divide),
+        };
+        single.then_some(1)
+    }
+
+    /// `-x`, `+x` and `not x`; the operand cannot itself be unary.
+    fn unary(&mut self) -> NodeResult {
+        if !matches!(self.current_kind(), TokenType::Minus | TokenType::Plus | TokenType::Not) {
+            return self.postfix();
         }
-        
-        self.parse_postfix()
+        let mut children = vec![self.bump()];
+        let operand = self.postfix();
+        push(&mut children, operand)?;
+        Ok(SyntaxNode::new(SyntaxKind::Unary, children))
     }
-    
-    pub fn parse_postfix(&mut self) -> BcclResult<Expr> {
-        let mut expr = self.parse_primary()?;
-        
-        // Handle postfix operations like indexing
-        while let Some(token) = self.current_token() {
-            match token.token_type {
-                TokenType::LeftBracket => {
-                    self.advance(); // consume '['
-                    let index = self.parse_expression()?;
-                    let right_bracket = self.expect_token(TokenType::RightBracket)?;
-                    let span = expr.span().combine(&right_bracket.span);
-                    
-                    expr = Expr::Index {
-                        object: Box::new(expr),
-                        index: Box::new(index),
-                        span,
-                    };
-                }
-                _ => break,
-            }
+
+    /// A primary expression followed by any number of `[index]`.
+    fn postfix(&mut self) -> NodeResult {
+        let mut expr = self.primary()?;
+
+        while matches!(self.current_kind(), TokenType::LeftBracket) {
+            let mut children = vec![SyntaxElement::Node(expr), self.bump()];
+            let index = self.expression();
+            push(&mut children, index)?;
+            self.expect_token(&mut children, TokenType::RightBracket)?;
+            expr = SyntaxNode::new(SyntaxKind::Index, children);
         }
-        
+
         Ok(expr)
     }
-    
-    pub fn parse_primary(&mut self) -> BcclResult<Expr> {
-        match self.current_token() {
-            Some(Token { token_type: TokenType::Number(n), span }) => {
-                let value = *n;
-                let span = *span;
-                self.advance();
-                Ok(Expr::Number { value, span })
-            }
-            Some(Token { token_type: TokenType::Integer(i), span }) => {
-                let value = *i;
-                let span = *span;
-                self.advance();
-                Ok(Expr::Integer { value, span })
-            }
-            Some(Token { token_type: TokenType::Boolean(b), span }) => {
-                let value = *b;
-                let span = *span;
-                self.advance();
-                Ok(Expr::Boolean { value, span })
-            }
-            Some(Token { token_type: TokenType::String(s), span }) => {
-                let value = s.clone();
-                let span = *span;
-                self.advance();
-                Ok(Expr::String { value, span })
-            }
-            Some(Token { token_type: TokenType::Identifier(name), span }) => {
-                let name = name.clone();
-                let start_span = *span;
-                self.advance();
-                
-                // Check if this is a function call
-                if let Some(Token { token_type: TokenType::LeftParen, .. }) = self.current_token() {
-                    self.advance(); // consume '('
-                    
-                    let mut args = Vec::new();
-                    let mut kwargs = Vec::new();
-                    let mut found_kwarg = false;
-                    
-                    // Parse arguments (mix of positional and keyword)
-                    if !matches!(self.current_token(), Some(Token { token_type: TokenType::RightParen, .. })) {
-                        loop {
-                            // Check if this looks like a keyword argument (identifier = expression)
-                            if let Some(Token { token_type: TokenType::Identifier(param_name), .. }) = self.current_token() {
-                                if let Some(Token { token_type: TokenType::Assign, .. }) = self.peek_token() {
-                                    // This is a keyword argument
-                                    found_kwarg = true;
-                                    let param_name = param_name.clone();
-                                    self.advance(); // consume identifier
-                                    self.advance(); // consume '='
-                                    let value = self.parse_expression()?;
-                                    kwargs.push((param_name, value));
-                                } else {
-                                    // This is a positional argument (identifier expression)
-                                    if found_kwarg {
-                                        return Err(BcclError::unexpected_token(
-                                            "positional argument", 
-                                            self.current_token().unwrap().span, 
-                                            vec!["keyword argument".to_string()]
-                                        ));
-                                    }
-                                    args.push(self.parse_expression()?);
-                                }
-                            } else {
-                                // This is a positional argument (non-identifier expression)
-                                if found_kwarg {
-                                    return Err(BcclError::unexpected_token(
-                                        "positional argument", 
-                                        self.current_token().unwrap().span, 
-                                        vec!["keyword argument".to_string()]
-                                    ));
-                                }
-                                args.push(self.parse_expression()?);
-                            }
-                            
-                            match self.current_token() {
-                                Some(Token { token_type: TokenType::Comma, .. }) => {
-                                    self.advance();
-                                    continue;
-                                }
-                                Some(Token { token_type: TokenType::RightParen, .. }) => break,
-                                Some(token) => {
-                                    let found_str = self.token_type_name(&token.token_type);
-                                    return Err(BcclError::unexpected_token(&found_str, token.span, 
-                                        vec![",".to_string(), ")".to_string()]));
-                                }
-                                None => {
-                                    return Err(BcclError::unexpected_eof(start_span, 
-                                        vec![",".to_string(), ")".to_string()]));
-                                }
-                            }
-                        }
-                    }
-                    
-                    let right_paren = self.expect_token(TokenType::RightParen)?;
-                    let span = start_span.combine(&right_paren.span);
-                    
-                    Ok(Expr::FunctionCall { name, args, kwargs, span })
-                } else {
-                    Ok(Expr::Identifier { name, span: start_span })
-                }
-            }
-            Some(Token { token_type: TokenType::LeftParen, span }) => {
-                let left_paren_span = *span;
-                self.advance();
-                let expr = self.parse_expression()?;
-                let right_paren = self.expect_token(TokenType::RightParen)?;
-                let span = left_paren_span.combine(&right_paren.span);
-                Ok(expr)
-            }
-            Some(Token { token_type: TokenType::LeftBracket, span }) => {
-                self.parse_list()
+
+    fn primary(&mut self) -> NodeResult {
+        let token = self.current_token();
+        match &token.kind {
+            TokenType::Number(_) | TokenType::Integer(_) | TokenType::Boolean(_) | TokenType::String(_) => {
+                Ok(SyntaxNode::new(SyntaxKind::Literal, vec![self.bump()]))
             }
-            Some(Token { token_type: TokenType::LeftBrace, span }) => {
-                self.parse_dictionary()
+            TokenType::Identifier(_) if matches!(self.peek_kind(), Some(TokenType::LeftParen)) => self.call(),
+            TokenType::Identifier(_) => Ok(SyntaxNode::new(SyntaxKind::Name, vec![self.bump()])),
+            TokenType::LeftParen => {
+                let mut children = vec![self.bump()];
+                let inner = self.expression();
+                push(&mut children, inner)?;
+                self.expect_token(&mut children, TokenType::RightParen)?;
+                Ok(SyntaxNode::new(SyntaxKind::Paren, children))
             }
-            Some(Token { token_type: TokenType::Eof, span }) => {
+            TokenType::LeftBracket => self.list(),
+            TokenType::LeftBrace => self.dictionary(),
+            TokenType::Eof => {
                 // EOF should be treated as unexpected end of file
                 // Use a single-character span at the end to show where input is expected
-                let expected_span = if span.start > 0 {
-                    Span::single(span.start - 1)
+                let expected_span = if token.span.start > 0 {
+                    Span::single(token.span.start - 1)
                 } else {
                     Span::single(0)
                 };
-                Err(BcclError::unexpected_eof(expected_span, 
-                    vec!["number".to_string(), "identifier".to_string(), "(".to_string()]))
+                Err(fail(&mut Vec::new(), BcclError::unexpected_eof(expected_span,
+                    vec!["number".to_string(), "identifier".to_string(), "(".to_string()])))
             }
-            Some(token) => {
-                let found_str = self.token_type_name(&token.token_type);
-                Err(BcclError::unexpected_token(&found_str, token.span, 
-                    vec!["number".to_string(), "identifier".to_string(), "(".to_string()]))
+            kind => {
+                let found_str = self.token_type_name(kind);
+                Err(fail(&mut Vec::new(), BcclError::unexpected_token(&found_str, token.span,
+                    vec!["number".to_string(), "identifier".to_string(), "(".to_string()])))
             }
-            None => {
-                let span = if self.position > 0 {
-                    self.tokens[self.position - 1].span
-                } else {
-                    Span::single(0)
-                };
-                Err(BcclError::unexpected_eof(span, 
-                    vec!["number".to_string(), "identifier".to_string(), "(".to_string()]))
+        }
+    }
+
+    /// `name(arguments)`: positional arguments, `*xs` unpacking a list, then
+    /// `name = value` keywords and `**d` unpacking a dictionary.
+    fn call(&mut self) -> NodeResult {
+        // The name and '('
+        let mut children = vec![self.bump(), self.bump()];
+        let mut found_kwarg = false;
+
+        // Parse arguments (mix of positional and keyword)
+        if !matches!(self.current_kind(), TokenType::RightParen) {
+            loop {
+                let argument = self.argument(&mut found_kwarg);
+                push(&mut children, argument)?;
+
+                let token = self.current_token();
+                match &token.kind {
+                    TokenType::Comma => children.push(self.bump()),
+                    TokenType::RightParen => break,
+                    kind => {
+                        let found_str = self.token_type_name(kind);
+                        let error = BcclError::unexpected_token(&found_str, token.span,
+                            vec![",".to_string(), ")".to_string()]);
+                        return Err(fail(&mut children, error));
+                    }
+                }
+            }
+        }
+
+        self.expect_token(&mut children, TokenType::RightParen)?;
+        Ok(SyntaxNode::new(SyntaxKind::Call, children))
+    }
+
+    /// One call argument with its `*`, `**` or `name =` prefix.
+    fn argument(&mut self, found_kwarg: &mut bool) -> NodeResult {
+        let mut children = Vec::new();
+        match self.current_kind() {
+            // `**d` unpacks a dictionary into keyword arguments
+            TokenType::DoubleStar => {
+                *found_kwarg = true;
+                children.push(self.bump());
+            }
+            TokenType::Identifier(_) if matches!(self.peek_kind(), Some(TokenType::Assign)) => {
+                *found_kwarg = true;
+                children.push(self.bump());
+                children.push(self.bump());
+            }
+            // Positional arguments, unpacked or not, may not follow keywords
+            _ if *found_kwarg => {
+                let error = BcclError::unexpected_token(
+                    "positional argument",
+                    self.current_token().span,
+                    vec!["keyword argument".to_string()]
+                );
+                return Err(fail(&mut children, error));
             }
+            // `*xs` unpacks a list into positional arguments
+            TokenType::Multiply => children.push(self.bump()),
+            _ => {}
         }
+        let value = self.expression();
+        push(&mut children, value)?;
+        Ok(SyntaxNode::new(SyntaxKind::Argument, children))
     }
-}
\ No newline at end of file
+}
@@ -1,67 +1,102 @@
 use crate::lexer::{Token, TokenType};
 use crate::error::{BcclError, BcclResult, Span};
+use crate::syntax::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken, SyntaxTree};
 
 mod ast;
 mod statements;
 mod expressions;
 mod collections;
+mod display;
+mod visit;
 
-pub use ast::{Expr, Stmt, Program, BinaryOp, UnaryOp, CompoundOp};
+#[cfg(test)]
+mod tests;
 
+pub use ast::{Expr, Stmt, Program, Slot, TypeAnnotation, BinaryOp, UnaryOp, CompoundOp};
+pub use visit::{Visitor, VisitorMut, walk_program, walk_stmt, walk_expr, walk_program_mut, walk_stmt_mut, walk_expr_mut};
+
+/// Recursive descent parser for BCCL.
+/// 
+/// The grammar builds the concrete syntax tree (`SyntaxTree`), keeping every
+/// token it consumes; the AST is lowered from that tree. `SyntaxTree::parse`
+/// runs the same parser over tokens that carry their text and trivia, so there
+/// is one grammar for both.
 pub struct Parser {
-    tokens: Vec<Token>,
+    tokens: Vec<SyntaxToken>,
     position: usize,
 }
 
+/// A grammar rule that failed: the error, and every token consumed so far,
+/// which recovery keeps in an `Error` node.
+pub(crate) struct Failure {
+    error: Box<BcclError>,
+    consumed: Vec<SyntaxElement>,
+}
+
+type NodeResult = Result<SyntaxNode, Failure>;
+
 impl Parser {
+    /// Creates a parser for `tokens`, as produced by `Lexer::tokenize`. An
+    /// `Eof` token is added if the tokens do not end with one.
     pub fn new(tokens: Vec<Token>) -> Self {
+        Self::from_syntax_tokens(tokens.into_iter()
+            .map(|token| SyntaxToken { kind: token.token_type, text: String::new(), span: token.span, leading_trivia: Vec::new() })
+            .collect())
+    }
+    
+    /// Creates a parser for tokens that carry their source text and trivia,
+    /// as `SyntaxTree::parse` does.
+    pub(crate) fn from_syntax_tokens(mut tokens: Vec<SyntaxToken>) -> Self {
+        if !tokens.last().is_some_and(|token| token.kind == TokenType::Eof) {
+            let end = tokens.last().map_or(0, |token| token.span.end);
+            tokens.push(SyntaxToken { kind: TokenType::Eof, text: String::new(), span: Span::single(end), leading_trivia: Vec::new() });
+        }
         Self { tokens, position: 0 }
     }
     
-    fn current_token(&self) -> Option<&Token> {
-        self.tokens.get(self.position)
+    /// The current token; the `Eof` token is never consumed.
+    fn current_token(&self) -> &SyntaxToken {
+        &self.tokens[self.position.min(self.tokens.len() - 1)]
     }
     
-    fn peek_token(&self) -> Option<&Token> {
-        self.tokens.get(self.position + 1)
+    fn current_kind(&self) -> &TokenType {
+        &self.current_token().kind
     }
     
-    fn advance(&mut self) {
+    fn peek_kind(&self) -> Option<&TokenType> {
+        self.tokens.get(self.position + 1).map(|token| &token.kind)
+    }
+    
+    /// Consumes the current token.
+    fn bump(&mut self) -> SyntaxElement {
+        let token = self.current_token().clone();
         self.position += 1;
+        SyntaxElement::Token(token)
     }
     
-    fn expect_token(&mut self, expected: TokenType) -> BcclResult<Token> {
-        match self.current_token() {
-            Some(token) if std::mem::discriminant(&token.token_type) == std::mem::discriminant(&expected) => {
-                let token = token.clone();
-                self.advance();
-                Ok(token)
-            }
-            Some(token) if matches!(token.token_type, TokenType::Eof) => {
-                // EOF token should be treated as unexpected end of file
-                let expected_str = self.token_type_name(&expected);
-                let span = if token.span.start > 0 {
-                    Span::single(token.span.start - 1)
-                } else {
-                    Span::single(0)
-                };
-                Err(BcclError::unexpected_eof(span, vec![expected_str]))
-            }
-            Some(token) => {
-                let expected_str = self.token_type_name(&expected);
-                let found_str = self.token_type_name(&token.token_type);
-                Err(BcclError::unexpected_token(&found_str, token.span, vec![expected_str]))
-            }
-            None => {
-                let expected_str = self.token_type_name(&expected);
-                let span = if self.position > 0 {
-                    self.tokens[self.position - 1].span
-                } else {
-                    Span::single(0)
-                };
-                Err(BcclError::unexpected_eof(span, vec![expected_str]))
-            }
+    /// Consumes a token of the same type as `expected` into `children`, or
+    /// fails with everything in `children`.
+    fn expect_token(&mut self, children: &mut Vec<SyntaxElement>, expected: TokenType) -> Result<(), Failure> {
+        let token = self.current_token();
+        if std::mem::discriminant(&token.kind) == std::mem::discriminant(&expected) {
+            let token = self.bump();
+            children.push(token);
+            return Ok(());
         }
+        let expected_str = self.token_type_name(&expected);
+        let error = if matches!(token.kind, TokenType::Eof) {
+            // EOF token should be treated as unexpected end of file
+            let span = if token.span.start > 0 {
+                Span::single(token.span.start - 1)
+            } else {
+                Span::single(0)
+            };
+            BcclError::unexpected_eof(span, vec![expected_str])
+        } else {
+            let found_str = self.token_type_name(&token.kind);
+            BcclError::unexpected_token(&found_str, token.span, vec![expected_str])
+        };
+        Err(fail(children, error))
     }
     
     fn token_type_name(&self, token_type: &TokenType) -> String {
@@ -78,6 +113,7 @@ impl Parser {
             TokenType::Minus => "-".to_string(),
             TokenType::Multiply => "*".to_string(),
             TokenType::Divide => "/".to_string(),
+            TokenType::DoubleStar => "**".to_string(),
             
             // Comparison operators
             TokenType::Equal => "==".to_string(),
@@ -110,24 +146,181 @@ impl Parser {
             TokenType::Comma => ",".to_string(),
             TokenType::Colon => ":".to_string(),
             TokenType::Semicolon => ";".to_string(),
+            TokenType::Newline => "new line".to_string(),
             
             // Special
             TokenType::Eof => "end of input".to_string(),
+            TokenType::Error(_) => "invalid token".to_string(),
         }
     }
     
+    /// Parses the whole token stream.
+    /// 
+    /// Parsing recovers from errors at statement boundaries (see
+    /// `parse_with_recovery`), so every syntax error in the input is reported
+    /// at once: a single error is returned as-is, several as
+    /// `BcclError::Multiple` in source order.
     pub fn parse(&mut self) -> BcclResult<Program> {
-        let mut statements = Vec::new();
+        let (program, errors) = self.parse_with_recovery();
+        if errors.is_empty() {
+            Ok(program)
+        } else {
+            Err(BcclError::multiple(errors))
+        }
+    }
+    
+    /// Parses the whole token stream, collecting errors instead of stopping at
+    /// the first one.
+    /// 
+    /// Statements end at a `;` or a line break (see `TokenType::Newline`); a
+    /// second statement on the same line is reported as
+    /// `BcclError::MissingSeparator` and then parsed normally.
+    /// 
+    /// After any other error the parser skips ahead to the next statement
+    /// boundary: a `;` or line break, the `}` closing the broken construct, or
+    /// an `name =` / `name +=` / `name:` assignment outside any brackets.
+    /// Statements that failed are left out of the returned program, which is
+    /// still useful to tools.
+    /// 
+    /// Error tokens from `Lexer::tokenize_with_recovery` are reported as the
+    /// lexical errors they stand for, including those skipped while
+    /// recovering.
+    /// 
+    /// # Returns
+    /// 
+    /// The statements that parsed, and every error in source order.
+    pub fn parse_with_recovery(&mut self) -> (Program, Vec<BcclError>) {
+        let tree = self.parse_tree();
+        (tree.lower(), tree.into_errors())
+    }
+    
+    /// Parses the whole token stream into a concrete syntax tree, which
+    /// `parse_with_recovery` lowers. Statements that failed become `Error`
+    /// nodes holding the tokens that were skipped.
+    pub(crate) fn parse_tree(&mut self) -> SyntaxTree {
+        let mut children = Vec::new();
+        let mut errors = Vec::new();
         
-        while let Some(token) = self.current_token() {
-            if matches!(token.token_type, TokenType::Eof) {
-                break;
+        loop {
+            match self.current_kind() {
+                TokenType::Eof => {
+                    children.push(self.bump());
+                    break;
+                }
+                // Blank lines between statements
+                TokenType::Newline => {
+                    children.push(self.bump());
+                    continue;
+                }
+                _ => {}
             }
             
-            let stmt = self.parse_statement()?;
-            statements.push(stmt);
+            let start = self.position;
+            match self.statement() {
+                Ok(stmt) => {
+                    children.push(SyntaxElement::Node(stmt));
+                    match self.end_statement(&mut children) {
+                        Ok(()) => {}
+                        // The next statement starts right here
+                        Err(error @ BcclError::MissingSeparator { .. }) => errors.push(error),
+                        Err(error) => {
+                            let failed_at = self.position;
+                            errors.push(self.lexical_error(failed_at).unwrap_or(error));
+                            let skipped = self.synchronize(failed_at, failed_at, Vec::new(), &mut errors);
+                            children.push(SyntaxElement::Node(skipped));
+                        }
+                    }
+                }
+                Err(failure) => {
+                    let failed_at = self.position;
+                    errors.push(self.lexical_error(failed_at).unwrap_or(*failure.error));
+                    let skipped = self.synchronize(start, failed_at, failure.consumed, &mut errors);
+                    children.push(SyntaxElement::Node(skipped));
+                }
+            }
+        }
+        
+        SyntaxTree::new(SyntaxNode::new(SyntaxKind::Program, children), errors)
+    }
+    
+    /// The lexical error for the error token at `position`, if it is one.
+    fn lexical_error(&self, position: usize) -> Option<BcclError> {
+        match self.tokens.get(position) {
+            Some(SyntaxToken { kind: TokenType::Error(message), span, .. }) => Some(BcclError::lex_error(message, *span)),
+            _ => None,
+        }
+    }
+    
+    /// Skips the rest of a statement that failed to parse, which started at
+    /// token `start` and failed at token `failed_at` (already reported), and
+    /// returns an `Error` node with the tokens `consumed` before the failure
+    /// and those skipped. Error tokens skipped on the way are reported too.
+    fn synchronize(&mut self, start: usize, failed_at: usize, mut consumed: Vec<SyntaxElement>, errors: &mut Vec<BcclError>) -> SyntaxNode {
+        // Always make progress, or a stray token would fail forever
+        if self.position == start {
+            consumed.push(self.bump());
         }
+        let mut depth: i32 = self.tokens[start..self.position]
+            .iter()
+            .map(|token| nesting(&token.kind))
+            .sum();
         
-        Ok(Program { statements })
+        loop {
+            match self.current_kind() {
+                TokenType::Eof => break,
+                TokenType::Semicolon | TokenType::Newline => {
+                    consumed.push(self.bump());
+                    break;
+                }
+                TokenType::RightBrace if depth <= 1 => {
+                    consumed.push(self.bump());
+                    if matches!(self.current_kind(), TokenType::Semicolon | TokenType::Newline) {
+                        consumed.push(self.bump());
+                    }
+                    break;
+                }
+                TokenType::Identifier(_) if depth <= 0 && self.peek_kind().is_some_and(|next| matches!(
+                    next,
+                    TokenType::Assign | TokenType::PlusAssign | TokenType::MinusAssign |
+                    TokenType::MultiplyAssign | TokenType::DivideAssign | TokenType::Colon
+                )) => break,
+                kind => depth += nesting(kind),
+            }
+            if self.position != failed_at && let Some(error) = self.lexical_error(self.position) {
+                errors.push(error);
+            }
+            consumed.push(self.bump());
+        }
+        SyntaxNode::new(SyntaxKind::Error, consumed)
+    }
+}
+
+/// Ends a rule that failed, taking everything it consumed.
+fn fail(children: &mut Vec<SyntaxElement>, error: BcclError) -> Failure {
+    Failure { error: Box::new(error), consumed: std::mem::take(children) }
+}
+
+/// Appends a parsed child node, or on failure fails with everything consumed
+/// so far, including the failed child's tokens.
+fn push(children: &mut Vec<SyntaxElement>, result: NodeResult) -> Result<(), Failure> {
+    match result {
+        Ok(node) => {
+            children.push(SyntaxElement::Node(node));
+            Ok(())
+        }
+        Err(failure) => {
+            children.extend(failure.consumed);
+            Err(Failure { error: failure.error, consumed: std::mem::take(children) })
+        }
     }
-}
\ No newline at end of file
+}
+
+/// How a token changes bracket nesting: +1 for an opening bracket, -1 for a
+/// closing one.
+fn nesting(token_type: &TokenType) -> i32 {
+    match token_type {
+        TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftBrace => 1,
+        TokenType::RightParen | TokenType::RightBracket | TokenType::RightBrace => -1,
+        _ => 0,
+    }
+}
@@ -1,102 +1,103 @@
-use crate::lexer::{Token, TokenType};
-use crate::error::{BcclError, BcclResult, Span};
-use super::ast::{Stmt, CompoundOp};
-use super::Parser;
+use crate::lexer::TokenType;
+use crate::error::{BcclError, BcclResult};
+use crate::syntax::{lower_statement, SyntaxElement, SyntaxKind, SyntaxNode};
+use super::ast::Stmt;
+use super::{push, NodeResult, Parser};
 
 impl Parser {
+    /// Parses one statement, without the separator that ends it.
     pub fn parse_statement(&mut self) -> BcclResult<Stmt> {
-        if let Some(token) = self.current_token() {
-            if let TokenType::Identifier(_name) = &token.token_type {
-                if let Some(next_token) = self.peek_token() {
-                    match next_token.token_type {
-                        TokenType::Assign => {
-                            return self.parse_assignment();
-                        }
-                        TokenType::PlusAssign | TokenType::MinusAssign | 
-                        TokenType::MultiplyAssign | TokenType::DivideAssign => {
-                            return self.parse_compound_assignment();
-                        }
-                        _ => {}
-                    }
+        self.statement().map(|node| lower_statement(&node)).map_err(|failure| *failure.error)
+    }
+
+    pub(super) fn statement(&mut self) -> NodeResult {
+        if let TokenType::Identifier(_) = self.current_kind()
+            && let Some(next) = self.peek_kind()
+        {
+            match next {
+                TokenType::Assign | TokenType::Colon => {
+                    return self.assignment();
                 }
+                TokenType::PlusAssign | TokenType::MinusAssign |
+                TokenType::MultiplyAssign | TokenType::DivideAssign => {
+                    return self.compound_assignment();
+                }
+                _ => {}
             }
         }
-        
-        let expr = self.parse_expression()?;
-        let span = expr.span();
-        
-        if let Some(token) = self.current_token() {
-            if matches!(token.token_type, TokenType::Semicolon) {
-                self.advance();
-            }
-        }
-        
-        Ok(Stmt::Expression { expr, span })
+
+        let expr = self.expression()?;
+        Ok(SyntaxNode::new(SyntaxKind::ExpressionStmt, vec![SyntaxElement::Node(expr)]))
     }
-    
-    pub fn parse_assignment(&mut self) -> BcclResult<Stmt> {
-        let (name, name_span) = if let Some(Token { token_type: TokenType::Identifier(name), span }) = self.current_token() {
-            let name = name.clone();
-            let span = *span;
-            self.advance();
-            (name, span)
-        } else {
-            return Err(BcclError::unexpected_token("Expected identifier", 
-                self.current_token().map_or(Span::single(0), |t| t.span), 
-                vec!["identifier".to_string()]));
-        };
-        
-        let assign_token = self.expect_token(TokenType::Assign)?;
-        let value = self.parse_expression()?;
-        let span = name_span.combine(&value.span());
-        
-        if let Some(token) = self.current_token() {
-            if matches!(token.token_type, TokenType::Semicolon) {
-                self.advance();
-            }
+
+    /// `name = value`, or `name: type = value`
+    fn assignment(&mut self) -> NodeResult {
+        let mut children = vec![self.bump()];
+
+        // `name: type = value` declares the variable's type
+        if matches!(self.current_kind(), TokenType::Colon) {
+            let annotation = self.annotation();
+            push(&mut children, annotation)?;
         }
-        
-        Ok(Stmt::Assignment { name, value, span })
+
+        self.expect_token(&mut children, TokenType::Assign)?;
+        let value = self.expression();
+        push(&mut children, value)?;
+
+        Ok(SyntaxNode::new(SyntaxKind::AssignmentStmt, children))
     }
-    
-    pub fn parse_compound_assignment(&mut self) -> BcclResult<Stmt> {
-        let (name, name_span) = if let Some(Token { token_type: TokenType::Identifier(name), span }) = self.current_token() {
-            let name = name.clone();
-            let span = *span;
-            self.advance();
-            (name, span)
-        } else {
-            return Err(BcclError::unexpected_token("Expected identifier", 
-                self.current_token().map_or(Span::single(0), |t| t.span), 
-                vec!["identifier".to_string()]));
-        };
-        
-        let operator = match self.current_token() {
-            Some(Token { token_type: TokenType::PlusAssign, .. }) => CompoundOp::Add,
-            Some(Token { token_type: TokenType::MinusAssign, .. }) => CompoundOp::Subtract,
-            Some(Token { token_type: TokenType::MultiplyAssign, .. }) => CompoundOp::Multiply,
-            Some(Token { token_type: TokenType::DivideAssign, .. }) => CompoundOp::Divide,
-            Some(token) => {
-                let found_str = self.token_type_name(&token.token_type);
-                return Err(BcclError::unexpected_token(&found_str, token.span, 
-                    vec!["+=".to_string(), "-=".to_string(), "*=".to_string(), "/=".to_string()]));
-            }
-            None => {
-                return Err(BcclError::unexpected_eof(name_span, 
-                    vec!["+=".to_string(), "-=".to_string(), "*=".to_string(), "/=".to_string()]));
+
+    /// `: type`
+    fn annotation(&mut self) -> NodeResult {
+        let mut children = vec![self.bump()];
+        self.expect_token(&mut children, TokenType::Identifier(String::new()))?;
+        Ok(SyntaxNode::new(SyntaxKind::Annotation, children))
+    }
+
+    /// `name += value` and the other compound operators
+    fn compound_assignment(&mut self) -> NodeResult {
+        // The name and the operator, which `statement` has looked at
+        let mut children = vec![self.bump(), self.bump()];
+        let value = self.expression();
+        push(&mut children, value)?;
+
+        Ok(SyntaxNode::new(SyntaxKind::CompoundAssignmentStmt, children))
+    }
+
+    /// Consumes the `;` or line break that ends a statement into `children`.
+    /// The end of input also ends one, and is left in place.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The statement ended properly
+    /// * `Err(BcclError::MissingSeparator)` - Another statement follows on the
+    ///   same line; nothing is consumed, so parsing can continue with it
+    /// * `Err(BcclError::UnexpectedToken)` - Any other token follows
+    pub(super) fn end_statement(&mut self, children: &mut Vec<SyntaxElement>) -> BcclResult<()> {
+        let token = self.current_token();
+        match &token.kind {
+            TokenType::Semicolon | TokenType::Newline => {
+                let separator = self.bump();
+                children.push(separator);
+                Ok(())
             }
-        };
-        
-        self.advance(); // consume the compound assignment operator
-        let value = self.parse_expression()?;
-        let span = name_span.combine(&value.span());
-        
-        if let Some(token) = self.current_token() {
-            if matches!(token.token_type, TokenType::Semicolon) {
-                self.advance();
+            TokenType::Eof => Ok(()),
+            kind if starts_statement(kind) => Err(BcclError::missing_separator(token.span)),
+            kind => {
+                let found_str = self.token_type_name(kind);
+                Err(BcclError::unexpected_token(&found_str, token.span, vec![";".to_string(), "new line".to_string()]))
             }
         }
-        
-        Ok(Stmt::CompoundAssignment { name, operator, value, span })
     }
-}
\ No newline at end of file
+}
+
+/// Whether a statement can begin with a token of this type.
+fn starts_statement(token_type: &TokenType) -> bool {
+    matches!(
+        token_type,
+        TokenType::Number(_) | TokenType::Integer(_) | TokenType::Boolean(_) |
+        TokenType::String(_) | TokenType::Identifier(_) |
+        TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftBrace |
+        TokenType::Minus | TokenType::Plus | TokenType::Not
+    )
+}
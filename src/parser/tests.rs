@@ -0,0 +1,172 @@
+use super::*;
+use crate::Lexer;
+use crate::test_support::Rng;
+
+fn parse(code: &str) -> Program {
+    let tokens = Lexer::new(code).tokenize().unwrap();
+    Parser::new(tokens).parse().unwrap_or_else(|error| panic!("{:?} for {}", error, code))
+}
+
+impl Rng {
+    /// Trees the parser can produce: no negative literals or stray unpacking.
+    fn expr(&mut self, depth: usize) -> Expr {
+        const OPS: [BinaryOp; 14] = [
+            BinaryOp::Add, BinaryOp::Subtract, BinaryOp::Multiply, BinaryOp::Divide, BinaryOp::Equal,
+            BinaryOp::NotEqual, BinaryOp::Less, BinaryOp::Greater, BinaryOp::LessEqual, BinaryOp::GreaterEqual,
+            BinaryOp::And, BinaryOp::Or, BinaryOp::In, BinaryOp::NotIn,
+        ];
+        let span = Span::new(0, 0);
+        let kinds = if depth == 0 { 5 } else { 11 };
+        match self.below(kinds) {
+            0 => Expr::Number { value: [0.5, 3.0, 1e300, 1e-7, 0.1][self.below(5)], span },
+            1 => Expr::Integer { value: self.below(100) as i64, span },
+            2 => Expr::Boolean { value: self.below(2) == 0, span },
+            3 => Expr::String { value: ["", "a\"b", "tab\there", "back\\slash", "日本"][self.below(5)].to_string(), span },
+            4 => Expr::Identifier { name: ["x", "total", "_y"][self.below(3)].to_string(), span, slot: None },
+            5 | 6 => Expr::Binary {
+                left: Box::new(self.expr(depth - 1)),
+                operator: OPS[self.below(OPS.len())],
+                right: Box::new(self.expr(depth - 1)),
+                span,
+            },
+            7 => Expr::Unary {
+                operator: [UnaryOp::Minus, UnaryOp::Plus, UnaryOp::Not][self.below(3)],
+                operand: Box::new(self.expr(depth - 1)),
+                span,
+            },
+            8 => {
+                let mut args: Vec<Expr> = (0..self.below(3)).map(|_| self.expr(depth - 1)).collect();
+                if self.below(2) == 0 {
+                    args.push(Expr::Unpack { value: Box::new(self.expr(depth - 1)), span });
+                }
+                let kwargs = (0..self.below(3))
+                    .map(|i| ((i % 2 == 0).then(|| "key".to_string()), self.expr(depth - 1)))
+                    .collect();
+                Expr::FunctionCall { name: "f".to_string(), args, kwargs, span, builtin: false, slot: None }
+            }
+            9 => match self.below(2) {
+                0 => Expr::List { elements: (0..self.below(3)).map(|_| self.expr(depth - 1)).collect(), span },
+                _ => Expr::Dictionary { pairs: (0..self.below(3)).map(|_| ("k".to_string(), self.expr(depth - 1))).collect(), span },
+            },
+            _ => Expr::Index { object: Box::new(self.expr(depth - 1)), index: Box::new(self.expr(depth - 1)), span },
+        }
+    }
+}
+
+#[test]
+fn test_display_parses_back_to_the_same_tree() {
+    let mut rng = Rng(0x5eed_0046);
+    for _ in 0..3000 {
+        let expr = rng.expr(4);
+        let source = expr.to_string();
+        let program = parse(&source);
+        assert_eq!(program.statements.len(), 1, "{}", source);
+        assert_eq!(program.statements[0].to_sexp(), expr.to_sexp(), "{}", source);
+    }
+}
+
+#[test]
+fn test_display_uses_minimal_parentheses() {
+    let cases = [
+        ("((1 + 2)) * 3", "(1 + 2) * 3"),
+        ("1 + (2 * 3)", "1 + 2 * 3"),
+        ("(a - b) - c", "a - b - c"),
+        ("a - (b - c)", "a - (b - c)"),
+        ("a / (b * c)", "a / (b * c)"),
+        ("(a or b) and c", "(a or b) and c"),
+        ("not (a in b)", "not (a in b)"),
+        ("(not a) in b", "not a in b"),
+        ("-(-x)", "-(-x)"),
+        ("(-x)[0]", "(-x)[0]"),
+        ("-(x[0])", "-x[0]"),
+        ("f(*(a + b), k=(1), **(d))", "f(*a + b, k=1, **d)"),
+        ("{\"a\": [1, (2)]}[\"a\"]", "{\"a\": [1, 2]}[\"a\"]"),
+        ("total += (x)", "total += x"),
+    ];
+    for (source, expected) in cases {
+        assert_eq!(parse(source).to_string(), expected, "{}", source);
+    }
+}
+
+#[test]
+fn test_display_keeps_numbers_distinct_from_integers() {
+    let number = |value: f64| Expr::Number { value, span: Span::new(0, 0) }.to_string();
+    assert_eq!(number(3.0), "3.0");
+    assert_eq!(number(0.1), "0.1");
+    assert_eq!(number(1e-7), "0.0000001");
+    assert_eq!(number(-2.5), "-2.5");
+    assert!(matches!(
+        &parse(&number(1e300)).statements[0],
+        Stmt::Expression { expr: Expr::Number { value, .. }, .. } if *value == 1e300
+    ));
+    assert_eq!(Expr::String { value: "say \"hi\"\n".to_string(), span: Span::new(0, 0) }.to_string(), "\"say \\\"hi\\\"\\n\"");
+}
+
+#[test]
+fn test_sexp_dump() {
+    let program = parse("x = 1 + 2 * y\ntotal -= -len(xs)\nok = a not in [1, 2.5] or not b\nd = {\"k\": f(*xs, n=1, **o)}[\"k\"]");
+    assert_eq!(program.to_sexp(), [
+        "(= x (+ 1 (* 2 y)))",
+        "(-= total (- (call len xs)))",
+        "(= ok (or (not-in a (list 1 2.5)) (not b)))",
+        "(= d (index (dict (\"k\" (call f (unpack xs) (kw n 1) (unpack-kw o)))) \"k\"))",
+    ].join("\n"));
+}
+
+/// Counts every expression it is shown.
+#[derive(Default)]
+struct Count(usize);
+
+impl Visitor<'_> for Count {
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.0 += 1;
+        walk_expr(self, expr);
+    }
+}
+
+impl VisitorMut for Count {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        self.0 += 1;
+        walk_expr_mut(self, expr);
+    }
+}
+
+#[test]
+fn test_visitors_reach_every_expression() {
+    let mut rng = Rng(0x5eed_0047);
+    for _ in 0..500 {
+        let mut expr = rng.expr(4);
+        // Every expression node has exactly one span
+        let nodes = format!("{:?}", expr).matches("span:").count();
+        let mut count = Count::default();
+        count.visit_expr(&expr);
+        assert_eq!(count.0, nodes, "{:?}", expr);
+        let mut count = Count::default();
+        count.visit_expr_mut(&mut expr);
+        assert_eq!(count.0, nodes, "{:?}", expr);
+        assert_eq!(expr.children().len(), match &expr {
+            Expr::Binary { .. } | Expr::Index { .. } => 2,
+            Expr::Unary { .. } | Expr::Unpack { .. } => 1,
+            Expr::FunctionCall { args, kwargs, .. } => args.len() + kwargs.len(),
+            Expr::List { elements, .. } => elements.len(),
+            Expr::Dictionary { pairs, .. } => pairs.len(),
+            _ => 0,
+        });
+    }
+}
+
+#[test]
+fn test_name_utilities() {
+    let program = parse("x = 1\ny += f(x, k=z)[w]\nx -= 1\nprint(-v)");
+    let mut assigned: Vec<_> = program.assigned_names().into_iter().collect();
+    assigned.sort();
+    assert_eq!(assigned, ["x", "y"]);
+
+    let references: Vec<_> = program.statements.iter()
+        .map(|stmt| match stmt {
+            Stmt::Expression { expr, .. } | Stmt::Assignment { value: expr, .. }
+            | Stmt::CompoundAssignment { value: expr, .. } => expr.referenced_names(),
+        })
+        .collect();
+    assert_eq!(references, [vec![], vec!["f", "x", "z", "w"], vec![], vec!["print", "v"]]);
+}
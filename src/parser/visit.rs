@@ -0,0 +1,256 @@
+//! Traversal of the AST. `Visitor` reads a tree and `VisitorMut` rewrites one
+//! in place; both visit every node by default, so an analysis overrides only
+//! the nodes it cares about and calls the matching `walk_*` function to carry
+//! on into the children.
+//!
+//! The `walk_*` functions are the only code that needs to know the shape of
+//! every node: a new kind of node is visited by every existing tool once it is
+//! added here.
+
+use std::collections::HashSet;
+use super::ast::{Expr, Program, Stmt};
+
+/// Visits an AST by shared reference. The `'ast` lifetime lets a visitor
+/// keep references into the tree it visits.
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::{Expr, Lexer, Parser, Visitor, walk_expr};
+/// /// Counts the calls in a program, however deeply nested.
+/// struct Calls(usize);
+///
+/// impl Visitor<'_> for Calls {
+///     fn visit_expr(&mut self, expr: &Expr) {
+///         if let Expr::FunctionCall { .. } = expr {
+///             self.0 += 1;
+///         }
+///         walk_expr(self, expr);
+///     }
+/// }
+///
+/// let program = Parser::new(Lexer::new("x = max(len(xs), 1); [abs(x)]").tokenize().unwrap()).parse().unwrap();
+/// let mut calls = Calls(0);
+/// calls.visit_program(&program);
+/// assert_eq!(calls.0, 3);
+/// ```
+pub trait Visitor<'ast> {
+    fn visit_program(&mut self, program: &'ast Program) {
+        walk_program(self, program);
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        walk_expr(self, expr);
+    }
+}
+
+/// Visits an AST by mutable reference, for passes that rewrite it in place.
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::{Expr, Lexer, Parser, VisitorMut, walk_expr_mut};
+/// /// Renames every use of a variable.
+/// struct Rename;
+///
+/// impl VisitorMut for Rename {
+///     fn visit_expr_mut(&mut self, expr: &mut Expr) {
+///         if let Expr::Identifier { name, .. } = expr && name == "old" {
+///             *name = "new".to_string();
+///         }
+///         walk_expr_mut(self, expr);
+///     }
+/// }
+///
+/// let mut program = Parser::new(Lexer::new("f(old, [old + 1])").tokenize().unwrap()).parse().unwrap();
+/// Rename.visit_program_mut(&mut program);
+/// assert_eq!(program.to_string(), "f(new, [new + 1])");
+/// ```
+pub trait VisitorMut {
+    fn visit_program_mut(&mut self, program: &mut Program) {
+        walk_program_mut(self, program);
+    }
+
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        walk_stmt_mut(self, stmt);
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr);
+    }
+}
+
+/// Visits each statement of `program` in order.
+pub fn walk_program<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, program: &'ast Program) {
+    for stmt in &program.statements {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+/// Visits the expression in `stmt`.
+pub fn walk_stmt<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, stmt: &'ast Stmt) {
+    match stmt {
+        Stmt::Expression { expr, .. } => visitor.visit_expr(expr),
+        Stmt::Assignment { value, .. } | Stmt::CompoundAssignment { value, .. } => visitor.visit_expr(value),
+    }
+}
+
+/// Visits the direct subexpressions of `expr`, left to right as they appear
+/// in source.
+pub fn walk_expr<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, expr: &'ast Expr) {
+    match expr {
+        Expr::Number { .. } | Expr::Integer { .. } | Expr::Boolean { .. }
+        | Expr::String { .. } | Expr::Identifier { .. } => {}
+        Expr::Binary { left, right, .. } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        }
+        Expr::Unary { operand, .. } => visitor.visit_expr(operand),
+        Expr::FunctionCall { args, kwargs, .. } => {
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+            for (_, value) in kwargs {
+                visitor.visit_expr(value);
+            }
+        }
+        Expr::Unpack { value, .. } => visitor.visit_expr(value),
+        Expr::List { elements, .. } => {
+            for element in elements {
+                visitor.visit_expr(element);
+            }
+        }
+        Expr::Dictionary { pairs, .. } => {
+            for (_, value) in pairs {
+                visitor.visit_expr(value);
+            }
+        }
+        Expr::Index { object, index, .. } => {
+            visitor.visit_expr(object);
+            visitor.visit_expr(index);
+        }
+    }
+}
+
+/// Visits each statement of `program` in order.
+pub fn walk_program_mut<V: VisitorMut + ?Sized>(visitor: &mut V, program: &mut Program) {
+    for stmt in &mut program.statements {
+        visitor.visit_stmt_mut(stmt);
+    }
+}
+
+/// Visits the expression in `stmt`.
+pub fn walk_stmt_mut<V: VisitorMut + ?Sized>(visitor: &mut V, stmt: &mut Stmt) {
+    match stmt {
+        Stmt::Expression { expr, .. } => visitor.visit_expr_mut(expr),
+        Stmt::Assignment { value, .. } | Stmt::CompoundAssignment { value, .. } => visitor.visit_expr_mut(value),
+    }
+}
+
+/// Visits the direct subexpressions of `expr`, left to right as they appear
+/// in source.
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+    match expr {
+        Expr::Number { .. } | Expr::Integer { .. } | Expr::Boolean { .. }
+        | Expr::String { .. } | Expr::Identifier { .. } => {}
+        Expr::Binary { left, right, .. } => {
+            visitor.visit_expr_mut(left);
+            visitor.visit_expr_mut(right);
+        }
+        Expr::Unary { operand, .. } => visitor.visit_expr_mut(operand),
+        Expr::FunctionCall { args, kwargs, .. } => {
+            for arg in args {
+                visitor.visit_expr_mut(arg);
+            }
+            for (_, value) in kwargs {
+                visitor.visit_expr_mut(value);
+            }
+        }
+        Expr::Unpack { value, .. } => visitor.visit_expr_mut(value),
+        Expr::List { elements, .. } => {
+            for element in elements {
+                visitor.visit_expr_mut(element);
+            }
+        }
+        Expr::Dictionary { pairs, .. } => {
+            for (_, value) in pairs {
+                visitor.visit_expr_mut(value);
+            }
+        }
+        Expr::Index { object, index, .. } => {
+            visitor.visit_expr_mut(object);
+            visitor.visit_expr_mut(index);
+        }
+    }
+}
+
+impl Program {
+    /// The names the program assigns to, with `=` or a compound assignment.
+    pub fn assigned_names(&self) -> HashSet<&str> {
+        struct Assigned<'ast>(HashSet<&'ast str>);
+
+        impl<'ast> Visitor<'ast> for Assigned<'ast> {
+            fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+                if let Stmt::Assignment { name, .. } | Stmt::CompoundAssignment { name, .. } = stmt {
+                    self.0.insert(name);
+                }
+                walk_stmt(self, stmt);
+            }
+        }
+
+        let mut assigned = Assigned(HashSet::new());
+        assigned.visit_program(self);
+        assigned.0
+    }
+}
+
+impl Expr {
+    /// The names `expr` reads, in source order and without repeats: variables
+    /// used as values and the names of called functions.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bccl::{Lexer, Parser, Stmt};
+    /// let program = Parser::new(Lexer::new("max(x, y * x) + len(xs)").tokenize().unwrap()).parse().unwrap();
+    /// let Stmt::Expression { expr, .. } = &program.statements[0] else { unreachable!() };
+    /// assert_eq!(expr.referenced_names(), ["max", "x", "y", "len", "xs"]);
+    /// ```
+    pub fn referenced_names(&self) -> Vec<&str> {
+        struct Referenced<'ast>(Vec<&'ast str>);
+
+        impl<'ast> Visitor<'ast> for Referenced<'ast> {
+            fn visit_expr(&mut self, expr: &'ast Expr) {
+                if let Expr::Identifier { name, .. } | Expr::FunctionCall { name, .. } = expr
+                    && !self.0.contains(&name.as_str())
+                {
+                    self.0.push(name);
+                }
+                walk_expr(self, expr);
+            }
+        }
+
+        let mut referenced = Referenced(Vec::new());
+        referenced.visit_expr(self);
+        referenced.0
+    }
+
+    /// The direct subexpressions of `expr`, in source order.
+    pub fn children(&self) -> Vec<&Expr> {
+        struct Children<'ast>(Vec<&'ast Expr>);
+
+        impl<'ast> Visitor<'ast> for Children<'ast> {
+            fn visit_expr(&mut self, expr: &'ast Expr) {
+                self.0.push(expr);
+            }
+        }
+
+        let mut children = Children(Vec::new());
+        walk_expr(&mut children, self);
+        children.0
+    }
+}
@@ -0,0 +1,153 @@
+//! # Resolver Module
+//!
+//! A pass that runs just before evaluation and assigns every variable
+//! reference a `Slot` in the evaluator's `Environment`, so the tree-walker
+//! reads and writes variables by index instead of hashing names.
+//!
+//! ## What Gets a Slot
+//!
+//! - Assignment and compound assignment targets
+//! - Identifiers used as values (`x`, or `str` in `map(str, xs)`)
+//! - Call names (`f(3)` may call a variable holding a function)
+//!
+//! A name the program assigns anywhere always gets a slot of the evaluator's
+//! own environment, so every reference sees its writes. A name it only reads
+//! may resolve to a shared global in a base environment (`depth > 0`).
+//!
+//! A name that turns out not to be a variable (a built-in function, or an
+//! undefined name) simply has an empty slot; the evaluator then falls back
+//! to the function registry or reports the undefined variable, exactly as
+//! with by-name lookup.
+//!
+//! ## Validity
+//!
+//! Slots are only meaningful for the environment they were resolved against.
+//! `Evaluator::evaluate_program` resolves every program it runs, which costs a
+//! copy of the program and a pass over it. A host that runs the same program
+//! many times resolves it once with `Evaluator::resolve_program` and runs the
+//! `ResolvedProgram` with `Evaluator::run_resolved`, which only resolves again
+//! if the environment's layout changed since (see `ResolvedProgram`). The
+//! bytecode VM does not use these slots: it maps its chunk's name table to
+//! slots when a run starts, so one chunk can run on many evaluators.
+
+use std::collections::HashSet;
+use crate::parser::{Expr, Stmt, Program, Slot, VisitorMut, walk_stmt_mut, walk_expr_mut};
+use crate::evaluator::Environment;
+
+#[cfg(test)]
+mod tests;
+
+/// Assigns variable slots in a program.
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::{Environment, Expr, Lexer, Parser, Resolver, Stmt};
+/// let tokens = Lexer::new("x = 1; x").tokenize().unwrap();
+/// let mut program = Parser::new(tokens).parse().unwrap();
+/// let mut environment = Environment::new();
+/// Resolver::new(&mut environment).resolve(&mut program);
+///
+/// match (&program.statements[0], &program.statements[1]) {
+///     (Stmt::Assignment { slot: target, .. }, Stmt::Expression { expr: Expr::Identifier { slot, .. }, .. }) => {
+///         assert!(target.is_some());
+///         assert_eq!(target, slot);
+///     }
+///     other => panic!("unexpected {:?}", other),
+/// }
+/// ```
+pub struct Resolver<'a> {
+    environment: &'a mut Environment,
+    /// Names the program being resolved assigns
+    assigned: HashSet<String>,
+}
+
+impl<'a> Resolver<'a> {
+    /// Creates a resolver that declares slots in `environment`.
+    pub fn new(environment: &'a mut Environment) -> Self {
+        Self { environment, assigned: HashSet::new() }
+    }
+    
+    /// Fills in the slot of every variable reference in `program`.
+    pub fn resolve(&mut self, program: &mut Program) {
+        self.assigned = program.assigned_names().into_iter().map(str::to_string).collect();
+        self.visit_program_mut(program);
+    }
+    
+    /// The slot a reference to `name` reads.
+    fn read_slot(&mut self, name: &str) -> Slot {
+        if self.assigned.contains(name) {
+            self.environment.declare(name)
+        } else {
+            self.environment.resolve(name)
+        }
+    }
+}
+
+/// A program with its slots resolved for one evaluator's environment, made
+/// by `Evaluator::resolve_program` and run by `Evaluator::run_resolved`.
+///
+/// The program remembers the environment layout it was resolved at. Running
+/// it on a different environment, or after the environment declared new
+/// names (a variable shadowing a global, or another program's new variables),
+/// resolves it again first, so a resolved program never reads a wrong slot.
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::{Evaluator, Lexer, Parser, Program};
+/// let parse = |code: &str| -> Program { Parser::new(Lexer::new(code).tokenize().unwrap()).parse().unwrap() };
+///
+/// let mut evaluator = Evaluator::new();
+/// evaluator.evaluate_program(&parse("count = 0")).unwrap();
+/// let mut resolved = evaluator.resolve_program(&parse("count = count + 1"));
+/// for _ in 0..3 {
+///     evaluator.run_resolved(&mut resolved).unwrap();
+/// }
+/// assert_eq!(evaluator.get_variable("count").unwrap().display(), "3");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResolvedProgram {
+    program: Program,
+    /// `Environment::layout` right after resolving
+    layout: (u64, usize),
+}
+
+impl ResolvedProgram {
+    /// Resolves `program` (already optimized, if wanted) in `environment`.
+    pub(crate) fn new(mut program: Program, environment: &mut Environment) -> Self {
+        Resolver::new(environment).resolve(&mut program);
+        Self { program, layout: environment.layout() }
+    }
+
+    /// The resolved program.
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// Resolves the program again if `environment` is not the layout it was
+    /// resolved at, and returns it.
+    pub(crate) fn refresh(&mut self, environment: &mut Environment) -> &Program {
+        if self.layout != environment.layout() {
+            Resolver::new(environment).resolve(&mut self.program);
+            self.layout = environment.layout();
+        }
+        &self.program
+    }
+}
+
+impl VisitorMut for Resolver<'_> {
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        walk_stmt_mut(self, stmt);
+        if let Stmt::Assignment { name, slot, .. } | Stmt::CompoundAssignment { name, slot, .. } = stmt {
+            *slot = Some(self.environment.declare(name));
+        }
+    }
+    
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr);
+        if let Expr::Identifier { name, slot, .. } | Expr::FunctionCall { name, slot, .. } = expr {
+            *slot = Some(self.read_slot(name));
+        }
+    }
+}
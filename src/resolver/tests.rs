@@ -0,0 +1,104 @@
+use super::*;
+use std::sync::Arc;
+use crate::{Lexer, Parser, Evaluator, Value};
+use crate::parser::Slot;
+
+fn resolved(environment: &mut Environment, code: &str) -> Program {
+    let tokens = Lexer::new(code).tokenize().unwrap();
+    let mut program = Parser::new(tokens).parse().unwrap();
+    Resolver::new(environment).resolve(&mut program);
+    program
+}
+
+/// The slot of the identifier in an expression statement.
+fn identifier_slot(stmt: &Stmt) -> Option<Slot> {
+    match stmt {
+        Stmt::Expression { expr: Expr::Identifier { slot, .. }, .. } => *slot,
+        other => panic!("Expected identifier statement, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_same_name_shares_a_slot() {
+    let mut environment = Environment::new();
+    let program = resolved(&mut environment, "x = 1; y = 2; x; y");
+    let x = identifier_slot(&program.statements[2]).unwrap();
+    let y = identifier_slot(&program.statements[3]).unwrap();
+    assert_ne!(x, y);
+    assert_eq!(x.depth, 0);
+    match &program.statements[0] {
+        Stmt::Assignment { slot, .. } => assert_eq!(*slot, Some(x)),
+        other => panic!("Expected assignment, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_slots_are_stable_across_programs() {
+    let mut environment = Environment::new();
+    let first = resolved(&mut environment, "a = 1; a");
+    let second = resolved(&mut environment, "b = 2; a");
+    assert_eq!(identifier_slot(&first.statements[1]), identifier_slot(&second.statements[1]));
+}
+
+#[test]
+fn test_nested_references_are_resolved() {
+    let mut environment = Environment::new();
+    let program = resolved(&mut environment, "f(*xs, key=k)[i] + -n");
+    let Stmt::Expression { expr: Expr::Binary { left, right, .. }, .. } = &program.statements[0] else {
+        panic!("Expected binary expression");
+    };
+    let Expr::Index { object, index, .. } = left.as_ref() else { panic!("Expected index") };
+    assert!(matches!(index.as_ref(), Expr::Identifier { slot: Some(_), .. }));
+    let Expr::FunctionCall { args, kwargs, slot, .. } = object.as_ref() else { panic!("Expected call") };
+    assert!(slot.is_some());
+    assert!(matches!(&args[0], Expr::Unpack { value, .. } if matches!(value.as_ref(), Expr::Identifier { slot: Some(_), .. })));
+    assert!(matches!(&kwargs[0].1, Expr::Identifier { slot: Some(_), .. }));
+    assert!(matches!(right.as_ref(), Expr::Unary { operand, .. } if matches!(operand.as_ref(), Expr::Identifier { slot: Some(_), .. })));
+}
+
+#[test]
+fn test_unassigned_slots_are_not_variables() {
+    let mut evaluator = Evaluator::new();
+    let tokens = Lexer::new("x = 1; map(str, [x])").tokenize().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+    evaluator.evaluate_program(&program).unwrap();
+    // `map` and `str` received slots but were never assigned
+    assert_eq!(evaluator.get_variable_names(), vec!["x".to_string()]);
+    assert!(matches!(evaluator.get_variable("x"), Some(Value::Integer(1))));
+}
+
+fn parse(code: &str) -> Program {
+    Parser::new(Lexer::new(code).tokenize().unwrap()).parse().unwrap()
+}
+
+#[test]
+fn test_resolved_program_is_reused() {
+    let mut evaluator = Evaluator::new();
+    evaluator.evaluate_program(&parse("total = 0")).unwrap();
+    let mut resolved = evaluator.resolve_program(&parse("total += 2; total"));
+    let before = format!("{:?}", resolved.program());
+    for expected in ["2", "4", "6"] {
+        assert_eq!(evaluator.run_resolved(&mut resolved).unwrap().unwrap().display(), expected);
+    }
+    // Nothing moved, so the slots were never written again
+    assert_eq!(format!("{:?}", resolved.program()), before);
+}
+
+#[test]
+fn test_resolved_program_follows_its_environment() {
+    let mut globals = Environment::new();
+    globals.define("rate".to_string(), Value::Integer(2));
+    let mut evaluator = Evaluator::with_globals(Arc::new(globals));
+    let mut resolved = evaluator.resolve_program(&parse("rate * 10"));
+    assert_eq!(evaluator.run_resolved(&mut resolved).unwrap().unwrap().display(), "20");
+
+    // Shadowing the global moves `rate` into the evaluator's own environment
+    evaluator.evaluate_program(&parse("rate = 3")).unwrap();
+    assert_eq!(evaluator.run_resolved(&mut resolved).unwrap().unwrap().display(), "30");
+
+    // On another evaluator the program is resolved for that one
+    let mut other = Evaluator::new();
+    other.evaluate_program(&parse("unrelated = 1; rate = 5")).unwrap();
+    assert_eq!(other.run_resolved(&mut resolved).unwrap().unwrap().display(), "50");
+    assert_eq!(evaluator.run_resolved(&mut resolved).unwrap().unwrap().display(), "30");
+}
@@ -0,0 +1,169 @@
+use crate::lexer::TokenType;
+use crate::parser::{BinaryOp, CompoundOp, Expr, Program, Stmt, TypeAnnotation, UnaryOp};
+use super::tree::{SyntaxKind, SyntaxNode, SyntaxToken};
+
+/// Lowers a `Program` node to the AST. Spans are computed exactly as the
+/// parser computes them, and `Error` nodes are left out just as the parser
+/// leaves out statements that failed.
+pub(crate) fn lower_program(root: &SyntaxNode) -> Program {
+    let statements = root.child_nodes()
+        .filter(|node| node.kind() != SyntaxKind::Error)
+        .map(lower_statement)
+        .collect();
+    Program { statements }
+}
+
+pub(crate) fn lower_statement(node: &SyntaxNode) -> Stmt {
+    let value = lower_expression(node.child_nodes().last().expect("statement has a value"));
+    match node.kind() {
+        SyntaxKind::ExpressionStmt => {
+            let span = value.span();
+            Stmt::Expression { expr: value, span }
+        }
+        SyntaxKind::AssignmentStmt => {
+            let name = first_token(node);
+            let span = name.span.combine(&value.span());
+            let annotation = node.child_nodes()
+                .find(|child| child.kind() == SyntaxKind::Annotation)
+                .map(|annotation| {
+                    let token = annotation.child_tokens().last().expect("annotation has a type");
+                    TypeAnnotation { name: identifier(token), span: token.span }
+                });
+            Stmt::Assignment { name: identifier(name), value, span, slot: None, annotation }
+        }
+        SyntaxKind::CompoundAssignmentStmt => {
+            let mut tokens = node.child_tokens();
+            let name = tokens.next().expect("compound assignment has a name");
+            let operator = match tokens.next().map(|token| &token.kind) {
+                Some(TokenType::PlusAssign) => CompoundOp::Add,
+                Some(TokenType::MinusAssign) => CompoundOp::Subtract,
+                Some(TokenType::MultiplyAssign) => CompoundOp::Multiply,
+                _ => CompoundOp::Divide,
+            };
+            let span = name.span.combine(&value.span());
+            Stmt::CompoundAssignment { name: identifier(name), operator, value, span, slot: None }
+        }
+        kind => unreachable!("{:?} is not a statement", kind),
+    }
+}
+
+pub(crate) fn lower_expression(node: &SyntaxNode) -> Expr {
+    match node.kind() {
+        SyntaxKind::Literal => {
+            let token = first_token(node);
+            let span = token.span;
+            match &token.kind {
+                TokenType::Number(value) => Expr::Number { value: *value, span },
+                TokenType::Integer(value) => Expr::Integer { value: *value, span },
+                TokenType::Boolean(value) => Expr::Boolean { value: *value, span },
+                TokenType::String(value) => Expr::String { value: value.clone(), span },
+                kind => unreachable!("{:?} is not a literal", kind),
+            }
+        }
+        SyntaxKind::Name => {
+            let token = first_token(node);
+            Expr::Identifier { name: identifier(token), span: token.span, slot: None }
+        }
+        SyntaxKind::Binary => {
+            let mut operands = node.child_nodes().map(lower_expression);
+            let (left, right) = (operands.next().expect("left operand"), operands.next().expect("right operand"));
+            let operator = match &first_token(node).kind {
+                TokenType::Or => BinaryOp::Or,
+                TokenType::And => BinaryOp::And,
+                TokenType::Equal => BinaryOp::Equal,
+                TokenType::NotEqual => BinaryOp::NotEqual,
+                TokenType::Less => BinaryOp::Less,
+                TokenType::Greater => BinaryOp::Greater,
+                TokenType::LessEqual => BinaryOp::LessEqual,
+                TokenType::GreaterEqual => BinaryOp::GreaterEqual,
+                TokenType::In => BinaryOp::In,
+                TokenType::Not => BinaryOp::NotIn,
+                TokenType::Plus => BinaryOp::Add,
+                TokenType::Minus => BinaryOp::Subtract,
+                TokenType::Multiply => BinaryOp::Multiply,
+                TokenType::Divide => BinaryOp::Divide,
+                kind => unreachable!("{:?} is not a binary operator", kind),
+            };
+            let span = left.span().combine(&right.span());
+            Expr::Binary { left: Box::new(left), operator, right: Box::new(right), span }
+        }
+        SyntaxKind::Unary => {
+            let token = first_token(node);
+            let operator = match token.kind {
+                TokenType::Minus => UnaryOp::Minus,
+                TokenType::Plus => UnaryOp::Plus,
+                _ => UnaryOp::Not,
+            };
+            let operand = lower_expression(first_node(node));
+            let span = token.span.combine(&operand.span());
+            Expr::Unary { operator, operand: Box::new(operand), span }
+        }
+        // The parser keeps no trace of parentheses
+        SyntaxKind::Paren => lower_expression(first_node(node)),
+        SyntaxKind::Index => {
+            let mut operands = node.child_nodes().map(lower_expression);
+            let (object, index) = (operands.next().expect("indexed object"), operands.next().expect("index"));
+            let span = object.span().combine(&last_token(node).span);
+            Expr::Index { object: Box::new(object), index: Box::new(index), span }
+        }
+        SyntaxKind::Call => {
+            let name = first_token(node);
+            let mut args = Vec::new();
+            let mut kwargs = Vec::new();
+            for argument in node.child_nodes() {
+                let value = lower_expression(first_node(argument));
+                match argument.child_tokens().next() {
+                    Some(token) if token.kind == TokenType::DoubleStar => kwargs.push((None, value)),
+                    Some(token) if token.kind == TokenType::Multiply => {
+                        let span = token.span.combine(&value.span());
+                        args.push(Expr::Unpack { value: Box::new(value), span });
+                    }
+                    Some(keyword) => kwargs.push((Some(identifier(keyword)), value)),
+                    None => args.push(value),
+                }
+            }
+            let span = name.span.combine(&last_token(node).span);
+            Expr::FunctionCall { name: identifier(name), args, kwargs, span, builtin: false, slot: None }
+        }
+        SyntaxKind::List => {
+            let elements = node.child_nodes().map(lower_expression).collect();
+            let span = first_token(node).span.combine(&last_token(node).span);
+            Expr::List { elements, span }
+        }
+        SyntaxKind::Dictionary => {
+            let pairs = node.child_nodes()
+                .map(|entry| {
+                    let key = match &first_token(entry).kind {
+                        TokenType::String(key) => key.clone(),
+                        kind => unreachable!("{:?} is not a dictionary key", kind),
+                    };
+                    (key, lower_expression(first_node(entry)))
+                })
+                .collect();
+            let span = first_token(node).span.combine(&last_token(node).span);
+            Expr::Dictionary { pairs, span }
+        }
+        kind => unreachable!("{:?} is not an expression", kind),
+    }
+}
+
+fn first_node(node: &SyntaxNode) -> &SyntaxNode {
+    node.child_nodes().next().unwrap_or_else(|| panic!("{:?} node without a child node", node.kind()))
+}
+
+/// The first token that is a direct child of `node`.
+fn first_token(node: &SyntaxNode) -> &SyntaxToken {
+    node.child_tokens().next().unwrap_or_else(|| panic!("{:?} node without a token", node.kind()))
+}
+
+/// The last token that is a direct child of `node`, e.g. a closing bracket.
+fn last_token(node: &SyntaxNode) -> &SyntaxToken {
+    node.child_tokens().last().unwrap_or_else(|| panic!("{:?} node without a token", node.kind()))
+}
+
+fn identifier(token: &SyntaxToken) -> String {
+    match &token.kind {
+        TokenType::Identifier(name) => name.clone(),
+        kind => unreachable!("{:?} is not an identifier", kind),
+    }
+}
@@ -0,0 +1,104 @@
+//! # Syntax Module
+//!
+//! A lossless concrete syntax tree (CST) for tools that need the source as it
+//! was written: formatters, linters and editors. Unlike the AST, it keeps
+//! every character of the input.
+//!
+//! ## Shape
+//!
+//! - `SyntaxToken`: a token with its exact text (`1.50` stays `1.50`) and the
+//!   whitespace and `#` comments in front of it as `Trivia`
+//! - `SyntaxNode`: a `SyntaxKind` and its children, nodes and tokens in
+//!   source order. Parentheses, commas and separators are all there
+//! - The root is a `Program` node whose last child is the `Eof` token, which
+//!   carries any trailing whitespace and comments
+//!
+//! ## Guarantees
+//!
+//! `SyntaxTree::text` reproduces the input exactly, for any input, including
+//! invalid ones: text the parser could not make sense of ends up in `Error`
+//! nodes (or `TokenType::Error` tokens) instead of being dropped.
+//!
+//! The tree is built by `Parser` itself, over tokens that carry their text
+//! and trivia; the parser gets its `Program` by lowering the same tree, so
+//! `SyntaxTree::lower` returns exactly what `Parser::parse_with_recovery`
+//! does, spans included, and `SyntaxTree::errors` holds the same diagnostics.
+
+use crate::error::BcclError;
+use crate::parser::{Parser, Program};
+
+mod tree;
+mod tokens;
+mod lower;
+
+#[cfg(test)]
+mod tests;
+
+pub use tree::{SyntaxKind, SyntaxNode, SyntaxElement, SyntaxToken, Trivia, TriviaKind};
+pub(crate) use lower::{lower_statement, lower_expression};
+
+/// A parsed source file: the concrete syntax tree and the errors in it.
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::{SyntaxTree, TriviaKind};
+/// let source = "total = 1.50  # in euros\ntotal *= 2\n";
+/// let tree = SyntaxTree::parse(source);
+/// assert_eq!(tree.text(), source);
+/// assert!(tree.errors().is_empty());
+///
+/// let comment = tree.root().tokens().into_iter()
+///     .flat_map(|token| &token.leading_trivia)
+///     .find(|trivia| trivia.kind == TriviaKind::Comment)
+///     .unwrap();
+/// assert_eq!(comment.text, "# in euros");
+/// assert_eq!(tree.lower().statements.len(), 2);
+/// ```
+#[derive(Debug)]
+pub struct SyntaxTree {
+    root: SyntaxNode,
+    errors: Vec<BcclError>,
+}
+
+impl SyntaxTree {
+    /// Parses `source` into a syntax tree. Never fails; see `errors`.
+    pub fn parse(source: &str) -> Self {
+        Parser::from_syntax_tokens(tokens::lex(source)).parse_tree()
+    }
+
+    pub(crate) fn new(root: SyntaxNode, errors: Vec<BcclError>) -> Self {
+        Self { root, errors }
+    }
+
+    /// The `Program` node.
+    pub fn root(&self) -> &SyntaxNode {
+        &self.root
+    }
+
+    /// Every lexical and syntax error in the input, in source order, as
+    /// `Parser::parse_with_recovery` reports them.
+    pub fn errors(&self) -> &[BcclError] {
+        &self.errors
+    }
+
+    /// The errors, taking them out of the tree.
+    pub fn into_errors(self) -> Vec<BcclError> {
+        self.errors
+    }
+
+    /// The source text, byte for byte.
+    pub fn text(&self) -> String {
+        self.root.text()
+    }
+
+    /// Lowers the tree to the AST.
+    ///
+    /// # Returns
+    ///
+    /// The program `Parser::parse_with_recovery` returns for the same input:
+    /// when `errors` is empty, the program `Parser::parse` returns.
+    pub fn lower(&self) -> Program {
+        lower::lower_program(&self.root)
+    }
+}
@@ -0,0 +1,134 @@
+use super::*;
+use crate::{Lexer, TokenType};
+use crate::test_support::Rng;
+
+/// Inputs that exercise every construct, valid and not.
+const SCRIPTS: &[&str] = &[
+    "",
+    "   \n\n  ",
+    "# only a comment",
+    "x = 1",
+    "x = 1.50; y = 2e3\nx + y\n",
+    "  total = sum(*[1, 2, 3], **{\"k\": 1})   # trailing\n\n",
+    "prices = {\n    \"apple\": 1.5,   # per kg\n    \"pear\": 2,\n}\n",
+    "items = [\r\n  1,\r\n  2\r\n]\r\nitems += -items[1]\r\n",
+    "name = \"日本 🦀\\n\"  # ünïcode comment\nlen(name) not in [1, 2] or not false\n",
+    "f(a, b = 2, **c)\n((1 + 2)) * 3 / 4 - 5 >= 6 and 7 != 8",
+    "x = [1, 2\ny = 3",
+    "x = 1 y = 2",
+    "x = ) 1; z = 3",
+    "f(a = 1, 2)\n{\"a\" 1}\n[1,,2]",
+    "x = 1 @ 2.3.4 \"open",
+    "} } ] ) ;; \n = += not in",
+    "x = (1 +\n  # comment inside brackets\n  2)\n",
+    "count: int = 1\nname :str=\"a\"  # typed\nratio: any = count / 2",
+    "x: = 1\ny: int 2\nz: int = 3\n[w: int = 4]\n: int = 5",
+];
+
+/// Fragments to glue into random, mostly invalid, programs.
+const PIECES: &[&str] = &[
+    "x", "y", "f", "1", "2.5", "1e", "true", "\"s\"", "\"", "=", "+=", "+", "-", "*", "**", "/", "==",
+    "!", "!=", "<=", "not", "in", "and", "or", "(", ")", "[", "]", "{", "}", ",", ":", ";", "\n", "\r\n",
+    " ", "\t", "# note", "@", "é", "日本",
+];
+
+fn parser_result(source: &str) -> (Program, Vec<BcclError>) {
+    Parser::new(Lexer::new(source).tokenize_with_recovery()).parse_with_recovery()
+}
+
+fn trivia_of_kind(tree: &SyntaxTree, kind: TriviaKind) -> Vec<String> {
+    tree.root().tokens().into_iter()
+        .flat_map(|token| &token.leading_trivia)
+        .filter(|trivia| trivia.kind == kind)
+        .map(|trivia| trivia.text.clone())
+        .collect()
+}
+
+#[test]
+fn test_text_round_trips_scripts() {
+    for source in SCRIPTS {
+        assert_eq!(SyntaxTree::parse(source).text(), *source);
+    }
+}
+
+#[test]
+fn test_text_round_trips_random_input() {
+    let mut rng = Rng(0x5eed_0044);
+    for _ in 0..2000 {
+        let source: String = (0..rng.below(30)).map(|_| PIECES[rng.below(PIECES.len())]).collect();
+        let tree = SyntaxTree::parse(&source);
+        assert_eq!(tree.text(), source);
+        assert_eq!(tree.root().kind(), SyntaxKind::Program);
+    }
+}
+
+#[test]
+fn test_lowers_to_the_parsers_program() {
+    let mut rng = Rng(0x5eed_0045);
+    let random = (0..2000).map(|_| (0..rng.below(30)).map(|_| PIECES[rng.below(PIECES.len())]).collect::<String>());
+    for source in SCRIPTS.iter().map(|s| s.to_string()).chain(random) {
+        let tree = SyntaxTree::parse(&source);
+        let (program, errors) = parser_result(&source);
+        assert_eq!(tree.lower(), program, "{:?}", source);
+        assert_eq!(tree.errors().len(), errors.len(), "{:?}", source);
+    }
+}
+
+#[test]
+fn test_valid_program_lowers_like_parse() {
+    let source = "a = f(*xs, k = {\"x\": [1, (2)]})\n a += -(a[1]) # c\nnot a not in b";
+    let tree = SyntaxTree::parse(source);
+    assert!(tree.errors().is_empty());
+    let expected = Parser::new(Lexer::new(source).tokenize().unwrap()).parse().unwrap();
+    assert_eq!(tree.lower(), expected);
+}
+
+#[test]
+fn test_comments_and_whitespace_are_trivia() {
+    let tree = SyntaxTree::parse("x = 1  # one\n\n# two\ny = [\n  2, # three\n]\n  # end");
+    assert_eq!(trivia_of_kind(&tree, TriviaKind::Comment), vec!["# one", "# two", "# three", "# end"]);
+
+    let eof = tree.root().tokens().pop().unwrap();
+    assert_eq!(eof.kind, TokenType::Eof);
+    assert_eq!(eof.text, "");
+    assert_eq!(eof.leading_trivia.last().unwrap().text, "# end");
+}
+
+#[test]
+fn test_tokens_keep_their_spelling() {
+    let tree = SyntaxTree::parse("x = 1.50 + 0010 + \"a\\tb\"");
+    let texts: Vec<&str> = tree.root().tokens().into_iter().map(|token| token.text.as_str()).collect();
+    assert_eq!(texts, vec!["x", "=", "1.50", "+", "0010", "+", "\"a\\tb\"", ""]);
+}
+
+#[test]
+fn test_tree_shape() {
+    let tree = SyntaxTree::parse("x = (1 + 2) * f(a, *b)");
+    let statement = tree.root().child_nodes().next().unwrap();
+    assert_eq!(statement.kind(), SyntaxKind::AssignmentStmt);
+
+    let product = statement.child_nodes().next().unwrap();
+    assert_eq!(product.kind(), SyntaxKind::Binary);
+    let operands: Vec<SyntaxKind> = product.child_nodes().map(|node| node.kind()).collect();
+    assert_eq!(operands, vec![SyntaxKind::Paren, SyntaxKind::Call]);
+
+    let call = product.child_nodes().nth(1).unwrap();
+    assert_eq!(call.text(), " f(a, *b)");
+    assert_eq!(call.span(), Some(crate::Span::new(14, 22)));
+    assert_eq!(call.child_nodes().filter(|node| node.kind() == SyntaxKind::Argument).count(), 2);
+}
+
+#[test]
+fn test_errors_keep_their_text() {
+    let source = "x = ) 1\ny = 2\nz = [1,";
+    let tree = SyntaxTree::parse(source);
+    assert_eq!(tree.errors().len(), 2);
+
+    let kinds: Vec<SyntaxKind> = tree.root().child_nodes().map(|node| node.kind()).collect();
+    assert_eq!(kinds, vec![SyntaxKind::Error, SyntaxKind::AssignmentStmt, SyntaxKind::Error]);
+    let broken: Vec<String> = tree.root().child_nodes()
+        .filter(|node| node.kind() == SyntaxKind::Error)
+        .map(|node| node.text())
+        .collect();
+    assert_eq!(broken, vec!["x = ) 1\n", "z = [1,"]);
+}
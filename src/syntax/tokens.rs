@@ -0,0 +1,40 @@
+use crate::lexer::Lexer;
+use super::tree::{SyntaxToken, Trivia, TriviaKind};
+
+/// Lexes `source` without stopping at errors and attaches the text between
+/// tokens to the following token as trivia. The text of every token plus its
+/// trivia, in order, is exactly `source`; the `Eof` token has no text and
+/// carries whatever trails the last real token.
+pub(crate) fn lex(source: &str) -> Vec<SyntaxToken> {
+    let chars: Vec<char> = source.chars().collect();
+    let slice = |start: usize, end: usize| chars[start..end].iter().collect::<String>();
+    let mut position = 0;
+
+    Lexer::new(source)
+        .tokenize_with_recovery()
+        .into_iter()
+        .map(|token| {
+            let start = token.span.start.clamp(position, chars.len());
+            let end = token.span.end.clamp(start, chars.len());
+            let leading_trivia = trivia(&chars[position..start]);
+            position = end;
+            SyntaxToken { kind: token.token_type, text: slice(start, end), span: token.span, leading_trivia }
+        })
+        .collect()
+}
+
+/// Splits the text between two tokens into comments and whitespace runs.
+fn trivia(chars: &[char]) -> Vec<Trivia> {
+    let mut pieces = Vec::new();
+    let mut rest = chars;
+    while let Some(&first) = rest.first() {
+        let (kind, len) = if first == '#' {
+            (TriviaKind::Comment, rest.iter().position(|&c| c == '\n').unwrap_or(rest.len()))
+        } else {
+            (TriviaKind::Whitespace, rest.iter().position(|&c| c == '#').unwrap_or(rest.len()))
+        };
+        pieces.push(Trivia { kind, text: rest[..len].iter().collect() });
+        rest = &rest[len..];
+    }
+    pieces
+}
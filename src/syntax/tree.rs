@@ -0,0 +1,171 @@
+use crate::error::Span;
+use crate::lexer::TokenType;
+
+/// The grammar construct a `SyntaxNode` stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxKind {
+    /// The whole input; its last child is the `Eof` token
+    Program,
+    /// `name = value`, or `name: type = value` with an `Annotation`
+    AssignmentStmt,
+    /// `name += value` and the other compound operators
+    CompoundAssignmentStmt,
+    /// An expression used as a statement
+    ExpressionStmt,
+    /// A number, integer, boolean or string literal
+    Literal,
+    /// A variable reference
+    Name,
+    /// Two operands and the operator token(s) between them (`not in` is two)
+    Binary,
+    /// A prefix operator and its operand
+    Unary,
+    /// A parenthesized expression
+    Paren,
+    /// `name(arguments)`; the arguments are `Argument` nodes
+    Call,
+    /// One call argument, with its `*`, `**` or `name =` prefix if any
+    Argument,
+    /// `[elements]`
+    List,
+    /// `{entries}`; the entries are `DictEntry` nodes
+    Dictionary,
+    /// `"key": value`
+    DictEntry,
+    /// `object[index]`
+    Index,
+    /// `: type` in an annotated assignment
+    Annotation,
+    /// A statement that failed to parse, up to where the parser resumed
+    Error,
+}
+
+/// Whether a piece of trivia is whitespace or a comment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// Spaces, tabs and line breaks that do not end a statement
+    Whitespace,
+    /// A `#` comment, up to but not including the line break
+    Comment,
+}
+
+/// Source text between tokens that the parser does not look at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+}
+
+/// A token with its exact source text and the trivia in front of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxToken {
+    /// The token as the parser sees it
+    pub kind: TokenType,
+    /// The token's text as written, e.g. `1.50` or `"a\tb"` with its quotes
+    pub text: String,
+    /// Character offsets of `text` in the input
+    pub span: Span,
+    /// Whitespace and comments between the previous token and this one
+    pub leading_trivia: Vec<Trivia>,
+}
+
+impl SyntaxToken {
+    /// Appends the leading trivia and the token text to `out`.
+    fn write_text(&self, out: &mut String) {
+        for trivia in &self.leading_trivia {
+            out.push_str(&trivia.text);
+        }
+        out.push_str(&self.text);
+    }
+}
+
+/// A child of a `SyntaxNode`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+/// An interior node of the syntax tree: a kind and its children in source
+/// order.
+///
+/// Nodes are only built by `SyntaxTree::parse`, so a node of a given kind
+/// always has the children its grammar rule produces, except inside `Error`
+/// nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxNode {
+    kind: SyntaxKind,
+    children: Vec<SyntaxElement>,
+}
+
+impl SyntaxNode {
+    pub(crate) fn new(kind: SyntaxKind, children: Vec<SyntaxElement>) -> Self {
+        Self { kind, children }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn children(&self) -> &[SyntaxElement] {
+        &self.children
+    }
+
+    /// The child nodes, skipping tokens.
+    pub fn child_nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// The tokens that are direct children, skipping nodes.
+    pub fn child_tokens(&self) -> impl Iterator<Item = &SyntaxToken> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Token(token) => Some(token),
+            SyntaxElement::Node(_) => None,
+        })
+    }
+
+    /// Every token under this node, in source order.
+    pub fn tokens(&self) -> Vec<&SyntaxToken> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(&mut tokens);
+        tokens
+    }
+
+    fn collect_tokens<'a>(&'a self, tokens: &mut Vec<&'a SyntaxToken>) {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.collect_tokens(tokens),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+    }
+
+    /// The node's source text, including the trivia in front of each of its
+    /// tokens.
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        self.write_text(&mut out);
+        out
+    }
+
+    fn write_text(&self, out: &mut String) {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.write_text(out),
+                SyntaxElement::Token(token) => token.write_text(out),
+            }
+        }
+    }
+
+    /// From the start of the first token to the end of the last, without
+    /// leading trivia. `None` for a node without tokens.
+    pub fn span(&self) -> Option<Span> {
+        let tokens = self.tokens();
+        let first = tokens.first()?;
+        let last = tokens.last()?;
+        Some(first.span.combine(&last.span))
+    }
+}
@@ -1,5 +1,79 @@
 /// Test error recovery and state consistency
-use crate::{Lexer, Parser, Evaluator};
+use miette::Diagnostic;
+use crate::{Lexer, Parser, Evaluator, BcclError, Stmt};
+
+/// Parses `source` and returns the offset of every syntax error, in order.
+fn error_offsets(source: &str) -> Vec<usize> {
+    let tokens = Lexer::new(source).tokenize().unwrap();
+    let error = Parser::new(tokens).parse().unwrap_err();
+    error.into_errors().iter().map(|error| match error {
+        BcclError::UnexpectedToken { span, .. } | BcclError::UnexpectedEof { span, .. } => span.offset(),
+        other => panic!("Expected a syntax error, got: {:?}", other),
+    }).collect()
+}
+
+/// Names assigned by the statements that survived recovery.
+fn assigned_names(source: &str) -> (Vec<String>, usize) {
+    let tokens = Lexer::new(source).tokenize().unwrap();
+    let (program, errors) = Parser::new(tokens).parse_with_recovery();
+    let names = program.statements.iter().filter_map(|stmt| match stmt {
+        Stmt::Assignment { name, .. } | Stmt::CompoundAssignment { name, .. } => Some(name.clone()),
+        Stmt::Expression { .. } => None,
+    }).collect();
+    (names, errors.len())
+}
+
+#[test]
+fn test_parser_reports_every_syntax_error() {
+    let source = "x = (1 + ; y = [1, 2 z = 3; w = )";
+    let tokens = Lexer::new(source).tokenize().unwrap();
+    let error = Parser::new(tokens).parse().unwrap_err();
+    
+    match &error {
+        BcclError::Multiple { errors } => {
+            assert_eq!(errors.len(), 3);
+            assert_eq!(error.related().map(|related| related.count()), Some(3));
+            assert_eq!(error.to_string(), "found 3 problems");
+        }
+        other => panic!("Expected Multiple, got: {:?}", other),
+    }
+    assert_eq!(error_offsets(source), vec![9, 21, 32]);
+}
+
+#[test]
+fn test_single_syntax_error_is_not_wrapped() {
+    let tokens = Lexer::new("a = 1; b = (2 +").tokenize().unwrap();
+    match Parser::new(tokens).parse() {
+        Err(BcclError::UnexpectedEof { .. }) => {}
+        other => panic!("Expected UnexpectedEof, got: {:?}", other),
+    }
+    assert_eq!(error_offsets("a = 1; b = (2 +").len(), 1);
+}
+
+#[test]
+fn test_recovery_keeps_valid_statements() {
+    assert_eq!(assigned_names("a = 1; b = (2 + ; c = 3"), (vec!["a".to_string(), "c".to_string()], 1));
+    assert_eq!(assigned_names("a = ]\nb = 2\nc += )\nd = 4"), (vec!["b".to_string(), "d".to_string()], 2));
+    // `b = 2 c += ...` also reports the missing separator, but keeps `b`
+    assert_eq!(assigned_names("a = ] b = 2 c += ) d = 4"), (vec!["b".to_string(), "d".to_string()], 3));
+    // One error per broken statement, however many tokens are skipped
+    assert_eq!(assigned_names(") ) ) e = 5"), (vec!["e".to_string()], 1));
+}
+
+#[test]
+fn test_recovery_at_closing_brace() {
+    let source = r#"d = {"a": 1 2}; e = 1; f = ]"#;
+    assert_eq!(error_offsets(source), vec![12, 27]);
+    assert_eq!(assigned_names(source), (vec!["e".to_string()], 2));
+}
+
+#[test]
+fn test_keyword_arguments_do_not_end_recovery() {
+    // `key=value` inside the call is not a new statement
+    let source = "r = f(1 2, key=3); s = 1";
+    assert_eq!(error_offsets(source), vec![8]);
+    assert_eq!(assigned_names(source), (vec!["s".to_string()], 1));
+}
 
 #[test]
 fn test_state_preservation_after_error() {
@@ -165,4 +165,68 @@ fn test_key_not_found() {
     } else {
         panic!("Expected KeyNotFound error");
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_conversion_error_points_at_argument() {
+    let source = r#"x = int("4x2")"#;
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().unwrap();
+    let mut evaluator = Evaluator::new();
+    
+    let result = evaluator.evaluate_program(&program);
+    
+    match result {
+        Err(BcclError::ConversionError { message, target_type, span, label, .. }) => {
+            assert_eq!(target_type, "integer");
+            assert!(message.contains("\"4x2\""), "message should quote the text: {}", message);
+            assert!(label.contains("\"4x2\""));
+            // The label covers exactly the argument, not the whole call
+            assert_eq!(&source[span.offset()..span.offset() + span.len()], r#""4x2""#);
+        }
+        other => panic!("Expected ConversionError, got: {:?}", other),
+    }
+    
+    // The failed assignment must not define the variable
+    assert!(evaluator.get_variable("x").is_none());
+}
+
+#[test]
+fn test_sum_and_product_errors_point_at_argument() {
+    for source in [r#"sum(1, "two", 3)"#, r#"product(1, "two", 3)"#] {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        match Evaluator::new().evaluate_program(&program) {
+            Err(BcclError::FunctionArgumentTypeError { span, .. }) => {
+                assert_eq!(&source[span.offset()..span.offset() + span.len()], r#""two""#, "{}", source);
+            }
+            other => panic!("Expected FunctionArgumentTypeError for {}, got: {:?}", source, other),
+        }
+    }
+}
+
+#[test]
+fn test_callback_error_notes_calling_builtin() {
+    let source = r#"map(int, ["1", "x"])"#;
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().unwrap();
+    let mut evaluator = Evaluator::new();
+    
+    let result = evaluator.evaluate_program(&program);
+    
+    match result {
+        Err(BcclError::CallbackError { function_name, span, errors }) => {
+            assert_eq!(function_name, "map");
+            // The note points at the whole map() call
+            assert_eq!(span.offset(), 0);
+            assert_eq!(span.len(), source.len());
+            assert_eq!(errors.len(), 1);
+            assert!(matches!(errors[0], BcclError::ConversionError { .. }), "got: {:?}", errors[0]);
+        }
+        other => panic!("Expected CallbackError, got: {:?}", other),
+    }
+}
@@ -80,7 +80,7 @@ fn test_nested_collection_errors() {
 
 #[test]
 fn test_function_with_wrong_args() {
-    let mut lexer = Lexer::new("max(1)");
+    let mut lexer = Lexer::new("map(str)");
     let tokens = lexer.tokenize().unwrap();
     let mut parser = Parser::new(tokens);
     let program = parser.parse().unwrap();
@@ -93,10 +93,10 @@ fn test_function_with_wrong_args() {
     // results in a MissingParameter error rather than WrongArgumentCount
     match result {
         Err(BcclError::FunctionArgumentError { function_name, .. }) => {
-            assert_eq!(function_name, "max");
+            assert_eq!(function_name, "map");
         }
         Err(BcclError::WrongArgumentCount { function_name, expected, actual, .. }) => {
-            assert_eq!(function_name, "max");
+            assert_eq!(function_name, "map");
             assert_eq!(expected, 2);
             assert_eq!(actual, 1);
         }
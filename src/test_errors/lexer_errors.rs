@@ -30,7 +30,7 @@ fn test_malformed_number_error() {
 #[test]
 fn test_multiple_invalid_characters() {
     let test_cases = vec![
-        ("x # y", '#'),
+        ("x ? y", '?'),
         ("a $ b", '$'),
         ("test & value", '&'),
         ("foo % bar", '%'),
@@ -45,4 +45,32 @@ fn test_multiple_invalid_characters() {
             assert!(message.contains(&format!("Invalid character '{}'", expected_char)));
         }
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_every_lexical_and_syntax_problem_is_reported() {
+    use crate::{Parser, Stmt};
+    
+    let source = "a = 1 $ 2; b = (3 +; c = 4.5.6; d = 7 & 8 ! 9; e = 10";
+    let tokens = Lexer::new(source).tokenize_with_recovery();
+    let (program, errors) = Parser::new(tokens).parse_with_recovery();
+    
+    let found: Vec<(String, usize)> = errors.iter().map(|error| match error {
+        BcclError::LexError { message, span } => (message.clone(), span.offset()),
+        BcclError::UnexpectedToken { span, .. } => ("syntax".to_string(), span.offset()),
+        other => panic!("Unexpected error: {:?}", other),
+    }).collect();
+    assert_eq!(found, vec![
+        ("Invalid character '$'".to_string(), 6),
+        ("syntax".to_string(), 19),
+        ("Malformed number '4.5.6'".to_string(), 25),
+        ("Invalid character '&'".to_string(), 38),
+        ("Invalid character '!'".to_string(), 42),
+    ]);
+    
+    // Statements around the problems still parse
+    let names: Vec<&str> = program.statements.iter().filter_map(|stmt| match stmt {
+        Stmt::Assignment { name, .. } => Some(name.as_str()),
+        _ => None,
+    }).collect();
+    assert_eq!(names, vec!["a", "d", "e"]);
+}
@@ -1,9 +1,9 @@
-/// Comprehensive error testing module for BCCL interpreter
-/// 
-/// This module contains test cases that demonstrate the enhanced error handling
-/// and diagnostics capabilities of the BCCL interpreter.
+//! Comprehensive error testing module for BCCL interpreter
+//! 
+//! This module contains test cases that demonstrate the enhanced error handling
+//! and diagnostics capabilities of the BCCL interpreter.
 
-use crate::{Lexer, Parser, Evaluator, BcclError};
+use crate::{Lexer, Parser, BcclError};
 
 mod lexer_errors;
 mod parser_errors;
@@ -155,4 +155,40 @@ fn test_kwargs_unknown_parameter() {
         }
         other => panic!("Expected FunctionArgumentError for wrong parameter names, got: {:?}", other),
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_multi_line_program_parses() {
+    use crate::Stmt;
+    
+    let source = "prices = {\n  \"a\": 1.5,\n  \"b\": 2\n}\n\ntotal = prices[\"a\"] +\n  prices[\"b\"]\ntotal *= 2; label = \"x\"\nlabel\n";
+    let tokens = Lexer::new(source).tokenize().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+    
+    assert_eq!(program.statements.len(), 5);
+    assert!(matches!(&program.statements[1], Stmt::Assignment { name, .. } if name == "total"));
+    assert!(matches!(&program.statements[4], Stmt::Expression { .. }));
+}
+
+#[test]
+fn test_two_statements_on_one_line() {
+    let tokens = Lexer::new("x = 1 y = 2").tokenize().unwrap();
+    match Parser::new(tokens).parse() {
+        Err(BcclError::MissingSeparator { span }) => assert_eq!(span.offset(), 6),
+        other => panic!("Expected MissingSeparator, got: {:?}", other),
+    }
+    
+    // Both statements are still parsed, so tools see them
+    let tokens = Lexer::new("print(1) print(2)").tokenize().unwrap();
+    let (program, errors) = Parser::new(tokens).parse_with_recovery();
+    assert_eq!(program.statements.len(), 2);
+    assert_eq!(errors.len(), 1);
+    
+    // Anything that cannot start a statement is an ordinary syntax error
+    let tokens = Lexer::new("x = 1 )").tokenize().unwrap();
+    match Parser::new(tokens).parse() {
+        Err(BcclError::UnexpectedToken { expected, .. }) => {
+            assert_eq!(expected, vec![";".to_string(), "new line".to_string()]);
+        }
+        other => panic!("Expected UnexpectedToken, got: {:?}", other),
+    }
+}
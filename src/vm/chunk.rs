@@ -0,0 +1,173 @@
+//! Instructions and compiled chunks.
+
+use std::fmt;
+use crate::parser::{BinaryOp, UnaryOp, CompoundOp};
+use crate::error::Span;
+use crate::evaluator::Value;
+
+/// A single VM instruction.
+///
+/// Operands are indices into the pools of the `Chunk` that holds the
+/// instruction. Every instruction that pushes a value pushes exactly one,
+/// tagged with the instruction's span from the span table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    /// Pushes `constants[index]`
+    Constant(u32),
+    /// Pushes the variable or built-in function `names[index]`
+    LoadName(u32),
+    /// Pushes the variable `names[index]` (compound assignment; no built-ins)
+    LoadVariable(u32),
+    /// Stores the top of the stack in `names[index]`, leaving it on the stack
+    StoreName(u32),
+    /// Pops the right-hand side and the current value, pushes the result of
+    /// the compound operator on `names[index]`
+    Compound(CompoundOp, u32),
+    /// Pops one value and pushes the result of the operator
+    Unary(UnaryOp),
+    /// Pops two values and pushes the result of the operator
+    Binary(BinaryOp),
+    /// Pops an index and a collection and pushes the element
+    Index,
+    /// Pops `count` values and pushes them as a list
+    BuildList(u32),
+    /// Pops one value per key in `key_sets[index]` and pushes a dictionary
+    BuildDict(u32),
+    /// Checks that the top of the stack is a list (for `*xs`)
+    CheckUnpack,
+    /// Checks that the top of the stack is a dictionary (for `**d`)
+    CheckUnpackKeywords,
+    /// Pops the arguments described by `calls[index]` and pushes the result
+    Call(u32),
+    /// Discards the top of the stack
+    Pop,
+    /// Ends the program, returning the top of the stack (if any)
+    Return,
+}
+
+/// How one positional argument of a call was written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallArgument {
+    /// A plain argument: one value, at the span of its expression
+    Value,
+    /// `*xs`: the items of a list, each at the span of the whole `*xs`
+    Unpack(Span),
+}
+
+/// The shape of a call site: the callee name and how its arguments are
+/// laid out on the stack (positional arguments first, then keywords).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallShape {
+    /// Name of the called function or variable
+    pub name: String,
+    /// Index of `name` in `Chunk::names`, used to find the callee's slot
+    pub name_index: u32,
+    /// Whether the optimizer resolved `name` to a registered function
+    pub builtin: bool,
+    /// Positional arguments in source order
+    pub args: Vec<CallArgument>,
+    /// Keyword arguments in source order; `None` is a `**d` unpack
+    pub keywords: Vec<Option<String>>,
+}
+
+impl CallShape {
+    /// Number of stack slots the call's arguments occupy.
+    pub fn arity(&self) -> usize {
+        self.args.len() + self.keywords.len()
+    }
+    
+    /// Whether the arguments are plain positional values, which the VM can
+    /// pass straight from its stack.
+    pub fn is_simple(&self) -> bool {
+        self.keywords.is_empty() && self.args.iter().all(|arg| *arg == CallArgument::Value)
+    }
+}
+
+/// A compiled program.
+///
+/// `code[i]` is located in the source by `spans[i]`; this span table is how
+/// runtime errors from the VM are reported at the right place by miette.
+#[derive(Debug, Clone, Default)]
+pub struct Chunk {
+    /// The instructions
+    pub code: Vec<Instruction>,
+    /// Span table: source span of each instruction
+    pub spans: Vec<Span>,
+    /// Statement table: the first instruction and the span of each statement
+    pub statements: Vec<(usize, Span)>,
+    /// Step table: for each statement and expression node, the instruction
+    /// before which its `EvaluatorLimits` step is counted, and its span. The
+    /// entries are in the order the tree-walker counts them
+    pub steps: Vec<(usize, Span)>,
+    /// Literal values
+    pub constants: Vec<Value>,
+    /// Variable and function names
+    pub names: Vec<String>,
+    /// Key lists of dictionary literals
+    pub key_sets: Vec<Vec<String>>,
+    /// Call sites
+    pub calls: Vec<CallShape>,
+}
+
+impl Chunk {
+    /// Returns the source span of the instruction at `ip`.
+    pub fn span_at(&self, ip: usize) -> Span {
+        self.spans[ip]
+    }
+    
+    /// Renders the chunk as human-readable assembly, one instruction per line.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bccl::{Compiler, Lexer, Parser};
+    /// let tokens = Lexer::new("1 + x").tokenize().unwrap();
+    /// let program = Parser::new(tokens).parse().unwrap();
+    /// let chunk = Compiler::compile(&program).unwrap();
+    /// assert_eq!(chunk.disassemble(), "\
+    /// 0000  0..1    CONSTANT 0 (1)
+    /// 0001  4..5    LOAD_NAME 0 (x)
+    /// 0002  0..5    BINARY Add
+    /// 0003  0..5    RETURN
+    /// ");
+    /// ```
+    pub fn disassemble(&self) -> String {
+        let mut output = String::new();
+        for (ip, instruction) in self.code.iter().enumerate() {
+            let span = self.spans[ip];
+            let location = format!("{}..{}", span.start, span.end);
+            output.push_str(&format!("{:04}  {:<7} {}\n", ip, location, self.describe(instruction)));
+        }
+        output
+    }
+    
+    /// Renders one instruction with its operands resolved.
+    fn describe(&self, instruction: &Instruction) -> String {
+        match instruction {
+            Instruction::Constant(i) => format!("CONSTANT {} ({})", i, self.constants[*i as usize].display()),
+            Instruction::LoadName(i) => format!("LOAD_NAME {} ({})", i, self.names[*i as usize]),
+            Instruction::LoadVariable(i) => format!("LOAD_VARIABLE {} ({})", i, self.names[*i as usize]),
+            Instruction::StoreName(i) => format!("STORE_NAME {} ({})", i, self.names[*i as usize]),
+            Instruction::Compound(op, i) => format!("COMPOUND {:?} {} ({})", op, i, self.names[*i as usize]),
+            Instruction::Unary(op) => format!("UNARY {:?}", op),
+            Instruction::Binary(op) => format!("BINARY {:?}", op),
+            Instruction::Index => "INDEX".to_string(),
+            Instruction::BuildList(count) => format!("BUILD_LIST {}", count),
+            Instruction::BuildDict(i) => format!("BUILD_DICT {} ({})", i, self.key_sets[*i as usize].join(", ")),
+            Instruction::CheckUnpack => "CHECK_UNPACK".to_string(),
+            Instruction::CheckUnpackKeywords => "CHECK_UNPACK_KEYWORDS".to_string(),
+            Instruction::Call(i) => {
+                let shape = &self.calls[*i as usize];
+                format!("CALL {} ({}, {} args, {} keywords)", i, shape.name, shape.args.len(), shape.keywords.len())
+            }
+            Instruction::Pop => "POP".to_string(),
+            Instruction::Return => "RETURN".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Chunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.disassemble())
+    }
+}
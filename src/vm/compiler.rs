@@ -0,0 +1,188 @@
+//! Lowering of a `Program` into a `Chunk`.
+
+use std::collections::HashMap;
+use crate::parser::{Expr, Stmt, Program};
+use crate::error::{BcclResult, Span};
+use crate::evaluator::Value;
+use crate::evaluator::operations;
+use super::chunk::{CallArgument, CallShape, Chunk, Instruction};
+
+/// Compiles programs to bytecode.
+///
+/// Expressions are compiled in evaluation order (left to right, operands
+/// before operators), and the last instruction of every expression carries
+/// that expression's span.
+///
+/// # Example
+///
+/// ```rust
+/// # use bccl::{Compiler, Lexer, Parser};
+/// let tokens = Lexer::new("max(1, 2)").tokenize().unwrap();
+/// let program = Parser::new(tokens).parse().unwrap();
+/// let chunk = Compiler::compile(&program).unwrap();
+/// assert_eq!(chunk.code.len(), 4); // two constants, the call, return
+/// ```
+#[derive(Default)]
+pub struct Compiler {
+    chunk: Chunk,
+    /// Index of each name already in `chunk.names`
+    name_indices: HashMap<String, u32>,
+    /// Spans of the nodes entered since the last instruction, whose steps
+    /// are counted before the next one
+    pending_steps: Vec<Span>,
+}
+
+impl Compiler {
+    /// Compiles a whole program.
+    ///
+    /// Each statement leaves its value on the stack; all but the last are
+    /// popped, and the final `Return` hands the last one back.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(chunk)` - The compiled program
+    /// * `Err(error)` - For ASTs the parser never produces, such as `*xs`
+    ///   outside call arguments
+    pub fn compile(program: &Program) -> BcclResult<Chunk> {
+        let mut compiler = Compiler::default();
+        let count = program.statements.len();
+        for (i, stmt) in program.statements.iter().enumerate() {
+            compiler.chunk.statements.push((compiler.chunk.code.len(), stmt.span()));
+            compiler.compile_statement(stmt)?;
+            if i + 1 < count {
+                compiler.emit(Instruction::Pop, stmt.span());
+            }
+        }
+        let end = program.statements.last()
+            .map(|stmt| stmt.span())
+            .unwrap_or(Span::zero_width(0));
+        compiler.emit(Instruction::Return, end);
+        Ok(compiler.chunk)
+    }
+    
+    fn compile_statement(&mut self, stmt: &Stmt) -> BcclResult<()> {
+        self.pending_steps.push(stmt.span());
+        match stmt {
+            Stmt::Expression { expr, span: _ } => self.compile_expression(expr),
+            Stmt::Assignment { name, value, span, .. } => {
+                self.compile_expression(value)?;
+                let index = self.name(name);
+                self.emit(Instruction::StoreName(index), *span);
+                Ok(())
+            }
+            Stmt::CompoundAssignment { name, operator, value, span, .. } => {
+                // The variable is looked up before the right-hand side runs
+                let index = self.name(name);
+                self.emit(Instruction::LoadVariable(index), *span);
+                self.compile_expression(value)?;
+                self.emit(Instruction::Compound(*operator, index), *span);
+                self.emit(Instruction::StoreName(index), *span);
+                Ok(())
+            }
+        }
+    }
+    
+    fn compile_expression(&mut self, expr: &Expr) -> BcclResult<()> {
+        let span = expr.span();
+        // Like the tree-walker, a node's step comes before its operands'
+        self.pending_steps.push(span);
+        match expr {
+            Expr::Number { value, .. } => self.constant(Value::Number(*value), span),
+            Expr::Integer { value, .. } => self.constant(Value::Integer(*value), span),
+            Expr::Boolean { value, .. } => self.constant(Value::Boolean(*value), span),
+            Expr::String { value, .. } => self.constant(Value::string(value.clone()), span),
+            Expr::Identifier { name, .. } => {
+                let index = self.name(name);
+                self.emit(Instruction::LoadName(index), span);
+            }
+            Expr::Binary { left, operator, right, .. } => {
+                self.compile_expression(left)?;
+                self.compile_expression(right)?;
+                self.emit(Instruction::Binary(*operator), span);
+            }
+            Expr::Unary { operator, operand, .. } => {
+                self.compile_expression(operand)?;
+                self.emit(Instruction::Unary(*operator), span);
+            }
+            Expr::FunctionCall { name, args, kwargs, builtin, .. } => {
+                let name_index = self.name(name);
+                let mut shape = CallShape {
+                    name: name.clone(),
+                    name_index,
+                    builtin: *builtin,
+                    args: Vec::with_capacity(args.len()),
+                    keywords: Vec::with_capacity(kwargs.len()),
+                };
+                for arg in args {
+                    if let Expr::Unpack { value, span: unpack_span } = arg {
+                        self.compile_expression(value)?;
+                        self.emit(Instruction::CheckUnpack, value.span());
+                        shape.args.push(CallArgument::Unpack(*unpack_span));
+                    } else {
+                        self.compile_expression(arg)?;
+                        shape.args.push(CallArgument::Value);
+                    }
+                }
+                for (param_name, value_expr) in kwargs {
+                    self.compile_expression(value_expr)?;
+                    if param_name.is_none() {
+                        self.emit(Instruction::CheckUnpackKeywords, value_expr.span());
+                    }
+                    shape.keywords.push(param_name.clone());
+                }
+                let index = self.chunk.calls.len() as u32;
+                self.chunk.calls.push(shape);
+                self.emit(Instruction::Call(index), span);
+            }
+            Expr::Unpack { .. } => return Err(operations::unpack_outside_call(span)),
+            Expr::List { elements, .. } => {
+                for element in elements {
+                    self.compile_expression(element)?;
+                }
+                self.emit(Instruction::BuildList(elements.len() as u32), span);
+            }
+            Expr::Dictionary { pairs, .. } => {
+                let mut keys = Vec::with_capacity(pairs.len());
+                for (key, value_expr) in pairs {
+                    self.compile_expression(value_expr)?;
+                    keys.push(key.clone());
+                }
+                let index = self.chunk.key_sets.len() as u32;
+                self.chunk.key_sets.push(keys);
+                self.emit(Instruction::BuildDict(index), span);
+            }
+            Expr::Index { object, index, .. } => {
+                self.compile_expression(object)?;
+                self.compile_expression(index)?;
+                self.emit(Instruction::Index, span);
+            }
+        }
+        Ok(())
+    }
+    
+    /// Appends an instruction and its entry in the span table. The steps of
+    /// the nodes entered since the previous instruction are counted before it.
+    fn emit(&mut self, instruction: Instruction, span: Span) {
+        let ip = self.chunk.code.len();
+        self.chunk.steps.extend(self.pending_steps.drain(..).map(|step| (ip, step)));
+        self.chunk.code.push(instruction);
+        self.chunk.spans.push(span);
+    }
+    
+    fn constant(&mut self, value: Value, span: Span) {
+        let index = self.chunk.constants.len() as u32;
+        self.chunk.constants.push(value);
+        self.emit(Instruction::Constant(index), span);
+    }
+    
+    /// Returns the index of `name` in the name pool, adding it if needed.
+    fn name(&mut self, name: &str) -> u32 {
+        if let Some(index) = self.name_indices.get(name) {
+            return *index;
+        }
+        let index = self.chunk.names.len() as u32;
+        self.chunk.names.push(name.to_string());
+        self.name_indices.insert(name.to_string(), index);
+        index
+    }
+}
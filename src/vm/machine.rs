@@ -0,0 +1,200 @@
+//! The stack machine that runs compiled chunks.
+
+use std::collections::HashMap;
+use crate::error::{BcclResult, Span};
+use crate::evaluator::{Evaluator, Value};
+use crate::evaluator::operations;
+use crate::parser::Slot;
+use super::chunk::{CallArgument, Chunk, Instruction};
+
+/// A stack-based virtual machine bound to an evaluator.
+///
+/// The VM reads and writes the evaluator's variables and calls its functions,
+/// so a REPL session can switch backends without losing state. Every stack
+/// slot holds a value together with the span of the expression that produced
+/// it; operators use those spans for their errors, exactly as the tree-walker
+/// uses `expr.span()`.
+pub struct Vm<'a> {
+    evaluator: &'a mut Evaluator,
+    stack: Vec<(Value, Span)>,
+}
+
+impl<'a> Vm<'a> {
+    /// Creates a VM that runs chunks against `evaluator`.
+    pub fn new(evaluator: &'a mut Evaluator) -> Self {
+        Self {
+            evaluator,
+            stack: Vec::new(),
+        }
+    }
+    
+    /// Runs a chunk to completion.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(value))` - The value of the last statement
+    /// * `Ok(None)` - If the program was empty
+    /// * `Err(error)` - The first runtime error; earlier statements keep
+    ///   their effects, as with the tree-walker
+    pub fn run(&mut self, chunk: &Chunk) -> BcclResult<Option<Value>> {
+        self.stack.clear();
+        
+        // Map the chunk's name table to variable slots once per run
+        let mut assigned = vec![false; chunk.names.len()];
+        for instruction in &chunk.code {
+            if let Instruction::StoreName(index) = instruction {
+                assigned[*index as usize] = true;
+            }
+        }
+        let slots: Vec<Option<Slot>> = chunk.names.iter()
+            .zip(assigned)
+            .map(|(name, assigned)| Some(self.evaluator.slot_for(name, assigned)))
+            .collect();
+        
+        let mut statements = chunk.statements.iter().peekable();
+        let mut steps = chunk.steps.iter().peekable();
+        for (ip, instruction) in chunk.code.iter().enumerate() {
+            if let Some((_, statement_span)) = statements.next_if(|(start, _)| *start == ip) {
+                self.evaluator.enter_statement(*statement_span)?;
+            }
+            // Steps are counted per AST node, as on the tree-walker
+            while let Some((_, step_span)) = steps.next_if(|(at, _)| *at == ip) {
+                self.evaluator.step(*step_span)?;
+            }
+            let span = chunk.span_at(ip);
+            match *instruction {
+                Instruction::Constant(index) => {
+                    let value = chunk.constants[index as usize].clone();
+                    self.evaluator.allocate(&value, span)?;
+                    self.stack.push((value, span));
+                }
+                Instruction::LoadName(index) => {
+                    let value = self.evaluator.load_name(&chunk.names[index as usize], slots[index as usize], span)?;
+                    self.stack.push((value, span));
+                }
+                Instruction::LoadVariable(index) => {
+                    let value = self.evaluator.load_variable(&chunk.names[index as usize], slots[index as usize], span)?;
+                    self.stack.push((value, span));
+                }
+                Instruction::StoreName(index) => {
+                    let (value, _) = self.peek();
+                    let value = value.clone();
+                    self.evaluator.store_variable(&chunk.names[index as usize], slots[index as usize], value);
+                }
+                Instruction::Compound(operator, index) => {
+                    let (rhs, _) = self.pop();
+                    let (current, _) = self.pop();
+                    let result = operations::compound(&current, &operator, &rhs, &chunk.names[index as usize], span)?;
+                    self.evaluator.allocate(&result, span)?;
+                    self.stack.push((result, span));
+                }
+                Instruction::Unary(operator) => {
+                    let (operand, operand_span) = self.pop();
+                    let result = operations::unary(&operator, operand, operand_span)?;
+                    self.stack.push((result, span));
+                }
+                Instruction::Binary(operator) => {
+                    let (right, right_span) = self.pop();
+                    let (left, left_span) = self.pop();
+                    let result = operations::binary(&operator, left, left_span, right, right_span, span)?;
+                    self.evaluator.allocate(&result, span)?;
+                    self.stack.push((result, span));
+                }
+                Instruction::Index => {
+                    let (index, index_span) = self.pop();
+                    let (object, object_span) = self.pop();
+                    let result = operations::index(&object, object_span, &index, index_span, span)?;
+                    self.stack.push((result, span));
+                }
+                Instruction::BuildList(count) => {
+                    let base = self.stack.len() - count as usize;
+                    let items = self.stack.drain(base..).map(|(value, _)| value).collect();
+                    let list = Value::list(items);
+                    self.evaluator.allocate(&list, span)?;
+                    self.stack.push((list, span));
+                }
+                Instruction::BuildDict(index) => {
+                    let keys = &chunk.key_sets[index as usize];
+                    let base = self.stack.len() - keys.len();
+                    let mut dict = HashMap::with_capacity(keys.len());
+                    for (key, (value, _)) in keys.iter().zip(self.stack.drain(base..)) {
+                        dict.insert(key.clone(), value);
+                    }
+                    let dict = Value::dict(dict);
+                    self.evaluator.allocate(&dict, span)?;
+                    self.stack.push((dict, span));
+                }
+                Instruction::CheckUnpack => {
+                    let (value, value_span) = self.peek();
+                    operations::check_unpack_positional(value, *value_span)?;
+                }
+                Instruction::CheckUnpackKeywords => {
+                    let (value, value_span) = self.peek();
+                    operations::check_unpack_keywords(value, *value_span)?;
+                }
+                Instruction::Call(index) => {
+                    let result = self.call(chunk, &slots, index as usize, span)?;
+                    self.evaluator.allocate(&result, span)?;
+                    self.stack.push((result, span));
+                }
+                Instruction::Pop => {
+                    self.pop();
+                }
+                Instruction::Return => {
+                    return Ok(self.stack.pop().map(|(value, _)| value));
+                }
+            }
+        }
+        Ok(self.stack.pop().map(|(value, _)| value))
+    }
+    
+    /// Performs the call `chunk.calls[index]`, whose arguments are on top of
+    /// the stack.
+    fn call(&mut self, chunk: &Chunk, slots: &[Option<Slot>], index: usize, span: Span) -> BcclResult<Value> {
+        let shape = &chunk.calls[index];
+        let base = self.stack.len() - shape.arity();
+        
+        // The callee is resolved after its arguments, as in the tree-walker
+        let callee = if shape.builtin {
+            Value::Function(shape.name.clone())
+        } else {
+            self.evaluator.resolve_callee(&shape.name, slots[shape.name_index as usize], span)?
+        };
+        
+        // Plain positional arguments are passed straight from the stack
+        if shape.is_simple() {
+            let result = self.evaluator.call_value(&callee, &self.stack[base..], &[], span);
+            self.stack.truncate(base);
+            return result;
+        }
+        
+        let mut slots = self.stack.drain(base..);
+        let mut args = Vec::with_capacity(shape.args.len());
+        for arg in &shape.args {
+            let (value, value_span) = slots.next().expect("call arguments on the stack");
+            match arg {
+                CallArgument::Value => args.push((value, value_span)),
+                CallArgument::Unpack(unpack_span) => operations::unpack_positional(value, *unpack_span, &mut args),
+            }
+        }
+        let mut kwargs = Vec::with_capacity(shape.keywords.len());
+        for keyword in &shape.keywords {
+            let (value, value_span) = slots.next().expect("call arguments on the stack");
+            match keyword {
+                Some(name) => kwargs.push((name.clone(), value, value_span)),
+                None => operations::unpack_keywords(value, value_span, &mut kwargs),
+            }
+        }
+        drop(slots);
+        
+        self.evaluator.call_value(&callee, &args, &kwargs, span)
+    }
+    
+    fn pop(&mut self) -> (Value, Span) {
+        self.stack.pop().expect("VM stack underflow")
+    }
+    
+    fn peek(&self) -> &(Value, Span) {
+        self.stack.last().expect("VM stack underflow")
+    }
+}
@@ -0,0 +1,41 @@
+//! # Bytecode Backend
+//!
+//! An alternative to walking the AST: a `Program` is compiled once into a
+//! compact `Chunk` of instructions, which a stack-based `Vm` then executes.
+//! Compiled chunks can be run again and again without re-parsing, which suits
+//! hosts that run the same script on every request.
+//!
+//! ## Pipeline
+//!
+//! ```text
+//! Program ──Compiler::compile──▶ Chunk ──Vm::run──▶ Option<Value>
+//! ```
+//!
+//! ## Identical Diagnostics
+//!
+//! The VM must behave exactly like `Backend::TreeWalk`, errors included:
+//!
+//! - **Span table**: every instruction records the span of the expression it
+//!   completes (`Chunk::spans`), and every stack slot carries the span of the
+//!   expression that produced it, so errors point at the same source as the
+//!   tree-walker's `expr.span()`
+//! - **Shared semantics**: operators, indexing, membership and unpacking come
+//!   from `evaluator::operations`; names, callees and calls go through the same
+//!   `Evaluator` methods
+//! - **Same order**: operands are evaluated left to right and checks (such as
+//!   `*xs` needing a list) happen at the same point as in the tree-walker, so
+//!   the first error reported is the same one
+//!
+//! ## Components
+//!
+//! - **`chunk`**: `Instruction`, `Chunk` and the call shapes
+//! - **`compiler`**: `Compiler`, which lowers a `Program` into a `Chunk`
+//! - **`machine`**: `Vm`, the stack machine
+
+mod chunk;
+mod compiler;
+mod machine;
+
+pub use chunk::{CallArgument, CallShape, Chunk, Instruction};
+pub use compiler::Compiler;
+pub use machine::Vm;
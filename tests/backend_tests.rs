@@ -0,0 +1,221 @@
+//! Differential tests for the two execution backends.
+//!
+//! Every program in the corpus is run on fresh `Backend::TreeWalk` and
+//! `Backend::Bytecode` evaluators, each with and without the optimizer. The
+//! results must be identical: the same value, or the same error with the same
+//! spans, and the same variables afterwards.
+
+// Test inputs use literals like 3.14 as BCCL source, not as approximations of PI
+#![allow(clippy::approx_constant)]
+
+use bccl::{Backend, BuiltinCall, BcclResult, Compiler, Evaluator, FunctionSignature, Lexer, Parameters, Parser, Value};
+
+/// Programs that evaluate successfully.
+const VALUES: &[&str] = &[
+    // Literals and arithmetic
+    "42",
+    "3.14",
+    "\"hello\"",
+    "true",
+    "-5 + +3",
+    "(1 + 2) * 3 - 4 / 2",
+    "not 0",
+    // Variables and compound assignment
+    "x = 10; x += 5; x *= 2; x",
+    "a = 1; b = a; a = 2; b",
+    "total = 0; total -= 1.5; total /= 2",
+    // Comparison, equality and logic
+    "1 < 2",
+    "\"apple\" < \"banana\"",
+    "2 >= 2.0",
+    "1 == 1.0",
+    "[1, 2] == [1, 2]",
+    "0 or \"default\"",
+    "1 and 2",
+    "false and 1",
+    // Collections
+    "[1, \"two\", [3]]",
+    "{\"a\": 1, \"b\": [2, 3]}[\"b\"][1]",
+    "{\"a\": 1, \"a\": 2}[\"a\"]",
+    "2 in [1, 2, 3]",
+    "\"k\" not in {\"k\": 1}",
+    // Calls
+    "max(3, 7, 2)",
+    "min([4, 1, 9])",
+    "max([], default=0)",
+    "max([\"bb\", \"a\"], key=str)",
+    "sum(1, 2, 3)",
+    "int(\"3\") + float(\"0.5\")",
+    "sorted([3, 1, 2], reverse=true)",
+    "map(str, [1, 2])",
+    "reduce(max, [3, 9, 4], 0)",
+    "group_by(type, [1, \"a\", 2])",
+    "f = str; f(3)",
+    "max(*[1, 5], 3)",
+    "args = [2, 8]; min(*args, *[5])",
+    "sorted(**{\"values\": [2, 1]})",
+    "echo(1)",
+    "echo(1, 2, 3, 4, flag=true, mode=\"x\")",
+    "echo(*[1, 2], **{\"flag\": true})",
+    "str(map)",
+    "max == max",
+    // Constant folding
+    "day = 60 * 60 * 24; day * 2",
+    "not (\"a\" < \"b\") or [1, 2][1] == 2",
+    "-(2 + 3) * {\"k\": 4}[\"k\"]",
+];
+
+/// Programs that fail; the errors must match exactly.
+const ERRORS: &[&str] = &[
+    "1 + \"a\"",
+    "\"a\" * 2",
+    "10 / (5 - 5)",
+    "-\"x\"",
+    "\"a\" < 1",
+    "[1] < 2",
+    "undefined_var",
+    "y += 1",
+    "x = \"s\"; x += 1",
+    "x = 1; x /= 0",
+    "[1, 2][5]",
+    "[1, 2][\"a\"]",
+    "{\"a\": 1}[\"b\"]",
+    "{\"a\": 1}[0]",
+    "5[0]",
+    "1 in 5",
+    "1 in {\"a\": 1}",
+    "nope(1)",
+    "x = 3; x(1)",
+    "max()",
+    "max(1, 2, bogus=3)",
+    "str(1, 2)",
+    "int(\"abc\")",
+    "map(int, [\"1\", \"x\"])",
+    "sorted([1, \"a\"])",
+    "max(*5)",
+    "max(*5, undefined_var)",
+    "max(1, **[1])",
+    "echo(1, flag=1, flag=2)",
+    "echo()",
+    // The right operand of `and`/`or` is always evaluated
+    "false and undefined_var",
+    // Constant expressions that fail are reported at runtime, in order
+    "x = 1; 2 * 3 + 1 / 0",
+    "undefined_var + 1 / 0",
+    "max(1, 2); max = 5; max(1)",
+    "x = 1; y = x + \"a\"; x",
+];
+
+fn native_echo(call: &BuiltinCall) -> BcclResult<Value> {
+    let mut items = call.args.to_vec();
+    let mut names: Vec<&String> = call.options.keys().collect();
+    names.sort();
+    for name in names {
+        items.push(call.options[name].0.clone());
+    }
+    Ok(Value::list(items))
+}
+
+/// An evaluator on `backend` with the `echo(a, b=2, *rest, flag=false, **opts)` native.
+fn evaluator(backend: Backend) -> Evaluator {
+    let mut evaluator = Evaluator::with_backend(backend);
+    evaluator.define_function(FunctionSignature::new_native_with_parameters(
+        "echo",
+        Parameters::new()
+            .positional("a")
+            .positional_with_default("b", Value::Integer(2))
+            .rest("rest")
+            .keyword_only_with_default("flag", Value::Boolean(false))
+            .var_keyword("opts"),
+        native_echo,
+    ));
+    evaluator
+}
+
+/// Runs `code` on `backend` and renders the outcome and the final variables.
+fn run(backend: Backend, code: &str) -> (String, String) {
+    run_with(backend, true, code)
+}
+
+fn run_with(backend: Backend, optimize: bool, code: &str) -> (String, String) {
+    let tokens = Lexer::new(code).tokenize().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+    let mut evaluator = evaluator(backend);
+    evaluator.set_optimize(optimize);
+    let outcome = match evaluator.evaluate_program(&program) {
+        Ok(value) => format!("Ok({:?})", value.map(|v| v.display())),
+        Err(error) => format!("Err({:?})", error),
+    };
+    let mut names = evaluator.get_variable_names();
+    names.sort();
+    let variables = names.iter()
+        .map(|name| format!("{}={}", name, evaluator.get_variable(name).unwrap().display()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    (outcome, variables)
+}
+
+#[test]
+fn test_backends_agree_on_values() {
+    for code in VALUES {
+        let tree = run(Backend::TreeWalk, code);
+        let vm = run(Backend::Bytecode, code);
+        assert!(tree.0.starts_with("Ok"), "{} should succeed, got {}", code, tree.0);
+        assert_eq!(tree, vm, "backends disagree on {}", code);
+    }
+}
+
+#[test]
+fn test_backends_agree_on_errors() {
+    for code in ERRORS {
+        let tree = run(Backend::TreeWalk, code);
+        let vm = run(Backend::Bytecode, code);
+        assert!(tree.0.starts_with("Err"), "{} should fail, got {}", code, tree.0);
+        assert_eq!(tree, vm, "backends disagree on {}", code);
+    }
+}
+
+#[test]
+fn test_optimizer_does_not_change_results() {
+    for code in VALUES.iter().chain(ERRORS) {
+        let reference = run_with(Backend::TreeWalk, false, code);
+        for backend in [Backend::TreeWalk, Backend::Bytecode] {
+            assert_eq!(run_with(backend, true, code), reference, "optimized {:?} disagrees on {}", backend, code);
+            assert_eq!(run_with(backend, false, code), reference, "unoptimized {:?} disagrees on {}", backend, code);
+        }
+    }
+}
+
+#[test]
+fn test_switching_backend_keeps_state() {
+    let mut evaluator = Evaluator::new();
+    let program = |code: &str| Parser::new(Lexer::new(code).tokenize().unwrap()).parse().unwrap();
+
+    evaluator.evaluate_program(&program("x = 20")).unwrap();
+    evaluator.set_backend(Backend::Bytecode);
+    evaluator.evaluate_program(&program("x += 1")).unwrap();
+    evaluator.set_backend(Backend::TreeWalk);
+    let result = evaluator.evaluate_program(&program("x * 2")).unwrap().unwrap();
+    assert_eq!(result.display(), "42");
+}
+
+#[test]
+fn test_compiled_chunk_can_be_rerun() {
+    let tokens = Lexer::new("n += 1; n * 10").tokenize().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+    let chunk = Compiler::compile(&program).unwrap();
+    assert_eq!(chunk.code.len(), chunk.spans.len(), "one span per instruction");
+
+    let mut evaluator = Evaluator::new();
+    evaluator.evaluate_program(&Parser::new(Lexer::new("n = 0").tokenize().unwrap()).parse().unwrap()).unwrap();
+    for expected in ["10", "20", "30"] {
+        let result = evaluator.run_chunk(&chunk).unwrap().unwrap();
+        assert_eq!(result.display(), expected);
+    }
+}
+
+#[test]
+fn test_empty_program() {
+    assert_eq!(run(Backend::TreeWalk, ""), run(Backend::Bytecode, ""));
+    assert_eq!(run(Backend::Bytecode, "").0, "Ok(None)");
+}
@@ -0,0 +1,247 @@
+//! Table-driven tests for `FunctionSignature::bind_arguments`.
+//!
+//! Every case binds a call against one of a small set of signatures and
+//! checks either the bound values (in parameter order, then `*rest`) or the
+//! error. A second test binds every way of splitting a call between
+//! positional and keyword arguments, in every keyword order, and checks that
+//! the result never depends on how the call was written.
+
+use bccl::{BcclError, FunctionSignature, Parameters, Span, Value};
+
+fn noop(_args: &[Value]) -> bccl::BcclResult<Value> {
+    Ok(Value::Boolean(true))
+}
+
+fn native_noop(_call: &bccl::BuiltinCall) -> bccl::BcclResult<Value> {
+    Ok(Value::Boolean(true))
+}
+
+/// The signatures under test, by short name.
+fn signature(name: &str) -> FunctionSignature {
+    match name {
+        // f(a, b)
+        "fixed" => FunctionSignature::new_fixed("fixed", vec!["a", "b"], noop),
+        // f(values...)
+        "varargs" => FunctionSignature::new_variadic("varargs", vec![], "values", noop),
+        // f(a, b, rest...)
+        "mixed" => FunctionSignature::new_variadic("mixed", vec!["a", "b"], "rest", noop),
+        // f(a, b=2, *rest, flag=false, **opts)
+        "full" => FunctionSignature::new_native_with_parameters(
+            "full",
+            Parameters::new()
+                .positional("a")
+                .positional_with_default("b", Value::Integer(2))
+                .rest("rest")
+                .keyword_only_with_default("flag", Value::Boolean(false))
+                .var_keyword("opts"),
+            native_noop,
+        ),
+        // f(a, *, key) with a required keyword-only parameter
+        "kwonly" => FunctionSignature::new_native_with_parameters(
+            "kwonly",
+            Parameters::new().positional("a").keyword_only("key"),
+            native_noop,
+        ),
+        other => panic!("unknown signature {}", other),
+    }
+}
+
+/// One table row: signature name, positional values, keyword arguments, expected outcome.
+type Case = (&'static str, Vec<i64>, Vec<(&'static str, Value)>, Expect);
+
+/// Expected outcome of a binding.
+enum Expect {
+    /// Display of the bound positional values, e.g. `[1, 2, 3]`
+    Args(&'static str),
+    /// Substring of the error's display
+    Error(&'static str),
+}
+
+use Expect::*;
+
+/// Positional argument `i` is given the span `10*i..10*i+1` so error spans can be checked.
+fn positional(values: &[i64]) -> Vec<(Value, Span)> {
+    values.iter()
+        .enumerate()
+        .map(|(i, v)| (Value::Integer(*v), Span::new(10 * i, 10 * i + 1)))
+        .collect()
+}
+
+fn keywords(pairs: &[(&str, Value)]) -> Vec<(String, Value, Span)> {
+    pairs.iter()
+        .enumerate()
+        .map(|(i, (name, value))| (name.to_string(), value.clone(), Span::new(100 + i, 101 + i)))
+        .collect()
+}
+
+fn int(v: i64) -> Value {
+    Value::Integer(v)
+}
+
+fn list(values: &[i64]) -> Value {
+    Value::list(values.iter().map(|v| Value::Integer(*v)).collect())
+}
+
+#[test]
+fn test_binding_table() {
+    let cases: Vec<Case> = vec![
+        // ----- fixed: f(a, b) -----
+        ("fixed", vec![1, 2], vec![], Args("[1, 2]")),
+        ("fixed", vec![1], vec![("b", int(2))], Args("[1, 2]")),
+        ("fixed", vec![], vec![("b", int(2)), ("a", int(1))], Args("[1, 2]")),
+        ("fixed", vec![1], vec![], Error("Missing required parameter 'b'")),
+        ("fixed", vec![1, 2, 3], vec![], Error("expects 2 arguments, got 3")),
+        ("fixed", vec![1, 2], vec![("a", int(3))], Error("'a' specified multiple times")),
+        ("fixed", vec![1], vec![("b", int(2)), ("b", int(3))], Error("'b' specified multiple times")),
+        ("fixed", vec![1, 2], vec![("c", int(3))], Error("Unknown parameter 'c'")),
+        // ----- varargs: f(values...) -----
+        ("varargs", vec![], vec![], Args("[]")),
+        ("varargs", vec![1, 2, 3], vec![], Args("[1, 2, 3]")),
+        ("varargs", vec![], vec![("values", list(&[1, 2]))], Args("[1, 2]")),
+        ("varargs", vec![], vec![("values", list(&[]))], Args("[]")),
+        ("varargs", vec![1], vec![("values", list(&[2]))], Error("both extra positional arguments and a keyword")),
+        ("varargs", vec![], vec![("values", list(&[1])), ("values", list(&[2]))], Error("'values' specified multiple times")),
+        ("varargs", vec![], vec![("values", int(1))], Error("takes a list when passed by keyword")),
+        ("varargs", vec![], vec![("other", int(1))], Error("Unknown parameter 'other'")),
+        // ----- mixed: f(a, b, rest...) -----
+        ("mixed", vec![1, 2], vec![], Args("[1, 2]")),
+        ("mixed", vec![1, 2, 3, 4], vec![], Args("[1, 2, 3, 4]")),
+        // Keywords for required parameters never shift the rest values
+        ("mixed", vec![], vec![("b", int(2)), ("a", int(1))], Args("[1, 2]")),
+        ("mixed", vec![1], vec![("b", int(2))], Args("[1, 2]")),
+        ("mixed", vec![], vec![("rest", list(&[3, 4])), ("b", int(2)), ("a", int(1))], Args("[1, 2, 3, 4]")),
+        ("mixed", vec![1, 2], vec![("rest", list(&[3]))], Args("[1, 2, 3]")),
+        ("mixed", vec![1, 2, 3], vec![("b", int(9))], Error("'b' specified multiple times")),
+        ("mixed", vec![1, 2, 3], vec![("rest", list(&[4]))], Error("both extra positional arguments and a keyword")),
+        ("mixed", vec![1], vec![], Error("Missing required parameter 'b'")),
+        ("mixed", vec![], vec![("rest", list(&[3]))], Error("Missing required parameter 'a'")),
+        // ----- full: f(a, b=2, *rest, flag=false, **opts) -----
+        ("full", vec![1], vec![], Args("[1, 2]")),
+        ("full", vec![1, 5, 6], vec![], Args("[1, 5, 6]")),
+        ("full", vec![], vec![("a", int(1))], Args("[1, 2]")),
+        ("full", vec![1], vec![("flag", Value::Boolean(true)), ("mode", int(3))], Args("[1, 2]")),
+        ("full", vec![1], vec![("rest", list(&[7]))], Args("[1, 2, 7]")),
+        ("full", vec![], vec![], Error("Missing required parameter 'a'")),
+        ("full", vec![1], vec![("flag", int(1)), ("flag", int(2))], Error("'flag' specified multiple times")),
+        ("full", vec![1], vec![("mode", int(1)), ("mode", int(2))], Error("'mode' specified multiple times")),
+        // ----- kwonly: f(a, *, key) -----
+        ("kwonly", vec![1], vec![("key", int(2))], Args("[1]")),
+        ("kwonly", vec![1], vec![], Error("Missing required parameter 'key'")),
+        ("kwonly", vec![1, 2], vec![], Error("expects 1 arguments, got 2")),
+    ];
+
+    for (sig_name, args, kwargs, expect) in cases {
+        let sig = signature(sig_name);
+        let call = format!("{}({:?}, {:?})", sig_name, args, kwargs.iter().map(|(k, v)| format!("{}={}", k, v.display())).collect::<Vec<_>>());
+        let result = sig.bind_arguments(&positional(&args), &keywords(&kwargs), Span::new(0, 1));
+        match (result, expect) {
+            (Ok(bound), Args(expected)) => {
+                assert_eq!(Value::list(bound.args.clone()).display(), expected, "bound args for {}", call);
+                assert_eq!(bound.args.len(), bound.spans.len(), "one span per value for {}", call);
+            }
+            (Err(error), Error(expected)) => {
+                assert!(error.to_string().contains(expected), "error for {}: expected '{}', got '{}'", call, expected, error);
+            }
+            (Ok(bound), Error(expected)) => {
+                panic!("{} should fail with '{}', bound {:?}", call, expected, bound.args);
+            }
+            (Err(error), Args(expected)) => {
+                panic!("{} should bind {}, failed with {}", call, expected, error);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_keyword_bindings() {
+    let sig = signature("full");
+    let bound = sig.bind_arguments(
+        &positional(&[1]),
+        &keywords(&[("mode", int(3)), ("flag", Value::Boolean(true))]),
+        Span::new(0, 1),
+    ).unwrap();
+    assert_eq!(bound.keywords["flag"].0.display(), "true");
+    assert_eq!(bound.keywords["opts"].0.display(), "{\"mode\": 3}");
+
+    // Defaults are filled in and reported at the call span
+    let bound = sig.bind_arguments(&positional(&[1]), &[], Span::new(0, 1)).unwrap();
+    assert_eq!(bound.keywords["flag"].0.display(), "false");
+    assert_eq!(bound.keywords["opts"].0.display(), "{}");
+    assert_eq!(bound.spans[1], Span::new(0, 1));
+}
+
+#[test]
+fn test_error_spans() {
+    // Too many arguments points at the first surplus argument
+    let error = signature("fixed").bind_arguments(&positional(&[1, 2, 3]), &[], Span::new(0, 1)).unwrap_err();
+    match error {
+        BcclError::WrongArgumentCount { span, .. } => assert_eq!(span.offset(), 20),
+        other => panic!("Expected WrongArgumentCount, got {:?}", other),
+    }
+
+    // An ambiguous variadic keyword points at that keyword
+    let error = signature("varargs")
+        .bind_arguments(&positional(&[1]), &keywords(&[("values", list(&[2]))]), Span::new(0, 1))
+        .unwrap_err();
+    match error {
+        BcclError::FunctionArgumentError { span, .. } => assert_eq!(span.offset(), 100),
+        other => panic!("Expected FunctionArgumentError, got {:?}", other),
+    }
+}
+
+/// Every permutation of `items`.
+fn permutations<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+    if items.is_empty() {
+        return vec![vec![]];
+    }
+    let mut result = Vec::new();
+    for i in 0..items.len() {
+        let mut rest = items.to_vec();
+        let item = rest.remove(i);
+        for mut tail in permutations(&rest) {
+            tail.insert(0, item.clone());
+            result.push(tail);
+        }
+    }
+    result
+}
+
+#[test]
+fn test_binding_is_independent_of_call_form() {
+    // For each signature and full argument list, pass the first `split`
+    // parameters positionally and the rest by keyword in every order
+    let cases: Vec<(&str, Vec<&str>, Vec<i64>)> = vec![
+        ("fixed", vec!["a", "b"], vec![1, 2]),
+        ("mixed", vec!["a", "b"], vec![1, 2]),
+        ("full", vec!["a", "b"], vec![1, 2]),
+    ];
+
+    for (sig_name, names, values) in cases {
+        let sig = signature(sig_name);
+        let expected = sig.bind_arguments(&positional(&values), &[], Span::new(0, 1)).unwrap();
+
+        for split in 0..=names.len() {
+            let named: Vec<(&str, Value)> = names[split..].iter()
+                .zip(&values[split..])
+                .map(|(name, value)| (*name, int(*value)))
+                .collect();
+            for order in permutations(&named) {
+                let bound = sig.bind_arguments(&positional(&values[..split]), &keywords(&order), Span::new(0, 1))
+                    .unwrap_or_else(|e| panic!("{} split {} failed: {}", sig_name, split, e));
+                assert_eq!(
+                    Value::list(bound.args).display(),
+                    Value::list(expected.args.clone()).display(),
+                    "{} with {} positional and keywords {:?}", sig_name, split, order.iter().map(|(k, _)| *k).collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+
+    // With `rest` supplied by keyword, every keyword order gives the same result
+    let sig = signature("mixed");
+    let named = vec![("a", int(1)), ("b", int(2)), ("rest", list(&[3, 4]))];
+    for order in permutations(&named) {
+        let bound = sig.bind_arguments(&[], &keywords(&order), Span::new(0, 1)).unwrap();
+        assert_eq!(Value::list(bound.args).display(), "[1, 2, 3, 4]");
+    }
+}
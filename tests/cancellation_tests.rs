@@ -0,0 +1,98 @@
+//! Tests for stopping a run through a `CancellationToken` or a deadline.
+
+use std::thread;
+use std::time::{Duration, Instant};
+use bccl::{Backend, BcclError, BuiltinCall, BcclResult, Evaluator, FunctionSignature, Lexer, Parameters, Parser, Span};
+
+/// `slow(x)`: sleeps for a millisecond and returns `x`.
+fn native_slow(call: &BuiltinCall) -> BcclResult<bccl::Value> {
+    thread::sleep(Duration::from_millis(1));
+    Ok(call.args[0].clone())
+}
+
+fn evaluator(backend: Backend) -> Evaluator {
+    let mut evaluator = Evaluator::with_backend(backend);
+    evaluator.define_function(FunctionSignature::new_native_with_parameters(
+        "slow",
+        Parameters::new().positional("x"),
+        native_slow,
+    ));
+    evaluator
+}
+
+/// A program whose second statement calls `slow` 5000 times (about five seconds).
+fn long_program() -> (String, Span) {
+    let items = vec!["1"; 5000].join(", ");
+    let second = format!("b = map(slow, [{}])", items);
+    let code = format!("a = 1; {}; c = 3", second);
+    (code, Span::new(7, 7 + second.len()))
+}
+
+fn run(evaluator: &mut Evaluator, code: &str) -> BcclResult<Option<bccl::Value>> {
+    let tokens = Lexer::new(code).tokenize().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+    evaluator.evaluate_program(&program)
+}
+
+fn assert_stopped(result: BcclResult<Option<bccl::Value>>, expected_reason: &str, expected_span: Span) {
+    match result {
+        Err(BcclError::Cancelled { reason, span, .. }) => {
+            assert_eq!(reason, expected_reason);
+            assert_eq!(Span::new(span.offset(), span.offset() + span.len()), expected_span);
+        }
+        other => panic!("Expected Cancelled, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_cancel_from_another_thread() {
+    let (code, statement) = long_program();
+    for backend in [Backend::TreeWalk, Backend::Bytecode] {
+        let mut evaluator = evaluator(backend);
+        let token = evaluator.cancellation_token();
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            token.cancel();
+        });
+
+        let started = Instant::now();
+        let result = run(&mut evaluator, &code);
+        canceller.join().unwrap();
+        assert!(started.elapsed() < Duration::from_secs(2), "{:?} did not stop promptly", backend);
+        assert_stopped(result, "cancelled", statement);
+
+        // The environment holds exactly the completed statements
+        assert_eq!(evaluator.get_variable_names(), vec!["a".to_string()]);
+    }
+}
+
+#[test]
+fn test_deadline() {
+    let (code, statement) = long_program();
+    for backend in [Backend::TreeWalk, Backend::Bytecode] {
+        let mut evaluator = evaluator(backend);
+        evaluator.set_deadline(Some(Instant::now() + Duration::from_millis(50)));
+        assert_stopped(run(&mut evaluator, &code), "deadline", statement);
+        assert_eq!(evaluator.get_variable_names(), vec!["a".to_string()]);
+
+        evaluator.set_deadline(None);
+        assert!(run(&mut evaluator, "c = 3").is_ok());
+    }
+}
+
+#[test]
+fn test_shared_token_and_reset() {
+    let token = bccl::CancellationToken::new();
+    let mut first = evaluator(Backend::TreeWalk);
+    let mut second = evaluator(Backend::Bytecode);
+    first.set_cancellation_token(token.clone());
+    second.set_cancellation_token(token.clone());
+
+    token.cancel();
+    assert_stopped(run(&mut first, "x = 1"), "cancelled", Span::new(0, 5));
+    assert_stopped(run(&mut second, "y = 2; z = 3"), "cancelled", Span::new(0, 5));
+
+    token.reset();
+    assert!(run(&mut first, "x = 1").is_ok());
+    assert!(run(&mut second, "y = 2").is_ok());
+}
@@ -0,0 +1,129 @@
+//! Tests for capability-gated system built-ins.
+
+use bccl::{Backend, BcclError, BcclResult, Capabilities, Capability, Evaluator, FunctionSignature, Lexer, Parser, Value};
+
+fn run(evaluator: &mut Evaluator, code: &str) -> BcclResult<Option<Value>> {
+    let tokens = Lexer::new(code).tokenize().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+    evaluator.evaluate_program(&program)
+}
+
+/// Runs `code` on both backends of an evaluator with `capabilities`.
+fn run_both(capabilities: Capabilities, code: &str) -> Vec<BcclResult<Option<Value>>> {
+    [Backend::TreeWalk, Backend::Bytecode].into_iter().map(|backend| {
+        let mut evaluator = Evaluator::with_capabilities(capabilities);
+        evaluator.set_backend(backend);
+        run(&mut evaluator, code)
+    }).collect()
+}
+
+fn assert_missing(result: BcclResult<Option<Value>>, function: &str, expected: &str) {
+    match result {
+        Err(BcclError::MissingCapability { function_name, capability, .. }) => {
+            assert_eq!(function_name, function);
+            assert_eq!(capability, expected);
+        }
+        other => panic!("Expected MissingCapability for {}, got {:?}", function, other),
+    }
+}
+
+#[test]
+fn test_sandboxed_by_default() {
+    assert_eq!(Evaluator::new().capabilities(), Capabilities::none());
+    for (code, function, capability) in [
+        ("now()", "now", "clock"),
+        ("random() + 1", "random", "random"),
+        ("getenv(\"HOME\")", "getenv", "env"),
+        ("x = read_file(\"a.txt\")", "read_file", "fs_read"),
+        ("write_file(\"a.txt\", \"text\")", "write_file", "fs_write"),
+        ("f = read_file", "read_file", "fs_read"),
+        ("map(now, [1])", "now", "clock"),
+        ("random()", "random", "random"),
+    ] {
+        for result in run_both(Capabilities::none(), code) {
+            assert_missing(result, function, capability);
+        }
+    }
+}
+
+#[test]
+fn test_unknown_functions_are_still_undefined() {
+    for result in run_both(Capabilities::none(), "nope(1)") {
+        match result {
+            Err(BcclError::UndefinedFunction { suggestion, .. }) => {
+                assert!(!suggestion.unwrap_or_default().contains("read_file"), "ungranted built-ins are not suggested");
+            }
+            other => panic!("Expected UndefinedFunction, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_granted_capabilities() {
+    let capabilities = Capabilities::none().with(Capability::Clock).with(Capability::Random).with(Capability::Env);
+    for result in run_both(capabilities, "now() > 1000000000") {
+        assert!(matches!(result, Ok(Some(Value::Boolean(true)))));
+    }
+    for result in run_both(capabilities, "r = random(); r >= 0 and r < 1") {
+        assert!(matches!(result, Ok(Some(Value::Boolean(true)))));
+    }
+    for result in run_both(capabilities, "getenv(\"BCCL_SURELY_UNSET_VARIABLE\", default=\"none\")") {
+        assert_eq!(result.unwrap().unwrap().display(), "\"none\"");
+    }
+    for result in run_both(capabilities, "getenv(\"BCCL_SURELY_UNSET_VARIABLE\")") {
+        assert!(matches!(result, Err(BcclError::EvaluationError { .. })));
+    }
+    // Only the granted groups are registered
+    for result in run_both(capabilities, "read_file(\"a.txt\")") {
+        assert_missing(result, "read_file", "fs_read");
+    }
+}
+
+#[test]
+fn test_file_round_trip() {
+    let path = std::env::temp_dir().join(format!("bccl_capabilities_{}.txt", std::process::id()));
+    let path = path.to_str().unwrap().replace('\\', "/");
+
+    let mut writer = Evaluator::with_capabilities(Capabilities::none().with(Capability::FsWrite));
+    let written = run(&mut writer, &format!("write_file(\"{}\", \"hello\")", path)).unwrap().unwrap();
+    assert!(matches!(written, Value::Integer(5)));
+    assert_missing(run(&mut writer, &format!("read_file(\"{}\")", path)), "read_file", "fs_read");
+
+    let mut reader = Evaluator::with_capabilities(Capabilities::none().with(Capability::FsRead));
+    let read = run(&mut reader, &format!("read_file(\"{}\")", path)).unwrap().unwrap();
+    assert_eq!(read.display(), "\"hello\"");
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(matches!(run(&mut reader, &format!("read_file(\"{}\")", path)), Err(BcclError::EvaluationError { .. })));
+    assert!(matches!(run(&mut reader, "read_file(1)"), Err(BcclError::FunctionArgumentTypeError { .. })));
+}
+
+#[test]
+fn test_set_capabilities_keeps_host_functions() {
+    fn answer(_: &[Value]) -> BcclResult<Value> {
+        Ok(Value::Integer(42))
+    }
+
+    let mut evaluator = Evaluator::new();
+    evaluator.define_function(FunctionSignature::new_fixed("answer", vec![], answer));
+
+    evaluator.set_capabilities(Capabilities::all());
+    assert!(run(&mut evaluator, "now()").is_ok());
+
+    evaluator.set_capabilities(Capabilities::none());
+    assert_missing(run(&mut evaluator, "now()"), "now", "clock");
+    assert_eq!(run(&mut evaluator, "answer()").unwrap().unwrap().display(), "42");
+}
+
+#[test]
+fn test_parse_capabilities() {
+    assert_eq!(Capabilities::parse("all"), Ok(Capabilities::all()));
+    assert_eq!(Capabilities::parse("none"), Ok(Capabilities::none()));
+    assert_eq!(Capabilities::parse(""), Ok(Capabilities::none()));
+    assert_eq!(
+        Capabilities::parse("fs_read, clock"),
+        Ok(Capabilities::none().with(Capability::FsRead).with(Capability::Clock))
+    );
+    assert_eq!(Capabilities::parse("clock,network"), Err("network".to_string()));
+    assert_eq!(Capabilities::all().without(Capability::Process).iter().count(), 5);
+}
@@ -0,0 +1,27 @@
+//! Helpers shared by the integration tests. Each test file that needs them
+//! declares `mod common;`, so not every helper is used by every file.
+#![allow(dead_code)]
+
+/// A small xorshift generator, so failures are reproducible from the seed.
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    pub fn chance(&mut self) -> bool {
+        self.next() & 1 == 0
+    }
+
+    pub fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+}
@@ -0,0 +1,103 @@
+//! Tests for sharing programs, chunks and globals across threads.
+
+use std::sync::Arc;
+use std::thread;
+use bccl::{
+    BcclError, CancellationToken, Capabilities, Chunk, Compiler, Environment, Evaluator, EvaluatorLimits,
+    Lexer, Parser, Program, Value,
+};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+fn parse(code: &str) -> Program {
+    Parser::new(Lexer::new(code).tokenize().unwrap()).parse().unwrap()
+}
+
+/// Globals shared by every evaluation.
+fn globals() -> Arc<Environment> {
+    let mut setup = Evaluator::new();
+    setup.evaluate_program(&parse("rate = 3; table = {\"a\": 10, \"b\": 20}; names = [\"x\", \"y\"]")).unwrap();
+    Arc::new(setup.into_environment())
+}
+
+/// Reads the globals, shadows one of them and combines them with the `seed` and `pick` inputs.
+const PROGRAM: &str = "n = seed * rate; total = n + table[\"a\"]; rate += seed; [total, rate, names[pick]]";
+
+fn expected(seed: i64) -> String {
+    let names = ["x", "y"];
+    format!("[{}, {}, \"{}\"]", seed * 3 + 10, 3 + seed, names[(seed % 2) as usize])
+}
+
+#[test]
+fn test_types_are_send_and_sync() {
+    assert_send_sync::<Value>();
+    assert_send_sync::<Program>();
+    assert_send_sync::<Chunk>();
+    assert_send_sync::<Environment>();
+    assert_send_sync::<Evaluator>();
+    assert_send_sync::<EvaluatorLimits>();
+    assert_send_sync::<Capabilities>();
+    assert_send_sync::<CancellationToken>();
+    assert_send_sync::<BcclError>();
+}
+
+#[test]
+fn test_parallel_evaluations_share_program_chunk_and_globals() {
+    let globals = globals();
+    let program = Arc::new(parse(PROGRAM));
+    let chunk = Arc::new(Compiler::compile(&program).unwrap());
+
+    thread::scope(|scope| {
+        for worker in 0..8i64 {
+            let (globals, program, chunk) = (globals.clone(), program.clone(), chunk.clone());
+            scope.spawn(move || {
+                for iteration in 0..250i64 {
+                    let seed = worker * 1000 + iteration;
+                    let mut evaluator = Evaluator::with_globals(globals.clone());
+                    evaluator.set_variable("seed", Value::Integer(seed));
+                    evaluator.set_variable("pick", Value::Integer(seed % 2));
+
+                    // Alternate between walking the shared program and running the shared chunk
+                    let result = if iteration % 2 == 0 {
+                        evaluator.evaluate_program(&program)
+                    } else {
+                        evaluator.run_chunk(&chunk)
+                    };
+                    assert_eq!(result.unwrap().unwrap().display(), expected(seed), "seed {}", seed);
+
+                    // The shadowed global is local to this evaluator
+                    assert_eq!(evaluator.get_variable("rate").unwrap().display(), (3 + seed).to_string());
+                }
+            });
+        }
+    });
+
+    // Nothing wrote through to the shared globals
+    assert_eq!(globals.get("rate").unwrap().display(), "3");
+    assert_eq!(globals.variable_names(), vec!["names", "rate", "table"]);
+}
+
+#[test]
+fn test_evaluator_moves_between_threads() {
+    let mut evaluator = Evaluator::with_globals(globals());
+    evaluator.evaluate_program(&parse("count = 1")).unwrap();
+    for _ in 0..4 {
+        evaluator = thread::spawn(move || {
+            evaluator.evaluate_program(&parse("count *= rate")).unwrap();
+            evaluator
+        }).join().unwrap();
+    }
+    assert_eq!(evaluator.get_variable("count").unwrap().display(), "81");
+}
+
+#[test]
+fn test_shadowing_before_assignment_reads_the_global() {
+    for code in ["x = rate; rate = 5; [x, rate]", "y = rate + 0; rate += 1; [y, rate]"] {
+        let tree = Evaluator::with_globals(globals()).evaluate_program(&parse(code)).unwrap().unwrap();
+        let chunk = Compiler::compile(&parse(code)).unwrap();
+        let vm = Evaluator::with_globals(globals()).run_chunk(&chunk).unwrap().unwrap();
+        assert_eq!(tree.display(), vm.display(), "{}", code);
+    }
+    let result = Evaluator::with_globals(globals()).evaluate_program(&parse("x = rate; rate = 5; [x, rate]")).unwrap();
+    assert_eq!(result.unwrap().display(), "[3, 5]");
+}
@@ -0,0 +1,150 @@
+//! Tests for the formatter: golden files in `tests/fmt`, where each
+//! `NAME.input.bccl` must format to `NAME.output.bccl`, plus generated
+//! programs that must keep their meaning and format to a fixed point.
+
+use std::path::Path;
+use bccl::{format_source, FormatOptions, Lexer, Parser, Program, SyntaxTree, TriviaKind};
+
+mod common;
+
+use common::Rng;
+
+impl Rng {
+    /// Optional whitespace or a comment where a line break is allowed.
+    fn gap(&mut self) -> String {
+        match self.below(8) {
+            0 => "\n  ".to_string(),
+            1 => " # note\n".to_string(),
+            2 => "  ".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    fn expression(&mut self, depth: usize) -> String {
+        let kinds = if depth == 0 { 3 } else { 10 };
+        match self.below(kinds) {
+            0 => self.pick(&["0", "1.50", "0.25", "true", "\"text\"", "\"a # b\""]).to_string(),
+            1 => self.pick(&["x", "total", "values"]).to_string(),
+            2 => {
+                let operator = self.pick(&["-", "not "]);
+                let gap = self.gap();
+                format!("{}{}{}", operator, gap, self.pick(&["1", "x", "(2)"]))
+            }
+            3 | 4 => {
+                let operator = self.pick(&["+", "-", "*", "/", "==", "<", ">=", "and", "or", "in", "not in"]);
+                let left = self.expression(depth - 1);
+                let gap = self.gap();
+                format!("{} {}{}{}", left, operator, if gap.is_empty() { " " } else { &gap }, self.expression(depth - 1))
+            }
+            5 => format!("({})", self.expression(depth - 1)),
+            6 => format!("[{}]", self.elements(depth, |rng, depth| rng.expression(depth - 1))),
+            7 => format!("{{{}}}", self.elements(depth, |rng, depth| format!("\"k\":{}", rng.expression(depth - 1)))),
+            8 => {
+                let object = self.pick(&["x", "values", "[1, 2]"]);
+                let (before, index, after) = (self.gap(), self.expression(depth - 1), self.gap());
+                format!("{}[{}{}{}]", object, before, index, after)
+            }
+            _ => {
+                let mut args = self.elements(depth, |rng, depth| rng.expression(depth - 1));
+                if self.below(2) == 0 {
+                    let keyword = format!("default = {}", self.expression(depth - 1));
+                    args = if args.is_empty() { keyword } else { format!("{}, {}", args, keyword) };
+                }
+                format!("{}({})", self.pick(&["f", "max", "sum"]), args)
+            }
+        }
+    }
+
+    fn elements(&mut self, depth: usize, element: fn(&mut Rng, usize) -> String) -> String {
+        let count = self.below(if depth > 1 { 8 } else { 4 });
+        (0..count)
+            .map(|_| {
+                let gap = self.gap();
+                format!("{}{}", gap, element(self, depth))
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    fn program(&mut self) -> String {
+        let mut source = String::new();
+        for _ in 0..self.below(6) {
+            let statement = match self.below(3) {
+                0 => self.expression(3),
+                1 => format!("{} = {}", self.pick(&["x", "total"]), self.expression(3)),
+                _ => format!("{} += {}", self.pick(&["x", "total"]), self.expression(3)),
+            };
+            source.push_str(&statement);
+            source.push_str(self.pick(&["\n", "; ", "  # end of line\n", "\n\n\n", ";\n"]));
+        }
+        source
+    }
+}
+
+fn parse(source: &str) -> Program {
+    Parser::new(Lexer::new(source).tokenize().unwrap()).parse()
+        .unwrap_or_else(|error| panic!("{:?} in:\n{}", error, source))
+}
+
+/// The program's debug form without spans, which formatting moves.
+fn shape(program: &Program) -> String {
+    let spans = regex::Regex::new(r"span: Span \{ start: \d+, end: \d+ \}").unwrap();
+    spans.replace_all(&format!("{:?}", program), "span").to_string()
+}
+
+fn comments(source: &str) -> Vec<String> {
+    SyntaxTree::parse(source).root().tokens().into_iter()
+        .flat_map(|token| &token.leading_trivia)
+        .filter(|trivia| trivia.kind == TriviaKind::Comment)
+        .map(|trivia| trivia.text.trim_end().to_string())
+        .collect()
+}
+
+#[test]
+fn test_golden_files() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fmt");
+    let mut checked = 0;
+    for entry in std::fs::read_dir(&directory).unwrap() {
+        let path = entry.unwrap().path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()).and_then(|name| name.strip_suffix(".input.bccl")) else {
+            continue;
+        };
+        let input = std::fs::read_to_string(&path).unwrap();
+        let expected = std::fs::read_to_string(directory.join(format!("{}.output.bccl", name))).unwrap();
+        let formatted = format_source(&input, &FormatOptions::default()).unwrap();
+        assert_eq!(formatted, expected, "{}", name);
+        assert_eq!(format_source(&expected, &FormatOptions::default()).unwrap(), expected, "{} is not stable", name);
+        checked += 1;
+    }
+    assert!(checked >= 4);
+}
+
+#[test]
+fn test_formatting_keeps_meaning_and_comments() {
+    let mut rng = Rng(0x5eed_f0f0);
+    for _ in 0..1000 {
+        let source = rng.program();
+        let options = FormatOptions { width: 20 + rng.below(60), indent: 2 + rng.below(3) };
+        let formatted = format_source(&source, &options).unwrap_or_else(|error| panic!("{:?} in:\n{}", error, source));
+
+        assert_eq!(shape(&parse(&formatted)), shape(&parse(&source)), "\n{}\n=>\n{}", source, formatted);
+        assert_eq!(comments(&formatted), comments(&source), "\n{}\n=>\n{}", source, formatted);
+        assert_eq!(format_source(&formatted, &options).unwrap(), formatted, "not idempotent for:\n{}", source);
+    }
+}
+
+#[test]
+fn test_width_option() {
+    let source = "values = [100, 200, 300]\n";
+    assert_eq!(format_source(source, &FormatOptions::default()).unwrap(), source);
+    let narrow = FormatOptions { width: 20, indent: 2 };
+    assert_eq!(format_source(source, &narrow).unwrap(), "values = [\n  100,\n  200,\n  300\n]\n");
+}
+
+#[test]
+fn test_rejects_invalid_source() {
+    assert!(format_source("x = [1, 2", &FormatOptions::default()).is_err());
+    assert!(format_source("x = 1 @ 2", &FormatOptions::default()).is_err());
+    assert_eq!(format_source("", &FormatOptions::default()).unwrap(), "");
+    assert_eq!(format_source("  \n\n", &FormatOptions::default()).unwrap(), "");
+}
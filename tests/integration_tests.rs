@@ -1,7 +1,10 @@
-/// Comprehensive integration tests for BCCL interpreter
-/// 
-/// This module consolidates all the binary test files into proper cargo tests,
-/// providing better organization and integration with the standard test framework.
+//! Comprehensive integration tests for BCCL interpreter
+//! 
+//! This module consolidates all the binary test files into proper cargo tests,
+//! providing better organization and integration with the standard test framework.
+
+// Test inputs use literals like 3.14 as BCCL source, not as approximations of PI
+#![allow(clippy::approx_constant)]
 
 use bccl::{Lexer, Parser, Evaluator, Value};
 
@@ -33,10 +36,7 @@ fn eval_number(code: &str, expected: f64) {
 
 /// Helper function to evaluate code and expect an error
 fn eval_error(code: &str) {
-    match eval_code(code) {
-        Ok(_) => panic!("Expected error but evaluation succeeded for input: {}", code),
-        Err(_) => {} // Expected error
-    }
+    assert!(eval_code(code).is_err(), "Expected error but evaluation succeeded for input: {}", code);
 }
 
 /// Helper function to evaluate code with a persistent evaluator
@@ -79,18 +79,17 @@ mod function_tests {
 
     #[test]
     fn test_function_kwargs() {
-        eval_number("max(a=5, b=10)", 10.0);
-        eval_number("max(5, b=10)", 10.0);
-        eval_number("min(a=3, b=8)", 3.0);
-        eval_number("min(3, b=8)", 3.0);
+        eval_number("max(values=[5, 10])", 10.0);
+        eval_number("max([], default=10)", 10.0);
+        eval_number("min(3, 8, default=0)", 3.0);
+        eval_number("min(values=[3, 8])", 3.0);
     }
 
     #[test]
     fn test_function_errors() {
-        // Wrong argument count
-        eval_error("max(5)");
-        eval_error("max(5, 10, 15)");
+        // Nothing to choose from
         eval_error("min()");
+        eval_error("max([])");
         
         // Wrong argument types
         eval_error("max(5, true)");
@@ -101,7 +100,8 @@ mod function_tests {
         
         // Invalid kwargs
         eval_error("max(x=5, y=10)");  // Unknown parameters
-        eval_error("max(5, a=10)");    // Duplicate parameter
+        eval_error("max(5, a=10)");    // Unknown parameter
+        eval_error("max(5, key=str, key=int)");  // Duplicate option
     }
 }
 
@@ -132,9 +132,9 @@ mod variadic_function_tests {
 
     #[test]
     fn test_sum_function_kwargs() {
-        eval_number("sum(values=5, values=10, values=15)", 30.0);
-        eval_number("sum(5, values=10, values=15)", 30.0);
-        eval_number("sum(values=1, values=2, values=3, values=4)", 10.0);
+        // The variadic parameter takes a list when passed by keyword
+        eval_number("sum(values=[5, 10, 15])", 30.0);
+        eval_number("sum(values=[1, 2, 3, 4])", 10.0);
     }
 
     #[test]
@@ -160,9 +160,8 @@ mod variadic_function_tests {
 
     #[test]
     fn test_product_function_kwargs() {
-        eval_number("product(values=2, values=3, values=4)", 24.0);
-        eval_number("product(2, values=3, values=4)", 24.0);
-        eval_number("product(values=1, values=2, values=3)", 6.0);
+        eval_number("product(values=[2, 3, 4])", 24.0);
+        eval_number("product(values=[1, 2, 3])", 6.0);
     }
 
     #[test]
@@ -180,6 +179,11 @@ mod variadic_function_tests {
         // Invalid kwargs
         eval_error("sum(x=5, y=10)");      // Unknown parameters
         eval_error("product(items=5)");    // Unknown parameter name
+        
+        // Ambiguous or malformed variadic keywords
+        eval_error("sum(5, values=[10, 15])");         // Positional values and keyword
+        eval_error("sum(values=[5], values=[10])");    // Keyword given twice
+        eval_error("sum(values=5)");                   // Keyword must be a list
     }
 
     #[test]
@@ -188,9 +192,9 @@ mod variadic_function_tests {
         eval_number("sum(1, 1, 1, 1, 1, 1, 1, 1, 1, 1)", 10.0);
         eval_number("product(1, 1, 1, 1, 1, 1, 1, 1, 1, 2)", 2.0);
         
-        // Test mixed positional and keyword arguments with many values
-        eval_number("sum(1, 2, values=3, values=4, values=5)", 15.0);
-        eval_number("product(2, values=1, values=1, values=1, values=5)", 10.0);
+        // Test many values passed as one keyword list or unpacked
+        eval_number("sum(values=[1, 2, 3, 4, 5])", 15.0);
+        eval_number("product(*[2, 1, 1, 1, 5])", 10.0);
     }
 
     #[test]
@@ -201,6 +205,356 @@ mod variadic_function_tests {
     }
 }
 
+#[cfg(test)]
+mod conversion_tests {
+    use super::*;
+
+    #[test]
+    fn test_int_conversion() {
+        match eval_code("int(\"42\")").unwrap().unwrap() {
+            Value::Integer(42) => {},
+            other => panic!("Expected Integer(42), got {:?}", other),
+        }
+        
+        match eval_code("int(-3.9)").unwrap().unwrap() {
+            Value::Integer(-3) => {},
+            other => panic!("Expected Integer(-3), got {:?}", other),
+        }
+        
+        match eval_code("int(true) + int(\" 7 \")").unwrap().unwrap() {
+            Value::Number(8.0) => {},
+            other => panic!("Expected Number(8), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_float_str_bool_conversion() {
+        eval_number("float(\"3.5\")", 3.5);
+        eval_number("float(2)", 2.0);
+        
+        match eval_code("str(42)").unwrap().unwrap() {
+            Value::String(s) if s.as_ref() == "42" => {},
+            other => panic!("Expected String(\"42\"), got {:?}", other),
+        }
+        
+        match eval_code("str([1, \"a\"])").unwrap().unwrap() {
+            Value::String(s) if s.as_ref() == "[1, \"a\"]" => {},
+            other => panic!("Expected list display string, got {:?}", other),
+        }
+        
+        match eval_code("bool(\"\") or bool([0])").unwrap().unwrap() {
+            Value::Boolean(true) => {},
+            other => panic!("Expected Boolean(true), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_collection_conversion() {
+        match eval_code("list(\"ab\")").unwrap().unwrap() {
+            Value::List(items) => assert_eq!(items.len(), 2),
+            other => panic!("Expected List, got {:?}", other),
+        }
+        
+        match eval_code("list({\"b\": 2, \"a\": 1})[0]").unwrap().unwrap() {
+            Value::String(s) if s.as_ref() == "a" => {},
+            other => panic!("Expected sorted keys, got {:?}", other),
+        }
+        
+        match eval_code("dict([[\"a\", 1], [\"b\", 2]])[\"b\"]").unwrap().unwrap() {
+            Value::Integer(2) => {},
+            other => panic!("Expected Integer(2), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_type_introspection() {
+        let cases = [
+            ("type(1)", "integer"),
+            ("type(1.5)", "number"),
+            ("type(true)", "boolean"),
+            ("type(\"x\")", "string"),
+            ("type([])", "list"),
+            ("type({})", "dictionary"),
+            ("type(int(\"4\"))", "integer"),
+        ];
+        for (code, expected) in cases {
+            match eval_code(code).unwrap().unwrap() {
+                Value::String(s) if s.as_ref() == expected => {},
+                other => panic!("Expected {} for {}, got {:?}", expected, code, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_type_predicates() {
+        let cases = [
+            ("is_number(1.5)", true),
+            ("is_number(2)", true),
+            ("is_number(\"2\")", false),
+            ("is_integer(2)", true),
+            ("is_integer(2.0)", false),
+            ("is_string(\"x\")", true),
+            ("is_boolean(false)", true),
+            ("is_list([1])", true),
+            ("is_dict({})", true),
+            ("is_dict([])", false),
+        ];
+        for (code, expected) in cases {
+            match eval_code(code).unwrap().unwrap() {
+                Value::Boolean(b) => assert_eq!(b, expected, "for {}", code),
+                other => panic!("Expected Boolean for {}, got {:?}", code, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_conversion_errors() {
+        eval_error("int(\"4x2\")");
+        eval_error("int(\"3.5\")");
+        eval_error("float(\"abc\")");
+        eval_error("int([1])");
+        eval_error("dict([1, 2])");
+        eval_error("dict([[1, 2]])");
+        eval_error("list(5)");
+        eval_error("int()");
+        eval_error("type(1, 2)");
+    }
+}
+
+#[cfg(test)]
+mod min_max_tests {
+    use super::*;
+
+    #[test]
+    fn test_variadic_min_max() {
+        eval_number("max(1, 2, 3)", 3.0);
+        eval_number("min(4, -2.5, 9, 0)", -2.5);
+        eval_number("max(5)", 5.0);
+        eval_number("x = [4, 7, 1]; max(x) - min(x)", 6.0);
+    }
+
+    #[test]
+    fn test_min_max_strings() {
+        match eval_code("max(\"pear\", \"apple\", \"fig\")").unwrap().unwrap() {
+            Value::String(s) => assert_eq!(s.as_ref(), "pear"),
+            other => panic!("Expected String, got {:?}", other),
+        }
+        match eval_code("min([\"b\", \"a\", \"c\"])").unwrap().unwrap() {
+            Value::String(s) => assert_eq!(s.as_ref(), "a"),
+            other => panic!("Expected String, got {:?}", other),
+        }
+        match eval_code("min({\"y\": 1, \"x\": 2})").unwrap().unwrap() {
+            Value::String(s) => assert_eq!(s.as_ref(), "x"),
+            other => panic!("Expected String, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_min_max_default_and_key() {
+        eval_number("max([], default=-1)", -1.0);
+        match eval_code("min([], default=\"none\")").unwrap().unwrap() {
+            Value::String(s) => assert_eq!(s.as_ref(), "none"),
+            other => panic!("Expected String, got {:?}", other),
+        }
+        // Key results are compared, the original item is returned
+        match eval_code("max([\"9\", \"10\", \"2\"], key=int)").unwrap().unwrap() {
+            Value::String(s) => assert_eq!(s.as_ref(), "10"),
+            other => panic!("Expected String, got {:?}", other),
+        }
+        // Ties keep the first candidate
+        match eval_code("min([\"1\", 1], key=float)").unwrap().unwrap() {
+            Value::String(s) => assert_eq!(s.as_ref(), "1"),
+            other => panic!("Expected String, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_string_comparison_operators() {
+        for (code, expected) in [
+            ("\"apple\" < \"banana\"", true),
+            ("\"b\" >= \"b\"", true),
+            ("\"Z\" > \"a\"", false),
+        ] {
+            match eval_code(code).unwrap().unwrap() {
+                Value::Boolean(b) => assert_eq!(b, expected, "for {}", code),
+                other => panic!("Expected Boolean for {}, got {:?}", code, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_min_max_errors() {
+        eval_error("max(1, \"a\")");
+        eval_error("min([1, [2]])");
+        eval_error("max(true, false)");
+        eval_error("max([1, 2], [3])");
+        eval_error("\"a\" < 1");
+        eval_error("1 < \"a\"");
+    }
+
+    #[test]
+    fn test_legacy_min_max_match_builtins() {
+        use bccl::{builtin_max, builtin_min};
+        let strings = [Value::string("pear"), Value::string("apple")];
+        assert_eq!(builtin_max(&strings).unwrap().display(), "\"pear\"");
+        assert_eq!(builtin_min(&[Value::Integer(4), Value::Integer(-2), Value::Integer(9)]).unwrap().display(), "-2");
+        assert_eq!(builtin_max(&[Value::list(vec![Value::Integer(1), Value::Integer(7)])]).unwrap().display(), "7");
+        assert!(builtin_min(&[]).is_err());
+        assert!(builtin_max(&[Value::Integer(1), Value::string("a")]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod signature_tests {
+    use super::*;
+    use bccl::{BuiltinCall, FunctionSignature, Parameters, BcclResult};
+
+    /// Echoes its bindings as `[a, b, rest, flag, opts]` so tests can inspect them.
+    fn echo(call: &BuiltinCall) -> BcclResult<Value> {
+        Ok(Value::list(vec![
+            call.args[0].clone(),
+            call.args[1].clone(),
+            Value::list(call.args[2..].to_vec()),
+            call.option("flag").unwrap().0.clone(),
+            call.option("opts").unwrap().0.clone(),
+        ]))
+    }
+
+    /// Evaluates `code` with `f(a, b=2, *rest, flag=false, **opts)` defined.
+    fn eval_with_f(code: &str) -> Result<Option<Value>, Box<dyn std::error::Error>> {
+        let mut evaluator = Evaluator::new();
+        let params = Parameters::new()
+            .positional("a")
+            .positional_with_default("b", Value::Integer(2))
+            .rest("rest")
+            .keyword_only_with_default("flag", Value::Boolean(false))
+            .var_keyword("opts");
+        evaluator.define_function(FunctionSignature::new_native_with_parameters("f", params, echo));
+        eval_with_evaluator(&mut evaluator, code)
+    }
+
+    fn f_display(code: &str) -> String {
+        eval_with_f(code).unwrap().unwrap().display()
+    }
+
+    #[test]
+    fn test_defaults_and_keyword_only() {
+        assert_eq!(f_display("f(1)"), "[1, 2, [], false, {}]");
+        assert_eq!(f_display("f(1, 5)"), "[1, 5, [], false, {}]");
+        assert_eq!(f_display("f(b=5, a=1)"), "[1, 5, [], false, {}]");
+        assert_eq!(f_display("f(1, flag=true)"), "[1, 2, [], true, {}]");
+    }
+
+    #[test]
+    fn test_rest_and_var_keyword() {
+        assert_eq!(f_display("f(1, 2, 3, 4)"), "[1, 2, [3, 4], false, {}]");
+        assert_eq!(f_display("f(1, mode=\"fast\")"), "[1, 2, [], false, {\"mode\": \"fast\"}]");
+    }
+
+    #[test]
+    fn test_call_site_unpacking() {
+        assert_eq!(f_display("xs = [1, 2, 3]; f(*xs)"), "[1, 2, [3], false, {}]");
+        assert_eq!(f_display("f(0, *[7], 8)"), "[0, 7, [8], false, {}]");
+        assert_eq!(f_display("d = {\"flag\": true, \"b\": 9}; f(1, **d)"), "[1, 9, [], true, {}]");
+        eval_number("sum(*[1, 2, 3])", 6.0);
+        eval_number("max(*[], **{\"default\": 4})", 4.0);
+    }
+
+    #[test]
+    fn test_builtin_keyword_only_default() {
+        assert_eq!(eval_code("sorted([2, 1])").unwrap().unwrap().display(), "[1, 2]");
+        assert_eq!(eval_code("sorted([2, 1], reverse=true)").unwrap().unwrap().display(), "[2, 1]");
+        // reverse is keyword-only
+        eval_error("sorted([2, 1], true)");
+    }
+
+    #[test]
+    fn test_signature_errors() {
+        assert!(eval_with_f("f()").is_err());                   // missing a
+        assert!(eval_with_f("f(1, a=2)").is_err());             // a given twice
+        assert!(eval_with_f("f(1, flag=true, flag=false)").is_err());
+        assert!(eval_with_f("f(1, **{\"a\": 2})").is_err());     // a given twice via **
+        assert!(eval_with_f("f(*5)").is_err());                 // only lists unpack with *
+        assert!(eval_with_f("f(1, **[1])").is_err());           // only dictionaries unpack with **
+        assert!(eval_with_f("f(x=1, *[2])").is_err());          // positional after keyword
+        eval_error("max(1, 2, nope=3)");                        // no **kwargs: unknown parameter
+    }
+}
+
+#[cfg(test)]
+mod higher_order_tests {
+    use super::*;
+
+    fn eval_display(code: &str) -> String {
+        eval_code(code).unwrap().unwrap().display()
+    }
+
+    #[test]
+    fn test_builtins_are_values() {
+        assert_eq!(eval_display("str"), "<function str>");
+        assert_eq!(eval_display("type(max)"), "\"function\"");
+        assert_eq!(eval_display("f = str; f(3)"), "\"3\"");
+    }
+
+    #[test]
+    fn test_map_and_filter() {
+        assert_eq!(eval_display("map(str, [1, 2])"), "[\"1\", \"2\"]");
+        assert_eq!(eval_display("map(int, \"12\")"), "[1, 2]");
+        assert_eq!(eval_display("filter(is_number, [1, \"a\", 2.5])"), "[1, 2.5]");
+        assert_eq!(eval_display("filter(bool, [])"), "[]");
+    }
+
+    #[test]
+    fn test_reduce() {
+        eval_number("reduce(max, [3, 9, 4], 0)", 9.0);
+        eval_number("reduce(sum, [1, 2, 3], 10)", 16.0);
+        eval_number("reduce(sum, [], 10)", 10.0);
+    }
+
+    #[test]
+    fn test_sorted() {
+        assert_eq!(eval_display("sorted([3, 1.5, 2])"), "[1.5, 2, 3]");
+        assert_eq!(eval_display("sorted([\"b\", \"c\", \"a\"], reverse=true)"), "[\"c\", \"b\", \"a\"]");
+        assert_eq!(eval_display("sorted({\"b\": 1, \"a\": 2})"), "[\"a\", \"b\"]");
+        // Stable: equal keys keep their original order
+        assert_eq!(eval_display("sorted([\"2\", 1, 2, \"1\"], key=int)"), "[1, \"1\", \"2\", 2]");
+        assert_eq!(eval_display("sorted([\"2\", 1, 2, \"1\"], key=int, reverse=true)"), "[\"2\", 2, 1, \"1\"]");
+    }
+
+    #[test]
+    fn test_min_max_with_key() {
+        assert_eq!(eval_display("max(\"9\", \"10\", key=int)"), "\"10\"");
+        assert_eq!(eval_display("min(\"9\", \"10\", key=int)"), "\"9\"");
+        eval_number("max(3, 7)", 7.0);
+    }
+
+    #[test]
+    fn test_group_by() {
+        let result = eval_code("group_by(type, [1, \"a\", 2])").unwrap().unwrap();
+        match result {
+            Value::Dictionary(groups) => {
+                assert_eq!(groups.len(), 2);
+                assert_eq!(groups["integer"].display(), "[1, 2]");
+                assert_eq!(groups["string"].display(), "[\"a\"]");
+            }
+            other => panic!("Expected Dictionary, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_higher_order_errors() {
+        eval_error("max = 3; max(1, 2)");
+        eval_error("map(3, [1, 2])");
+        eval_error("map(str, 5)");
+        eval_error("map(int, [\"1\", \"x\"])");
+        eval_error("sorted([1, \"a\"])");
+        eval_error("sorted([1, 2], reverse=1)");
+        eval_error("sorted([1, 2], order=1)");
+        eval_error("sorted([1, 2], key=str, key=int)");
+        eval_error("reduce(max, [1, 2])");
+    }
+}
+
 #[cfg(test)]
 mod compound_assignment_tests {
     use super::*;
@@ -266,7 +620,7 @@ mod collection_tests {
         }
         
         match eval_code("[\"a\", \"b\", \"c\"][1]").unwrap().unwrap() {
-            Value::String(s) if s == "b" => {},
+            Value::String(s) if s.as_ref() == "b" => {},
             other => panic!("Expected String(\"b\"), got {:?}", other),
         }
         
@@ -281,7 +635,7 @@ mod collection_tests {
     fn test_dictionary_operations() {
         // Dictionary creation and lookup
         match eval_code("{\"name\": \"John\"}[\"name\"]").unwrap().unwrap() {
-            Value::String(s) if s == "John" => {},
+            Value::String(s) if s.as_ref() == "John" => {},
             other => panic!("Expected String(\"John\"), got {:?}", other),
         }
         
@@ -357,7 +711,7 @@ mod logical_operator_tests {
         }
         
         match eval_code("true and \"hello\"").unwrap().unwrap() {
-            Value::String(s) if s == "hello" => {},
+            Value::String(s) if s.as_ref() == "hello" => {},
             other => panic!("Expected String(\"hello\"), got {:?}", other),
         }
     }
@@ -381,7 +735,7 @@ mod logical_operator_tests {
         }
         
         match eval_code("false or \"hello\"").unwrap().unwrap() {
-            Value::String(s) if s == "hello" => {},
+            Value::String(s) if s.as_ref() == "hello" => {},
             other => panic!("Expected String(\"hello\"), got {:?}", other),
         }
     }
@@ -534,10 +888,24 @@ mod error_diagnostic_tests {
         ];
 
         for (input, _description) in error_cases {
-            match eval_code(input) {
-                Ok(_) => panic!("Expected error for input: {}", input),
-                Err(_) => {} // Expected - we're just testing that errors occur
-            }
+            // We're just testing that errors occur
+            assert!(eval_code(input).is_err(), "Expected error for input: {}", input);
         }
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_multi_line_script() {
+    let script = "
+rates = {
+    \"low\": 0.5,
+    \"high\": 2
+}
+base = 10 *
+    rates[\"high\"]
+base += sum(1,
+            2)
+base
+";
+    eval_number(script, 23.0);
+    eval_error("base = 1 base");
+}
@@ -0,0 +1,161 @@
+//! Tests for `EvaluatorLimits` on both backends.
+
+use bccl::{Backend, BcclError, Evaluator, EvaluatorLimits, Lexer, Parser, Span, Value};
+
+fn evaluator(backend: Backend, limits: EvaluatorLimits) -> Evaluator {
+    let mut evaluator = Evaluator::with_backend(backend);
+    evaluator.set_limits(limits);
+    evaluator
+}
+
+fn run(evaluator: &mut Evaluator, code: &str) -> Result<Option<Value>, BcclError> {
+    let tokens = Lexer::new(code).tokenize().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+    evaluator.evaluate_program(&program)
+}
+
+/// Runs `code` on both backends and returns the limit that was hit and its span.
+fn exceeded(limits: EvaluatorLimits, code: &str) -> (String, Span) {
+    let mut results = Vec::new();
+    for backend in [Backend::TreeWalk, Backend::Bytecode] {
+        match run(&mut evaluator(backend, limits), code) {
+            Err(BcclError::LimitExceeded { limit, span, .. }) => results.push((limit, Span::new(span.offset(), span.offset() + span.len()))),
+            other => panic!("{:?}: expected LimitExceeded for {}, got {:?}", backend, code, other),
+        }
+    }
+    assert_eq!(results[0], results[1], "backends disagree on {}", code);
+    results.remove(0)
+}
+
+#[test]
+fn test_no_limits_by_default() {
+    let evaluator = Evaluator::new();
+    assert_eq!(*evaluator.limits(), EvaluatorLimits::new());
+    let mut evaluator = Evaluator::new();
+    let result = run(&mut evaluator, "x = [1, 2, 3]; sum(*x) + max(map(int, [\"4\"]))").unwrap().unwrap();
+    assert_eq!(result.display(), "10");
+}
+
+#[test]
+fn test_step_limit() {
+    for backend in [Backend::TreeWalk, Backend::Bytecode] {
+        let mut evaluator = evaluator(backend, EvaluatorLimits::new().with_max_steps(20));
+        assert!(run(&mut evaluator, "x = 1; x + 2").is_ok());
+
+        let error = run(&mut evaluator, "x = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20]");
+        assert!(matches!(error, Err(BcclError::LimitExceeded { ref limit, max: 20, .. }) if limit == "steps"), "{:?}: {:?}", backend, error);
+
+        // Usage is reset for every run
+        assert!(run(&mut evaluator, "x = 1; x + 2").is_ok());
+    }
+}
+
+#[test]
+fn test_call_depth_limit() {
+    let limits = EvaluatorLimits::new().with_max_call_depth(1);
+    for backend in [Backend::TreeWalk, Backend::Bytecode] {
+        assert!(run(&mut evaluator(backend, limits), "max(1, 2)").is_ok());
+    }
+
+    // The callback passed to map() is a nested call; its error is wrapped by map()
+    for backend in [Backend::TreeWalk, Backend::Bytecode] {
+        match run(&mut evaluator(backend, limits), "map(str, [1])") {
+            Err(BcclError::CallbackError { errors, .. }) => {
+                assert!(matches!(&errors[0], BcclError::LimitExceeded { limit, .. } if limit == "call_depth"));
+            }
+            other => panic!("{:?}: expected CallbackError, got {:?}", backend, other),
+        }
+    }
+}
+
+#[test]
+fn test_collection_size_limit() {
+    let limits = EvaluatorLimits::new().with_max_collection_size(3);
+    assert_eq!(exceeded(limits, "x = [1, 2]; [0, 1, 2, 3]"), ("collection_size".to_string(), Span::new(12, 24)));
+    assert_eq!(exceeded(limits, "{\"a\": 1, \"b\": 2, \"c\": 3, \"d\": 4}").0, "collection_size");
+    assert_eq!(exceeded(limits, "list(\"abcd\")"), ("collection_size".to_string(), Span::new(0, 12)));
+}
+
+#[test]
+fn test_total_elements_limit() {
+    let limits = EvaluatorLimits::new().with_max_total_elements(5);
+    assert_eq!(exceeded(limits, "a = [1, 2, 3]; b = [4, 5, 6]"), ("total_elements".to_string(), Span::new(19, 28)));
+
+    let mut evaluator = evaluator(Backend::TreeWalk, limits);
+    assert!(run(&mut evaluator, "a = [1, 2, 3]").is_ok());
+    assert!(run(&mut evaluator, "b = [4, 5, 6]").is_ok(), "the total is per run");
+}
+
+#[test]
+fn test_string_length_limit() {
+    let limits = EvaluatorLimits::new().with_max_string_length(5);
+    assert!(run(&mut evaluator(Backend::TreeWalk, limits), "\"hello\"").is_ok());
+    assert_eq!(exceeded(limits, "s = \"abc\"; str([1, 2, 3])"), ("string_length".to_string(), Span::new(11, 25)));
+    assert_eq!(exceeded(limits, "\"too long\"").0, "string_length");
+}
+
+#[test]
+fn test_limit_error_keeps_earlier_effects() {
+    for backend in [Backend::TreeWalk, Backend::Bytecode] {
+        let mut evaluator = evaluator(backend, EvaluatorLimits::new().with_max_collection_size(2));
+        assert!(run(&mut evaluator, "x = 1; y = [1, 2, 3]").is_err());
+        assert_eq!(evaluator.get_variable("x").unwrap().display(), "1");
+        assert!(evaluator.get_variable("y").is_none());
+    }
+}
+
+#[test]
+fn test_limits_apply_to_constant_expressions() {
+    let cases = [
+        (EvaluatorLimits::new().with_max_collection_size(2), "[1, 2, 3][0]", "collection_size"),
+        (EvaluatorLimits::new().with_max_collection_size(2), "2 in [1, 2, 3]", "collection_size"),
+        (EvaluatorLimits::new().with_max_string_length(3), "\"abcdef\" == \"x\"", "string_length"),
+        (EvaluatorLimits::new().with_max_total_elements(2), "x = [1, 2][0]; [3, 4][1]", "total_elements"),
+    ];
+    for (limits, code, expected) in cases {
+        let mut results = Vec::new();
+        for optimize in [true, false] {
+            for backend in [Backend::TreeWalk, Backend::Bytecode] {
+                let mut evaluator = evaluator(backend, limits);
+                evaluator.set_optimize(optimize);
+                match run(&mut evaluator, code) {
+                    Err(BcclError::LimitExceeded { limit, span, .. }) => {
+                        assert_eq!(limit, expected, "{}", code);
+                        results.push(Span::new(span.offset(), span.offset() + span.len()));
+                    }
+                    other => panic!("{:?} (optimize: {}): expected LimitExceeded for {}, got {:?}", backend, optimize, code, other),
+                }
+            }
+        }
+        assert!(results.iter().all(|span| *span == results[0]), "spans differ for {}: {:?}", code, results);
+    }
+}
+
+#[test]
+fn test_backends_count_the_same_steps() {
+    let programs = [
+        "x = 1",
+        "1 + 2 * 3",
+        "x = 1; x += 2; [x, -x][0]",
+        "d = {\"a\": [1, 2]}; max(*d[\"a\"], 3) + sum(1, 2)",
+        "map(str, [1, 2, 3])",
+    ];
+    for code in programs {
+        for max in 0..25 {
+            let limits = EvaluatorLimits::new().with_max_steps(max);
+            let mut outcomes = Vec::new();
+            for optimize in [true, false] {
+                for backend in [Backend::TreeWalk, Backend::Bytecode] {
+                    let mut evaluator = evaluator(backend, limits);
+                    evaluator.set_optimize(optimize);
+                    outcomes.push(match run(&mut evaluator, code) {
+                        Ok(value) => format!("{:?}", value.map(|value| value.display())),
+                        Err(BcclError::LimitExceeded { limit, span, .. }) => format!("{} at {:?}", limit, span),
+                        Err(error) => format!("{:?}", error),
+                    });
+                }
+            }
+            assert!(outcomes.iter().all(|outcome| *outcome == outcomes[0]), "max_steps {} on {}: {:?}", max, code, outcomes);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Tests for `bccl-lsp`: a scripted client drives the binary over stdio
+//! through a whole session, from `initialize` to `exit`. Needs the `lsp`
+//! feature: `cargo test --features lsp`.
+#![cfg(feature = "lsp")]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use serde_json::{json, Value as Json};
+
+const URI: &str = "file:///budget.bccl";
+
+/// A scripted client talking JSON-RPC to a `bccl-lsp` process.
+struct Client {
+    process: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: u64,
+}
+
+impl Client {
+    fn start() -> Self {
+        let mut process = Command::new(env!("CARGO_BIN_EXE_bccl-lsp"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stdin = process.stdin.take().unwrap();
+        let stdout = BufReader::new(process.stdout.take().unwrap());
+        Self { process, stdin, stdout, next_id: 1 }
+    }
+
+    fn send(&mut self, message: Json) {
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Json {
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            assert!(self.stdout.read_line(&mut line).unwrap() > 0, "the server closed its output");
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(value) = line.strip_prefix("Content-Length: ") {
+                length = value.parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        self.stdout.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn notify(&mut self, method: &str, params: Json) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    /// Sends a request and returns its result, checking the response's id.
+    fn request(&mut self, method: &str, params: Json) -> Json {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        let response = self.receive();
+        assert_eq!(response["id"], id, "{}", response);
+        assert!(response.get("error").is_none(), "{}", response);
+        response["result"].clone()
+    }
+
+    fn at(&mut self, method: &str, line: u32, character: u32) -> Json {
+        self.request(method, json!({ "textDocument": { "uri": URI }, "position": { "line": line, "character": character } }))
+    }
+
+    /// The diagnostics of the next notification, which must be for `URI`.
+    fn diagnostics(&mut self) -> Vec<Json> {
+        let notification = self.receive();
+        assert_eq!(notification["method"], "textDocument/publishDiagnostics");
+        assert_eq!(notification["params"]["uri"], URI);
+        notification["params"]["diagnostics"].as_array().unwrap().clone()
+    }
+}
+
+#[test]
+fn test_scripted_session() {
+    let mut client = Client::start();
+    let result = client.request("initialize", json!({ "processId": null, "rootUri": null, "capabilities": {} }));
+    let capabilities = &result["capabilities"];
+    assert_eq!(capabilities["textDocumentSync"], 1);
+    assert_eq!(capabilities["hoverProvider"], true);
+    assert_eq!(capabilities["definitionProvider"], true);
+    assert_eq!(capabilities["signatureHelpProvider"]["triggerCharacters"], json!(["(", ","]));
+    client.notify("initialized", json!({}));
+
+    // The type error is the string `"12"`, line 2, columns 14 to 18
+    let text = "rent = 1200\nfood = 300\nyear = rent * \"12\"";
+    client.notify("textDocument/didOpen", json!({
+        "textDocument": { "uri": URI, "languageId": "bccl", "version": 1, "text": text },
+    }));
+    let diagnostics = client.diagnostics();
+    let error = diagnostics.iter().find(|diagnostic| diagnostic["severity"] == 1).unwrap();
+    assert_eq!(error["code"], "bccl::types::mismatch");
+    assert_eq!(error["range"], json!({ "start": { "line": 2, "character": 14 }, "end": { "line": 2, "character": 18 } }));
+
+    let text = "rent = 1200\nfood = 300\nyear = sum([rent, food]) * 12\ntop = sorted([rent, food], )";
+    client.notify("textDocument/didChange", json!({
+        "textDocument": { "uri": URI, "version": 2 },
+        "contentChanges": [{ "text": text }],
+    }));
+    // The call being typed does not parse yet, but the rest still answers
+    let diagnostics = client.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["code"], "bccl::parser::unexpected_token");
+    assert_eq!(diagnostics[0]["range"]["start"], json!({ "line": 3, "character": 27 }));
+
+    let items = client.at("textDocument/completion", 3, 27);
+    let labels: Vec<&str> = items.as_array().unwrap().iter().map(|item| item["label"].as_str().unwrap()).collect();
+    for label in ["reverse=", "key=", "rent", "food", "year", "sorted", "getenv", "true"] {
+        assert!(labels.contains(&label), "{} is missing from {:?}", label, labels);
+    }
+
+    let hover = client.at("textDocument/hover", 2, 8);
+    assert_eq!(hover["contents"]["value"], "```bccl\nsum(*values)\n```\nBuilt-in function");
+    let hover = client.at("textDocument/hover", 2, 13);
+    assert_eq!(hover["contents"]["value"], "```bccl\nrent: int\n```\nVariable, first assigned on line 1");
+
+    let definition = client.at("textDocument/definition", 3, 21);
+    assert_eq!(definition, json!({
+        "uri": URI,
+        "range": { "start": { "line": 1, "character": 0 }, "end": { "line": 1, "character": 4 } },
+    }));
+
+    let help = client.at("textDocument/signatureHelp", 3, 27);
+    assert_eq!(help["signatures"][0]["label"], "sorted(values, *, reverse=false, key=...)");
+    // After the comma, with `sorted` taking one positional argument, no
+    // parameter is current until a keyword is typed
+    assert_eq!(help["activeParameter"], 3);
+
+    client.notify("textDocument/didClose", json!({ "textDocument": { "uri": URI } }));
+    assert!(client.diagnostics().is_empty());
+
+    assert_eq!(client.request("shutdown", Json::Null), Json::Null);
+    client.notify("exit", Json::Null);
+    assert!(client.process.wait().unwrap().success());
+}
+
+#[test]
+fn test_exit_without_shutdown_fails() {
+    let mut client = Client::start();
+    client.request("initialize", json!({ "capabilities": {} }));
+    client.notify("exit", Json::Null);
+    assert_eq!(client.process.wait().unwrap().code(), Some(1));
+}
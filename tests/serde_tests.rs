@@ -0,0 +1,249 @@
+//! Round-trip tests for the `serde` feature.
+//!
+//! Run with `cargo test --features serde`. Values, tokens and programs are
+//! generated from a fixed seed, serialized to JSON and read back; the result
+//! must be identical, down to the bits of every float.
+#![cfg(feature = "serde")]
+
+use std::collections::HashMap;
+use bccl::{
+    BinaryOp, CompoundOp, Evaluator, Expr, Lexer, Parser, Program, Slot, Snapshot, Span, Stmt, Token, TypeAnnotation,
+    UnaryOp, Value,
+};
+
+mod common;
+
+use common::Rng;
+
+impl Rng {
+    fn integer(&mut self) -> i64 {
+        match self.below(4) {
+            0 => [0, 1, -1, i64::MIN, i64::MAX][self.below(5)],
+            1 => self.below(1000) as i64 - 500,
+            _ => self.next() as i64,
+        }
+    }
+
+    /// Any finite float, including awkward ones.
+    fn number(&mut self) -> f64 {
+        match self.below(4) {
+            0 => [0.0, -0.0, 1.0, 0.1, f64::MIN_POSITIVE, f64::MAX, f64::MIN, f64::EPSILON, 5e-324][self.below(9)],
+            1 => self.below(1000) as f64 / 8.0,
+            _ => loop {
+                let n = f64::from_bits(self.next());
+                if n.is_finite() {
+                    break n;
+                }
+            },
+        }
+    }
+
+    fn string(&mut self) -> String {
+        const PIECES: &[&str] = &["a", "Z", "0", " ", "\"", "\\", "\n", "\t", "\u{0}", "é", "日本", "🦀", "{", "}"];
+        (0..self.below(8)).map(|_| PIECES[self.below(PIECES.len())]).collect()
+    }
+
+    fn name(&mut self) -> String {
+        const NAMES: &[&str] = &["x", "y", "total", "max", "map", "_private", "ünïcode"];
+        NAMES[self.below(NAMES.len())].to_string()
+    }
+
+    fn span(&mut self) -> Span {
+        let start = self.below(10_000);
+        Span::new(start, start + self.below(100))
+    }
+
+    fn slot(&mut self) -> Option<Slot> {
+        self.chance().then(|| Slot { depth: self.below(3) as u32, index: self.next() as u32 })
+    }
+
+    fn value(&mut self, depth: usize) -> Value {
+        let kinds = if depth == 0 { 5 } else { 7 };
+        match self.below(kinds) {
+            0 => Value::Integer(self.integer()),
+            1 => Value::Number(self.number()),
+            2 => Value::Boolean(self.chance()),
+            3 => Value::string(self.string()),
+            4 => Value::Function(self.name()),
+            5 => Value::list((0..self.below(4)).map(|_| self.value(depth - 1)).collect()),
+            _ => Value::dict((0..self.below(4)).map(|_| (self.string(), self.value(depth - 1))).collect::<HashMap<_, _>>()),
+        }
+    }
+
+    fn expr(&mut self, depth: usize) -> Expr {
+        let kinds = if depth == 0 { 5 } else { 12 };
+        let span = self.span();
+        match self.below(kinds) {
+            0 => Expr::Number { value: self.number(), span },
+            1 => Expr::Integer { value: self.integer(), span },
+            2 => Expr::Boolean { value: self.chance(), span },
+            3 => Expr::String { value: self.string(), span },
+            4 => Expr::Identifier { name: self.name(), span, slot: self.slot() },
+            5 => {
+                const OPS: [BinaryOp; 6] = [BinaryOp::Add, BinaryOp::Divide, BinaryOp::Less, BinaryOp::Equal, BinaryOp::And, BinaryOp::NotIn];
+                Expr::Binary {
+                    left: Box::new(self.expr(depth - 1)),
+                    operator: OPS[self.below(OPS.len())],
+                    right: Box::new(self.expr(depth - 1)),
+                    span,
+                }
+            }
+            6 => Expr::Unary {
+                operator: [UnaryOp::Minus, UnaryOp::Plus, UnaryOp::Not][self.below(3)],
+                operand: Box::new(self.expr(depth - 1)),
+                span,
+            },
+            7 => Expr::FunctionCall {
+                name: self.name(),
+                args: (0..self.below(3)).map(|_| self.expr(depth - 1)).collect(),
+                kwargs: (0..self.below(3)).map(|_| (self.chance().then(|| self.name()), self.expr(depth - 1))).collect(),
+                span,
+                builtin: self.chance(),
+                slot: self.slot(),
+            },
+            8 => Expr::Unpack { value: Box::new(self.expr(depth - 1)), span },
+            9 => Expr::List { elements: (0..self.below(4)).map(|_| self.expr(depth - 1)).collect(), span },
+            10 => Expr::Dictionary { pairs: (0..self.below(4)).map(|_| (self.string(), self.expr(depth - 1))).collect(), span },
+            _ => Expr::Index { object: Box::new(self.expr(depth - 1)), index: Box::new(self.expr(depth - 1)), span },
+        }
+    }
+
+    fn statement(&mut self) -> Stmt {
+        let span = self.span();
+        match self.below(3) {
+            0 => Stmt::Expression { expr: self.expr(3), span },
+            1 => Stmt::Assignment {
+                name: self.name(),
+                value: self.expr(3),
+                span,
+                slot: self.slot(),
+                annotation: (self.below(2) == 0).then(|| TypeAnnotation { name: self.name(), span: self.span() }),
+            },
+            _ => Stmt::CompoundAssignment {
+                name: self.name(),
+                operator: [CompoundOp::Add, CompoundOp::Subtract, CompoundOp::Multiply, CompoundOp::Divide][self.below(4)],
+                value: self.expr(3),
+                span,
+                slot: self.slot(),
+            },
+        }
+    }
+}
+
+fn round_trip<T: serde::Serialize + serde::de::DeserializeOwned>(value: &T) -> T {
+    let json = serde_json::to_string(value).unwrap();
+    serde_json::from_str(&json).unwrap_or_else(|error| panic!("{} in {}", error, json))
+}
+
+/// Structural equality: integers and numbers differ, floats compare by bits.
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Integer(x), Value::Integer(y)) => x == y,
+        (Value::Number(x), Value::Number(y)) => x.to_bits() == y.to_bits(),
+        (Value::Boolean(x), Value::Boolean(y)) => x == y,
+        (Value::String(x), Value::String(y)) => x == y,
+        (Value::Function(x), Value::Function(y)) => x == y,
+        (Value::List(x), Value::List(y)) => x.len() == y.len() && x.iter().zip(y.iter()).all(|(x, y)| same_value(x, y)),
+        (Value::Dictionary(x), Value::Dictionary(y)) => {
+            x.len() == y.len() && x.iter().all(|(key, x)| y.get(key).is_some_and(|y| same_value(x, y)))
+        }
+        _ => false,
+    }
+}
+
+#[test]
+fn test_value_schema() {
+    let cases = [
+        (Value::Integer(1), r#"{"type":"integer","value":1}"#),
+        (Value::Number(1.0), r#"{"type":"number","value":1.0}"#),
+        (Value::Boolean(false), r#"{"type":"boolean","value":false}"#),
+        (Value::string("hi"), r#"{"type":"string","value":"hi"}"#),
+        (Value::Function("max".to_string()), r#"{"type":"function","value":"max"}"#),
+        (Value::list(vec![]), r#"{"type":"list","value":[]}"#),
+        (
+            Value::dict(HashMap::from([
+                ("b".to_string(), Value::Integer(2)),
+                ("a".to_string(), Value::Number(2.5)),
+            ])),
+            r#"{"type":"dictionary","value":{"a":{"type":"number","value":2.5},"b":{"type":"integer","value":2}}}"#,
+        ),
+    ];
+    for (value, json) in cases {
+        assert_eq!(serde_json::to_string(&value).unwrap(), json);
+        assert!(same_value(&serde_json::from_str(json).unwrap(), &value), "{}", json);
+    }
+}
+
+#[test]
+fn test_values_round_trip() {
+    let mut rng = Rng(0x5eed_0001);
+    for _ in 0..2000 {
+        let value = rng.value(3);
+        assert!(same_value(&round_trip(&value), &value), "{:?}", value);
+    }
+}
+
+#[test]
+fn test_integers_and_numbers_stay_distinct() {
+    assert!(matches!(round_trip(&Value::Integer(3)), Value::Integer(3)));
+    assert!(matches!(round_trip(&Value::Number(3.0)), Value::Number(n) if n == 3.0));
+    assert!(matches!(round_trip(&Value::Integer(i64::MAX)), Value::Integer(i64::MAX)));
+    assert!(matches!(round_trip(&Value::Integer(i64::MIN)), Value::Integer(i64::MIN)));
+}
+
+#[test]
+fn test_programs_round_trip() {
+    let mut rng = Rng(0x5eed_0002);
+    for _ in 0..500 {
+        let program = Program { statements: (0..rng.below(5)).map(|_| rng.statement()).collect() };
+        assert_eq!(round_trip(&program), program);
+    }
+}
+
+#[test]
+fn test_tokens_round_trip() {
+    let mut rng = Rng(0x5eed_0003);
+    for _ in 0..500 {
+        let source = format!(
+            "{} = {} + {} * [\"{}\", {}]; {}(**{{\"k\": true}}) != {} in {}",
+            rng.name(), rng.integer(), rng.below(100) as f64 / 4.0, rng.string().replace(['"', '\\', '\n'], ""),
+            rng.integer().unsigned_abs(), rng.name(), rng.name(), rng.name()
+        );
+        let tokens = Lexer::new(&source).tokenize().unwrap_or_else(|error| panic!("{:?} for {}", error, source));
+        assert_eq!(round_trip(&tokens), tokens, "{}", source);
+        let token: Token = round_trip(&tokens[0]);
+        assert_eq!(token.span, tokens[0].span);
+    }
+}
+
+#[test]
+fn test_shipped_program_evaluates_the_same() {
+    let source = "prices = {\"a\": 1.5, \"b\": 2}; total = sum(*map(float, [1, 2, 3])); total += prices[\"b\"]; [total, prices]";
+    let program = Parser::new(Lexer::new(source).tokenize().unwrap()).parse().unwrap();
+    let shipped: Program = round_trip(&program);
+    assert_eq!(shipped, program);
+
+    let expected = Evaluator::new().evaluate_program(&program).unwrap().unwrap();
+    let actual = Evaluator::new().evaluate_program(&shipped).unwrap().unwrap();
+    assert_eq!(actual.display(), expected.display());
+    assert!(same_value(&round_trip(&actual), &actual));
+}
+
+#[test]
+fn test_snapshots_round_trip() {
+    let mut rng = Rng(0x5eed_0004);
+    let mut evaluator = Evaluator::new();
+    for i in 0..50 {
+        evaluator.set_variable(&format!("v{}", i), rng.value(3));
+    }
+    let snapshot = evaluator.snapshot();
+    let shipped: Snapshot = round_trip(&snapshot);
+    assert_eq!(shipped.version, Snapshot::FORMAT_VERSION);
+    assert_eq!(shipped.encode().unwrap(), snapshot.encode().unwrap());
+
+    let mut restored = Evaluator::new();
+    restored.restore(&shipped).unwrap();
+    for (name, value) in &snapshot.variables {
+        assert!(same_value(restored.get_variable(name).unwrap(), value), "{}", name);
+    }
+}
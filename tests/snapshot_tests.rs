@@ -0,0 +1,142 @@
+//! Snapshot encoding needs the `serde` feature: `cargo test --features serde`.
+#![cfg(feature = "serde")]
+
+use std::collections::HashMap;
+use bccl::{
+    Backend, BcclError, BcclResult, BuiltinCall, Evaluator, FunctionSignature, Lexer, Parser, Snapshot, Value,
+};
+
+fn run(evaluator: &mut Evaluator, source: &str) -> Option<Value> {
+    let tokens = Lexer::new(source).tokenize().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+    evaluator.evaluate_program(&program).unwrap()
+}
+
+fn double(call: &BuiltinCall) -> BcclResult<Value> {
+    Ok(Value::Integer(call.args[0].as_integer().unwrap_or(0) * 2))
+}
+
+fn snapshot_error(error: BcclError) -> String {
+    match error {
+        BcclError::SnapshotError { message, .. } => message,
+        other => panic!("expected a snapshot error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_values_survive_encoding() {
+    let values = vec![
+        Value::Integer(i64::MIN),
+        Value::Integer(i64::MAX),
+        Value::Number(2.0),
+        Value::Number(0.1),
+        Value::Number(-0.0),
+        Value::Number(1e300),
+        Value::Number(5e-324),
+        Value::Boolean(false),
+        Value::string("quote \" backslash \\ newline \n tab \t nul \u{0} 日本 🦀"),
+        Value::Function("max".to_string()),
+        Value::list(vec![Value::list(vec![]), Value::dict(HashMap::new())]),
+        Value::dict(HashMap::from([
+            ("a = b".to_string(), Value::Integer(1)),
+            ("\"k\"".to_string(), Value::list(vec![Value::Number(1.5), Value::string("x, y]")])),
+        ])),
+    ];
+    let snapshot = Snapshot {
+        version: Snapshot::FORMAT_VERSION,
+        variables: values.iter().enumerate().map(|(i, value)| (format!("v{}", i), value.clone())).collect(),
+        functions: vec![],
+    };
+
+    let decoded = Snapshot::decode(&snapshot.encode().unwrap()).unwrap();
+    assert_eq!(decoded.variables.len(), values.len());
+    for ((name, value), original) in decoded.variables.iter().zip(&values) {
+        assert_eq!(value.type_name(), original.type_name(), "{}", name);
+        assert_eq!(value.display(), original.display(), "{}", name);
+        if let (Value::Number(a), Value::Number(b)) = (value, original) {
+            assert_eq!(a.to_bits(), b.to_bits(), "{}", name);
+        }
+    }
+    assert_eq!(decoded.encode().unwrap(), snapshot.encode().unwrap());
+}
+
+#[test]
+fn test_restore_continues_the_session() {
+    let mut session = Evaluator::new();
+    run(&mut session, "prices = {\"a\": 1.5, \"b\": 2}; counts = [1, 2, 3]; f = max");
+    let image = session.snapshot().encode().unwrap();
+
+    let mut restored = Evaluator::with_backend(Backend::Bytecode);
+    run(&mut restored, "stale = 1");
+    restored.restore(&Snapshot::decode(&image).unwrap()).unwrap();
+
+    assert_eq!(restored.get_variable_names().len(), 3);
+    assert!(restored.get_variable("stale").is_none());
+    assert_eq!(restored.backend(), Backend::Bytecode);
+    let result = run(&mut restored, "f(*counts) + prices[\"a\"]").unwrap();
+    assert_eq!(result.display(), "4.5");
+}
+
+#[test]
+fn test_rejects_other_format_versions() {
+    let error = Snapshot::decode(r#"{"version": 99, "entries": {}}"#).unwrap_err();
+    assert_eq!(snapshot_error(error), "snapshot format version 99 is not supported (this build reads version 1)");
+
+    let mut evaluator = Evaluator::new();
+    evaluator.set_variable("x", Value::Integer(1));
+    let snapshot = Snapshot { version: 0, variables: vec![], functions: vec![] };
+    assert!(snapshot_error(evaluator.restore(&snapshot).unwrap_err()).contains("version 0"));
+    assert!(evaluator.get_variable("x").is_some());
+}
+
+#[test]
+fn test_rejects_malformed_images() {
+    assert_eq!(snapshot_error(Snapshot::decode("x = 1\n").unwrap_err()), "this is not a BCCL snapshot");
+    assert_eq!(snapshot_error(Snapshot::decode("").unwrap_err()), "this is not a BCCL snapshot");
+    assert_eq!(snapshot_error(Snapshot::decode(r#"{"variables": []}"#).unwrap_err()), "this is not a BCCL snapshot");
+    let truncated = r#"{"version": 1, "variables": [["x", {"type": "integer", "value": 1}]"#;
+    assert!(snapshot_error(Snapshot::decode(truncated).unwrap_err()).contains("line 1"));
+    let unknown = r#"{"version": 1, "variables": [["x", {"type": "set", "value": 1}]], "functions": []}"#;
+    assert!(snapshot_error(Snapshot::decode(unknown).unwrap_err()).contains("unknown variant `set`"));
+}
+
+#[test]
+fn test_any_variable_name_round_trips() {
+    let mut session = Evaluator::new();
+    run(&mut session, "function = 3; version = \"v\"; functions = [function]");
+    let snapshot = Snapshot::decode(&session.snapshot().encode().unwrap()).unwrap();
+    assert!(snapshot.functions.is_empty());
+
+    let mut restored = Evaluator::new();
+    restored.restore(&snapshot).unwrap();
+    assert_eq!(run(&mut restored, "function + sum(*functions)").unwrap().display(), "6");
+    assert_eq!(restored.get_variable("version").unwrap().display(), "\"v\"");
+}
+
+#[test]
+fn test_non_finite_numbers_are_not_saved() {
+    let mut session = Evaluator::new();
+    session.set_variable("ok", Value::Integer(1));
+    session.set_variable("overflow", Value::list(vec![Value::Number(f64::INFINITY)]));
+    let error = session.snapshot().encode().unwrap_err();
+    assert_eq!(snapshot_error(error), "'overflow' holds a number that is not finite, which a snapshot cannot store");
+}
+
+#[test]
+fn test_host_functions_must_be_registered() {
+    let mut session = Evaluator::new();
+    session.define_function(FunctionSignature::new_native("double", vec!["value"], double));
+    run(&mut session, "g = double; n = g(21)");
+    let snapshot = Snapshot::decode(&session.snapshot().encode().unwrap()).unwrap();
+    assert_eq!(snapshot.functions, vec!["double".to_string()]);
+
+    let mut bare = Evaluator::new();
+    let error = bare.restore(&snapshot).unwrap_err();
+    assert_eq!(snapshot_error(error), "the snapshot needs functions that are not defined: double");
+    assert!(bare.get_variable_names().is_empty());
+
+    let mut host = Evaluator::new();
+    host.define_function(FunctionSignature::new_native("double", vec!["value"], double));
+    host.restore(&snapshot).unwrap();
+    assert_eq!(run(&mut host, "g(n)").unwrap().display(), "84");
+}